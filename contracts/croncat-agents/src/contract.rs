@@ -7,22 +7,32 @@ use crate::state::*;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    has_coins, to_binary, Addr, Attribute, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo,
-    Order, QuerierWrapper, Response, StdError, StdResult, Storage, Uint64,
+    coins, has_coins, to_binary, Addr, Attribute, BankMsg, Binary, BlockInfo, Coin, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage,
+    Uint128, Uint64,
 };
 use croncat_sdk_agents::msg::{
     AgentInfo, AgentResponse, AgentTaskResponse, ApprovedAgentAddresses, GetAgentIdsResponse,
     TaskStats, UpdateConfig,
 };
-use croncat_sdk_agents::types::{Agent, AgentNominationStatus, AgentStatus, Config};
+use croncat_sdk_agents::types::{
+    Agent, AgentNominationStatus, AgentPayout, AgentSlotAssignment, AgentStatus, AgentUnbonding,
+    Config, TaskDistribution,
+};
 use croncat_sdk_core::internal_messages::agents::{AgentOnTaskCompleted, AgentOnTaskCreated};
 use croncat_sdk_core::types::{DEFAULT_PAGINATION_FROM_INDEX, DEFAULT_PAGINATION_LIMIT};
+use croncat_sdk_core::version::{migrate_contract, StateMigration};
+use croncat_sdk_manager::msg::TreasuryQueryMsg;
+use croncat_sdk_manager::types::Config as ManagerConfig;
 use cw2::set_contract_version;
+use cw_utils::may_pay;
 use std::cmp::min;
 
 pub(crate) const CONTRACT_NAME: &str = "crate:croncat-agents";
 pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const STATE_MIGRATIONS: &[(&str, StateMigration<ContractError>)] = &[];
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -42,6 +52,10 @@ pub fn instantiate(
         min_active_agent_count,
         public_registration,
         allowed_agents,
+        min_agent_stake,
+        agent_slash_percentage,
+        agent_unbonding_period,
+        task_distribution,
     } = msg;
 
     validate_config_non_zero_u16(agent_nomination_duration, "agent_nomination_duration")?;
@@ -52,6 +66,7 @@ pub fn instantiate(
         min_coins_for_agent_registration,
         "min_coins_for_agent_registration",
     )?;
+    validate_config_percentage(agent_slash_percentage, "agent_slash_percentage")?;
 
     // Validate all entries
     let validated_allowed_agents = if let Some(agent_addrs) = &allowed_agents {
@@ -85,6 +100,10 @@ pub fn instantiate(
             .unwrap_or(DEFAULT_MIN_COINS_FOR_AGENT_REGISTRATION),
         min_active_agent_count: min_active_agent_count.unwrap_or(DEFAULT_MIN_ACTIVE_AGENT_COUNT),
         public_registration,
+        min_agent_stake: min_agent_stake.unwrap_or(DEFAULT_MIN_AGENT_STAKE),
+        agent_slash_percentage: agent_slash_percentage.unwrap_or(DEFAULT_AGENT_SLASH_PERCENTAGE),
+        agent_unbonding_period: agent_unbonding_period.unwrap_or(DEFAULT_AGENT_UNBONDING_PERIOD),
+        task_distribution: task_distribution.unwrap_or_default(),
     };
 
     // Store the approved agents if public registration is closed
@@ -118,6 +137,18 @@ pub fn instantiate(
         .add_attribute("owner", config.owner_addr.to_string()))
 }
 
+/// Migrate
+/// See [`migrate_contract`] for the allowed migrations
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_contract(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        STATE_MIGRATIONS,
+    )
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -131,6 +162,9 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetAgentTasks { account_id } => {
             to_binary(&query_get_agent_tasks(deps, env, account_id)?)
         }
+        QueryMsg::GetAgentUnbonding { account_id } => {
+            to_binary(&query_get_agent_unbonding(deps, account_id)?)
+        }
         QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::Paused {} => to_binary(&PAUSED.load(deps.storage)?),
     }
@@ -144,18 +178,26 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::RegisterAgent { payable_account_id } => {
-            register_agent(deps, info, env, payable_account_id)
-        }
-        ExecuteMsg::UnregisterAgent { from_behind } => {
-            unregister_agent(deps.storage, &deps.querier, &info.sender, from_behind)
-        }
-        ExecuteMsg::UpdateAgent { payable_account_id } => {
-            update_agent(deps, info, env, payable_account_id)
-        }
+        ExecuteMsg::RegisterAgent {
+            payable_account_id,
+            payout,
+        } => register_agent(deps, info, env, payable_account_id, payout),
+        ExecuteMsg::UnregisterAgent { from_behind } => unregister_agent(
+            deps.storage,
+            &deps.querier,
+            &env.block,
+            &info.sender,
+            from_behind,
+        ),
+        ExecuteMsg::WithdrawUnbondedStake {} => withdraw_unbonded_stake(deps, info, env),
+        ExecuteMsg::BondStake {} => bond_agent_stake(deps, info),
+        ExecuteMsg::UpdateAgent {
+            payable_account_id,
+            payout,
+        } => update_agent(deps, info, env, payable_account_id, payout),
         ExecuteMsg::CheckInAgent {} => accept_nomination_agent(deps, info, env),
         ExecuteMsg::OnTaskCreated(msg) => on_task_created(env, deps, info, msg),
-        ExecuteMsg::OnTaskCompleted(msg) => on_task_completed(deps, info, msg),
+        ExecuteMsg::OnTaskCompleted(msg) => on_task_completed(deps, env, info, msg),
         ExecuteMsg::UpdateConfig { config } => execute_update_config(deps, info, config),
         ExecuteMsg::Tick {} => execute_tick(deps, env),
         ExecuteMsg::PauseContract {} => execute_pause(deps, info),
@@ -198,6 +240,8 @@ fn query_get_agent(deps: Deps, env: Env, account_id: String) -> StdResult<AgentR
             payable_account_id: a.payable_account_id,
             balance: rewards,
             register_start: a.register_start,
+            stake: a.stake,
+            payout: a.payout,
             last_executed_slot: stats.last_executed_slot,
             completed_block_tasks: Uint64::from(stats.completed_block_tasks),
             completed_cron_tasks: Uint64::from(stats.completed_cron_tasks),
@@ -208,6 +252,11 @@ fn query_get_agent(deps: Deps, env: Env, account_id: String) -> StdResult<AgentR
     Ok(agent_response)
 }
 
+fn query_get_agent_unbonding(deps: Deps, account_id: String) -> StdResult<Option<AgentUnbonding>> {
+    let account_id = deps.api.addr_validate(&account_id)?;
+    AGENT_UNBONDINGS.may_load(deps.storage, &account_id)
+}
+
 /// Get a list of agent addresses
 fn query_get_agent_ids(
     deps: Deps,
@@ -269,20 +318,56 @@ fn query_get_agent_tasks(deps: Deps, env: Env, account_id: String) -> StdResult<
             },
         });
     }
-    AGENT_TASK_DISTRIBUTOR
-        .get_agent_tasks(
-            &deps,
-            &env,
-            account_id,
-            (Some(block_slots), Some(cron_slots)),
-        )
-        .map_err(|err| StdError::generic_err(err.to_string()))
+    distribute_agent_tasks(
+        &deps,
+        &env,
+        &config,
+        account_id,
+        (Some(block_slots), Some(cron_slots)),
+    )
+    .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+/// Gets agent tasks from the distributor selected in the config
+fn distribute_agent_tasks(
+    deps: &Deps,
+    env: &Env,
+    config: &Config,
+    agent_id: Addr,
+    slot_items: (Option<u64>, Option<u64>),
+) -> Result<AgentTaskResponse, ContractError> {
+    match config.task_distribution {
+        TaskDistribution::RoundRobin => {
+            AGENT_TASK_DISTRIBUTOR.get_agent_tasks(deps, env, agent_id, slot_items)
+        }
+        TaskDistribution::Weighted => {
+            WEIGHTED_AGENT_TASK_DISTRIBUTOR.get_agent_tasks(deps, env, agent_id, slot_items)
+        }
+    }
+}
+
+fn distribute_all_agents_tasks(
+    deps: &Deps,
+    env: &Env,
+    config: &Config,
+    slot_items: (Option<u64>, Option<u64>),
+) -> Result<Vec<(Addr, TaskStats)>, ContractError> {
+    match config.task_distribution {
+        TaskDistribution::RoundRobin => {
+            AGENT_TASK_DISTRIBUTOR.get_all_agents_tasks(deps, env, slot_items)
+        }
+        TaskDistribution::Weighted => {
+            WEIGHTED_AGENT_TASK_DISTRIBUTOR.get_all_agents_tasks(deps, env, slot_items)
+        }
+    }
 }
 
 /// If registration is public, adds any account as an agent that will be able to execute tasks.
 /// If registration is restricted to the whitelist, it's consulted.
 /// Registering allows for rewards accruing with micro-payments which will accumulate to more long-term.
 ///
+/// Attached native coins are bonded as the agent stake, if the stake is required.
+///
 /// Optional Parameters:
 /// "payable_account_id" - Allows a different account id to be specified, so a user can receive funds at a different account than the agent account.
 fn register_agent(
@@ -290,15 +375,16 @@ fn register_agent(
     info: MessageInfo,
     env: Env,
     payable_account_id: Option<String>,
+    payout: Option<AgentPayout>,
 ) -> Result<Response, ContractError> {
-    if !info.funds.is_empty() {
-        return Err(ContractError::NoFundsShouldBeAttached);
-    }
     if PAUSED.load(deps.storage)? {
         return Err(ContractError::ContractPaused);
     }
     let c = CONFIG.load(deps.storage)?;
-    let account = info.sender;
+    if c.min_agent_stake == 0 && !info.funds.is_empty() {
+        return Err(ContractError::NoFundsShouldBeAttached);
+    }
+    let account = info.sender.clone();
 
     // Check if registration is public, return error if the calling agent isn't allowed
     if !c.public_registration && !APPROVED_AGENTS.has(deps.storage, &account) {
@@ -310,11 +396,25 @@ fn register_agent(
     let agent_wallet_balances = deps.querier.query_all_balances(account.clone())?;
 
     // Get the denom from the manager contract
-    let manager_config = croncat_manager_contract::query_manager_config(&deps.as_ref(), &c)?;
+    let manager_config = croncat_manager_contract::query_manager_config(&deps.querier, &c)?;
+
+    let stake = if c.min_agent_stake == 0 {
+        Uint128::zero()
+    } else {
+        let stake_needed = Coin::new(c.min_agent_stake.into(), &manager_config.native_denom);
+        match may_pay(&info, &manager_config.native_denom) {
+            Ok(stake) if stake >= stake_needed.amount => stake,
+            _ => {
+                return Err(ContractError::InsufficientStake {
+                    amount_needed: stake_needed,
+                })
+            }
+        }
+    };
 
     let agents_needs_coin = Coin::new(
         c.min_coins_for_agent_registration.into(),
-        manager_config.native_denom,
+        &manager_config.native_denom,
     );
     if !has_coins(&agent_wallet_balances, &agents_needs_coin) || agent_wallet_balances.is_empty() {
         return Err(ContractError::InsufficientFunds {
@@ -327,6 +427,9 @@ fn register_agent(
     } else {
         account.clone()
     };
+    if let Some(payout) = &payout {
+        validate_agent_payout(deps.as_ref(), &manager_config, payout)?;
+    }
 
     let mut active_agents_vec: Vec<Addr> = AGENTS_ACTIVE
         .may_load(deps.storage)?
@@ -354,6 +457,8 @@ fn register_agent(
                         payable_account_id: payable_id,
                         // REF: https://github.com/CosmWasm/cosmwasm/blob/main/packages/std/src/types.rs#L57
                         register_start: env.block.time,
+                        stake,
+                        payout,
                     })
                 }
             }
@@ -372,20 +477,28 @@ fn register_agent(
     )?;
     Ok(Response::new()
         .add_attribute("action", "register_agent")
-        .add_attribute("agent_status", agent_status.to_string()))
+        .add_attribute("agent_status", agent_status.to_string())
+        .add_attribute("stake", stake))
 }
 
-/// Update agent details, specifically the payable account id for an agent.
+/// Update agent details, specifically the payable account id and the automatic payout for an agent.
 fn update_agent(
     deps: DepsMut,
     info: MessageInfo,
     _env: Env,
     payable_account_id: String,
+    payout: Option<AgentPayout>,
 ) -> Result<Response, ContractError> {
     let payable_account_id = deps.api.addr_validate(&payable_account_id)?;
     if PAUSED.load(deps.storage)? {
         return Err(ContractError::ContractPaused);
     }
+    if let Some(payout) = &payout {
+        let config = CONFIG.load(deps.storage)?;
+        let manager_config =
+            croncat_manager_contract::query_manager_config(&deps.querier, &config)?;
+        validate_agent_payout(deps.as_ref(), &manager_config, payout)?;
+    }
 
     AGENTS.update(
         deps.storage,
@@ -395,6 +508,7 @@ fn update_agent(
                 Some(agent) => {
                     let mut ag = agent;
                     ag.payable_account_id = payable_account_id;
+                    ag.payout = payout;
                     Ok(ag)
                 }
                 None => Err(ContractError::AgentNotRegistered {}),
@@ -405,6 +519,36 @@ fn update_agent(
     Ok(Response::new().add_attribute("action", "update_agent"))
 }
 
+/// Payout in cw20 has to be whitelisted by the manager and supported by the treasury
+fn validate_agent_payout(
+    deps: Deps,
+    manager_config: &ManagerConfig,
+    payout: &AgentPayout,
+) -> Result<(), ContractError> {
+    let cw20_addr = match &payout.cw20_addr {
+        Some(cw20_addr) => cw20_addr,
+        None => return Ok(()),
+    };
+    let cw20_addr = deps.api.addr_validate(cw20_addr.as_str())?;
+    let supported = match &manager_config.treasury_addr {
+        Some(treasury_addr) if manager_config.cw20_whitelist.contains(&cw20_addr) => {
+            deps.querier.query_wasm_smart(
+                treasury_addr,
+                &TreasuryQueryMsg::SupportsCw20Payout {
+                    cw20_addr: cw20_addr.to_string(),
+                },
+            )?
+        }
+        _ => false,
+    };
+    if !supported {
+        return Err(ContractError::UnsupportedPayoutCw20 {
+            cw20_addr: cw20_addr.into_string(),
+        });
+    }
+    Ok(())
+}
+
 /// Allows an agent to accept a nomination within a certain amount of time to become an active agent.
 fn accept_nomination_agent(
     deps: DepsMut,
@@ -421,6 +565,16 @@ fn accept_nomination_agent(
     let agent_position = pending_queue_iter
         .position(|a| a.map_or_else(|_| false, |v| info.sender == v))
         .ok_or(ContractError::AgentNotPending)?;
+    // Agents registered before the stake was required have to bond it first
+    let stake = AGENTS
+        .may_load(deps.storage, &info.sender)?
+        .map_or(Uint128::zero(), |agent| agent.stake);
+    if stake < Uint128::from(c.min_agent_stake) {
+        let manager_config = croncat_manager_contract::query_manager_config(&deps.querier, &c)?;
+        return Err(ContractError::InsufficientStake {
+            amount_needed: Coin::new(c.min_agent_stake.into(), manager_config.native_denom),
+        });
+    }
     let agent_nomination_status = AGENT_NOMINATION_STATUS.load(deps.storage)?;
     // edge case if last agent left
     if active_agents.is_empty() && agent_position == 0 {
@@ -490,10 +644,12 @@ fn accept_nomination_agent(
 
 /// Removes the agent from the active set of AGENTS.
 /// Withdraws all reward balances to the agent payable account id.
+/// Agent stake stays bonded for the unbonding period.
 /// In case it fails to unregister pending agent try to set `from_behind` to true
 fn unregister_agent(
     storage: &mut dyn Storage,
     querier: &QuerierWrapper<Empty>,
+    block: &BlockInfo,
     agent_id: &Addr,
     from_behind: Option<bool>,
 ) -> Result<Response, ContractError> {
@@ -504,12 +660,30 @@ fn unregister_agent(
     let agent = AGENTS
         .may_load(storage, agent_id)?
         .ok_or(ContractError::AgentNotRegistered {})?;
+    let msg = croncat_manager_contract::create_withdraw_rewards_submsg(
+        querier,
+        &config,
+        agent_id.as_str(),
+        agent.payable_account_id.to_string(),
+    )?;
+
+    // Agent missing too many slots is slashed, whoever unregisters it
+    update_missed_slots(storage, &config, block.height, agent_id)?;
+    let missed = AGENT_SLOT_ASSIGNMENTS
+        .may_load(storage, agent_id)?
+        .is_some_and(|assignment| assignment.missed_slots > config.agents_eject_threshold);
+    let slash_msg = if missed {
+        slash_agent_stake(querier, &config, agent.stake)?
+    } else {
+        None
+    };
 
     // Remove from the list of active agents if the agent in this list
     let mut active_agents: Vec<Addr> = AGENTS_ACTIVE.load(storage)?;
     if let Some(index) = active_agents.iter().position(|addr| addr == agent_id) {
         //Notify the balancer agent has been removed, to rebalance itself
         AGENT_STATS.remove(storage, agent_id);
+        AGENT_SLOT_ASSIGNMENTS.remove(storage, agent_id);
         active_agents.remove(index);
         AGENTS_ACTIVE.save(storage, &active_agents)?;
     } else {
@@ -541,23 +715,113 @@ fn unregister_agent(
             }
         }
     }
-    let msg = croncat_manager_contract::create_withdraw_rewards_submsg(
-        querier,
-        &config,
-        agent_id.as_str(),
-        agent.payable_account_id.to_string(),
-    )?;
     AGENTS.remove(storage, agent_id);
+    let slashed = slash_msg
+        .as_ref()
+        .map_or(Uint128::zero(), |(slashed, _)| *slashed);
+    let unbonding_stake = agent.stake - slashed;
+    if !unbonding_stake.is_zero() {
+        AGENT_UNBONDINGS.update(storage, agent_id, |unbonding| -> StdResult<_> {
+            Ok(AgentUnbonding {
+                amount: unbonding.map_or(Uint128::zero(), |u| u.amount) + unbonding_stake,
+                release_time: block.time.plus_seconds(config.agent_unbonding_period),
+            })
+        })?;
+    }
 
-    let responses = Response::new()
+    let mut responses = Response::new()
         // Send withdraw rewards message to manager contract
         .add_message(msg)
         .add_attribute("action", "unregister_agent")
-        .add_attribute("account_id", agent_id);
+        .add_attribute("account_id", agent_id)
+        .add_attribute("unbonding_stake", unbonding_stake);
+    if let Some((slashed, slash_msg)) = slash_msg {
+        responses = responses
+            .add_attribute("slashed_stake", slashed)
+            .add_message(slash_msg);
+    }
 
     Ok(responses)
 }
 
+/// Adds attached native coins to the stake of the registered agent
+fn bond_agent_stake(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if config.min_agent_stake == 0 {
+        return Err(ContractError::NoFundsShouldBeAttached);
+    }
+    let mut agent = AGENTS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::AgentNotRegistered {})?;
+    let manager_config = croncat_manager_contract::query_manager_config(&deps.querier, &config)?;
+    let amount = may_pay(&info, &manager_config.native_denom)
+        .ok()
+        .filter(|amount| !amount.is_zero())
+        .ok_or_else(|| ContractError::InsufficientStake {
+            amount_needed: Coin::new(config.min_agent_stake.into(), &manager_config.native_denom),
+        })?;
+    agent.stake += amount;
+    AGENTS.save(deps.storage, &info.sender, &agent)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "bond_stake")
+        .add_attribute("account_id", info.sender)
+        .add_attribute("stake", agent.stake))
+}
+
+/// Sends the stake of the unregistered agent back, once the unbonding period passed
+fn withdraw_unbonded_stake(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let unbonding = AGENT_UNBONDINGS
+        .may_load(deps.storage, &info.sender)?
+        .filter(|unbonding| unbonding.release_time <= env.block.time)
+        .ok_or(ContractError::NoUnbondedStake {})?;
+    AGENT_UNBONDINGS.remove(deps.storage, &info.sender);
+
+    let config = CONFIG.load(deps.storage)?;
+    let manager_config = croncat_manager_contract::query_manager_config(&deps.querier, &config)?;
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_unbonded_stake")
+        .add_attribute("amount", unbonding.amount)
+        .add_message(BankMsg::Send {
+            to_address: info.sender.into_string(),
+            amount: coins(unbonding.amount.u128(), manager_config.native_denom),
+        }))
+}
+
+/// Slashed part of the unregistering agent stake and its transfer to the treasury,
+/// slashed stake doesn't get to the unbonding
+fn slash_agent_stake(
+    querier: &QuerierWrapper<Empty>,
+    config: &Config,
+    stake: Uint128,
+) -> Result<Option<(Uint128, BankMsg)>, ContractError> {
+    let slashed = stake.multiply_ratio(config.agent_slash_percentage, 100u16);
+    if slashed.is_zero() {
+        return Ok(None);
+    }
+    let manager_config = croncat_manager_contract::query_manager_config(querier, config)?;
+    let treasury_addr = manager_config
+        .treasury_addr
+        .unwrap_or(manager_config.owner_addr);
+    Ok(Some((
+        slashed,
+        BankMsg::Send {
+            to_address: treasury_addr.into_string(),
+            amount: coins(slashed.u128(), manager_config.native_denom),
+        },
+    )))
+}
+
 pub fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -573,6 +837,10 @@ pub fn execute_update_config(
         agents_eject_threshold,
         min_active_agent_count,
         public_registration,
+        min_agent_stake,
+        agent_slash_percentage,
+        agent_unbonding_period,
+        task_distribution,
     } = msg;
 
     CONFIG.update(deps.storage, |config| {
@@ -584,6 +852,7 @@ pub fn execute_update_config(
             min_coins_for_agent_registration,
             "min_coins_for_agent_registration",
         )?;
+        validate_config_percentage(agent_slash_percentage, "agent_slash_percentage")?;
 
         if info.sender != config.owner_addr {
             return Err(ContractError::Unauthorized {});
@@ -611,6 +880,10 @@ pub fn execute_update_config(
             min_active_agent_count: min_active_agent_count
                 .unwrap_or(DEFAULT_MIN_ACTIVE_AGENT_COUNT),
             public_registration: public_registration.unwrap_or(config.public_registration),
+            min_agent_stake: min_agent_stake.unwrap_or(config.min_agent_stake),
+            agent_slash_percentage: agent_slash_percentage.unwrap_or(config.agent_slash_percentage),
+            agent_unbonding_period: agent_unbonding_period.unwrap_or(config.agent_unbonding_period),
+            task_distribution: task_distribution.unwrap_or(config.task_distribution),
         };
         Ok(new_config)
     })?;
@@ -695,7 +968,7 @@ fn max_agent_nomination_index(
     }
 }
 
-pub fn execute_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+pub fn execute_tick(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let block_height = env.block.height;
     let config = CONFIG.load(deps.storage)?;
     let mut attributes = vec![];
@@ -704,23 +977,41 @@ pub fn execute_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError>
     let total_remove_agents: usize = agents_active.len();
     let mut total_removed = 0;
 
+    for agent_id in agents_active.iter() {
+        update_missed_slots(deps.storage, &config, block_height, agent_id)?;
+    }
     for agent_id in agents_active {
         let skip = (config.min_active_agent_count as usize) >= total_remove_agents - total_removed;
         if !skip {
-            let stats = AGENT_STATS
-                .load(deps.storage, &agent_id)
-                .unwrap_or_default();
-            if block_height > stats.last_executed_slot + config.agents_eject_threshold {
-                let resp = unregister_agent(deps.storage, &deps.querier, &agent_id, None)
-                    .unwrap_or_default();
-                // Save attributes and messages
-                attributes.extend_from_slice(&resp.attributes);
-                submessages.extend_from_slice(&resp.messages);
-                total_removed += 1;
+            let missed_slots = AGENT_SLOT_ASSIGNMENTS
+                .may_load(deps.storage, &agent_id)?
+                .map_or(0, |assignment| assignment.missed_slots);
+            let stake = AGENTS
+                .may_load(deps.storage, &agent_id)?
+                .map_or(Uint128::zero(), |agent| agent.stake);
+            let missed = missed_slots > config.agents_eject_threshold;
+            // Agents registered before the stake was required are removed until they bond it
+            let under_bonded = stake < Uint128::from(config.min_agent_stake);
+            if missed || under_bonded {
+                // Failure to remove one agent doesn't stop the tick for the others
+                match unregister_agent(deps.storage, &deps.querier, &env.block, &agent_id, None) {
+                    Ok(resp) => {
+                        // Save attributes and messages
+                        attributes.extend_from_slice(&resp.attributes);
+                        submessages.extend_from_slice(&resp.messages);
+                        total_removed += 1;
+                    }
+                    Err(_) => {
+                        attributes.push(Attribute::new("unregister_failed", agent_id.as_str()))
+                    }
+                }
             }
         }
     }
 
+    // Remaining agents have until the next tick to serve the current slots
+    assign_agent_slots(deps.branch(), &env, &config)?;
+
     // Check if there isn't any active or pending agents
     if AGENTS_ACTIVE.load(deps.storage)?.is_empty() && AGENTS_PENDING.is_empty(deps.storage)? {
         attributes.push(Attribute::new("lifecycle", "tick_failure"))
@@ -732,6 +1023,98 @@ pub fn execute_tick(deps: DepsMut, env: Env) -> Result<Response, ContractError>
     Ok(response)
 }
 
+/// Assignment is scored only after the agent had the nomination duration to serve it,
+/// so ticking every block doesn't count the tasks agent had no chance to serve as missed
+fn assignment_window_passed(
+    assignment: &AgentSlotAssignment,
+    config: &Config,
+    block_height: u64,
+) -> bool {
+    block_height >= assignment.slot + config.agent_nomination_block_duration as u64
+}
+
+/// Counts the tasks agent was assigned on the last tick, but didn't complete since, as missed.
+/// Only completions up to the assigned tasks count, so serving extra tasks doesn't cover the missed ones.
+/// Slots passed since then are missed in proportion to the missed part of the assigned tasks,
+/// unless agent served all of its tasks
+fn update_missed_slots(
+    storage: &mut dyn Storage,
+    config: &Config,
+    block_height: u64,
+    agent_id: &Addr,
+) -> Result<(), ContractError> {
+    let mut assignment = match AGENT_SLOT_ASSIGNMENTS.may_load(storage, agent_id)? {
+        Some(assignment) if assignment_window_passed(&assignment, config, block_height) => {
+            assignment
+        }
+        _ => return Ok(()),
+    };
+    let mut stats = AGENT_STATS.may_load(storage, agent_id)?.unwrap_or_default();
+    let missed_block_tasks = assignment.block_tasks
+        - stats
+            .completed_block_tasks
+            .saturating_sub(assignment.completed_block_tasks)
+            .min(assignment.block_tasks);
+    let missed_cron_tasks = assignment.cron_tasks
+        - stats
+            .completed_cron_tasks
+            .saturating_sub(assignment.completed_cron_tasks)
+            .min(assignment.cron_tasks);
+    let assigned_tasks = assignment.block_tasks + assignment.cron_tasks;
+    let missed_tasks = missed_block_tasks + missed_cron_tasks;
+    if missed_tasks > 0 {
+        stats.missed_blocked_tasks += missed_block_tasks;
+        stats.missed_cron_tasks += missed_cron_tasks;
+        AGENT_STATS.save(storage, agent_id, &stats)?;
+        // Rounded up, so any missed task counts
+        let passed_slots = block_height - assignment.slot;
+        assignment.missed_slots += (passed_slots * missed_tasks).div_ceil(assigned_tasks);
+    } else if assigned_tasks > 0 {
+        assignment.missed_slots = 0;
+    }
+    AGENT_SLOT_ASSIGNMENTS.save(storage, agent_id, &assignment)?;
+    Ok(())
+}
+
+/// Saves tasks the distributor assigns to every active agent for the current slots,
+/// assignment of all agents is computed at once.
+/// Agents keep the assignment that isn't scored yet
+fn assign_agent_slots(deps: DepsMut, env: &Env, config: &Config) -> Result<(), ContractError> {
+    let (block_slots, cron_slots) =
+        croncat_tasks_contract::query_tasks_slots(deps.as_ref(), config)?;
+    let agents_tasks = distribute_all_agents_tasks(
+        &deps.as_ref(),
+        env,
+        config,
+        (Some(block_slots), Some(cron_slots)),
+    )?;
+    for (agent_id, tasks) in agents_tasks {
+        let assignment = AGENT_SLOT_ASSIGNMENTS.may_load(deps.storage, &agent_id)?;
+        if assignment.as_ref().is_some_and(|assignment| {
+            !assignment_window_passed(assignment, config, env.block.height)
+        }) {
+            continue;
+        }
+        let stats = AGENT_STATS
+            .may_load(deps.storage, &agent_id)?
+            .unwrap_or_default();
+        let missed_slots = assignment.map_or(0, |assignment| assignment.missed_slots);
+        AGENT_SLOT_ASSIGNMENTS.save(
+            deps.storage,
+            &agent_id,
+            &AgentSlotAssignment {
+                slot: env.block.height,
+                block_tasks: tasks.num_block_tasks.u64(),
+                cron_tasks: tasks.num_cron_tasks.u64(),
+                completed_block_tasks: stats.completed_block_tasks,
+                completed_cron_tasks: stats.completed_cron_tasks,
+                missed_slots,
+            },
+        )?;
+    }
+    Ok(())
+}
+
 pub fn execute_pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
     if PAUSED.load(deps.storage)? {
         return Err(ContractError::ContractPaused);
@@ -817,6 +1200,7 @@ fn on_task_created(
 
 fn on_task_completed(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     args: AgentOnTaskCompleted,
 ) -> Result<Response, ContractError> {
@@ -834,6 +1218,7 @@ fn on_task_completed(
     } else {
         stats.completed_cron_tasks += 1;
     }
+    stats.last_executed_slot = env.block.height;
     AGENT_STATS.save(deps.storage, &args.agent_id, &stats)?;
 
     let response = Response::new().add_attribute("action", "on_task_completed");
@@ -875,6 +1260,15 @@ fn validate_config_non_zero_u64(
     }
 }
 
+fn validate_config_percentage(opt_num: Option<u16>, field_name: &str) -> Result<(), ContractError> {
+    match opt_num {
+        Some(num) if num > 100 => Err(InvalidConfigurationValue {
+            field: field_name.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
 // Thank you cw1 for the handy function
 // pub fn map_validate(deps: &DepsMut, admins: &Vec<Addr>) -> StdResult<Vec<Addr>> {
 pub fn map_validate(deps: &DepsMut, agents: &[String]) -> StdResult<Vec<Addr>> {
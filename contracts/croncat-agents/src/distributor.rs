@@ -1,12 +1,20 @@
+use std::cmp::Reverse;
+
 use cosmwasm_std::{Addr, Deps, Env, Storage, Uint64};
 use croncat_sdk_agents::msg::{AgentTaskResponse, TaskStats};
 use croncat_sdk_tasks::types::SlotType;
 
 use crate::{
     error::ContractError,
-    state::{AGENTS_ACTIVE, AGENT_STATS},
+    state::{AGENTS, AGENTS_ACTIVE, AGENT_STATS, CONFIG},
 };
 
+/// Success rate of the agent without any missed slots
+const FULL_SUCCESS_RATE: u128 = 10_000;
+
+/// Most the stake can multiply the weight of the agent
+const MAX_STAKE_MULTIPLIER: u128 = 2;
+
 pub trait RoundRobinAgentTaskDistributor<'a> {
     #[doc = r".Gets agent tasks count for block/cron slots
     # Errors
@@ -17,7 +25,22 @@ pub trait RoundRobinAgentTaskDistributor<'a> {
         env: &Env,
         agent_id: Addr,
         slot_items: (Option<u64>, Option<u64>),
-    ) -> Result<AgentTaskResponse, ContractError>;
+    ) -> Result<AgentTaskResponse, ContractError> {
+        self.get_all_agents_tasks(deps, env, slot_items)?
+            .into_iter()
+            .find(|(id, _)| *id == agent_id)
+            .map(|(_, stats)| AgentTaskResponse { stats })
+            .ok_or(ContractError::AgentNotRegistered {})
+    }
+
+    #[doc = r"Gets tasks count for block/cron slots of every active agent at once,
+    in the order of active agents"]
+    fn get_all_agents_tasks(
+        &self,
+        deps: &Deps,
+        env: &Env,
+        slot_items: (Option<u64>, Option<u64>),
+    ) -> Result<Vec<(Addr, TaskStats)>, ContractError>;
 
     #[doc = r"Updates agent stats when agent completed task on specified slot"]
     fn on_task_completed(
@@ -41,88 +64,60 @@ impl AgentTaskDistributor {
 /// See <https://github.com/CronCats/cw-croncat/pull/415>
 #[allow(clippy::op_ref)]
 impl<'a> RoundRobinAgentTaskDistributor<'a> for AgentTaskDistributor {
-    fn get_agent_tasks(
+    fn get_all_agents_tasks(
         &self,
         deps: &Deps,
         _env: &Env,
-        agent_id: Addr,
         slot_items: (Option<u64>, Option<u64>),
-    ) -> Result<AgentTaskResponse, ContractError> {
-        let mut active = AGENTS_ACTIVE.load(deps.storage)?;
-        if !active.contains(&agent_id) {
-            return Err(ContractError::AgentNotRegistered {});
-        }
+    ) -> Result<Vec<(Addr, TaskStats)>, ContractError> {
+        let active = AGENTS_ACTIVE.load(deps.storage)?;
+        let mut tasks: Vec<(Addr, TaskStats)> = active
+            .iter()
+            .map(|agent_id| {
+                (
+                    agent_id.clone(),
+                    TaskStats {
+                        num_block_tasks: Uint64::zero(),
+                        num_cron_tasks: Uint64::zero(),
+                    },
+                )
+            })
+            .collect();
         if slot_items == (None, None) {
-            return Ok(AgentTaskResponse {
-                stats: TaskStats {
-                    num_block_tasks: Uint64::zero(),
-                    num_cron_tasks: Uint64::zero(),
-                },
-            });
+            return Ok(tasks);
         }
         let agent_count = active.len() as u64;
+        let mut stats = Vec::with_capacity(active.len());
+        for agent_id in active.iter() {
+            stats.push(AGENT_STATS.load(deps.storage, agent_id).unwrap_or_default());
+        }
         let (block_slots, cron_slots) = slot_items;
 
-        let mut equalizer = |slot_type: SlotType,
-                             total_tasks: u64|
-         -> Result<Uint64, ContractError> {
-            if total_tasks < 1 {
-                return Ok(Uint64::zero());
-            }
+        // Indexes of the active agents, agents with the least completed tasks go first.
+        // Cron slots are sorted starting from the order of the block slots
+        let mut order: Vec<usize> = (0..active.len()).collect();
+        let block_slots = block_slots.unwrap_or_default();
+        if block_slots > 0 {
             //This sort is unstable (i.e., may reorder equal elements), in-place (i.e., does not allocate),
             //and O(n log n) worst-case.
             //It is typically faster than stable sorting, except in a few special cases,
             //e.g., when the slice consists of several concatenated sorted sequences.
-            active.sort_unstable_by(|left, right| {
-                let stats1 = AGENT_STATS.load(deps.storage, left).unwrap_or_default();
-                let stats2 = AGENT_STATS.load(deps.storage, right).unwrap_or_default();
-                match slot_type {
-                    SlotType::Block => stats1
-                        .completed_block_tasks
-                        .partial_cmp(&stats2.completed_block_tasks)
-                        .unwrap(),
-                    SlotType::Cron => stats1
-                        .completed_cron_tasks
-                        .partial_cmp(&stats2.completed_cron_tasks)
-                        .unwrap(),
-                }
-            });
-            let agent_diff_index = active
-                .iter()
-                .position(|x| x == &agent_id)
-                .ok_or(ContractError::AgentNotRegistered {})?
-                as u64;
-
-            if total_tasks <= active.len() as u64 {
-                let agent_tasks_total = 1u64
-                    .saturating_sub(agent_diff_index.saturating_sub(total_tasks.saturating_sub(1)));
-                Ok(agent_tasks_total.into())
-            } else {
-                let leftover = total_tasks % agent_count;
-                let mut extra = 0u64;
-                if leftover > 0 {
-                    extra = 1u64.saturating_sub(
-                        agent_diff_index.saturating_sub(leftover.saturating_sub(1)),
-                    );
-                }
-                let agent_tasks_total = total_tasks.saturating_div(agent_count) + extra;
-
-                Ok(agent_tasks_total.into())
+            order.sort_unstable_by_key(|&index| stats[index].completed_block_tasks);
+            for (agent_diff_index, &index) in order.iter().enumerate() {
+                tasks[index].1.num_block_tasks =
+                    equalize_tasks(agent_diff_index as u64, agent_count, block_slots).into();
             }
-        };
-
-        let n = equalizer(SlotType::Block, block_slots.unwrap_or_default())?;
-        let num_block_tasks = n;
-
-        let n = equalizer(SlotType::Cron, cron_slots.unwrap_or_default())?;
-        let num_cron_tasks = n;
+        }
+        let cron_slots = cron_slots.unwrap_or_default();
+        if cron_slots > 0 {
+            order.sort_unstable_by_key(|&index| stats[index].completed_cron_tasks);
+            for (agent_diff_index, &index) in order.iter().enumerate() {
+                tasks[index].1.num_cron_tasks =
+                    equalize_tasks(agent_diff_index as u64, agent_count, cron_slots).into();
+            }
+        }
 
-        Ok(AgentTaskResponse {
-            stats: TaskStats {
-                num_block_tasks,
-                num_cron_tasks,
-            },
-        })
+        Ok(tasks)
     }
 
     fn on_task_completed(
@@ -132,13 +127,7 @@ impl<'a> RoundRobinAgentTaskDistributor<'a> for AgentTaskDistributor {
         agent_id: &Addr,
         slot_type: SlotType,
     ) -> Result<(), ContractError> {
-        let mut stats = AGENT_STATS.may_load(storage, agent_id)?.unwrap_or_default();
-        match slot_type {
-            SlotType::Block => stats.completed_block_tasks += 1,
-            SlotType::Cron => stats.completed_cron_tasks += 1,
-        }
-        AGENT_STATS.save(storage, agent_id, &stats)?;
-        Ok(())
+        record_completed_task(storage, agent_id, slot_type)
     }
 }
 
@@ -147,3 +136,155 @@ impl Default for AgentTaskDistributor {
         AgentTaskDistributor::new()
     }
 }
+
+/// Tasks of the agent at the `agent_diff_index` of the agents sorted by completed tasks,
+/// leftover tasks go to the agents at the start
+fn equalize_tasks(agent_diff_index: u64, agent_count: u64, total_tasks: u64) -> u64 {
+    if total_tasks <= agent_count {
+        1u64.saturating_sub(agent_diff_index.saturating_sub(total_tasks.saturating_sub(1)))
+    } else {
+        let leftover = total_tasks % agent_count;
+        let mut extra = 0u64;
+        if leftover > 0 {
+            extra =
+                1u64.saturating_sub(agent_diff_index.saturating_sub(leftover.saturating_sub(1)));
+        }
+        total_tasks.saturating_div(agent_count) + extra
+    }
+}
+
+/// Splits the slots by the weight of the agents.
+/// Weight is the success rate of the agent for the slot type,
+/// multiplied by its stake over the minimum stake (capped by `MAX_STAKE_MULTIPLIER`), if stake is required.
+/// Every agent gets the floor of its weighted share, leftover slots go to the agents
+/// that completed the least tasks compared to their weighted share, so allocations are fair over many slots.
+pub struct WeightedAgentTaskDistributor {}
+
+impl WeightedAgentTaskDistributor {
+    pub const fn new() -> WeightedAgentTaskDistributor {
+        WeightedAgentTaskDistributor {}
+    }
+}
+
+impl<'a> RoundRobinAgentTaskDistributor<'a> for WeightedAgentTaskDistributor {
+    fn get_all_agents_tasks(
+        &self,
+        deps: &Deps,
+        _env: &Env,
+        slot_items: (Option<u64>, Option<u64>),
+    ) -> Result<Vec<(Addr, TaskStats)>, ContractError> {
+        let active = AGENTS_ACTIVE.load(deps.storage)?;
+        let min_agent_stake = CONFIG.load(deps.storage)?.min_agent_stake as u128;
+
+        let mut agents = Vec::with_capacity(active.len());
+        for agent in active.iter() {
+            let stats = AGENT_STATS
+                .may_load(deps.storage, agent)?
+                .unwrap_or_default();
+            let stake = match AGENTS.may_load(deps.storage, agent)? {
+                Some(agent) => agent.stake.u128(),
+                None => 0,
+            };
+            let stake_multiplier = stake
+                .checked_div(min_agent_stake)
+                .map_or(1, |multiplier| multiplier.clamp(1, MAX_STAKE_MULTIPLIER));
+            agents.push((stats, stake_multiplier));
+        }
+
+        let weighted_slots = |slot_type: SlotType, total_tasks: u64| -> Vec<u128> {
+            let shares: Vec<(u128, u128)> = agents
+                .iter()
+                .map(|(stats, stake_multiplier)| {
+                    let (completed, missed) = match slot_type {
+                        SlotType::Block => {
+                            (stats.completed_block_tasks, stats.missed_blocked_tasks)
+                        }
+                        SlotType::Cron => (stats.completed_cron_tasks, stats.missed_cron_tasks),
+                    };
+                    let (completed, missed) = (completed as u128, missed as u128);
+                    // Agents without history start with the full success rate
+                    let success_rate =
+                        ((completed + 1) * FULL_SUCCESS_RATE / (completed + missed + 1)).max(1);
+                    (completed, success_rate * stake_multiplier)
+                })
+                .collect();
+            allocate_slots(&shares, total_tasks as u128)
+        };
+        let block_slots = weighted_slots(SlotType::Block, slot_items.0.unwrap_or_default());
+        let cron_slots = weighted_slots(SlotType::Cron, slot_items.1.unwrap_or_default());
+
+        Ok(active
+            .into_iter()
+            .zip(block_slots.into_iter().zip(cron_slots))
+            .map(|(agent_id, (block_tasks, cron_tasks))| {
+                (
+                    agent_id,
+                    TaskStats {
+                        num_block_tasks: Uint64::new(block_tasks as u64),
+                        num_cron_tasks: Uint64::new(cron_tasks as u64),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    fn on_task_completed(
+        &self,
+        storage: &'a mut dyn Storage,
+        _env: &Env,
+        agent_id: &Addr,
+        slot_type: SlotType,
+    ) -> Result<(), ContractError> {
+        record_completed_task(storage, agent_id, slot_type)
+    }
+}
+
+impl Default for WeightedAgentTaskDistributor {
+    fn default() -> WeightedAgentTaskDistributor {
+        WeightedAgentTaskDistributor::new()
+    }
+}
+
+/// Splits the slots between the agents, given as `(completed_tasks, weight)`
+fn allocate_slots(shares: &[(u128, u128)], total_tasks: u128) -> Vec<u128> {
+    let total_weight: u128 = shares.iter().map(|(_, weight)| weight).sum();
+    let total_completed: u128 = shares.iter().map(|(completed, _)| completed).sum();
+
+    let mut slots: Vec<u128> = shares
+        .iter()
+        .map(|(_, weight)| total_tasks * weight / total_weight)
+        .collect();
+    let leftover = total_tasks - slots.iter().sum::<u128>();
+
+    // How far agent falls behind its weighted share, scaled by the total weight
+    let mut deficits: Vec<(usize, i128)> = shares
+        .iter()
+        .zip(slots.iter())
+        .enumerate()
+        .map(|(index, ((completed, weight), slots))| {
+            let expected = (total_completed + total_tasks) * weight;
+            let received = (completed + slots) * total_weight;
+            (index, expected as i128 - received as i128)
+        })
+        .collect();
+    // Stable sort keeps the order of active agents on ties
+    deficits.sort_by_key(|(_, deficit)| Reverse(*deficit));
+    for (index, _) in deficits.into_iter().take(leftover as usize) {
+        slots[index] += 1;
+    }
+    slots
+}
+
+fn record_completed_task(
+    storage: &mut dyn Storage,
+    agent_id: &Addr,
+    slot_type: SlotType,
+) -> Result<(), ContractError> {
+    let mut stats = AGENT_STATS.may_load(storage, agent_id)?.unwrap_or_default();
+    match slot_type {
+        SlotType::Block => stats.completed_block_tasks += 1,
+        SlotType::Cron => stats.completed_cron_tasks += 1,
+    }
+    AGENT_STATS.save(storage, agent_id, &stats)?;
+    Ok(())
+}
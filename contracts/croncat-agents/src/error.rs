@@ -1,4 +1,5 @@
 use cosmwasm_std::{Coin, StdError};
+use croncat_sdk_core::error::MigrationError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -24,6 +25,15 @@ pub enum ContractError {
     #[error("Insufficient funds. Need a balance of at least {amount_needed:?} to cover the first few task chain fees")]
     InsufficientFunds { amount_needed: Coin },
 
+    #[error("Insufficient stake. Need to bond at least {amount_needed:?}")]
+    InsufficientStake { amount_needed: Coin },
+
+    #[error("No unbonded stake available for withdraw")]
+    NoUnbondedStake {},
+
+    #[error("Agent payout isn't supported in cw20: {cw20_addr}")]
+    UnsupportedPayoutCw20 { cw20_addr: String },
+
     #[error("Contract is in paused state")]
     ContractPaused,
 
@@ -74,4 +84,7 @@ pub enum ContractError {
 
     #[error("Agent registration has already become decentralized. Public registration cannot be set to false.")]
     DecentralizationEnabled {},
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
 }
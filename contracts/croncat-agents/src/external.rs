@@ -56,12 +56,14 @@ pub mod croncat_manager_contract {
 
     use super::*;
 
-    pub fn query_manager_config(deps: &Deps, config: &Config) -> StdResult<ManagerConfig> {
-        let manager_addr = query_manager_addr(&deps.querier, config)?;
+    pub fn query_manager_config(
+        querier: &QuerierWrapper<Empty>,
+        config: &Config,
+    ) -> StdResult<ManagerConfig> {
+        let manager_addr = query_manager_addr(querier, config)?;
         // Get the denom from the manager contract
-        let manager_config: ManagerConfig = deps
-            .querier
-            .query_wasm_smart(manager_addr, &ManagerQueryMsg::Config {})?;
+        let manager_config: ManagerConfig =
+            querier.query_wasm_smart(manager_addr, &ManagerQueryMsg::Config {})?;
 
         Ok(manager_config)
     }
@@ -114,6 +116,7 @@ pub mod croncat_manager_contract {
             addr,
             &ManagerQueryMsg::AgentRewards {
                 agent_id: agent_id.to_owned(),
+                denom: None,
             },
         )?;
 
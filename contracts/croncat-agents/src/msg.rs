@@ -1,5 +1,5 @@
 pub use croncat_sdk_agents::msg::{
-    AgentResponse, AgentTaskResponse, ExecuteMsg, GetAgentIdsResponse, InstantiateMsg, QueryMsg,
-    UpdateConfig,
+    AgentResponse, AgentTaskResponse, ExecuteMsg, GetAgentIdsResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, UpdateConfig,
 };
 pub use croncat_sdk_agents::types::{Agent, AgentStats, AgentStatus, Config};
@@ -1,15 +1,19 @@
 use crate::error::ContractError;
 use crate::msg::*;
 use crate::state::{
-    DEFAULT_AGENTS_EJECT_THRESHOLD, DEFAULT_MIN_ACTIVE_AGENT_COUNT,
+    AGENT_SLOT_ASSIGNMENTS, DEFAULT_AGENTS_EJECT_THRESHOLD, DEFAULT_MIN_ACTIVE_AGENT_COUNT,
     DEFAULT_MIN_COINS_FOR_AGENT_REGISTRATION, DEFAULT_NOMINATION_BLOCK_DURATION,
 };
 use crate::tests::common::*;
-use cosmwasm_std::{coins, to_binary, Addr, BankMsg, Coin, StdError, Uint128, Uint64, WasmMsg};
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, BankMsg, Coin, StdError, Uint128, Uint64, WasmMsg,
+};
 use croncat_sdk_agents::msg::{
     AgentResponse, ApprovedAgentAddresses, GetAgentIdsResponse, TaskStats,
 };
-use croncat_sdk_agents::types::Config;
+use croncat_sdk_agents::types::{AgentUnbonding, Config};
+use croncat_sdk_core::error::MigrationError;
+use croncat_sdk_core::internal_messages::agents::AgentOnTaskCompleted;
 use croncat_sdk_tasks::types::{Action, Interval, TaskRequest};
 
 use crate::tests::contracts;
@@ -33,6 +37,10 @@ fn test_contract_initialize_is_successful() {
         min_active_agent_count: Some(DEFAULT_MIN_ACTIVE_AGENT_COUNT),
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
     let croncat_agents_addr = app
         .instantiate_contract(
@@ -63,6 +71,10 @@ fn test_contract_initialize_is_successful() {
         min_active_agent_count: Some(DEFAULT_MIN_ACTIVE_AGENT_COUNT),
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
 
     let croncat_agents_addr = app
@@ -100,6 +112,7 @@ fn test_register_agent_is_successful() {
         croncat_agents_addr.clone(),
         &ExecuteMsg::RegisterAgent {
             payable_account_id: Some(ANYONE.to_string()),
+            payout: None,
         },
         &[],
     )
@@ -136,6 +149,7 @@ fn test_register_agent_fails() {
             croncat_agents_addr.clone(),
             &ExecuteMsg::RegisterAgent {
                 payable_account_id: Some(ANYONE.to_string()),
+                payout: None,
             },
             &[Coin {
                 denom: NATIVE_DENOM.to_string(),
@@ -162,6 +176,7 @@ fn test_register_agent_fails() {
             croncat_agents_addr,
             &ExecuteMsg::RegisterAgent {
                 payable_account_id: Some(ANYONE.to_string()),
+                payout: None,
             },
             &[],
         )
@@ -186,6 +201,7 @@ fn test_update_agent_is_successful() {
         croncat_agents_addr.clone(),
         &ExecuteMsg::RegisterAgent {
             payable_account_id: Some(ANYONE.to_string()),
+            payout: None,
         },
         &[],
     )
@@ -196,6 +212,7 @@ fn test_update_agent_is_successful() {
         croncat_agents_addr.clone(),
         &ExecuteMsg::UpdateAgent {
             payable_account_id: ADMIN.to_string(),
+            payout: None,
         },
         &[],
     )
@@ -232,6 +249,7 @@ fn test_update_agent_fails() {
         croncat_agents_addr.clone(),
         &ExecuteMsg::RegisterAgent {
             payable_account_id: Some(ANYONE.to_string()),
+            payout: None,
         },
         &[],
     )
@@ -243,6 +261,7 @@ fn test_update_agent_fails() {
             croncat_agents_addr.clone(),
             &ExecuteMsg::UpdateAgent {
                 payable_account_id: ADMIN.to_string(),
+                payout: None,
             },
             &[],
         )
@@ -329,6 +348,7 @@ fn test_update_agent_fails() {
             croncat_agents_addr,
             &ExecuteMsg::UpdateAgent {
                 payable_account_id: ADMIN.to_string(),
+                payout: None,
             },
             &[],
         )
@@ -1032,6 +1052,10 @@ fn test_tick() {
             agents_eject_threshold: Some(1000), // allow to miss 1000 slots
             min_active_agent_count: Some(1),
             public_registration: Some(true),
+            min_agent_stake: None,
+            agent_slash_percentage: None,
+            agent_unbonding_period: None,
+            task_distribution: None,
         },
     };
 
@@ -1071,6 +1095,23 @@ fn test_tick() {
     )
     .unwrap();
 
+    // Assign the tasks to the agents
+    let res = tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    assert!(!res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "unregister_agent")));
+    let stats: AgentResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgent {
+                account_id: AGENT1.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(stats.agent.unwrap().missed_blocked_tasks, Uint64::zero());
+
     app.update_block(|info| increment_block_height(info, Some(1001)));
     app.update_block(|info| add_seconds_to_block(info, 19));
 
@@ -1089,7 +1130,7 @@ fn test_tick() {
         .iter()
         .any(|attr| attr.key == "account_id" && attr.value == AGENT1)));
 
-    // The agent missed 1001 blocks and he was unregistered
+    // The agent missed 1001 slots without serving its tasks and he was unregistered
     // Pending agents weren't deleted
     let agents: GetAgentIdsResponse = app
         .wrap()
@@ -1103,6 +1144,17 @@ fn test_tick() {
         .unwrap();
     assert_eq!(agents.active.len(), 1);
     assert!(agents.pending.is_empty());
+    // Remaining agent didn't serve its tasks either
+    let stats: AgentResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgent {
+                account_id: AGENT0.to_owned(),
+            },
+        )
+        .unwrap();
+    assert!(!stats.agent.unwrap().missed_blocked_tasks.is_zero());
 
     register_agent(&mut app, &croncat_agents_addr, AGENT1, AGENT_BENEFICIARY).unwrap();
     register_agent(&mut app, &croncat_agents_addr, AGENT2, AGENT_BENEFICIARY).unwrap();
@@ -1263,6 +1315,10 @@ fn test_tick_respects_min_active_agent_count() {
         .unwrap();
     assert_eq!(agents.active.len(), 2);
 
+    // Assign the tasks to the agents
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+
+    app.update_block(|info| increment_block_height(info, Some(1001)));
     app.update_block(|info| add_seconds_to_block(info, 1000));
 
     tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
@@ -1283,6 +1339,84 @@ fn test_tick_respects_min_active_agent_count() {
 }
 
 /// Incorrectly instantiate the agents contract in a couple ways
+#[test]
+fn test_tick_missed_slots_in_proportion() {
+    let mut app = default_app();
+
+    let TestScope {
+        croncat_factory_addr: _,
+        croncat_agents_addr,
+        croncat_agents_code_id: _,
+        croncat_manager_addr,
+        croncat_tasks_addr,
+    } = init_test_scope(&mut app);
+
+    register_agent(&mut app, &croncat_agents_addr, AGENT1, AGENT1).unwrap();
+    create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT0).unwrap();
+    create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT1).unwrap();
+    app.update_block(|info| increment_block_height(info, Some(1)));
+
+    // Both tasks of the slot are assigned to the only agent
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    let assignment = AGENT_SLOT_ASSIGNMENTS
+        .query(
+            &app.wrap(),
+            croncat_agents_addr.clone(),
+            &Addr::unchecked(AGENT1),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(assignment.block_tasks, 2);
+
+    // Tick in the next block doesn't count the tasks agent had no chance to serve yet
+    app.update_block(|info| increment_block_height(info, Some(1)));
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    let next_assignment = AGENT_SLOT_ASSIGNMENTS
+        .query(
+            &app.wrap(),
+            croncat_agents_addr.clone(),
+            &Addr::unchecked(AGENT1),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(next_assignment, assignment);
+
+    // Agent serves one of them
+    app.execute_contract(
+        croncat_manager_addr,
+        croncat_agents_addr.clone(),
+        &ExecuteMsg::OnTaskCompleted(AgentOnTaskCompleted {
+            is_block_slot_task: true,
+            agent_id: Addr::unchecked(AGENT1),
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Half of the passed slots are missed
+    app.update_block(|info| increment_block_height(info, Some(9)));
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    let assignment = AGENT_SLOT_ASSIGNMENTS
+        .query(
+            &app.wrap(),
+            croncat_agents_addr.clone(),
+            &Addr::unchecked(AGENT1),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(assignment.missed_slots, 5);
+    let agent: AgentResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr,
+            &QueryMsg::GetAgent {
+                account_id: AGENT1.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(agent.agent.unwrap().missed_blocked_tasks, Uint64::one());
+}
+
 #[test]
 fn check_validation_instantiate() {
     let mut app = default_app();
@@ -1317,6 +1451,10 @@ fn check_validation_instantiate() {
         min_active_agent_count: None,
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
 
     // Check agent_nomination_duration
@@ -1495,6 +1633,10 @@ fn check_validation_update_config() {
             min_active_agent_count: None,
             allowed_agents: Some(vec![]),
             public_registration: true,
+            min_agent_stake: None,
+            agent_slash_percentage: None,
+            agent_unbonding_period: None,
+            task_distribution: None,
         })
         .unwrap(),
         contract_name: "agents".to_owned(),
@@ -1539,6 +1681,10 @@ fn check_validation_update_config() {
         min_active_agent_count: None,
         min_coins_for_agent_registration: None,
         public_registration: Some(true),
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
 
     let mut update_config_exec_msg = ExecuteMsg::UpdateConfig {
@@ -1669,6 +1815,10 @@ fn pause_admin_cases() {
         min_active_agent_count: None,
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
     // Attempt to initialize with short address for pause_admin
     let mut init_agent_contract_msg_short_addr = init_agent_contract_msg.clone();
@@ -1924,6 +2074,10 @@ fn test_agent_registration_whitelist() {
         allowed_agents: Some(vec![String::from("Foo")]),
         // Note: this is different than most tests
         public_registration: false,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
     let agents_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
         code_id: agents_code_id,
@@ -2025,6 +2179,7 @@ fn test_agent_registration_whitelist() {
             croncat_agents_addr.clone(),
             &ExecuteMsg::RegisterAgent {
                 payable_account_id: None,
+                payout: None,
             },
             &[],
         )
@@ -2066,6 +2221,7 @@ fn test_agent_registration_whitelist() {
             croncat_agents_addr.clone(),
             &ExecuteMsg::RegisterAgent {
                 payable_account_id: None,
+                payout: None,
             },
             &[],
         )
@@ -2145,6 +2301,10 @@ fn test_agent_registration_whitelist() {
                             agents_eject_threshold: None,
                             min_active_agent_count: None,
                             public_registration: Some(true),
+                            min_agent_stake: None,
+                            agent_slash_percentage: None,
+                            agent_unbonding_period: None,
+                            task_distribution: None,
                         },
                     })
                     .unwrap(),
@@ -2194,6 +2354,10 @@ fn test_agent_registration_whitelist() {
                             min_active_agent_count: None,
                             // This is prohibited once progressive decentralization has begun
                             public_registration: Some(false),
+                            min_agent_stake: None,
+                            agent_slash_percentage: None,
+                            agent_unbonding_period: None,
+                            task_distribution: None,
                         },
                     })
                     .unwrap(),
@@ -2212,6 +2376,408 @@ fn test_agent_registration_whitelist() {
     );
 }
 
+#[test]
+fn migrate_agents() {
+    let mut app = default_app();
+    let TestScope {
+        croncat_factory_addr,
+        croncat_agents_addr,
+        croncat_agents_code_id,
+        ..
+    } = init_test_scope(&mut app);
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            croncat_factory_addr,
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+                msg: WasmMsg::Migrate {
+                    contract_addr: croncat_agents_addr.to_string(),
+                    new_code_id: croncat_agents_code_id.unwrap(),
+                    msg: to_binary(&MigrateMsg {}).unwrap(),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "from_version" && attr.value == "0.1")));
+    let contract_version = cw2::CONTRACT
+        .query(&app.wrap(), croncat_agents_addr)
+        .unwrap();
+    assert_eq!(contract_version.contract, "crate:croncat-agents");
+    assert_eq!(contract_version.version, env!("CARGO_PKG_VERSION"));
+
+    // Can't migrate to the older version
+    let init_msg = InstantiateMsg {
+        version: Some("99.0".to_owned()),
+        pause_admin: Addr::unchecked(PAUSE_ADMIN),
+        agent_nomination_duration: None,
+        min_tasks_per_agent: None,
+        croncat_manager_key: ("manager".to_owned(), [0, 1]),
+        croncat_tasks_key: ("tasks".to_owned(), [0, 1]),
+        min_coins_for_agent_registration: None,
+        agents_eject_threshold: None,
+        min_active_agent_count: None,
+        allowed_agents: None,
+        public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
+    };
+    let newer_agents_addr = app
+        .instantiate_contract(
+            croncat_agents_code_id.unwrap(),
+            Addr::unchecked(ADMIN),
+            &init_msg,
+            &[],
+            "agents",
+            Some(ADMIN.to_owned()),
+        )
+        .unwrap();
+    let err: ContractError = app
+        .migrate_contract(
+            Addr::unchecked(ADMIN),
+            newer_agents_addr,
+            &MigrateMsg {},
+            croncat_agents_code_id.unwrap(),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Migration(MigrationError::InvalidMigrationVersion {
+            from: "99.0".to_owned(),
+            to: env!("CARGO_PKG_VERSION").to_owned()
+        })
+    );
+}
+
+#[test]
+fn agent_stake_slashing_and_unbonding() {
+    let mut app = default_app();
+
+    let TestScope {
+        croncat_factory_addr,
+        croncat_agents_addr,
+        croncat_agents_code_id: _,
+        croncat_manager_addr: _,
+        croncat_tasks_addr,
+    } = init_test_scope(&mut app);
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: UpdateConfig {
+            croncat_manager_key: None,
+            croncat_tasks_key: None,
+            min_tasks_per_agent: Some(2),
+            min_coins_for_agent_registration: None,
+            agent_nomination_duration: None,
+            agents_eject_threshold: Some(1000),
+            min_active_agent_count: Some(1),
+            public_registration: None,
+            min_agent_stake: Some(100_000),
+            agent_slash_percentage: Some(10),
+            agent_unbonding_period: Some(1000),
+            task_distribution: None,
+        },
+    };
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        croncat_factory_addr.clone(),
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: croncat_agents_addr.to_string(),
+                msg: to_binary(&update_config_msg).unwrap(),
+                funds: vec![],
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Stake is required
+    let error: ContractError = register_agent(&mut app, &croncat_agents_addr, AGENT1, AGENT1)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        error,
+        ContractError::InsufficientStake {
+            amount_needed: coin(100_000, NATIVE_DENOM),
+        }
+    );
+    for agent in [AGENT1, AGENT0] {
+        if agent == AGENT0 {
+            // Before we can register the second agent, we need to make sure there are enough tasks
+            create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT0).unwrap();
+            create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT1).unwrap();
+            create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT2).unwrap();
+        }
+        app.execute_contract(
+            Addr::unchecked(agent),
+            croncat_agents_addr.clone(),
+            &ExecuteMsg::RegisterAgent {
+                payable_account_id: None,
+                payout: None,
+            },
+            &coins(100_000, NATIVE_DENOM),
+        )
+        .unwrap();
+    }
+    let agent_response: AgentResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgent {
+                account_id: AGENT1.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_response.agent.unwrap().stake, Uint128::new(100_000));
+
+    app.update_block(|info| increment_block_height(info, Some(30)));
+    app.update_block(|info| add_seconds_to_block(info, 180));
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        croncat_agents_addr.clone(),
+        &ExecuteMsg::CheckInAgent {},
+        &[],
+    )
+    .unwrap();
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+
+    // Ejected agent gets slashed to the treasury, the rest of the stake is unbonding
+    app.update_block(|info| increment_block_height(info, Some(1001)));
+    app.update_block(|info| add_seconds_to_block(info, 19));
+    let treasury_balance = app
+        .wrap()
+        .query_balance(&croncat_factory_addr, NATIVE_DENOM)
+        .unwrap();
+    tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    let new_treasury_balance = app
+        .wrap()
+        .query_balance(&croncat_factory_addr, NATIVE_DENOM)
+        .unwrap();
+    assert_eq!(
+        new_treasury_balance.amount,
+        treasury_balance.amount + Uint128::new(10_000)
+    );
+
+    let block_time = app.block_info().time;
+    let unbonding: Option<AgentUnbonding> = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgentUnbonding {
+                account_id: AGENT1.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        unbonding,
+        Some(AgentUnbonding {
+            amount: Uint128::new(90_000),
+            release_time: block_time.plus_seconds(1000),
+        })
+    );
+
+    // Stake is locked for the unbonding period
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT1),
+            croncat_agents_addr.clone(),
+            &ExecuteMsg::WithdrawUnbondedStake {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(error, ContractError::NoUnbondedStake {});
+
+    app.update_block(|info| add_seconds_to_block(info, 1000));
+    let agent_balance = app.wrap().query_balance(AGENT1, NATIVE_DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT1),
+        croncat_agents_addr.clone(),
+        &ExecuteMsg::WithdrawUnbondedStake {},
+        &[],
+    )
+    .unwrap();
+    let new_agent_balance = app.wrap().query_balance(AGENT1, NATIVE_DENOM).unwrap();
+    assert_eq!(
+        new_agent_balance.amount,
+        agent_balance.amount + Uint128::new(90_000)
+    );
+    let unbonding: Option<AgentUnbonding> = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgentUnbonding {
+                account_id: AGENT1.to_owned(),
+            },
+        )
+        .unwrap();
+    assert!(unbonding.is_none());
+
+    // Agent missing the slots can't avoid slashing by unregistering itself
+    app.update_block(|info| increment_block_height(info, Some(1001)));
+    let treasury_balance = app
+        .wrap()
+        .query_balance(&croncat_factory_addr, NATIVE_DENOM)
+        .unwrap();
+    unregister_agent(&mut app, &croncat_agents_addr, AGENT0).unwrap();
+    let new_treasury_balance = app
+        .wrap()
+        .query_balance(&croncat_factory_addr, NATIVE_DENOM)
+        .unwrap();
+    assert_eq!(
+        new_treasury_balance.amount,
+        treasury_balance.amount + Uint128::new(10_000)
+    );
+    let unbonding: Option<AgentUnbonding> = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr,
+            &QueryMsg::GetAgentUnbonding {
+                account_id: AGENT0.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(unbonding.unwrap().amount, Uint128::new(90_000));
+}
+
+#[test]
+fn agents_registered_before_stake_must_bond() {
+    let mut app = default_app();
+
+    let TestScope {
+        croncat_factory_addr,
+        croncat_agents_addr,
+        croncat_agents_code_id: _,
+        croncat_manager_addr: _,
+        croncat_tasks_addr,
+    } = init_test_scope(&mut app);
+
+    // Both agents registered without the stake
+    register_agent(&mut app, &croncat_agents_addr, AGENT1, AGENT1).unwrap();
+    create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT0).unwrap();
+    create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT1).unwrap();
+    create_task(&mut app, croncat_tasks_addr.as_ref(), ADMIN, PARTICIPANT2).unwrap();
+    register_agent(&mut app, &croncat_agents_addr, AGENT0, AGENT_BENEFICIARY).unwrap();
+    app.update_block(|info| increment_block_height(info, Some(30)));
+    app.update_block(|info| add_seconds_to_block(info, 180));
+
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: UpdateConfig {
+            croncat_manager_key: None,
+            croncat_tasks_key: None,
+            min_tasks_per_agent: None,
+            min_coins_for_agent_registration: None,
+            agent_nomination_duration: None,
+            agents_eject_threshold: None,
+            min_active_agent_count: Some(1),
+            public_registration: None,
+            min_agent_stake: Some(100_000),
+            agent_slash_percentage: None,
+            agent_unbonding_period: None,
+            task_distribution: None,
+        },
+    };
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        croncat_factory_addr,
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: croncat_agents_addr.to_string(),
+                msg: to_binary(&update_config_msg).unwrap(),
+                funds: vec![],
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Pending agent can't check in until it bonds the stake
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            croncat_agents_addr.clone(),
+            &ExecuteMsg::CheckInAgent {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        error,
+        ContractError::InsufficientStake {
+            amount_needed: coin(100_000, NATIVE_DENOM),
+        }
+    );
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            croncat_agents_addr.clone(),
+            &ExecuteMsg::BondStake {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        error,
+        ContractError::InsufficientStake {
+            amount_needed: coin(100_000, NATIVE_DENOM),
+        }
+    );
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        croncat_agents_addr.clone(),
+        &ExecuteMsg::BondStake {},
+        &coins(100_000, NATIVE_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        croncat_agents_addr.clone(),
+        &ExecuteMsg::CheckInAgent {},
+        &[],
+    )
+    .unwrap();
+
+    // Active agent without the stake is removed on tick, without slashing
+    let res = tick(&mut app, &croncat_agents_addr, ANYONE).unwrap();
+    assert!(!res
+        .events
+        .iter()
+        .any(|ev| ev.attributes.iter().any(|attr| attr.key == "slashed_stake")));
+    let agents: GetAgentIdsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr.clone(),
+            &QueryMsg::GetAgentIds {
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(agents.active, vec![Addr::unchecked(AGENT0)]);
+    let agent_response: AgentResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_agents_addr,
+            &QueryMsg::GetAgent {
+                account_id: AGENT0.to_owned(),
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_response.agent.unwrap().stake, Uint128::new(100_000));
+}
+
 fn register_agent(
     app: &mut App,
     croncat_agents_addr: &Addr,
@@ -2223,6 +2789,7 @@ fn register_agent(
         croncat_agents_addr.clone(),
         &ExecuteMsg::RegisterAgent {
             payable_account_id: Some(beneficiary.to_string()),
+            payout: None,
         },
         &[],
     )
@@ -2334,6 +2901,9 @@ fn create_task(
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let request = TaskRequest {
         interval: Interval::Immediate,
@@ -2343,6 +2913,10 @@ fn create_task(
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(sender),
@@ -2368,6 +2942,9 @@ fn add_block_task_exec(
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let request = TaskRequest {
         interval: Interval::Block(block_num),
@@ -2377,6 +2954,10 @@ fn add_block_task_exec(
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(sender),
@@ -2403,6 +2984,9 @@ fn add_cron_task_exec(
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let request = TaskRequest {
         interval: Interval::Cron(format!("* {} * * * *", num_minutes)),
@@ -2412,6 +2996,10 @@ fn add_cron_task_exec(
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(sender),
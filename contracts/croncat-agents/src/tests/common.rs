@@ -1,11 +1,13 @@
 use crate::msg::*;
 use crate::state::{
-    DEFAULT_MIN_ACTIVE_AGENT_COUNT, DEFAULT_MIN_COINS_FOR_AGENT_REGISTRATION,
-    DEFAULT_MIN_TASKS_PER_AGENT, DEFAULT_NOMINATION_BLOCK_DURATION,
+    DEFAULT_AGENT_SLASH_PERCENTAGE, DEFAULT_AGENT_UNBONDING_PERIOD, DEFAULT_MIN_ACTIVE_AGENT_COUNT,
+    DEFAULT_MIN_AGENT_STAKE, DEFAULT_MIN_COINS_FOR_AGENT_REGISTRATION, DEFAULT_MIN_TASKS_PER_AGENT,
+    DEFAULT_NOMINATION_BLOCK_DURATION,
 };
 use crate::tests::contracts;
 use cosmwasm_std::{coins, to_binary, Addr};
 use cosmwasm_std::{BlockInfo, Coin, Uint128};
+use croncat_sdk_agents::types::TaskDistribution;
 use croncat_sdk_factory::msg::{
     ContractMetadataResponse, FactoryExecuteMsg, FactoryInstantiateMsg, ModuleInstantiateInfo,
     VersionKind,
@@ -67,6 +69,10 @@ pub(crate) fn mock_config(croncat_factory_addr: &str) -> Config {
         agents_eject_threshold: 600,
         min_active_agent_count: DEFAULT_MIN_ACTIVE_AGENT_COUNT,
         public_registration: true,
+        min_agent_stake: DEFAULT_MIN_AGENT_STAKE,
+        agent_slash_percentage: DEFAULT_AGENT_SLASH_PERCENTAGE,
+        agent_unbonding_period: DEFAULT_AGENT_UNBONDING_PERIOD,
+        task_distribution: TaskDistribution::RoundRobin,
     }
 }
 
@@ -80,6 +86,10 @@ pub(crate) fn mock_update_config(_croncat_factory_addr: &str) -> UpdateConfig {
         agents_eject_threshold: None,
         min_active_agent_count: None,
         public_registration: Some(true),
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     }
 }
 
@@ -192,6 +202,10 @@ pub(crate) fn init_test_scope(app: &mut App) -> TestScope {
             min_active_agent_count: None,
             allowed_agents: Some(vec![]),
             public_registration: true,
+            min_agent_stake: None,
+            agent_slash_percentage: None,
+            agent_unbonding_period: None,
+            task_distribution: None,
         })
         .unwrap(),
         contract_name: "agents".to_owned(),
@@ -36,6 +36,7 @@ pub(crate) fn croncat_agents_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_migrate(crate::contract::migrate);
     Box::new(contract)
 }
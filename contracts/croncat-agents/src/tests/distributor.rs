@@ -1,13 +1,15 @@
-use croncat_sdk_agents::types::Config;
+use croncat_sdk_agents::types::{Agent, AgentStats, Config};
 use croncat_sdk_tasks::types::SlotType;
 
-use crate::distributor::{AgentTaskDistributor, RoundRobinAgentTaskDistributor};
-use crate::state::{AGENTS_ACTIVE, AGENT_STATS};
+use crate::distributor::{
+    AgentTaskDistributor, RoundRobinAgentTaskDistributor, WeightedAgentTaskDistributor,
+};
+use crate::state::{AGENTS, AGENTS_ACTIVE, AGENT_STATS, CONFIG};
 use crate::tests::common::{AGENT0, AGENT1, AGENT2, AGENT3, AGENT4, AGENT5};
 use cosmwasm_std::testing::{
     mock_dependencies_with_balance, mock_env, MockApi, MockQuerier, MockStorage,
 };
-use cosmwasm_std::{coins, Addr, Empty, Env, MemoryStorage, OwnedDeps};
+use cosmwasm_std::{coins, Addr, Empty, Env, MemoryStorage, OwnedDeps, Uint128};
 
 use super::common::{mock_config, NATIVE_DENOM};
 
@@ -333,3 +335,155 @@ fn test_on_agent_unregister() {
     assert!(stats0.is_ok());
     assert!(stats1.is_err());
 }
+
+/// Runs the weighted distributor for the given number of rounds, agents complete every slot they get.
+/// Returns the total block tasks of every agent
+fn simulate_weighted_slots(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    env: &Env,
+    agents: &[&str],
+    rounds: u64,
+    block_slots: u64,
+) -> Vec<u64> {
+    let task_distributor = WeightedAgentTaskDistributor::new();
+    let mut totals = vec![0u64; agents.len()];
+    for _ in 0..rounds {
+        let slots: Vec<u64> = agents
+            .iter()
+            .map(|agent| {
+                task_distributor
+                    .get_agent_tasks(
+                        &deps.as_ref(),
+                        env,
+                        Addr::unchecked(*agent),
+                        (Some(block_slots), None),
+                    )
+                    .unwrap()
+                    .stats
+                    .num_block_tasks
+                    .u64()
+            })
+            .collect();
+        // Every slot is covered exactly once
+        assert_eq!(slots.iter().sum::<u64>(), block_slots);
+        for (index, agent) in agents.iter().enumerate() {
+            for _ in 0..slots[index] {
+                task_distributor
+                    .on_task_completed(
+                        &mut deps.storage,
+                        env,
+                        &Addr::unchecked(*agent),
+                        SlotType::Block,
+                    )
+                    .unwrap();
+            }
+            totals[index] += slots[index];
+        }
+    }
+    totals
+}
+
+fn save_weighted_agents(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    env: &Env,
+    min_agent_stake: u64,
+    agents: &[(&str, u64, u64)],
+) {
+    let mut config = mock_config("");
+    config.min_agent_stake = min_agent_stake;
+    CONFIG.save(&mut deps.storage, &config).unwrap();
+
+    let active: Vec<Addr> = agents.iter().map(|a| Addr::unchecked(a.0)).collect();
+    AGENTS_ACTIVE.save(&mut deps.storage, &active).unwrap();
+    for (agent, missed_blocked_tasks, stake) in agents {
+        let agent_addr = Addr::unchecked(*agent);
+        AGENTS
+            .save(
+                &mut deps.storage,
+                &agent_addr,
+                &Agent {
+                    payable_account_id: agent_addr.clone(),
+                    register_start: env.block.time,
+                    stake: Uint128::from(*stake),
+                    payout: None,
+                },
+            )
+            .unwrap();
+        AGENT_STATS
+            .save(
+                &mut deps.storage,
+                &agent_addr,
+                &AgentStats {
+                    missed_blocked_tasks: *missed_blocked_tasks,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_weighted_distributor_equal_agents() {
+    let mut deps = mock_dependencies_with_balance(&coins(200, NATIVE_DENOM));
+    let env = mock_env();
+    save_weighted_agents(
+        &mut deps,
+        &env,
+        0,
+        &[(AGENT0, 0, 0), (AGENT1, 0, 0), (AGENT2, 0, 0)],
+    );
+
+    // Without missed slots it's the same as round robin
+    let totals = simulate_weighted_slots(&mut deps, &env, &[AGENT0, AGENT1, AGENT2], 300, 1);
+    assert_eq!(totals, vec![100, 100, 100]);
+
+    let totals = simulate_weighted_slots(&mut deps, &env, &[AGENT0, AGENT1, AGENT2], 100, 7);
+    assert_eq!(totals, vec![234, 233, 233]);
+}
+
+#[test]
+fn test_weighted_distributor_penalizes_missed_slots() {
+    let mut deps = mock_dependencies_with_balance(&coins(200, NATIVE_DENOM));
+    let env = mock_env();
+    save_weighted_agents(
+        &mut deps,
+        &env,
+        0,
+        &[(AGENT0, 0, 0), (AGENT1, 50, 0), (AGENT2, 0, 0)],
+    );
+
+    let totals = simulate_weighted_slots(&mut deps, &env, &[AGENT0, AGENT1, AGENT2], 300, 1);
+    // Flawless agents split the slots evenly
+    assert!(totals[0].abs_diff(totals[2]) <= 1);
+    // Agent that missed slots gets a smaller share, but isn't starved
+    assert!(totals[1] > 0);
+    assert!(totals[1] < totals[0]);
+    assert!(totals[1] < totals[2]);
+
+    // Not registered agent
+    let error = WeightedAgentTaskDistributor::new()
+        .get_agent_tasks(
+            &deps.as_ref(),
+            &env,
+            Addr::unchecked(AGENT3),
+            (Some(1), None),
+        )
+        .unwrap_err();
+    assert_eq!(error, crate::error::ContractError::AgentNotRegistered {});
+}
+
+#[test]
+fn test_weighted_distributor_stake() {
+    let mut deps = mock_dependencies_with_balance(&coins(200, NATIVE_DENOM));
+    let env = mock_env();
+    // Stake above the cap counts as twice the minimum
+    save_weighted_agents(
+        &mut deps,
+        &env,
+        100,
+        &[(AGENT0, 0, 500), (AGENT1, 0, 100), (AGENT2, 0, 100)],
+    );
+
+    let totals = simulate_weighted_slots(&mut deps, &env, &[AGENT0, AGENT1, AGENT2], 400, 1);
+    assert_eq!(totals, vec![200, 100, 100]);
+}
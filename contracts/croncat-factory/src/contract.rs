@@ -7,6 +7,7 @@ use cosmwasm_std::{
     to_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult,
     Storage, SubMsg, WasmMsg,
 };
+use croncat_sdk_core::version::{migrate_contract, StateMigration};
 use croncat_sdk_factory::msg::{
     ContractMetadata, ContractMetadataInfo, ContractMetadataResponse, EntryResponse,
     ModuleInstantiateInfo, VersionKind,
@@ -15,7 +16,7 @@ use cw2::set_contract_version;
 use cw_utils::parse_reply_instantiate_data;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::state::{
     Config, TempReply, CONFIG, CONTRACT_ADDRS, CONTRACT_ADDRS_LOOKUP, CONTRACT_METADATAS,
     LATEST_ADDRS, LATEST_VERSIONS, MAX_URL_LENGTH, TEMP_REPLY,
@@ -25,6 +26,8 @@ use crate::state::{
 const CONTRACT_NAME: &str = "crate:croncat-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const STATE_MIGRATIONS: &[(&str, StateMigration<ContractError>)] = &[];
+
 /// Save metadata and generate wasm msg
 /// Note: this will override contract metadata if same contract name and version was stored already
 fn init_save_metadata_generate_wasm_msg(
@@ -321,6 +324,18 @@ fn execute_remove_nominate_owner(deps: DepsMut) -> Result<Response, ContractErro
     Ok(Response::new().add_attribute("action", "remove_nominate_owner"))
 }
 
+/// Migrate
+/// See [`migrate_contract`] for the allowed migrations
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_contract(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        STATE_MIGRATIONS,
+    )
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use croncat_sdk_core::error::MigrationError;
 use cw_utils::ParseReplyError;
 use thiserror::Error;
 
@@ -33,4 +34,7 @@ pub enum ContractError {
 
     #[error("Must not nominate current owner")]
     SameOwnerNominated {},
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
 }
@@ -1,4 +1,4 @@
 pub use croncat_sdk_factory::msg::{
     FactoryExecuteMsg as ExecuteMsg, FactoryInstantiateMsg as InstantiateMsg,
-    FactoryQueryMsg as QueryMsg,
+    FactoryMigrateMsg as MigrateMsg, FactoryQueryMsg as QueryMsg,
 };
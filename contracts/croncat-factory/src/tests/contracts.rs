@@ -8,6 +8,7 @@ pub(crate) fn croncat_factory_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::instantiate,
         crate::contract::query,
     )
+    .with_migrate(crate::contract::migrate)
     .with_reply(crate::contract::reply);
     Box::new(contract)
 }
@@ -17,7 +18,8 @@ pub(crate) fn croncat_tasks_contract() -> Box<dyn Contract<Empty>> {
         croncat_tasks::contract::execute,
         croncat_tasks::contract::instantiate,
         croncat_tasks::contract::query,
-    );
+    )
+    .with_migrate(croncat_tasks::contract::migrate);
     Box::new(contract)
 }
 
@@ -27,6 +29,7 @@ pub(crate) fn croncat_manager_contract() -> Box<dyn Contract<Empty>> {
         croncat_manager::contract::instantiate,
         croncat_manager::contract::query,
     )
+    .with_migrate(croncat_manager::contract::migrate)
     .with_reply(croncat_manager::contract::reply);
     Box::new(contract)
 }
@@ -36,7 +39,8 @@ pub(crate) fn croncat_agents_contract() -> Box<dyn Contract<Empty>> {
         croncat_agents::contract::execute,
         croncat_agents::contract::instantiate,
         croncat_agents::contract::query,
-    );
+    )
+    .with_migrate(croncat_agents::contract::migrate);
     Box::new(contract)
 }
 
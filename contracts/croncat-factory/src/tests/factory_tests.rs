@@ -1,5 +1,5 @@
-use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{to_binary, Addr, Binary, StdError, WasmMsg};
+use croncat_sdk_core::error::MigrationError;
 use croncat_sdk_core::types::GasPrice;
 use croncat_sdk_factory::msg::FactoryExecuteMsg::UpdateMetadata;
 use croncat_sdk_factory::msg::{
@@ -195,6 +195,10 @@ fn deploy_check() {
             min_active_agent_count: None,
             allowed_agents: Some(vec![]),
             public_registration: true,
+            min_agent_stake: None,
+            agent_slash_percentage: None,
+            agent_unbonding_period: None,
+            task_distribution: None,
         })
         .unwrap(),
         contract_name: "agents".to_owned(),
@@ -1042,9 +1046,6 @@ fn fail_and_success_proxy() {
     let manager_code_id = app.store_code(contracts::croncat_manager_contract());
     let manager_code_id_for_migrate = app.store_code(contracts::croncat_manager_contract());
 
-    #[cw_serde]
-    pub(crate) struct MigrateMsg {}
-
     let init_msg = InstantiateMsg {
         owner_addr: Some(ADMIN.to_owned()),
     };
@@ -1117,10 +1118,13 @@ fn fail_and_success_proxy() {
                     agent_fee: None,
                     treasury_fee: Some(10), // simulate moving to 0.01%
                     gas_price: None,
+                    gas_price_source: None,
                     croncat_tasks_key: None,
                     croncat_agents_key: None,
                     treasury_addr: None,
                     cw20_whitelist: None,
+                    gas_denoms: None,
+                    unbonding_period: None,
                 }),
             ))
             .unwrap(),
@@ -1130,8 +1134,8 @@ fn fail_and_success_proxy() {
 
     let proxy_migrate_msg = FactoryExecuteMsg::Proxy {
         msg: WasmMsg::Migrate {
-            contract_addr: manager_addr,
-            msg: to_binary(&MigrateMsg {}).unwrap(),
+            contract_addr: manager_addr.clone(),
+            msg: to_binary(&croncat_manager::msg::MigrateMsg {}).unwrap(),
             new_code_id: manager_code_id_for_migrate,
         },
     };
@@ -1154,10 +1158,13 @@ fn fail_and_success_proxy() {
                     agent_fee: None,
                     treasury_fee: Some(10), // simulate moving to 0.01%
                     gas_price: None,
+                    gas_price_source: None,
                     croncat_tasks_key: None,
                     croncat_agents_key: None,
                     treasury_addr: None,
                     cw20_whitelist: None,
+                    gas_denoms: None,
+                    unbonding_period: None,
                 }),
             ))
             .unwrap(),
@@ -1219,15 +1226,144 @@ fn fail_and_success_proxy() {
     assert_eq!(res.events[3].attributes[1].value, "update_config");
 
     // Okay ill let you migrate thangs
-    let res = app.execute_contract(
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &proxy_migrate_msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(res.events[1].attributes[2].value, "migrate");
+    let contract_version = cw2::CONTRACT
+        .query(&app.wrap(), Addr::unchecked(&manager_addr))
+        .unwrap();
+    assert_eq!(contract_version.contract, "crate:croncat-manager");
+    assert_eq!(contract_version.version, "1.1.0");
+
+    // Can't migrate back to the older version
+    let manager_module_instantiate_info = ModuleInstantiateInfo {
+        code_id: manager_code_id,
+        version: [0, 2],
+        commit_id: "some".to_owned(),
+        checksum: "qwe123".to_owned(),
+        changelog_url: None,
+        schema: None,
+        msg: to_binary(&croncat_manager::msg::InstantiateMsg {
+            version: Some("99.0".to_owned()),
+            croncat_tasks_key: ("tasks".to_owned(), [0, 1]),
+            croncat_agents_key: ("agents".to_owned(), [0, 1]),
+            pause_admin: Addr::unchecked(PAUSE_ADMIN),
+            gas_price: None,
+            treasury_addr: None,
+            cw20_whitelist: None,
+        })
+        .unwrap(),
+        contract_name: "manager".to_owned(),
+    };
+    app.execute_contract(
         Addr::unchecked(ADMIN),
-        contract_addr,
-        &proxy_migrate_msg,
-        &[],
+        contract_addr.clone(),
+        &FactoryExecuteMsg::Deploy {
+            kind: VersionKind::Manager,
+            module_instantiate_info: manager_module_instantiate_info,
+        },
+        &[get_manager_instantiate_denom_fee()],
+    )
+    .unwrap();
+    let manager_metadata: ContractMetadataResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::LatestContract {
+                contract_name: "manager".to_string(),
+            },
+        )
+        .unwrap();
+    let proxy_downgrade_msg = FactoryExecuteMsg::Proxy {
+        msg: WasmMsg::Migrate {
+            contract_addr: manager_metadata.metadata.unwrap().contract_addr.to_string(),
+            msg: to_binary(&croncat_manager::msg::MigrateMsg {}).unwrap(),
+            new_code_id: manager_code_id,
+        },
+    };
+    let err: croncat_manager::ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr,
+            &proxy_downgrade_msg,
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        croncat_manager::ContractError::Migration(MigrationError::InvalidMigrationVersion {
+            from: "99.0".to_owned(),
+            to: "1.1.0".to_owned()
+        })
     );
-    // NOTE: we must assert error here since the contract doesnt implement migration
-    // once there is a migration needed, this can check for the success of such migration.
-    assert!(res.is_err());
+}
+
+#[test]
+fn migrate_factory() {
+    let mut app = default_app();
+    let contract_code_id = app.store_code(contracts::croncat_factory_contract());
+    let manager_code_id = app.store_code(contracts::croncat_manager_contract());
+
+    let init_msg = InstantiateMsg {
+        owner_addr: Some(ADMIN.to_owned()),
+    };
+    let contract_addr = app
+        .instantiate_contract(
+            contract_code_id,
+            Addr::unchecked(ADMIN),
+            &init_msg,
+            &[],
+            "factory",
+            Some(ADMIN.to_owned()),
+        )
+        .unwrap();
+
+    // Can't migrate to the different contract
+    let err: croncat_manager::ContractError = app
+        .migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &croncat_manager::msg::MigrateMsg {},
+            manager_code_id,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        croncat_manager::ContractError::Migration(MigrationError::InvalidMigrationContract {
+            contract: "crate:croncat-factory".to_owned()
+        })
+    );
+
+    let res = app
+        .migrate_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr.clone(),
+            &MigrateMsg {},
+            contract_code_id,
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "migrate")
+    }));
+
+    // State untouched
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner_addr, Addr::unchecked(ADMIN));
 }
 
 /// Tests the changelog_url for validity
@@ -0,0 +1,64 @@
+use cosmwasm_std::{Addr, BankMsg, Binary, Coin, CosmosMsg, WasmMsg};
+
+use crate::{
+    proto::{encode_bytes_field, encode_string_field},
+    ContractError,
+};
+
+const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+const MSG_SEND_TYPE_URL: &str = "/cosmos.bank.v1beta1.MsgSend";
+const MSG_EXECUTE_CONTRACT_TYPE_URL: &str = "/cosmwasm.wasm.v1.MsgExecuteContract";
+
+fn encode_coin_field(field: u32, coin: &Coin, buf: &mut Vec<u8>) {
+    let mut value = vec![];
+    encode_string_field(1, &coin.denom, &mut value);
+    encode_string_field(2, &coin.amount.to_string(), &mut value);
+    encode_bytes_field(field, &value, buf);
+}
+
+/// Action of the task executed on behalf of the task owner,
+/// manager is the grantee of the owner's authorization
+pub(crate) fn authz_exec_msg(
+    manager_addr: &Addr,
+    owner_addr: &Addr,
+    msg: &CosmosMsg,
+) -> Result<CosmosMsg, ContractError> {
+    let (type_url, value) = match msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            let mut value = vec![];
+            encode_string_field(1, owner_addr.as_str(), &mut value);
+            encode_string_field(2, to_address, &mut value);
+            for coin in amount {
+                encode_coin_field(3, coin, &mut value);
+            }
+            (MSG_SEND_TYPE_URL, value)
+        }
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds,
+        }) => {
+            let mut value = vec![];
+            encode_string_field(1, owner_addr.as_str(), &mut value);
+            encode_string_field(2, contract_addr, &mut value);
+            encode_bytes_field(3, msg, &mut value);
+            for coin in funds {
+                encode_coin_field(5, coin, &mut value);
+            }
+            (MSG_EXECUTE_CONTRACT_TYPE_URL, value)
+        }
+        _ => return Err(ContractError::TaskNoLongerValid {}),
+    };
+
+    let mut any = vec![];
+    encode_string_field(1, type_url, &mut any);
+    encode_bytes_field(2, &value, &mut any);
+
+    let mut exec = vec![];
+    encode_string_field(1, manager_addr.as_str(), &mut exec);
+    encode_bytes_field(2, &any, &mut exec);
+    Ok(CosmosMsg::Stargate {
+        type_url: MSG_EXEC_TYPE_URL.to_owned(),
+        value: Binary(exec),
+    })
+}
@@ -1,15 +1,24 @@
 use cosmwasm_std::{
-    coins, from_binary, to_binary, Addr, BankMsg, Deps, DepsMut, MessageInfo, Order, Response,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    coin, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Empty,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
+use croncat_sdk_agents::types::AgentPayout;
 use croncat_sdk_core::types::GasPrice;
-use croncat_sdk_manager::types::Config;
+use croncat_sdk_manager::msg::TreasuryExecuteMsg;
+use croncat_sdk_manager::types::{Config, SponsoredTask, TaskSponsor};
 use cw20::{Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use crate::{
-    helpers::{check_if_sender_is_task_owner, check_ready_for_execution, gas_fee, get_tasks_addr},
+    contract::PAYOUT_REPLY,
+    helpers::{
+        check_if_sender_is_task_owner, check_ready_for_execution, gas_fee, get_tasks_addr,
+        query_agent, refund_task_sponsor,
+    },
     msg::ReceiveMsg,
-    state::{AGENT_REWARDS, CONFIG, PAUSED, TASKS_BALANCES, TEMP_BALANCES_CW20, TREASURY_BALANCE},
+    state::{
+        PayoutItem, AGENT_REWARDS, CONFIG, PAUSED, PAYOUT_QUEUE, SPONSORED_TASKS, TASKS_BALANCES,
+        TEMP_BALANCES_CW20, TREASURY_BALANCE,
+    },
     ContractError,
 };
 
@@ -49,15 +58,17 @@ pub(crate) fn sub_user_cw20(
     Ok(new_bal)
 }
 
-/// Adding agent and treasury rewards
+/// Adding agent and treasury rewards, in the denom task pays the gas in
 /// Refunding gas used by the agent for this task
 /// For example, if we have both `agent_fee`&`treasury_fee` set at 5% :
 /// 105% of gas cost goes to the agents (100% to cover gas used for this transaction and 5% as a reward)
 /// and remaining 5% goes to the treasury
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn add_fee_rewards(
     storage: &mut dyn Storage,
     gas: u64,
     gas_price: &GasPrice,
+    gas_denom: &str,
     agent_addr: &Addr,
     agent_fee: u16,
     treasury_fee: u16,
@@ -65,7 +76,7 @@ pub(crate) fn add_fee_rewards(
 ) -> Result<(), ContractError> {
     AGENT_REWARDS.update(
         storage,
-        agent_addr,
+        (agent_addr, gas_denom),
         |agent_balance| -> Result<_, ContractError> {
             // Adding base gas and agent_fee here
             let gas_fee = if reimburse_only {
@@ -79,16 +90,112 @@ pub(crate) fn add_fee_rewards(
     )?;
 
     if !reimburse_only {
-        TREASURY_BALANCE.update(storage, |balance| -> Result<_, ContractError> {
+        TREASURY_BALANCE.update(storage, gas_denom, |balance| -> Result<_, ContractError> {
             let gas_fee = gas_fee(gas, treasury_fee.into())?;
             let amount: Uint128 = gas_price.calculate(gas_fee).unwrap().into();
-            Ok(balance.saturating_add(amount))
+            Ok(balance.unwrap_or_default().saturating_add(amount))
         })?;
     }
 
     Ok(())
 }
 
+/// Takes out the agent rewards of every gas denom
+pub(crate) fn take_agent_rewards(
+    storage: &mut dyn Storage,
+    agent_addr: &Addr,
+) -> StdResult<Vec<Coin>> {
+    let rewards: Vec<Coin> = AGENT_REWARDS
+        .prefix(agent_addr)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<_>>()?;
+    for reward in rewards.iter() {
+        AGENT_REWARDS.remove(storage, (agent_addr, &reward.denom));
+    }
+    Ok(rewards
+        .into_iter()
+        .filter(|reward| !reward.amount.is_zero())
+        .collect())
+}
+
+/// Pays out the agent rewards once rewards of any gas denom reach the payout threshold of this denom set by the agent.
+/// Payout in cw20 goes through the treasury, it falls back to native coins if cw20 is no longer supported.
+/// Failed payout doesn't revert the task execution, rewards go back to the agent instead
+pub(crate) fn agent_auto_payout(
+    deps: DepsMut,
+    config: &Config,
+    agent_addr: &Addr,
+) -> Result<Option<SubMsg>, ContractError> {
+    let rewards = AGENT_REWARDS
+        .prefix(agent_addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+    if rewards.iter().all(|(_, amount)| amount.is_zero()) {
+        return Ok(None);
+    }
+    let agent = match query_agent(&deps.querier, config, agent_addr.to_string())?.agent {
+        Some(agent) => agent,
+        None => return Ok(None),
+    };
+    let threshold_reached = |payout: &AgentPayout| {
+        payout.thresholds.iter().any(|threshold| {
+            rewards
+                .iter()
+                .any(|(denom, amount)| *denom == threshold.denom && *amount >= threshold.amount)
+        })
+    };
+    let payout = match agent.payout {
+        Some(payout) if threshold_reached(&payout) => payout,
+        _ => return Ok(None),
+    };
+    let rewards = take_agent_rewards(deps.storage, agent_addr)?;
+    PAYOUT_QUEUE.save(
+        deps.storage,
+        &PayoutItem {
+            agent_addr: agent_addr.clone(),
+            rewards: rewards.clone(),
+        },
+    )?;
+
+    let msg: CosmosMsg = match (payout.cw20_addr, &config.treasury_addr) {
+        (Some(cw20_addr), Some(treasury_addr)) if config.cw20_whitelist.contains(&cw20_addr) => {
+            WasmMsg::Execute {
+                contract_addr: treasury_addr.to_string(),
+                msg: to_binary(&TreasuryExecuteMsg::AgentPayout {
+                    recipient: agent.payable_account_id.into_string(),
+                    cw20_addr: cw20_addr.into_string(),
+                })?,
+                funds: rewards,
+            }
+            .into()
+        }
+        _ => BankMsg::Send {
+            to_address: agent.payable_account_id.into_string(),
+            amount: rewards,
+        }
+        .into(),
+    };
+    Ok(Some(SubMsg::reply_always(msg, PAYOUT_REPLY)))
+}
+
+/// Gives the rewards of the failed payout back to the agent
+pub(crate) fn restore_agent_payout(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let PayoutItem {
+        agent_addr,
+        rewards,
+    } = PAYOUT_QUEUE.load(storage)?;
+    PAYOUT_QUEUE.remove(storage);
+    for reward in rewards {
+        AGENT_REWARDS.update(
+            storage,
+            (&agent_addr, &reward.denom),
+            |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + reward.amount) },
+        )?;
+    }
+    Ok(())
+}
+
 // Contract methods
 
 /// Execute: Receive
@@ -259,15 +366,24 @@ pub fn execute_owner_withdraw(deps: DepsMut, info: MessageInfo) -> Result<Respon
     }
     let address = config.treasury_addr.unwrap_or(config.owner_addr);
 
-    let withdraw = TREASURY_BALANCE.load(deps.storage)?;
-    TREASURY_BALANCE.save(deps.storage, &Uint128::zero())?;
+    let balances: Vec<Coin> = TREASURY_BALANCE
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<_>>()?;
+    for balance in balances.iter() {
+        TREASURY_BALANCE.remove(deps.storage, &balance.denom);
+    }
+    let withdraw: Vec<Coin> = balances
+        .into_iter()
+        .filter(|balance| !balance.amount.is_zero())
+        .collect();
 
-    if withdraw.is_zero() {
+    if withdraw.is_empty() {
         Err(ContractError::EmptyBalance {})
     } else {
         let bank_msg = BankMsg::Send {
             to_address: address.into_string(),
-            amount: coins(withdraw.u128(), config.native_denom),
+            amount: withdraw,
         };
         Ok(Response::new()
             .add_attribute("action", "owner_withdraw")
@@ -295,8 +411,9 @@ pub fn execute_refill_native_balance(
     if info.funds.len() > 2 {
         return Err(ContractError::InvalidAttachedCoins {});
     }
+    let gas_denom = task_balances.gas_denom(&config.native_denom).to_owned();
     for coin in info.funds {
-        if coin.denom == config.native_denom {
+        if coin.denom == gas_denom {
             task_balances.native_balance += coin.amount
         } else {
             let mut ibc = task_balances
@@ -313,6 +430,112 @@ pub fn execute_refill_native_balance(
     Ok(Response::new().add_attribute("action", "refill_native_balance"))
 }
 
+/// Sponsor pays for the gas of the task before the task balance does.
+/// Only the sponsor approved by the task owner can top up the gas balance
+pub fn execute_sponsor_task(
+    deps: DepsMut,
+    info: MessageInfo,
+    task_hash: String,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused {});
+    }
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut task_balance = TASKS_BALANCES
+        .may_load(deps.storage, task_hash.as_bytes())?
+        .ok_or(ContractError::NoTaskHash {})?;
+    // Gas is paid in the gas denom of the task only
+    let amount = cw_utils::must_pay(&info, task_balance.gas_denom(&config.native_denom))
+        .map_err(|_| ContractError::InvalidAttachedCoins {})?;
+
+    match &mut task_balance.sponsor {
+        Some(sponsor) if sponsor.sponsor_addr == info.sender => {
+            sponsor.gas_balance = sponsor
+                .gas_balance
+                .checked_add(amount)
+                .map_err(StdError::overflow)?;
+        }
+        _ => return Err(ContractError::SponsorNotApproved {}),
+    }
+    TASKS_BALANCES.save(deps.storage, task_hash.as_bytes(), &task_balance)?;
+    Ok(Response::new()
+        .add_attribute("action", "sponsor_task")
+        .add_attribute("task_hash", task_hash))
+}
+
+/// Task owner approves the address to sponsor the task.
+/// Previous sponsor is taken off the task and gets its unused gas balance back
+pub fn execute_approve_sponsor(
+    deps: DepsMut,
+    info: MessageInfo,
+    task_hash: String,
+    sponsor_addr: String,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused {});
+    }
+    let config: Config = CONFIG.load(deps.storage)?;
+    let tasks_addr = get_tasks_addr(&deps.querier, &config)?;
+    check_if_sender_is_task_owner(&deps.querier, &tasks_addr, &info.sender, &task_hash)?;
+    let sponsor_addr = deps.api.addr_validate(&sponsor_addr)?;
+
+    let mut task_balance = TASKS_BALANCES
+        .may_load(deps.storage, task_hash.as_bytes())?
+        .ok_or(ContractError::NoTaskHash {})?;
+    let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+    let sponsor_refund = refund_task_sponsor(
+        deps.storage,
+        task_hash.as_bytes(),
+        &mut task_balance,
+        &gas_denom,
+    )?;
+    task_balance.sponsor = Some(TaskSponsor {
+        sponsor_addr: sponsor_addr.clone(),
+        gas_balance: Uint128::zero(),
+    });
+    TASKS_BALANCES.save(deps.storage, task_hash.as_bytes(), &task_balance)?;
+    SPONSORED_TASKS.save(
+        deps.storage,
+        (&sponsor_addr, task_hash.as_bytes()),
+        &Empty {},
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "approve_sponsor")
+        .add_attribute("task_hash", task_hash)
+        .add_attribute("sponsor_addr", sponsor_addr)
+        .add_messages(sponsor_refund))
+}
+
+pub fn query_sponsored_tasks(
+    deps: Deps,
+    sponsor_addr: String,
+    from_index: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<SponsoredTask>> {
+    let config = CONFIG.load(deps.storage)?;
+    let addr = deps.api.addr_validate(&sponsor_addr)?;
+    let from_index = from_index.unwrap_or_default();
+    let limit = limit.unwrap_or(config.limit);
+
+    SPONSORED_TASKS
+        .prefix(&addr)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .skip(from_index as usize)
+        .take(limit as usize)
+        .map(|task_hash_res| {
+            let task_hash = task_hash_res?;
+            let task_balance = TASKS_BALANCES.load(deps.storage, &task_hash)?;
+            Ok(SponsoredTask {
+                task_hash: String::from_utf8(task_hash).map_err(StdError::invalid_utf8)?,
+                gas_balance: task_balance
+                    .sponsor
+                    .map(|sponsor| sponsor.gas_balance)
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
 /// Query: Cw20WalletBalances
 /// Used to get user's available cw20 coins balance that he can use to attach to the task balance
 /// Can be paginated
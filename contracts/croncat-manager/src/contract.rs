@@ -3,43 +3,76 @@ use std::collections::HashSet;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Attribute, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo,
-    Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    coin, from_binary, to_binary, Addr, Attribute, BankMsg, Binary, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Reply, Response, StdResult, SubMsg, SubMsgResult, Uint128, WasmMsg,
 };
 use croncat_sdk_core::internal_messages::agents::AgentWithdrawOnRemovalArgs;
-use croncat_sdk_core::internal_messages::manager::{ManagerCreateTaskBalance, ManagerRemoveTask};
-use croncat_sdk_manager::msg::{AgentWithdrawCallback, ManagerExecuteMsg::ProxyCallForwarded};
-use croncat_sdk_manager::types::{TaskBalance, TaskBalanceResponse, UpdateConfig};
+use croncat_sdk_core::internal_messages::manager::{
+    ManagerCreateTaskBalance, ManagerImportTaskBalance, ManagerMigrateTaskBalance,
+    ManagerRemoveTask, ManagerUpdateTaskBalance,
+};
+use croncat_sdk_core::version::{migrate_contract, StateMigration};
+use croncat_sdk_manager::msg::{
+    AgentWithdrawCallback, IbcLifecycleComplete, ManagerExecuteMsg::ProxyCallForwarded,
+    TaskEndedCallback,
+};
+use croncat_sdk_manager::types::{
+    GasPriceSource, TaskBalance, TaskBalanceResponse, TaskEndReason, TaskExecutionRecord,
+    UpdateConfig, DEFAULT_UNBONDING_PERIOD,
+};
 use croncat_sdk_tasks::types::{Interval, Task, TaskExecutionInfo, TaskInfo};
 use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
 use cw_utils::{may_pay, parse_reply_execute_data};
 
 use crate::balances::{
-    add_fee_rewards, execute_owner_withdraw, execute_receive_cw20, execute_refill_native_balance,
-    execute_refill_task_cw20, execute_user_withdraw, query_users_balances, sub_user_cw20,
+    add_fee_rewards, agent_auto_payout, execute_approve_sponsor, execute_owner_withdraw,
+    execute_receive_cw20, execute_refill_native_balance, execute_refill_task_cw20,
+    execute_sponsor_task, execute_user_withdraw, query_sponsored_tasks, query_users_balances,
+    restore_agent_payout, sub_user_cw20, take_agent_rewards,
 };
 use crate::error::ContractError;
+use crate::gas_price::{execution_gas_price, query_gas_price};
 use crate::helpers::{
-    assert_caller_is_agent_contract, attached_natives, calculate_required_natives,
-    check_if_sender_is_tasks, check_ready_for_execution, create_bank_send_message,
-    create_task_completed_msg, finalize_task, gas_with_fees, get_agents_addr, get_tasks_addr,
-    is_after_boundary, is_before_boundary, parse_reply_msg, process_queries, query_agent,
-    recalculate_cw20, remove_task_balance, replace_values, task_sub_msgs,
+    add_task_execution_record, assert_caller_is_agent_contract, attached_natives,
+    calculate_required_natives, check_if_sender_is_other_version, check_if_sender_is_tasks,
+    check_ready_for_execution, create_task_completed_msg, finalize_task, get_agents_addr,
+    get_tasks_addr, has_enough_for_gas_price, is_after_boundary, is_before_boundary,
+    move_task_execution_history, parse_reply_msg, process_queries, query_agent, recalculate_coins,
+    recalculate_cw20, refund_task_sponsor, remove_task_balance, replace_values,
+    set_last_task_end_reason, skip_unmet_actions, take_callback_fee, task_callback_submsg,
+    task_gas_price, task_sub_msgs,
+};
+use crate::ibc::{save_ibc_transfer, sudo_ibc_lifecycle_complete};
+use crate::migrations::migrate_rewards_per_denom;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+use crate::staking::{
+    drop_unbonding, execute_release_unbondings, has_enough_delegations, move_task_delegations,
+    query_task_rewards, undelegate_task_stake,
 };
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use crate::state::{
-    Config, QueueItem, AGENT_REWARDS, CONFIG, LAST_TASK_EXECUTION_INFO, PAUSED, REPLY_QUEUE,
-    TASKS_BALANCES, TREASURY_BALANCE,
+    Config, QueueItem, AGENT_REWARDS, CONFIG, LAST_TASK_EXECUTION_INFO, PAUSED, PAYOUT_QUEUE,
+    REPLY_QUEUE, RESCHEDULE_QUEUE, SPONSORED_TASKS, TASKS_BALANCES, TASK_EXECUTION_HISTORY,
+    TREASURY_BALANCE,
 };
 use crate::ContractError::InvalidPercentage;
 
 pub(crate) const CONTRACT_NAME: &str = "crate:croncat-manager";
 pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const STATE_MIGRATIONS: &[(&str, StateMigration<ContractError>)] =
+    &[("1.1.0", migrate_rewards_per_denom)];
+
 pub(crate) const DEFAULT_FEE: u16 = 5;
 
 /// reply id from tasks contract
 pub(crate) const TASK_REPLY: u64 = u64::from_be_bytes(*b"croncat1");
+/// reply id from task callback contracts
+pub(crate) const CALLBACK_REPLY: u64 = u64::from_be_bytes(*b"croncat2");
+/// reply id from agent payouts
+pub(crate) const PAYOUT_REPLY: u64 = u64::from_be_bytes(*b"croncat3");
+/// reply id prefix of the stake undelegations, lower half is the unbonding id
+pub(crate) const UNDELEGATE_REPLY: u64 = u64::from_be_bytes(*b"unbd\0\0\0\0");
 
 /// Instantiate
 /// First contract method before it runs on the chains
@@ -90,7 +123,7 @@ pub fn instantiate(
     if treasury_funds.is_err() {
         return Err(ContractError::RedundantFunds {});
     }
-    TREASURY_BALANCE.save(deps.storage, &treasury_funds.unwrap())?;
+    TREASURY_BALANCE.save(deps.storage, &denom, &treasury_funds.unwrap())?;
 
     let gas_price = gas_price.unwrap_or_default();
     // Make sure gas_price is valid
@@ -124,8 +157,11 @@ pub fn instantiate(
         agent_fee: DEFAULT_FEE,
         treasury_fee: DEFAULT_FEE,
         gas_price,
+        gas_price_source: GasPriceSource::Frozen {},
         cw20_whitelist,
         native_denom: denom,
+        gas_denoms: vec![],
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
         limit: 100,
         treasury_addr: treasury_addr
             .map(|human| deps.api.addr_validate(&human))
@@ -169,10 +205,21 @@ pub fn execute(
         ExecuteMsg::RefillTaskCw20Balance { task_hash, cw20 } => {
             execute_refill_task_cw20(deps, info, task_hash, cw20)
         }
+        ExecuteMsg::SponsorTask { task_hash } => execute_sponsor_task(deps, info, task_hash),
+        ExecuteMsg::ApproveSponsor {
+            task_hash,
+            sponsor_addr,
+        } => execute_approve_sponsor(deps, info, task_hash, sponsor_addr),
         ExecuteMsg::CreateTaskBalance(msg) => execute_create_task_balance(deps, info, *msg),
-        ExecuteMsg::RemoveTask(msg) => execute_remove_task(deps, info, msg),
+        ExecuteMsg::RemoveTask(msg) => execute_remove_task(deps, env, info, msg),
+        ExecuteMsg::UpdateTaskBalance(msg) => execute_update_task_balance(deps, info, *msg),
+        ExecuteMsg::MigrateTaskBalance(msg) => execute_migrate_task_balance(deps, env, info, msg),
+        ExecuteMsg::ImportTaskBalance(msg) => execute_import_task_balance(deps, env, info, msg),
         ExecuteMsg::OwnerWithdraw {} => execute_owner_withdraw(deps, info),
         ExecuteMsg::UserWithdraw { limit } => execute_user_withdraw(deps, info, limit),
+        ExecuteMsg::ReleaseUnbondings { limit } => {
+            execute_release_unbondings(deps, env, info, limit)
+        }
         ExecuteMsg::AgentWithdraw(args) => execute_withdraw_agent_rewards(deps, info, args),
         ExecuteMsg::PauseContract {} => execute_pause(deps, info),
         ExecuteMsg::UnpauseContract {} => execute_unpause(deps, info),
@@ -181,18 +228,30 @@ pub fn execute(
 
 fn execute_remove_task(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: ManagerRemoveTask,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     check_if_sender_is_tasks(&deps.querier, &config, &info.sender)?;
     let task_owner = msg.sender;
-    let task_balance = TASKS_BALANCES.load(deps.storage, &msg.task_hash)?;
+    let mut task_balance = TASKS_BALANCES.load(deps.storage, &msg.task_hash)?;
+    let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+    let undelegate_msgs = undelegate_task_stake(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &config,
+        &msg.task_hash,
+        &task_owner,
+    )?;
+    let sponsor_refund =
+        refund_task_sponsor(deps.storage, &msg.task_hash, &mut task_balance, &gas_denom)?;
     let coins_transfer = remove_task_balance(
         deps.storage,
         task_balance,
         &task_owner,
-        &config.native_denom,
+        &gas_denom,
         &msg.task_hash,
     )?;
 
@@ -202,6 +261,8 @@ fn execute_remove_task(
     };
     Ok(Response::new()
         .add_attribute("action", "remove_task")
+        .add_submessages(undelegate_msgs)
+        .add_messages(sponsor_refund)
         .add_message(bank_send))
 }
 
@@ -262,6 +323,13 @@ fn execute_proxy_call_internal(
                 queries: task.queries.unwrap_or_default(),
                 transforms: task.transforms,
                 version: task.version,
+                paused: task.paused,
+                callbacks: task.callbacks,
+                max_executions: task
+                    .remaining_executions
+                    .map(|remaining| remaining + task.executions),
+                executions: task.executions,
+                query_rule: task.query_rule,
             };
             if !t.is_evented() {
                 return Err(ContractError::NoTaskForAgent {});
@@ -293,6 +361,10 @@ fn execute_proxy_call_internal(
         // No task
         return Err(ContractError::NoTask {});
     };
+    // Paused tasks can't be executed, whether evented or scheduled
+    if task.paused {
+        return Err(ContractError::TaskPaused {});
+    }
     let task_hash = task.task_hash.to_owned();
     let task_version = task.version.to_owned();
 
@@ -308,6 +380,8 @@ fn execute_proxy_call_internal(
             config,
             agent_addr,
             tasks_addr,
+            &env,
+            TaskEndReason::BoundaryEnded,
             Some(vec![
                 Attribute::new("lifecycle", "task_ended"),
                 Attribute::new("task_hash", task_hash),
@@ -317,30 +391,114 @@ fn execute_proxy_call_internal(
         );
     }
 
+    // Gas price of this execution, if manager reads it from the source.
+    // Source prices only the native denom, tasks paying in other gas denoms keep the frozen price
+    let gas_price_source =
+        if task.amount_for_one_task.gas_denom(&config.native_denom) == config.native_denom {
+            config.gas_price_source.clone()
+        } else {
+            GasPriceSource::Frozen {}
+        };
+    if let Some((current_price, limits)) =
+        query_gas_price(&deps.querier, &gas_price_source, &config.native_denom)?
+    {
+        let gas_price = match execution_gas_price(
+            &task.amount_for_one_task.gas_price,
+            task.amount_for_one_task.gas_price_tolerance,
+            current_price,
+            limits,
+        )? {
+            Some(gas_price) => gas_price,
+            // Task can't pay the minimum gas price anymore
+            None => {
+                return end_task(
+                    deps,
+                    task,
+                    config,
+                    agent_addr,
+                    tasks_addr,
+                    &env,
+                    TaskEndReason::GasPriceTooLow,
+                    Some(vec![
+                        Attribute::new("lifecycle", "task_ended"),
+                        Attribute::new("task_hash", task_hash),
+                        Attribute::new("task_version", task_version),
+                    ]),
+                    true,
+                );
+            }
+        };
+        let task_balance = TASKS_BALANCES.load(deps.storage, task_hash.as_bytes())?;
+        let gas_denom = task_balance.gas_denom(&config.native_denom);
+        if !has_enough_for_gas_price(&task, &task_balance, &gas_price, gas_denom)? {
+            // Balance covers only the frozen gas price, charged when task ends
+            return end_task(
+                deps,
+                task,
+                config,
+                agent_addr,
+                tasks_addr,
+                &env,
+                TaskEndReason::BalanceDrained,
+                Some(vec![
+                    Attribute::new("lifecycle", "task_ended"),
+                    Attribute::new("task_hash", task_hash),
+                    Attribute::new("task_version", task_version),
+                ]),
+                true,
+            );
+        }
+        task.amount_for_one_task.gas_price = gas_price;
+    }
+
+    let mut skipped_actions = vec![];
     if task.queries.is_some() {
         // Process all the queries
         let query_responses = process_queries(&deps, &task)?;
-        if !query_responses.is_empty() {
-            replace_values(&mut task, query_responses)?;
+        // Only the actions with met conditions run and get charged
+        skipped_actions = skip_unmet_actions(&mut task, &query_responses)?;
+        if !query_responses.is_empty() && replace_values(&mut task, query_responses).is_err() {
+            return end_task(
+                deps,
+                task,
+                config,
+                agent_addr,
+                tasks_addr,
+                &env,
+                TaskEndReason::TransformFailed,
+                Some(vec![
+                    Attribute::new("lifecycle", "task_invalidated"),
+                    Attribute::new("task_hash", task_hash),
+                    Attribute::new("task_version", task_version),
+                ]),
+                false,
+            );
         }
 
-        // Recalculate cw20 usage and re-check for self-calls
-        let invalidated_after_transform = if let Ok(amounts) =
-            recalculate_cw20(&task, &config, deps.as_ref(), &env.contract.address)
-        {
-            task.amount_for_one_task.cw20 = amounts;
-            false
-        } else {
-            true
+        // Recalculate cw20 and native coins usage and re-check for self-calls
+        let invalidated_after_transform = match (
+            recalculate_cw20(&task, &config, deps.as_ref(), &env.contract.address),
+            recalculate_coins(&task),
+        ) {
+            (Ok(cw20), Ok(coin)) => {
+                task.amount_for_one_task.cw20 = cw20;
+                task.amount_for_one_task.coin = coin;
+                false
+            }
+            _ => true,
         };
 
-        // Need to re-check if task has enough cw20's
+        // Need to re-check if task has enough cw20's and native coins
         // because it could have been changed through transform
         let task_balance = TASKS_BALANCES.load(deps.storage, task_hash.as_bytes())?;
+        let gas_denom = task_balance.gas_denom(&config.native_denom);
         if invalidated_after_transform
-            || task_balance
-                .verify_enough_cw20(task.amount_for_one_task.cw20.clone(), Uint128::new(1))
-                .is_err()
+            || !has_enough_for_gas_price(
+                &task,
+                &task_balance,
+                &task.amount_for_one_task.gas_price,
+                gas_denom,
+            )?
         {
             // Task is no longer valid
             return end_task(
@@ -349,6 +507,8 @@ fn execute_proxy_call_internal(
                 config,
                 agent_addr,
                 tasks_addr,
+                &env,
+                TaskEndReason::Invalidated,
                 Some(vec![
                     Attribute::new("lifecycle", "task_invalidated"),
                     Attribute::new("task_hash", task_hash),
@@ -359,11 +519,39 @@ fn execute_proxy_call_internal(
         }
     }
 
-    let sub_msgs = task_sub_msgs(&task);
+    // Task can only move or return the stake it delegated
+    if !has_enough_delegations(deps.storage, &task)? {
+        return end_task(
+            deps,
+            task,
+            config,
+            agent_addr,
+            tasks_addr,
+            &env,
+            TaskEndReason::Invalidated,
+            Some(vec![
+                Attribute::new("lifecycle", "task_invalidated"),
+                Attribute::new("task_hash", task_hash),
+                Attribute::new("task_version", task_version),
+            ]),
+            false,
+        );
+    }
+
+    let staking_rewards = query_task_rewards(
+        deps.storage,
+        &deps.querier,
+        &env.contract.address,
+        &task,
+        &config.native_denom,
+    )?;
+    let sub_msgs = task_sub_msgs(&task, &env.block, &env.contract.address)?;
     let queue_item = QueueItem {
         task: task.clone(),
         agent_addr,
         failures: Default::default(),
+        skipped_actions,
+        staking_rewards,
     };
 
     REPLY_QUEUE.save(deps.storage, &queue_item)?;
@@ -458,23 +646,25 @@ fn execute_proxy_batch(
     Ok(Response::new().add_submessages(sub_msgs))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn end_task(
     deps: DepsMut,
     task: TaskInfo,
     config: Config,
     agent_addr: Addr,
     tasks_addr: Addr,
+    env: &Env,
+    end_reason: TaskEndReason,
     attrs: Option<Vec<Attribute>>,
     reimburse_only: bool,
 ) -> Result<Response, ContractError> {
     // Sub gas/fee from native
     let gas_with_fees = if reimburse_only {
-        gas_with_fees(task.amount_for_one_task.gas, 0u64)?
+        task.amount_for_one_task.gas
     } else {
-        gas_with_fees(
-            task.amount_for_one_task.gas,
-            (task.amount_for_one_task.agent_fee + task.amount_for_one_task.treasury_fee) as u64,
-        )?
+        task.amount_for_one_task
+            .gas_with_fees(task.amount_for_one_task.gas)
+            .map_err(|_| ContractError::InvalidGasCalculation {})?
     };
     let native_for_gas_required = task
         .amount_for_one_task
@@ -482,10 +672,8 @@ fn end_task(
         .calculate(gas_with_fees)
         .unwrap();
     let mut task_balance = TASKS_BALANCES.load(deps.storage, task.task_hash.as_bytes())?;
-    task_balance.native_balance = task_balance
-        .native_balance
-        .checked_sub(Uint128::new(native_for_gas_required))
-        .map_err(StdError::overflow)?;
+    let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+    task_balance.sub_gas(Uint128::new(native_for_gas_required))?;
 
     // Account for fees, to reimburse agent for efforts
     // TODO: Need to NOT add fee for non-reimberse
@@ -493,33 +681,85 @@ fn end_task(
         deps.storage,
         task.amount_for_one_task.gas,
         &task.amount_for_one_task.gas_price,
+        &gas_denom,
         &agent_addr,
         task.amount_for_one_task.agent_fee,
         task.amount_for_one_task.treasury_fee,
         reimburse_only,
     )?;
+    add_task_execution_record(
+        deps.storage,
+        task.task_hash.as_bytes(),
+        &TaskExecutionRecord {
+            block_height: env.block.height,
+            block_time: env.block.time,
+            agent_addr: agent_addr.clone(),
+            gas_charged: coin(native_for_gas_required, &gas_denom),
+            action_results: vec![],
+            end_reason: Some(end_reason.clone()),
+        },
+    )?;
+    let undelegate_msgs = undelegate_task_stake(
+        deps.storage,
+        &deps.querier,
+        env,
+        &config,
+        task.task_hash.as_bytes(),
+        &task.owner_addr,
+    )?;
+    let sponsor_refund = refund_task_sponsor(
+        deps.storage,
+        task.task_hash.as_bytes(),
+        &mut task_balance,
+        &gas_denom,
+    )?;
+    let callback = take_callback_fee(
+        deps.storage,
+        &task,
+        &agent_addr,
+        &end_reason,
+        &mut task_balance,
+        &gas_denom,
+    )?;
+    let cw20_refunded = task_balance.cw20_balance.clone();
 
     // refund the final balances to task owner
     let coins_transfer = remove_task_balance(
         deps.storage,
         task_balance,
         &task.owner_addr,
-        &config.native_denom,
+        &gas_denom,
         task.task_hash.as_bytes(),
     )?;
     let msg = croncat_sdk_core::internal_messages::tasks::TasksRemoveTaskByManager {
-        task_hash: task.task_hash.into_bytes(),
+        task_hash: task.task_hash.clone().into_bytes(),
     }
     .into_cosmos_msg(tasks_addr)?;
     let bank_send = BankMsg::Send {
         to_address: task.owner_addr.into_string(),
-        amount: coins_transfer,
+        amount: coins_transfer.clone(),
     };
-    Ok(Response::new()
+    let mut res = Response::new()
         .add_attribute("action", "end_task")
         .add_attributes(attrs.unwrap_or_default())
         .add_message(msg)
-        .add_message(bank_send))
+        .add_submessages(undelegate_msgs)
+        .add_messages(sponsor_refund)
+        .add_message(bank_send);
+    if let Some((callback_addr, gas_limit)) = callback {
+        res = res.add_submessage(task_callback_submsg(
+            callback_addr,
+            gas_limit,
+            TaskEndedCallback {
+                task_hash: task.task_hash,
+                end_reason,
+                refunded: coins_transfer,
+                cw20_refunded,
+            },
+        )?);
+    }
+    let payout = agent_auto_payout(deps, &config, &agent_addr)?;
+    Ok(res.add_submessages(payout))
 }
 
 /// Execute: UpdateConfig
@@ -537,10 +777,13 @@ pub fn execute_update_config(
             agent_fee,
             treasury_fee,
             gas_price,
+            gas_price_source,
             croncat_tasks_key,
             croncat_agents_key,
             treasury_addr,
             cw20_whitelist,
+            gas_denoms,
+            unbonding_period,
         } = msg;
 
         if info.sender != config.owner_addr {
@@ -568,6 +811,38 @@ pub fn execute_update_config(
             return Err(ContractError::InvalidGasPrice {});
         }
 
+        let gas_price_source = match gas_price_source {
+            Some(GasPriceSource::Oracle {
+                contract_addr,
+                limits,
+            }) => GasPriceSource::Oracle {
+                contract_addr: deps.api.addr_validate(&contract_addr)?.into_string(),
+                limits,
+            },
+            Some(source) => source,
+            None => config.gas_price_source,
+        };
+        if gas_price_source
+            .limits()
+            .is_some_and(|limits| !limits.is_valid())
+        {
+            return Err(ContractError::InvalidGasPrice {});
+        }
+
+        let gas_denoms = gas_denoms.unwrap_or(config.gas_denoms);
+        for (i, gas_denom) in gas_denoms.iter().enumerate() {
+            if !gas_denom.gas_price.is_valid() {
+                return Err(ContractError::InvalidGasPrice {});
+            }
+            if gas_denom.denom == config.native_denom
+                || gas_denoms[..i]
+                    .iter()
+                    .any(|other| other.denom == gas_denom.denom)
+            {
+                return Err(ContractError::InvalidGasDenoms {});
+            }
+        }
+
         let treasury_addr = if let Some(human) = treasury_addr {
             Some(deps.api.addr_validate(&human)?)
         } else {
@@ -591,8 +866,11 @@ pub fn execute_update_config(
             agent_fee: updated_agent_fee,
             treasury_fee: updated_treasury_fee,
             gas_price,
+            gas_price_source,
             cw20_whitelist: config.cw20_whitelist,
             native_denom: config.native_denom,
+            gas_denoms,
+            unbonding_period: unbonding_period.unwrap_or(config.unbonding_period),
             limit: config.limit,
             treasury_addr,
         };
@@ -609,7 +887,12 @@ fn execute_create_task_balance(
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     check_if_sender_is_tasks(&deps.querier, &config, &info.sender)?;
-    let (native, ibc) = attached_natives(&config.native_denom, info.funds)?;
+    let gas_denom = msg
+        .amount_for_one_task
+        .gas_denom(&config.native_denom)
+        .to_owned();
+    let gas_price = task_gas_price(&config, &gas_denom)?;
+    let (native, ibc) = attached_natives(&gas_denom, info.funds)?;
     let cw20 = msg.cw20;
     if let Some(attached_cw20) = &cw20 {
         sub_user_cw20(deps.storage, &msg.sender, attached_cw20)?;
@@ -618,22 +901,24 @@ fn execute_create_task_balance(
         native_balance: native,
         cw20_balance: cw20,
         ibc_balance: ibc,
+        sponsor: None,
+        gas_denom: msg.amount_for_one_task.gas_denom.clone(),
     };
     // Let's check if task has enough attached balance
     {
-        let gas_with_fees = gas_with_fees(
-            msg.amount_for_one_task.gas,
-            (config.agent_fee + config.treasury_fee) as u64,
-        )?;
-        let native_for_gas_required = config.gas_price.calculate(gas_with_fees).unwrap();
+        let gas_with_fees = msg
+            .amount_for_one_task
+            .gas_with_fees(msg.amount_for_one_task.gas)
+            .map_err(|_| ContractError::InvalidGasCalculation {})?;
+        let native_for_gas_required = gas_price.calculate(gas_with_fees).unwrap();
         let (native_for_sends_required, ibc_required) =
-            calculate_required_natives(msg.amount_for_one_task.coin, &config.native_denom)?;
+            calculate_required_natives(msg.amount_for_one_task.coin, &gas_denom)?;
         tasks_balance.verify_enough_attached(
             Uint128::from(native_for_gas_required) + native_for_sends_required,
             msg.amount_for_one_task.cw20,
             ibc_required,
             msg.recurring,
-            &config.native_denom,
+            &gas_denom,
         )?;
     }
     TASKS_BALANCES.save(deps.storage, &msg.task_hash, &tasks_balance)?;
@@ -641,6 +926,180 @@ fn execute_create_task_balance(
     Ok(Response::new().add_attribute("action", "create_task_balance"))
 }
 
+/// Moves the balance of the task to its new hash after the owner updated the task.
+/// Any attached funds are added to the balance, and the result must still cover
+/// the new `amount_for_one_task`
+fn execute_update_task_balance(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: ManagerUpdateTaskBalance,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_if_sender_is_tasks(&deps.querier, &config, &info.sender)?;
+    let mut tasks_balance = TASKS_BALANCES
+        .may_load(deps.storage, &msg.old_task_hash)?
+        .ok_or(ContractError::NoTaskHash {})?;
+    let gas_denom = tasks_balance.gas_denom(&config.native_denom).to_owned();
+    if msg.amount_for_one_task.gas_denom(&config.native_denom) != gas_denom {
+        return Err(ContractError::GasDenomChanged {});
+    }
+    let gas_price = task_gas_price(&config, &gas_denom)?;
+
+    // Add attached natives on top of the existing balance
+    let (native, ibc) = attached_natives(&gas_denom, info.funds)?;
+    tasks_balance.native_balance += native;
+    if let Some(attached_ibc) = ibc {
+        match &mut tasks_balance.ibc_balance {
+            Some(ibc_balance) if ibc_balance.denom == attached_ibc.denom => {
+                ibc_balance.amount += attached_ibc.amount
+            }
+            Some(_) => return Err(ContractError::InvalidAttachedCoins {}),
+            None => tasks_balance.ibc_balance = Some(attached_ibc),
+        }
+    }
+    if let Some(attached_cw20) = &msg.cw20 {
+        sub_user_cw20(deps.storage, &msg.sender, attached_cw20)?;
+        match &mut tasks_balance.cw20_balance {
+            Some(cw20_balance) if cw20_balance.address == attached_cw20.address => {
+                cw20_balance.amount += attached_cw20.amount
+            }
+            Some(_) => return Err(ContractError::InvalidAttachedCoins {}),
+            None => tasks_balance.cw20_balance = Some(attached_cw20.clone()),
+        }
+    }
+
+    // Updated task should still have enough balance
+    {
+        let gas_with_fees = msg
+            .amount_for_one_task
+            .gas_with_fees(msg.amount_for_one_task.gas)
+            .map_err(|_| ContractError::InvalidGasCalculation {})?;
+        let native_for_gas_required = gas_price.calculate(gas_with_fees).unwrap();
+        let (native_for_sends_required, ibc_required) =
+            calculate_required_natives(msg.amount_for_one_task.coin, &gas_denom)?;
+        let executions = if msg.recurring { 2u128 } else { 1u128 };
+        tasks_balance
+            .with_sponsored_gas(Uint128::new(native_for_gas_required * executions))
+            .verify_enough_attached(
+                Uint128::from(native_for_gas_required) + native_for_sends_required,
+                msg.amount_for_one_task.cw20,
+                ibc_required,
+                msg.recurring,
+                &gas_denom,
+            )?;
+    }
+    TASKS_BALANCES.remove(deps.storage, &msg.old_task_hash);
+    TASKS_BALANCES.save(deps.storage, &msg.new_task_hash, &tasks_balance)?;
+    move_task_delegations(deps.storage, &msg.old_task_hash, &msg.new_task_hash)?;
+    move_task_execution_history(deps.storage, &msg.old_task_hash, &msg.new_task_hash)?;
+    if let Some(sponsor) = &tasks_balance.sponsor {
+        SPONSORED_TASKS.remove(deps.storage, (&sponsor.sponsor_addr, &msg.old_task_hash));
+        SPONSORED_TASKS.save(
+            deps.storage,
+            (&sponsor.sponsor_addr, &msg.new_task_hash),
+            &Empty {},
+        )?;
+    }
+
+    Ok(Response::new().add_attribute("action", "update_task_balance"))
+}
+
+/// Sends the balance of the task to the manager of the newer version.
+/// Native and ibc coins attached to the import message, cw20 transferred right before it
+fn execute_migrate_task_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ManagerMigrateTaskBalance,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    check_if_sender_is_tasks(&deps.querier, &config, &info.sender)?;
+    let mut task_balance = TASKS_BALANCES
+        .may_load(deps.storage, &msg.task_hash)?
+        .ok_or(ContractError::NoTaskHash {})?;
+    TASKS_BALANCES.remove(deps.storage, &msg.task_hash);
+    let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+    // Stake and sponsorship can't be moved to the other manager
+    let undelegate_msgs = undelegate_task_stake(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &config,
+        &msg.task_hash,
+        &msg.sender,
+    )?;
+    let sponsor_refund =
+        refund_task_sponsor(deps.storage, &msg.task_hash, &mut task_balance, &gas_denom)?;
+
+    let mut funds = vec![];
+    if !task_balance.native_balance.is_zero() {
+        funds.push(coin(task_balance.native_balance.u128(), &gas_denom));
+    }
+    if let Some(ibc) = task_balance.ibc_balance {
+        if !ibc.amount.is_zero() {
+            funds.push(ibc);
+        }
+    }
+    let mut msgs = vec![];
+    if let Some(cw20) = &task_balance.cw20_balance {
+        if !cw20.amount.is_zero() {
+            msgs.push(WasmMsg::Execute {
+                contract_addr: cw20.address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: msg.new_manager_addr.to_string(),
+                    amount: cw20.amount,
+                })?,
+                funds: vec![],
+            });
+        }
+    }
+    let import_msg = ManagerImportTaskBalance {
+        task_hash: msg.task_hash,
+        cw20: task_balance.cw20_balance,
+        gas_denom: task_balance.gas_denom,
+    }
+    .into_cosmos_msg(msg.new_manager_addr.clone(), funds)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate_task_balance")
+        .add_attribute("new_manager_addr", msg.new_manager_addr)
+        .add_submessages(undelegate_msgs)
+        .add_messages(sponsor_refund)
+        .add_messages(msgs)
+        .add_message(import_msg))
+}
+
+/// Receives the balance of the migrated task from the other version of the manager.
+/// Balance gets verified by the tasks contract, once it imports the task itself
+fn execute_import_task_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ManagerImportTaskBalance,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    check_if_sender_is_other_version(&deps.querier, &config, &env.contract.address, &info.sender)?;
+    if TASKS_BALANCES.has(deps.storage, &msg.task_hash) {
+        return Err(ContractError::TaskBalanceExists {});
+    }
+    let gas_denom = msg.gas_denom.as_deref().unwrap_or(&config.native_denom);
+    task_gas_price(&config, gas_denom)?;
+    let (native, ibc) = attached_natives(gas_denom, info.funds)?;
+    let tasks_balance = TaskBalance {
+        native_balance: native,
+        cw20_balance: msg.cw20,
+        ibc_balance: ibc,
+        sponsor: None,
+        gas_denom: msg.gas_denom,
+    };
+    TASKS_BALANCES.save(deps.storage, &msg.task_hash, &tasks_balance)?;
+
+    Ok(Response::new().add_attribute("action", "import_task_balance"))
+}
+
 /// Allows an agent to withdraw all rewards, paid to the specified payable account id.
 fn execute_withdraw_agent_rewards(
     deps: DepsMut,
@@ -668,22 +1127,24 @@ fn execute_withdraw_agent_rewards(
             .ok_or(ContractError::NoRewardsOwnerAgentFound {})?;
         payable_account_id = agent.payable_account_id;
     }
-    let agent_rewards = AGENT_REWARDS
-        .may_load(deps.storage, &agent_id)?
-        .unwrap_or_default();
-
-    AGENT_REWARDS.remove(deps.storage, &agent_id);
+    let rewards = take_agent_rewards(deps.storage, &agent_id)?;
+    let rewards_attr = if rewards.is_empty() {
+        Uint128::zero().to_string()
+    } else {
+        rewards
+            .iter()
+            .map(|reward| reward.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
 
     let mut msgs = vec![];
-    // This will send all token balances to Agent
-    let msg = create_bank_send_message(
-        &payable_account_id,
-        &config.native_denom,
-        agent_rewards.u128(),
-    )?;
-
-    if !agent_rewards.is_zero() {
-        msgs.push(msg);
+    if !rewards.is_empty() {
+        // This will send all token balances to Agent
+        msgs.push(BankMsg::Send {
+            to_address: payable_account_id.to_string(),
+            amount: rewards.clone(),
+        });
     } else if fail_on_zero_balance {
         return Err(ContractError::NoWithdrawRewardsAvailable {});
     }
@@ -692,12 +1153,12 @@ fn execute_withdraw_agent_rewards(
         .add_messages(msgs)
         .set_data(to_binary(&AgentWithdrawCallback {
             agent_id: agent_id.to_string(),
-            amount: agent_rewards,
+            amount: rewards,
             payable_account_id: payable_account_id.to_string(),
         })?)
         .add_attribute("action", "withdraw_rewards")
         .add_attribute("payment_account_id", &payable_account_id)
-        .add_attribute("rewards", agent_rewards))
+        .add_attribute("rewards", rewards_attr))
 }
 
 pub fn execute_pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
@@ -724,12 +1185,34 @@ pub fn execute_unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, Con
     Ok(Response::new().add_attribute("action", "unpause_contract"))
 }
 
+/// Migrate
+/// See [`migrate_contract`] for the allowed migrations
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_contract(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        STATE_MIGRATIONS,
+    )
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::Paused {} => to_binary(&PAUSED.load(deps.storage)?),
-        QueryMsg::TreasuryBalance {} => to_binary(&TREASURY_BALANCE.load(deps.storage)?),
+        QueryMsg::TreasuryBalance { denom } => {
+            let denom = match denom {
+                Some(denom) => denom,
+                None => CONFIG.load(deps.storage)?.native_denom,
+            };
+            to_binary(
+                &TREASURY_BALANCE
+                    .may_load(deps.storage, &denom)?
+                    .unwrap_or_default(),
+            )
+        }
         QueryMsg::UsersBalances {
             address,
             from_index,
@@ -738,18 +1221,63 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::TaskBalance { task_hash } => to_binary(&TaskBalanceResponse {
             balance: TASKS_BALANCES.may_load(deps.storage, task_hash.as_bytes())?,
         }),
-        QueryMsg::AgentRewards { agent_id } => to_binary(
-            &AGENT_REWARDS
-                .may_load(deps.storage, &Addr::unchecked(agent_id))?
-                .unwrap_or(Uint128::zero()),
-        ),
+        QueryMsg::AgentRewards { agent_id, denom } => {
+            let denom = match denom {
+                Some(denom) => denom,
+                None => CONFIG.load(deps.storage)?.native_denom,
+            };
+            to_binary(
+                &AGENT_REWARDS
+                    .may_load(deps.storage, (&Addr::unchecked(agent_id), &denom))?
+                    .unwrap_or_default(),
+            )
+        }
+        QueryMsg::TaskExecutionHistory {
+            task_hash,
+            from_index,
+            limit,
+        } => to_binary(&query_task_execution_history(
+            deps, task_hash, from_index, limit,
+        )?),
+        QueryMsg::SponsoredTasks {
+            sponsor_addr,
+            from_index,
+            limit,
+        } => to_binary(&query_sponsored_tasks(
+            deps,
+            sponsor_addr,
+            from_index,
+            limit,
+        )?),
     }
 }
 
+fn query_task_execution_history(
+    deps: Deps,
+    task_hash: String,
+    from_index: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<TaskExecutionRecord>> {
+    let config = CONFIG.load(deps.storage)?;
+    let from_index = from_index.unwrap_or_default();
+    let limit = limit.unwrap_or(config.limit);
+
+    TASK_EXECUTION_HISTORY
+        .prefix(task_hash.as_bytes())
+        .range(deps.storage, None, None, Order::Descending)
+        .skip(from_index as usize)
+        .take(limit as usize)
+        .map(|record_res| record_res.map(|(_, record)| record))
+        .collect()
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(mut deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         TASK_REPLY => {
+            // Executed task kept only for the callbacks, in case task ended on reschedule
+            let rescheduled = RESCHEDULE_QUEUE.may_load(deps.storage)?;
+            RESCHEDULE_QUEUE.remove(deps.storage);
             let execute_data = parse_reply_execute_data(msg)?;
             let remove_task_msg: Option<ManagerRemoveTask> =
                 from_binary(&execute_data.data.unwrap())?;
@@ -758,24 +1286,100 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             };
             let config = CONFIG.load(deps.storage)?;
             let task_owner = msg.sender;
-            let task_balance = TASKS_BALANCES.load(deps.storage, &msg.task_hash)?;
+            // Task was rescheduled out of its boundary
+            let end_reason = TaskEndReason::BoundaryEnded;
+            set_last_task_end_reason(deps.storage, &msg.task_hash, end_reason.clone())?;
+            let mut task_balance = TASKS_BALANCES.load(deps.storage, &msg.task_hash)?;
+            let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+            let undelegate_msgs = undelegate_task_stake(
+                deps.storage,
+                &deps.querier,
+                &env,
+                &config,
+                &msg.task_hash,
+                &task_owner,
+            )?;
+            let sponsor_refund =
+                refund_task_sponsor(deps.storage, &msg.task_hash, &mut task_balance, &gas_denom)?;
+            let callback = match &rescheduled {
+                Some(queue_item) => take_callback_fee(
+                    deps.storage,
+                    &queue_item.task,
+                    &queue_item.agent_addr,
+                    &end_reason,
+                    &mut task_balance,
+                    &gas_denom,
+                )?,
+                None => None,
+            };
+            let cw20_refunded = task_balance.cw20_balance.clone();
             let coins_transfer = remove_task_balance(
                 deps.storage,
                 task_balance,
                 &task_owner,
-                &config.native_denom,
+                &gas_denom,
                 &msg.task_hash,
             )?;
 
             let bank_send = BankMsg::Send {
                 to_address: task_owner.into_string(),
-                amount: coins_transfer,
+                amount: coins_transfer.clone(),
+            };
+            let mut res = Response::new()
+                .add_submessages(undelegate_msgs)
+                .add_messages(sponsor_refund)
+                .add_message(bank_send);
+            if let (Some((callback_addr, gas_limit)), Some(queue_item)) = (callback, rescheduled) {
+                res = res.add_submessage(task_callback_submsg(
+                    callback_addr,
+                    gas_limit,
+                    TaskEndedCallback {
+                        task_hash: queue_item.task.task_hash,
+                        end_reason,
+                        refunded: coins_transfer,
+                        cw20_refunded,
+                    },
+                )?);
+            }
+            Ok(res)
+        }
+        // Failed callback shouldn't revert the task execution
+        CALLBACK_REPLY => {
+            let failure = match msg.result {
+                SubMsgResult::Err(err) => err,
+                SubMsgResult::Ok(_) => String::new(),
             };
-            Ok(Response::new().add_message(bank_send))
+            Ok(Response::new().add_attribute("callback_failure", failure))
+        }
+        // Failed payout shouldn't revert the task execution
+        PAYOUT_REPLY => match msg.result {
+            SubMsgResult::Ok(_) => {
+                PAYOUT_QUEUE.remove(deps.storage);
+                Ok(Response::new())
+            }
+            SubMsgResult::Err(failure) => {
+                restore_agent_payout(deps.storage)?;
+                Ok(Response::new().add_attribute("payout_failure", failure))
+            }
+        },
+        // Failed undelegation shouldn't revert the task removal
+        id if id >> 32 == UNDELEGATE_REPLY >> 32 => {
+            let failure = match msg.result {
+                SubMsgResult::Err(err) => err,
+                SubMsgResult::Ok(_) => String::new(),
+            };
+            let mut res = Response::new().add_attribute("undelegate_failure", failure);
+            if let Some(unbonding) = drop_unbonding(deps.storage, id) {
+                res = res.add_attribute("task_hash", unbonding.task_hash);
+            }
+            Ok(res)
         }
         _ => {
             let mut queue_item = REPLY_QUEUE.load(deps.storage)?;
-            let last = parse_reply_msg(deps.storage, &mut queue_item, msg);
+            if let SubMsgResult::Ok(response) = &msg.result {
+                save_ibc_transfer(deps.storage, &queue_item, msg.id, response.data.clone())?;
+            }
+            let last = parse_reply_msg(deps.storage, &mut queue_item, msg)?;
             if last {
                 let failures: Vec<Attribute> = queue_item
                     .failures
@@ -787,10 +1391,17 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
                     &deps.querier,
                     &config,
                     &queue_item.agent_addr,
-                    !matches!(queue_item.task.interval, Interval::Cron(_)),
+                    !matches!(
+                        queue_item.task.interval,
+                        Interval::Cron(_) | Interval::ZonedCron { .. } | Interval::Seconds(_)
+                    ),
                 )?;
-                Ok(finalize_task(deps, queue_item)?
+                let agent_addr = queue_item.agent_addr.clone();
+                let res = finalize_task(deps.branch(), &env, queue_item)?;
+                let payout = agent_auto_payout(deps, &config, &agent_addr)?;
+                Ok(res
                     .add_message(complete_msg)
+                    .add_submessages(payout)
                     .add_attributes(failures))
             } else {
                 Ok(Response::new())
@@ -799,6 +1410,21 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        }) => sudo_ibc_lifecycle_complete(deps, channel, sequence, success),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout { channel, sequence }) => {
+            sudo_ibc_lifecycle_complete(deps, channel, sequence, false)
+        }
+    }
+}
+
 /// Validate when a given value should be a reasonable percentage.
 /// Due to low native token prices on some chains, we must allow for
 /// greater than 100% in order to be sustainable, and have gone with
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use croncat_sdk_core::error::MigrationError;
 use croncat_sdk_manager::SdkError;
 use cw_utils::ParseReplyError;
 use thiserror::Error;
@@ -52,6 +53,12 @@ pub enum ContractError {
     #[error("Unknown task hash")]
     NoTaskHash {},
 
+    #[error("Task balance already exists")]
+    TaskBalanceExists {},
+
+    #[error("Sender is not approved by the task owner to sponsor this task")]
+    SponsorNotApproved {},
+
     #[error("Invalid version key, please update it")]
     InvalidKey {},
 
@@ -71,9 +78,15 @@ pub enum ContractError {
     #[error("No rewards owner agent found")]
     NoRewardsOwnerAgentFound {},
 
+    #[error("No unbondings ready to be released")]
+    NoUnbondingsReleased {},
+
     #[error("Task is no longer valid")]
     TaskNoLongerValid {},
 
+    #[error("Task is paused by the owner")]
+    TaskPaused {},
+
     #[error("Task is not ready yet")]
     TaskNotReady {},
 
@@ -89,6 +102,18 @@ pub enum ContractError {
     #[error("This cw20 address is not supported")]
     NotSupportedCw20 {},
 
+    #[error("Gas can't be paid in {denom}, denom is not whitelisted")]
+    UnsupportedGasDenom { denom: String },
+
+    #[error("Gas denom of the task can't be changed")]
+    GasDenomChanged {},
+
+    #[error("Gas denoms must be unique and different from the native denom")]
+    InvalidGasDenoms {},
+
     #[error("Must provide percentage value (0-100) for field: {field}")]
     InvalidPercentage { field: String },
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
 }
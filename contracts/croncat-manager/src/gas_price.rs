@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    to_vec, ContractResult, Decimal, Empty, QuerierWrapper, QueryRequest, SystemResult, Uint128,
+};
+use croncat_sdk_core::types::GasPrice;
+use croncat_sdk_manager::{
+    msg::GasPriceOracleQueryMsg,
+    types::{GasPriceLimits, GasPriceSource},
+};
+
+use crate::{proto::decode_string_field, ContractError};
+
+/// Precision of the execution gas price over the frozen one
+const GAS_PRICE_SCALE: u64 = 10_000;
+
+/// `sdk.Dec` is encoded as an integer with 18 decimal places
+const SDK_DEC_PLACES: u32 = 18;
+
+/// Reads the gas price of the source, bounded by its limits.
+/// Returns `None` if tasks pay the gas price frozen at their creation
+pub(crate) fn query_gas_price<'a>(
+    querier: &QuerierWrapper,
+    source: &'a GasPriceSource,
+    native_denom: &str,
+) -> Result<Option<(Decimal, &'a GasPriceLimits)>, ContractError> {
+    let (price, limits) = match source {
+        GasPriceSource::Frozen {} => return Ok(None),
+        GasPriceSource::Oracle {
+            contract_addr,
+            limits,
+        } => {
+            let price: Decimal = querier.query_wasm_smart(
+                contract_addr,
+                &GasPriceOracleQueryMsg::GasPrice {
+                    denom: native_denom.to_owned(),
+                },
+            )?;
+            (price, limits)
+        }
+        GasPriceSource::FeeMarket { path, data, limits } => {
+            let request: QueryRequest<Empty> = QueryRequest::Stargate {
+                path: path.clone(),
+                data: data.clone(),
+            };
+            let response = match querier.raw_query(&to_vec(&request)?) {
+                SystemResult::Ok(ContractResult::Ok(response)) => response,
+                _ => return Err(ContractError::InvalidGasPrice {}),
+            };
+            let price = decode_string_field(1, &response)
+                .and_then(|base_fee| Uint128::from_str(&base_fee).ok())
+                .and_then(|base_fee| Decimal::from_atomics(base_fee, SDK_DEC_PLACES).ok())
+                .ok_or(ContractError::InvalidGasPrice {})?;
+            (price, limits)
+        }
+    };
+    // Limits are validated, so minimum price can't be above the maximum
+    Ok(Some((
+        price.clamp(limits.min_price, limits.max_price),
+        limits,
+    )))
+}
+
+/// Gas price of the task execution: current gas price capped by the tolerance of the task.
+/// Returns `None` if the most task agreed to pay is below the minimum gas price
+pub(crate) fn execution_gas_price(
+    frozen: &GasPrice,
+    tolerance: Option<u16>,
+    current_price: Decimal,
+    limits: &GasPriceLimits,
+) -> Result<Option<GasPrice>, ContractError> {
+    let max_task_price = frozen.price() * Decimal::percent(tolerance.unwrap_or(100) as u64);
+    if max_task_price < limits.min_price {
+        return Ok(None);
+    }
+    let price = current_price.min(max_task_price);
+
+    let denominator = frozen
+        .denominator
+        .checked_mul(GAS_PRICE_SCALE)
+        .ok_or(ContractError::InvalidGasPrice {})?;
+    let numerator = u64::try_from((Uint128::from(denominator) * price).u128())
+        .map_err(|_| ContractError::InvalidGasPrice {})?;
+    let gas_adjustment_numerator = frozen
+        .gas_adjustment_numerator
+        .checked_mul(GAS_PRICE_SCALE)
+        .ok_or(ContractError::InvalidGasPrice {})?;
+    Ok(Some(GasPrice {
+        numerator,
+        denominator,
+        gas_adjustment_numerator,
+    }))
+}
@@ -1,21 +1,35 @@
+use std::mem::discriminant;
 use std::vec;
 
 use cosmwasm_std::{
-    coin, to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Empty,
-    MessageInfo, QuerierWrapper, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128,
-    WasmMsg, WasmQuery,
+    coin, to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
+    IbcMsg, MessageInfo, Order, QuerierWrapper, Reply, Response, StakingMsg, StdError, StdResult,
+    Storage, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
 use croncat_sdk_agents::msg::AgentResponse;
-use croncat_sdk_core::{internal_messages::agents::AgentOnTaskCompleted, types::AmountForOneTask};
-use croncat_sdk_manager::types::{Config, TaskBalance};
-use croncat_sdk_tasks::types::{Boundary, CosmosQuery, TaskInfo};
+use croncat_sdk_core::{
+    internal_messages::agents::AgentOnTaskCompleted,
+    types::{AmountForOneTask, GasPrice},
+};
+use croncat_sdk_manager::msg::{TaskCallbackMsg, TaskEndedCallback};
+use croncat_sdk_manager::types::{
+    ActionResult, Config, TaskBalance, TaskEndReason, TaskExecutionRecord,
+    TASK_EXECUTION_HISTORY_LIMIT,
+};
+use croncat_sdk_tasks::types::{Action, Boundary, CosmosQuery, TaskInfo};
 use cw20::{Cw20CoinVerified, Cw20ExecuteMsg};
 use serde_cw_value::Value;
 
 use crate::{
+    authz::authz_exec_msg,
     balances::{add_fee_rewards, add_user_cw20},
-    contract::TASK_REPLY,
-    state::{QueueItem, CONFIG, REPLY_QUEUE, TASKS_BALANCES},
+    contract::{CALLBACK_REPLY, TASK_REPLY},
+    ibc::ibc_transfer_msg,
+    staking::{undelegate_task_stake, update_task_delegations},
+    state::{
+        QueueItem, CONFIG, REPLY_QUEUE, RESCHEDULE_QUEUE, SPONSORED_TASKS, TASKS_BALANCES,
+        TASK_EXECUTION_HISTORY, TASK_EXECUTION_HISTORY_NEXT_IDX,
+    },
     ContractError,
 };
 
@@ -61,6 +75,28 @@ pub(crate) fn check_if_sender_is_tasks(
     Ok(())
 }
 
+/// Check if sender is the other version of this contract, registered in the factory
+pub(crate) fn check_if_sender_is_other_version(
+    deps_queries: &QuerierWrapper<Empty>,
+    config: &Config,
+    contract_addr: &Addr,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let lookup = |addr: &Addr| {
+        croncat_sdk_factory::state::CONTRACT_ADDRS_LOOKUP.query(
+            deps_queries,
+            config.croncat_factory_addr.clone(),
+            addr.clone(),
+        )
+    };
+    let contract_name = lookup(contract_addr)?;
+    if contract_name.is_none() || contract_name != lookup(sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
 pub(crate) fn get_agents_addr(
     deps_queries: &QuerierWrapper<Empty>,
     config: &Config,
@@ -75,12 +111,6 @@ pub(crate) fn get_agents_addr(
         .ok_or(ContractError::InvalidKey {})
 }
 
-pub(crate) fn gas_with_fees(gas_amount: u64, fee: u64) -> Result<u64, ContractError> {
-    gas_fee(gas_amount, fee)?
-        .checked_add(gas_amount)
-        .ok_or(ContractError::InvalidGasCalculation {})
-}
-
 pub(crate) fn gas_fee(gas_amount: u64, fee: u64) -> Result<u64, ContractError> {
     gas_amount
         .checked_mul(fee)
@@ -88,6 +118,18 @@ pub(crate) fn gas_fee(gas_amount: u64, fee: u64) -> Result<u64, ContractError> {
         .ok_or(ContractError::InvalidGasCalculation {})
 }
 
+/// Gas price of the denom task pays the gas in, errors if denom is not whitelisted
+pub(crate) fn task_gas_price<'a>(
+    config: &'a Config,
+    gas_denom: &str,
+) -> Result<&'a GasPrice, ContractError> {
+    config
+        .gas_price_of(gas_denom)
+        .ok_or_else(|| ContractError::UnsupportedGasDenom {
+            denom: gas_denom.to_owned(),
+        })
+}
+
 pub(crate) fn attached_natives(
     native_denom: &str,
     funds: Vec<Coin>,
@@ -160,98 +202,131 @@ pub fn query_agent(
     Ok(response)
 }
 
-pub(crate) fn create_bank_send_message(
-    to: &Addr,
-    denom: &str,
-    amount: u128,
-) -> StdResult<CosmosMsg> {
-    let coin = coin(amount, denom.to_owned());
-    let msg = BankMsg::Send {
-        to_address: to.into(),
-        amount: vec![coin],
-    };
-
-    Ok(msg.into())
-}
-
 /// Get sub messages for this task
 /// To minimize gas consumption for loads we only reply on failure
 /// And the last item to calculate rewards and reschedule or removal of the task
-pub(crate) fn task_sub_msgs(task: &croncat_sdk_tasks::types::TaskInfo) -> Vec<SubMsg> {
+pub(crate) fn task_sub_msgs(
+    task: &croncat_sdk_tasks::types::TaskInfo,
+    block: &BlockInfo,
+    manager_addr: &Addr,
+) -> Result<Vec<SubMsg>, ContractError> {
     let mut sub_msgs = Vec::with_capacity(task.actions.len());
-    let mut actions_iter = task.actions.iter().enumerate();
-
-    // safe unwrap here, we don't allow empty actions
-    let (last_idx, last_action) = actions_iter.next_back().unwrap();
+    // safe, we don't allow empty actions
+    let last_idx = task.actions.len() - 1;
 
-    for (idx, action) in actions_iter {
+    for (idx, action) in task.actions.iter().enumerate() {
+        let msg = match &action.msg {
+            msg if action.authz => authz_exec_msg(manager_addr, &task.owner_addr, msg)?,
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id,
+                to_address,
+                amount,
+                ..
+            }) => ibc_transfer_msg(
+                manager_addr,
+                block,
+                channel_id,
+                to_address,
+                amount,
+                action.ibc_timeout,
+            )?,
+            msg => msg.clone(),
+        };
+        // Last action finalizes the task, ibc transfers reply with the packet sequence
+        let sub_msg = if idx == last_idx || matches!(action.msg, CosmosMsg::Ibc(_)) {
+            SubMsg::reply_always(msg, idx as u64)
+        } else {
+            SubMsg::reply_on_error(msg, idx as u64)
+        };
         if let Some(gas_limit) = action.gas_limit {
-            sub_msgs.push(
-                SubMsg::reply_on_error(action.msg.clone(), idx as u64).with_gas_limit(gas_limit),
-            );
+            sub_msgs.push(sub_msg.with_gas_limit(gas_limit));
         } else {
-            sub_msgs.push(SubMsg::reply_on_error(action.msg.clone(), idx as u64));
+            sub_msgs.push(sub_msg);
         }
     }
-    if let Some(gas_limit) = last_action.gas_limit {
-        sub_msgs.push(
-            SubMsg::reply_always(last_action.msg.clone(), last_idx as u64)
-                .with_gas_limit(gas_limit),
-        );
-    } else {
-        sub_msgs.push(SubMsg::reply_always(
-            last_action.msg.clone(),
-            last_idx as u64,
-        ));
-    }
-    sub_msgs
+    Ok(sub_msgs)
 }
 
 pub(crate) fn parse_reply_msg(
     storage: &mut dyn Storage,
     queue_item: &mut QueueItem,
     msg: Reply,
-) -> bool {
+) -> StdResult<bool> {
     let id = msg.id as usize;
-    if let cosmwasm_std::SubMsgResult::Err(err) = msg.result {
+    let failed = if let cosmwasm_std::SubMsgResult::Err(err) = msg.result {
         queue_item.failures.push((id as u8, err));
-    }
+        true
+    } else {
+        false
+    };
     let last = queue_item.task.actions.len() == id + 1;
     // If last action let's clean state here
     if last {
         REPLY_QUEUE.remove(storage)
+    } else if failed {
+        // Keep the failure until the last action
+        REPLY_QUEUE.save(storage, queue_item)?;
     }
-    last
+    Ok(last)
+}
+
+/// Checks task balance covers the execution at the given gas price
+pub(crate) fn has_enough_for_gas_price(
+    task: &TaskInfo,
+    task_balance: &TaskBalance,
+    gas_price: &GasPrice,
+    native_denom: &str,
+) -> Result<bool, ContractError> {
+    let gas_with_fees = task
+        .amount_for_one_task
+        .gas_with_fees(task.amount_for_one_task.gas)
+        .map_err(|_| ContractError::InvalidGasCalculation {})?;
+    let native_for_gas_required = gas_price
+        .calculate(gas_with_fees)
+        .map_err(|_| ContractError::InvalidGasCalculation {})?;
+    let native_for_gas_required = Uint128::new(native_for_gas_required);
+    let (native_for_sends_required, ibc_required) =
+        calculate_required_natives(task.amount_for_one_task.coin.clone(), native_denom)?;
+    Ok(task_balance
+        .with_sponsored_gas(native_for_gas_required)
+        .verify_enough_attached(
+            native_for_sends_required + native_for_gas_required,
+            task.amount_for_one_task.cw20.clone(),
+            ibc_required,
+            false,
+            native_denom,
+        )
+        .is_ok())
 }
 
 pub(crate) fn finalize_task(
     deps: DepsMut,
+    env: &Env,
     queue_item: QueueItem,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut task_balance =
         TASKS_BALANCES.load(deps.storage, queue_item.task.task_hash.as_bytes())?;
+    let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
     // Sub native for gas
-    let gas_with_fees = gas_with_fees(
-        queue_item.task.amount_for_one_task.gas,
-        (queue_item.task.amount_for_one_task.agent_fee
-            + queue_item.task.amount_for_one_task.treasury_fee) as u64,
-    )?;
+    let gas_with_fees = queue_item
+        .task
+        .amount_for_one_task
+        .gas_with_fees(queue_item.task.amount_for_one_task.gas)
+        .map_err(|_| ContractError::InvalidGasCalculation {})?;
     let native_for_gas_required = queue_item
         .task
         .amount_for_one_task
         .gas_price
         .calculate(gas_with_fees)
         .unwrap();
-    task_balance.native_balance = task_balance
-        .native_balance
-        .checked_sub(Uint128::new(native_for_gas_required))
-        .map_err(StdError::overflow)?;
+    task_balance.sub_gas(Uint128::new(native_for_gas_required))?;
 
     add_fee_rewards(
         deps.storage,
         queue_item.task.amount_for_one_task.gas,
         &queue_item.task.amount_for_one_task.gas_price,
+        &gas_denom,
         &queue_item.agent_addr,
         queue_item.task.amount_for_one_task.agent_fee,
         queue_item.task.amount_for_one_task.treasury_fee,
@@ -263,40 +338,112 @@ pub(crate) fn finalize_task(
 
     // Sub transferred coins
     for coin in amounts_without_failed_txs.coin.iter().flatten() {
-        task_balance.sub_coin(coin, &config.native_denom)?;
+        task_balance.sub_coin(coin, &gas_denom)?;
     }
     // Sub transferred cw20s
     if let Some(cw20) = &amounts_without_failed_txs.cw20 {
         task_balance.sub_cw20(cw20)?;
     }
+    update_task_delegations(
+        deps.storage,
+        &config,
+        env.block.time,
+        &queue_item,
+        &mut task_balance,
+    )?;
     let (native_for_sends_required, ibc_required) =
-        calculate_required_natives(original_amounts.coin, &config.native_denom)?;
+        calculate_required_natives(original_amounts.coin, &gas_denom)?;
 
     // unregister task and return unused deposits if any of this:
     // - not recurring
     // - should stop on fail
+    // - this was the last allowed execution
     // - task balance drained
-    if matches!(
+    let end_reason = if matches!(
         queue_item.task.interval,
         croncat_sdk_tasks::types::Interval::Once
-    ) || (queue_item.task.stop_on_fail && !queue_item.failures.is_empty())
-        || task_balance
-            .verify_enough_attached(
-                native_for_sends_required + Uint128::new(native_for_gas_required),
-                original_amounts.cw20,
-                ibc_required,
-                false,
-                &config.native_denom,
-            )
-            .is_err()
+    ) {
+        Some(TaskEndReason::NotRecurring)
+    } else if queue_item.task.stop_on_fail && !queue_item.failures.is_empty() {
+        Some(TaskEndReason::StopOnFail)
+    } else if matches!(queue_item.task.remaining_executions, Some(remaining) if remaining <= 1) {
+        Some(TaskEndReason::MaxExecutions)
+    } else if task_balance
+        .with_sponsored_gas(Uint128::new(native_for_gas_required))
+        .verify_enough_attached(
+            native_for_sends_required + Uint128::new(native_for_gas_required),
+            original_amounts.cw20,
+            ibc_required,
+            false,
+            &gas_denom,
+        )
+        .is_err()
     {
+        Some(TaskEndReason::BalanceDrained)
+    } else {
+        None
+    };
+
+    let mut action_results: Vec<ActionResult> = (0..queue_item.task.actions.len())
+        .map(|idx| {
+            match queue_item
+                .failures
+                .iter()
+                .find(|(failed_idx, _)| *failed_idx as usize == idx)
+            {
+                Some((_, failure)) => ActionResult::Failure(failure.clone()),
+                None => ActionResult::Success,
+            }
+        })
+        .collect();
+    // Skipped indexes are ascending, so each one lands on its original position
+    for idx in queue_item.skipped_actions.iter() {
+        action_results.insert(*idx as usize, ActionResult::Skipped);
+    }
+    add_task_execution_record(
+        deps.storage,
+        queue_item.task.task_hash.as_bytes(),
+        &TaskExecutionRecord {
+            block_height: env.block.height,
+            block_time: env.block.time,
+            agent_addr: queue_item.agent_addr.clone(),
+            gas_charged: coin(native_for_gas_required, &gas_denom),
+            action_results,
+            end_reason: end_reason.clone(),
+        },
+    )?;
+
+    if let Some(end_reason) = end_reason {
+        let undelegate_msgs = undelegate_task_stake(
+            deps.storage,
+            &deps.querier,
+            env,
+            &config,
+            queue_item.task.task_hash.as_bytes(),
+            &queue_item.task.owner_addr,
+        )?;
+        let sponsor_refund = refund_task_sponsor(
+            deps.storage,
+            queue_item.task.task_hash.as_bytes(),
+            &mut task_balance,
+            &gas_denom,
+        )?;
+        let callback = take_callback_fee(
+            deps.storage,
+            &queue_item.task,
+            &queue_item.agent_addr,
+            &end_reason,
+            &mut task_balance,
+            &gas_denom,
+        )?;
+        let cw20_refunded = task_balance.cw20_balance.clone();
         // Transfer unused balances to the task creator and cw20s to the temp balances
         let task_hash = queue_item.task.task_hash;
         let coins_transfer = remove_task_balance(
             deps.storage,
             task_balance,
             &queue_item.task.owner_addr,
-            &config.native_denom,
+            &gas_denom,
             task_hash.as_bytes(),
         )?;
         // Remove task on tasks contract
@@ -305,14 +452,29 @@ pub(crate) fn finalize_task(
             task_hash: task_hash.clone().into_bytes(),
         }
         .into_cosmos_msg(tasks_addr)?;
-        Ok(Response::new()
+        let mut res = Response::new()
             .add_message(msg)
+            .add_submessages(undelegate_msgs)
+            .add_messages(sponsor_refund)
             .add_message(BankMsg::Send {
                 to_address: queue_item.task.owner_addr.into_string(),
-                amount: coins_transfer,
+                amount: coins_transfer.clone(),
             })
             .add_attribute("lifecycle", "task_ended")
-            .add_attribute("task_hash", task_hash))
+            .add_attribute("task_hash", task_hash.clone());
+        if let Some((callback_addr, gas_limit)) = callback {
+            res = res.add_submessage(task_callback_submsg(
+                callback_addr,
+                gas_limit,
+                TaskEndedCallback {
+                    task_hash,
+                    end_reason,
+                    refunded: coins_transfer,
+                    cw20_refunded,
+                },
+            )?);
+        }
+        Ok(res)
     } else {
         let tasks_addr = get_tasks_addr(&deps.querier, &config)?;
         TASKS_BALANCES.save(
@@ -320,6 +482,10 @@ pub(crate) fn finalize_task(
             queue_item.task.task_hash.as_bytes(),
             &task_balance,
         )?;
+        // Tasks contract can end the task on reschedule, keep it to notify callbacks
+        if queue_item.task.callbacks.is_some() {
+            RESCHEDULE_QUEUE.save(deps.storage, &queue_item)?;
+        }
         let msg = croncat_sdk_core::internal_messages::tasks::TasksRescheduleTask {
             task_hash: queue_item.task.task_hash.into_bytes(),
         }
@@ -328,41 +494,254 @@ pub(crate) fn finalize_task(
     }
 }
 
+/// Takes the callback gas from the task balance, if task has a callback for this end reason.
+/// Returns callback address with its gas limit, callback skipped if task balance can't cover it
+pub(crate) fn take_callback_fee(
+    storage: &mut dyn Storage,
+    task: &TaskInfo,
+    agent_addr: &Addr,
+    end_reason: &TaskEndReason,
+    task_balance: &mut TaskBalance,
+    gas_denom: &str,
+) -> Result<Option<(String, u64)>, ContractError> {
+    let Some(callbacks) = &task.callbacks else {
+        return Ok(None);
+    };
+    let callback = if end_reason.is_failure() {
+        &callbacks.on_failure
+    } else {
+        &callbacks.on_complete
+    };
+    let Some(callback_addr) = callback else {
+        return Ok(None);
+    };
+    let gas_limit = callbacks.gas_limit.unwrap_or_default();
+    let amounts = &task.amount_for_one_task;
+    let gas_with_fees = amounts
+        .gas_with_fees(gas_limit)
+        .map_err(|_| ContractError::InvalidGasCalculation {})?;
+    let native_for_callback = amounts
+        .gas_price
+        .calculate(gas_with_fees)
+        .map_err(|_| ContractError::InvalidGasCalculation {})?;
+    match task_balance
+        .native_balance
+        .checked_sub(Uint128::new(native_for_callback))
+    {
+        Ok(native_balance) => task_balance.native_balance = native_balance,
+        Err(_) => return Ok(None),
+    }
+    add_fee_rewards(
+        storage,
+        gas_limit,
+        &amounts.gas_price,
+        gas_denom,
+        agent_addr,
+        amounts.agent_fee,
+        amounts.treasury_fee,
+        false,
+    )?;
+    Ok(Some((callback_addr.clone(), gas_limit)))
+}
+
+/// Message to the task callback contract, failed callback doesn't revert the task execution
+pub(crate) fn task_callback_submsg(
+    callback_addr: String,
+    gas_limit: u64,
+    callback: TaskEndedCallback,
+) -> StdResult<SubMsg> {
+    let msg = WasmMsg::Execute {
+        contract_addr: callback_addr,
+        msg: to_binary(&TaskCallbackMsg::CroncatTaskEnded(callback))?,
+        funds: vec![],
+    };
+    Ok(SubMsg::reply_on_error(msg, CALLBACK_REPLY).with_gas_limit(gas_limit))
+}
+
+/// Adds the record to the execution history of the task,
+/// dropping the oldest record once the history is over the limit
+pub(crate) fn add_task_execution_record(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    record: &TaskExecutionRecord,
+) -> StdResult<()> {
+    let idx = TASK_EXECUTION_HISTORY_NEXT_IDX
+        .may_load(storage, task_hash)?
+        .unwrap_or_default();
+    TASK_EXECUTION_HISTORY.save(storage, (task_hash, idx), record)?;
+    if let Some(oldest_idx) = idx.checked_sub(TASK_EXECUTION_HISTORY_LIMIT) {
+        TASK_EXECUTION_HISTORY.remove(storage, (task_hash, oldest_idx));
+    }
+    TASK_EXECUTION_HISTORY_NEXT_IDX.save(storage, task_hash, &(idx + 1))
+}
+
+/// Moves the execution history along with the balance of the updated task
+pub(crate) fn move_task_execution_history(
+    storage: &mut dyn Storage,
+    old_task_hash: &[u8],
+    new_task_hash: &[u8],
+) -> StdResult<()> {
+    let Some(next_idx) = TASK_EXECUTION_HISTORY_NEXT_IDX.may_load(storage, old_task_hash)? else {
+        return Ok(());
+    };
+    let records = TASK_EXECUTION_HISTORY
+        .prefix(old_task_hash)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(u64, TaskExecutionRecord)>>>()?;
+    for (idx, _) in records.iter() {
+        TASK_EXECUTION_HISTORY.remove(storage, (old_task_hash, *idx));
+    }
+    TASK_EXECUTION_HISTORY_NEXT_IDX.remove(storage, old_task_hash);
+    for (idx, record) in records {
+        TASK_EXECUTION_HISTORY.save(storage, (new_task_hash, idx), &record)?;
+    }
+    TASK_EXECUTION_HISTORY_NEXT_IDX.save(storage, new_task_hash, &next_idx)
+}
+
+/// Sets the end reason on the latest execution record of the task,
+/// for tasks ended after the execution was recorded
+pub(crate) fn set_last_task_end_reason(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    end_reason: TaskEndReason,
+) -> StdResult<()> {
+    let last_idx = TASK_EXECUTION_HISTORY_NEXT_IDX
+        .may_load(storage, task_hash)?
+        .and_then(|next_idx| next_idx.checked_sub(1));
+    if let Some(last_idx) = last_idx {
+        if let Some(mut record) = TASK_EXECUTION_HISTORY.may_load(storage, (task_hash, last_idx))? {
+            record.end_reason = Some(end_reason);
+            TASK_EXECUTION_HISTORY.save(storage, (task_hash, last_idx), &record)?;
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn amounts_without_failed_txs(queue_item: &QueueItem) -> StdResult<AmountForOneTask> {
     let mut amounts = queue_item.task.amount_for_one_task.clone();
     for (idx, _) in queue_item.failures.iter() {
-        match &queue_item.task.actions[(*idx) as usize].msg {
-            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
-                for coin in amount {
-                    amounts.sub_coin(coin)?;
-                }
+        let action = &queue_item.task.actions[(*idx) as usize];
+        sub_action_amounts(&mut amounts, action)?;
+    }
+    Ok(amounts)
+}
+
+/// Subtracts the coins and cw20s sent by the action
+fn sub_action_amounts(amounts: &mut AmountForOneTask, action: &Action) -> StdResult<()> {
+    // Coins of the authz action were never taken from the task balance
+    if action.authz {
+        return Ok(());
+    }
+    match &action.msg {
+        CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+            for coin in amount {
+                amounts.sub_coin(coin)?;
             }
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                msg, contract_addr, ..
-            }) => {
-                if let Ok(cw20_msg) = cosmwasm_std::from_binary(msg) {
-                    match cw20_msg {
-                        Cw20ExecuteMsg::Send { amount, .. } => {
-                            amounts.sub_cw20(&Cw20CoinVerified {
-                                // Addr safe here because we checked it at `is_valid_msg_calculate_usage`
-                                address: Addr::unchecked(contract_addr),
-                                amount,
-                            })?;
-                        }
-                        Cw20ExecuteMsg::Transfer { amount, .. } => {
-                            amounts.sub_cw20(&Cw20CoinVerified {
-                                address: Addr::unchecked(contract_addr),
-                                amount,
-                            })?;
-                        }
-                        _ => (),
-                    };
-                }
+        }
+        CosmosMsg::Staking(StakingMsg::Delegate { amount, .. })
+        | CosmosMsg::Ibc(IbcMsg::Transfer { amount, .. }) => {
+            amounts.sub_coin(amount)?;
+        }
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            msg, contract_addr, ..
+        }) => {
+            if let Ok(cw20_msg) = cosmwasm_std::from_binary(msg) {
+                match cw20_msg {
+                    Cw20ExecuteMsg::Send { amount, .. } => {
+                        amounts.sub_cw20(&Cw20CoinVerified {
+                            // Addr safe here because we checked it at `is_valid_msg_calculate_usage`
+                            address: Addr::unchecked(contract_addr),
+                            amount,
+                        })?;
+                    }
+                    Cw20ExecuteMsg::Transfer { amount, .. } => {
+                        amounts.sub_cw20(&Cw20CoinVerified {
+                            address: Addr::unchecked(contract_addr),
+                            amount,
+                        })?;
+                    }
+                    _ => (),
+                };
             }
-            _ => (),
         }
+        _ => (),
     }
-    Ok(amounts)
+    Ok(())
+}
+
+/// Takes out the actions which conditions are not met by the query responses,
+/// gas and amounts of the skipped actions are not charged.
+/// Transforms of the skipped actions are dropped, the rest point to the new action indexes.
+/// Returns indexes of the skipped actions
+pub(crate) fn skip_unmet_actions(
+    task: &mut TaskInfo,
+    query_responses: &[Option<Binary>],
+) -> Result<Vec<u64>, ContractError> {
+    let mut skipped = vec![];
+    let mut new_indexes = Vec::with_capacity(task.actions.len());
+    let mut actions = Vec::with_capacity(task.actions.len());
+    for (idx, action) in std::mem::take(&mut task.actions).into_iter().enumerate() {
+        let met = match &action.condition {
+            Some(condition) => match query_responses
+                .get(condition.query_idx as usize)
+                .and_then(|opt| opt.as_ref())
+            {
+                // Response that can't be compared doesn't meet the condition
+                Some(query_response) => condition.is_met(query_response).unwrap_or(false),
+                // Query gave no data to compare
+                None => false,
+            },
+            None => true,
+        };
+        if met {
+            new_indexes.push(Some(actions.len() as u64));
+            actions.push(action);
+        } else {
+            new_indexes.push(None);
+            sub_action_amounts(&mut task.amount_for_one_task, &action)?;
+            // Conditional actions always have gas limit, it's checked on task creation
+            let action_gas = action.gas_limit.unwrap_or_default();
+            task.amount_for_one_task.gas = task.amount_for_one_task.gas.saturating_sub(action_gas);
+            skipped.push(idx as u64);
+        }
+    }
+    // Nothing to execute, same as the false query result
+    if actions.is_empty() {
+        return Err(ContractError::TaskQueryResultFalse {});
+    }
+    task.actions = actions;
+    task.transforms.retain_mut(|transform| {
+        match new_indexes.get(transform.action_idx as usize) {
+            Some(Some(new_idx)) => {
+                transform.action_idx = *new_idx;
+                true
+            }
+            Some(None) => false,
+            // Out of range, left for the transform validation
+            None => true,
+        }
+    });
+    Ok(skipped)
+}
+
+/// Takes the sponsor off the task balance, unused gas balance goes back to the sponsor
+pub(crate) fn refund_task_sponsor(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    task_balance: &mut TaskBalance,
+    native_denom: &str,
+) -> StdResult<Option<BankMsg>> {
+    let Some(sponsor) = task_balance.sponsor.take() else {
+        return Ok(None);
+    };
+    SPONSORED_TASKS.remove(storage, (&sponsor.sponsor_addr, task_hash));
+    if sponsor.gas_balance.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(BankMsg::Send {
+        to_address: sponsor.sponsor_addr.into_string(),
+        amount: vec![coin(sponsor.gas_balance.u128(), native_denom)],
+    }))
 }
 
 /// This function will
@@ -464,6 +843,8 @@ pub fn process_queries(
 ) -> Result<Vec<Option<cosmwasm_std::Binary>>, ContractError> {
     let mut responses: Vec<Option<Binary>> =
         Vec::with_capacity(task.queries.as_ref().unwrap().len());
+    // Results of the croncat queries, for the query rule
+    let mut results: Vec<Option<bool>> = Vec::with_capacity(responses.capacity());
 
     let queries = if let Some(qs) = &task.queries {
         qs
@@ -484,12 +865,15 @@ pub fn process_queries(
                     }
                     .into(),
                 )?;
-                if q.check_result && !res.result {
+                // Query rule replaces the check of every result
+                if task.query_rule.is_none() && q.check_result && !res.result {
                     return Err(ContractError::TaskQueryResultFalse {});
                 }
+                results.push(Some(res.result));
                 responses.push(Some(res.data));
             }
             CosmosQuery::Wasm(wq) => {
+                results.push(None);
                 // Cover all native wasm query types
                 match wq {
                     WasmQuery::Smart { contract_addr, msg } => {
@@ -556,6 +940,12 @@ pub fn process_queries(
         }
     }
 
+    if let Some(query_rule) = &task.query_rule {
+        if !query_rule.evaluate(&results) {
+            return Err(ContractError::TaskQueryResultFalse {});
+        }
+    }
+
     Ok(responses)
 }
 
@@ -566,6 +956,8 @@ pub fn process_queries(
 /// 1. Transforms will only validate indexes, but never content
 /// 2. Transforms are strict: If a query cannot find data or replace as intended, error
 /// 3. Only supported message types, otherwise dont use a transform until supported
+/// 4. Operations on the query value are checked: overflow, underflow or division by zero errors
+/// 5. Transformed message has to deserialize back into the same kind of message, nothing gets dropped
 pub fn replace_values(
     task: &mut TaskInfo,
     query_response_data: Vec<Option<cosmwasm_std::Binary>>,
@@ -591,7 +983,8 @@ pub fn replace_values(
         {
             let mut q_val = cosmwasm_std::from_binary(query_bin)
                 .map_err(|e| StdError::generic_err(e.to_string()))?;
-            let replace_value = transform.query_response_path.find_value(&mut q_val)?;
+            let query_value = transform.query_response_path.find_value(&mut q_val)?;
+            let replace_value = transform.apply_operations(query_value, &query_response_data)?;
 
             if let Some(action) = task.actions.get_mut(transform.action_idx as usize) {
                 // NOTE: This only covers the supported methods known to valid task actions!
@@ -610,76 +1003,22 @@ pub fn replace_values(
                                 .map_err(|e| StdError::generic_err(e.to_string()))?,
                         );
                     }
-                    CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-                        let mut action_value = serde_json_wasm::from_str::<Value>(&format!(
-                            r#"{{"bank":{{"send":{{"to_address": "{}", "amount": {}}}}}}}"#,
-                            to_address,
-                            serde_json_wasm::to_string(amount).unwrap()
-                        ))
-                        .unwrap();
+                    // Typed round-trip, so the result has to be a valid message of the same kind
+                    msg @ (CosmosMsg::Bank(_)
+                    | CosmosMsg::Staking(_)
+                    | CosmosMsg::Distribution(_)
+                    | CosmosMsg::Ibc(_)) => {
+                        let mut action_value = serde_cw_value::to_value(&*msg)
+                            .map_err(|_| ContractError::TaskInvalidTransform {})?;
                         let replaced_value = transform.action_path.find_value(&mut action_value)?;
                         *replaced_value = replace_value.clone();
-
-                        // we mutated, now apply back to bank msg
-                        // could this be more insane? probably. For now, hooray we hax'd it to werkz! :D
-                        if let Value::Map(parent_map) = &action_value {
-                            if let Some(Value::Map(bank_send_map)) =
-                                &parent_map.get(&Value::String("bank".to_string()))
-                            {
-                                if let Some(Value::Map(ref send_map)) =
-                                    bank_send_map.get(&Value::String("send".to_string()))
-                                {
-                                    if let Some(Value::String(ref new_to_address)) =
-                                        send_map.get(&Value::String("to_address".to_string()))
-                                    {
-                                        *to_address = new_to_address.clone();
-                                    }
-                                    if let Some(Value::Seq(ref new_amount)) =
-                                        send_map.get(&Value::String("amount".to_string()))
-                                    {
-                                        let coins: Vec<Coin> = new_amount
-                                            .iter()
-                                            .filter_map(|value| {
-                                                if let Value::Map(coin_map) = value {
-                                                    let amount = coin_map
-                                                        .get(&Value::String("amount".to_string()))
-                                                        .and_then(|v| {
-                                                            if let Value::String(amount_str) = v {
-                                                                amount_str.parse::<u128>().ok()
-                                                            } else {
-                                                                None
-                                                            }
-                                                        });
-                                                    let denom = coin_map
-                                                        .get(&Value::String("denom".to_string()))
-                                                        .and_then(|v| {
-                                                            if let Value::String(denom_str) = v {
-                                                                Some(denom_str.to_string())
-                                                            } else {
-                                                                None
-                                                            }
-                                                        });
-
-                                                    if let (Some(amount), Some(denom)) =
-                                                        (amount, denom)
-                                                    {
-                                                        Some(Coin {
-                                                            denom,
-                                                            amount: amount.into(),
-                                                        })
-                                                    } else {
-                                                        None
-                                                    }
-                                                } else {
-                                                    None
-                                                }
-                                            })
-                                            .collect();
-                                        *amount = coins;
-                                    }
-                                }
-                            }
+                        let new_msg: CosmosMsg = action_value
+                            .deserialize_into()
+                            .map_err(|_| ContractError::TaskInvalidTransform {})?;
+                        if !is_same_msg_kind(msg, &new_msg) {
+                            return Err(ContractError::TaskInvalidTransform {});
                         }
+                        *msg = new_msg;
                     }
                     _ => return Err(ContractError::TaskTransformUnsupported {}),
                 }
@@ -689,6 +1028,66 @@ pub fn replace_values(
     Ok(())
 }
 
+/// Transform can only change the values of the message, not what the message does
+fn is_same_msg_kind(msg: &CosmosMsg, new_msg: &CosmosMsg) -> bool {
+    match (msg, new_msg) {
+        (CosmosMsg::Bank(msg), CosmosMsg::Bank(new_msg)) => {
+            discriminant(msg) == discriminant(new_msg)
+        }
+        (CosmosMsg::Staking(msg), CosmosMsg::Staking(new_msg)) => {
+            discriminant(msg) == discriminant(new_msg)
+        }
+        (CosmosMsg::Distribution(msg), CosmosMsg::Distribution(new_msg)) => {
+            discriminant(msg) == discriminant(new_msg)
+        }
+        (CosmosMsg::Ibc(msg), CosmosMsg::Ibc(new_msg)) => {
+            discriminant(msg) == discriminant(new_msg)
+        }
+        _ => false,
+    }
+}
+
+/// Recalculate native coins usage for this task
+/// Transforms could change amounts or denoms of the coins, so it has to be re-verified against the task balance
+/// Denoms the task used initially stay required, even if the amount is zero now
+pub(crate) fn recalculate_coins(task: &TaskInfo) -> Result<[Option<Coin>; 2], ContractError> {
+    let mut amounts = AmountForOneTask {
+        coin: task.amount_for_one_task.coin.clone().map(|coin| {
+            coin.map(|coin| Coin {
+                denom: coin.denom,
+                amount: Uint128::zero(),
+            })
+        }),
+        ..Default::default()
+    };
+    for action in task.actions.iter() {
+        // Coins of the authz action are not taken from the task balance
+        if action.authz {
+            continue;
+        }
+        let coins = match &action.msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                // Restrict no-coin transfer
+                if amount.is_empty() {
+                    return Err(ContractError::TaskNoLongerValid {});
+                }
+                amount.as_slice()
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) => funds.as_slice(),
+            CosmosMsg::Staking(StakingMsg::Delegate { amount, .. })
+            | CosmosMsg::Ibc(IbcMsg::Transfer { amount, .. }) => std::slice::from_ref(amount),
+            _ => &[],
+        };
+        for coin in coins {
+            // Zero coins will fail the transaction
+            if coin.amount.is_zero() || !amounts.add_coin(coin.clone())? {
+                return Err(ContractError::TaskNoLongerValid {});
+            }
+        }
+    }
+    Ok(amounts.coin)
+}
+
 /// Recalculate cw20 usage for this task
 /// And check for self-calls
 /// It can be initially zero, but after transform we still have to check it does have only one type of cw20
@@ -722,6 +1121,9 @@ pub(crate) fn recalculate_cw20(
                 contract_addr,
                 msg,
             )?;
+            if action.authz {
+                continue;
+            }
             let validated_addr = deps.api.addr_validate(contract_addr)?;
             if let Ok(cw20_msg) = cosmwasm_std::from_binary::<Cw20ExecuteMsg>(msg) {
                 // Don't let change type of cw20
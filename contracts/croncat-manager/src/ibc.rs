@@ -0,0 +1,122 @@
+//! Ibc transfers of the task actions rely on the [ibc-hooks](https://github.com/osmosis-labs/osmosis/tree/main/x/ibc-hooks)
+//! module of the chain: the transfer is sent with the `ibc_callback` memo and the module
+//! reports the ack or timeout of the packet with the `IbcLifecycleComplete` sudo.
+//! Without ibc-hooks on the chain coins of the failed transfers won't get back to the task balance
+
+use cosmwasm_std::{
+    Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, DepsMut, IbcMsg, Response, StdResult,
+    Storage,
+};
+
+use crate::{
+    proto::{decode_uint64_field, encode_bytes_field, encode_string_field, encode_uint64_field},
+    state::{IbcTransfer, QueueItem, CONFIG, IBC_TRANSFERS, TASKS_BALANCES},
+    ContractError,
+};
+
+const MSG_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+/// Ibc transfer of the task action, `ibc_timeout` of the action is in seconds from the execution time.
+/// Sent through the transfer module with the ibc-hooks memo,
+/// so manager gets notified about the ack or timeout of the transfer
+pub(crate) fn ibc_transfer_msg(
+    manager_addr: &Addr,
+    block: &BlockInfo,
+    channel_id: &str,
+    to_address: &str,
+    amount: &Coin,
+    ibc_timeout: Option<u64>,
+) -> Result<CosmosMsg, ContractError> {
+    let ibc_timeout = ibc_timeout.ok_or(ContractError::TaskNoLongerValid {})?;
+
+    let mut token = vec![];
+    encode_string_field(1, &amount.denom, &mut token);
+    encode_string_field(2, &amount.amount.to_string(), &mut token);
+
+    let mut value = vec![];
+    encode_string_field(1, "transfer", &mut value);
+    encode_string_field(2, channel_id, &mut value);
+    encode_bytes_field(3, &token, &mut value);
+    encode_string_field(4, manager_addr.as_str(), &mut value);
+    encode_string_field(5, to_address, &mut value);
+    encode_uint64_field(7, block.time.plus_seconds(ibc_timeout).nanos(), &mut value);
+    encode_string_field(
+        8,
+        &format!(r#"{{"ibc_callback":"{manager_addr}"}}"#),
+        &mut value,
+    );
+    Ok(CosmosMsg::Stargate {
+        type_url: MSG_TRANSFER_TYPE_URL.to_owned(),
+        value: Binary(value),
+    })
+}
+
+/// Keeps the ibc transfer of the successful task action until it's acknowledged.
+/// Packet sequence comes with the `MsgTransferResponse`
+pub(crate) fn save_ibc_transfer(
+    storage: &mut dyn Storage,
+    queue_item: &QueueItem,
+    action_idx: u64,
+    data: Option<Binary>,
+) -> StdResult<()> {
+    let action_msg = queue_item
+        .task
+        .actions
+        .get(action_idx as usize)
+        .map(|action| &action.msg);
+    if let Some(CosmosMsg::Ibc(IbcMsg::Transfer {
+        channel_id, amount, ..
+    })) = action_msg
+    {
+        if let Some(sequence) = data.and_then(|data| decode_uint64_field(1, &data)) {
+            IBC_TRANSFERS.save(
+                storage,
+                (channel_id, sequence),
+                &IbcTransfer {
+                    task_hash: queue_item.task.task_hash.clone(),
+                    owner_addr: queue_item.task.owner_addr.clone(),
+                    amount: amount.clone(),
+                },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Sudo: IbcLifecycleComplete
+/// Coins of the failed or timed out transfer get back to the task balance,
+/// or to the task owner if the task is no longer around
+pub fn sudo_ibc_lifecycle_complete(
+    deps: DepsMut,
+    channel: String,
+    sequence: u64,
+    success: bool,
+) -> Result<Response, ContractError> {
+    let Some(transfer) = IBC_TRANSFERS.may_load(deps.storage, (&channel, sequence))? else {
+        return Ok(Response::new());
+    };
+    IBC_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    let res = Response::new()
+        .add_attribute("action", "ibc_lifecycle_complete")
+        .add_attribute("task_hash", transfer.task_hash.clone())
+        .add_attribute("success", success.to_string());
+    if success {
+        return Ok(res);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    match TASKS_BALANCES.may_load(deps.storage, transfer.task_hash.as_bytes())? {
+        Some(mut task_balance) => {
+            let gas_denom = task_balance.gas_denom(&config.native_denom).to_owned();
+            task_balance.add_coin(&transfer.amount, &gas_denom)?;
+            TASKS_BALANCES.save(deps.storage, transfer.task_hash.as_bytes(), &task_balance)?;
+            Ok(res.add_attribute("refunded_to", "task_balance"))
+        }
+        None => Ok(res
+            .add_attribute("refunded_to", transfer.owner_addr.as_str())
+            .add_message(BankMsg::Send {
+                to_address: transfer.owner_addr.into_string(),
+                amount: vec![transfer.amount],
+            })),
+    }
+}
@@ -1,10 +1,16 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
+mod authz;
 pub mod balances;
 pub mod contract;
 mod error;
+mod gas_price;
 mod helpers;
+mod ibc;
+mod migrations;
 pub mod msg;
+mod proto;
+mod staking;
 pub mod state;
 
 pub use error::ContractError;
@@ -0,0 +1,28 @@
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::{
+    state::{AGENT_REWARDS, CONFIG, TREASURY_BALANCE},
+    ContractError,
+};
+
+/// Agent rewards and treasury balance are tracked per gas denom since `1.1.0`,
+/// balances accrued before are in the native denom
+pub(crate) fn migrate_rewards_per_denom(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    const OLD_TREASURY_BALANCE: Item<Uint128> = Item::new("treasury_balance");
+    const OLD_AGENT_REWARDS: Map<&Addr, Uint128> = Map::new("agent_rewards");
+
+    let native_denom = CONFIG.load(storage)?.native_denom;
+    if let Some(balance) = OLD_TREASURY_BALANCE.may_load(storage)? {
+        TREASURY_BALANCE.save(storage, &native_denom, &balance)?;
+        OLD_TREASURY_BALANCE.remove(storage);
+    }
+    let agent_rewards = OLD_AGENT_REWARDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (agent_addr, rewards) in agent_rewards {
+        AGENT_REWARDS.save(storage, (&agent_addr, &native_denom), &rewards)?;
+        OLD_AGENT_REWARDS.remove(storage, &agent_addr);
+    }
+    Ok(())
+}
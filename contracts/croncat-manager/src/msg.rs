@@ -1,5 +1,5 @@
 pub use croncat_sdk_manager::msg::{
     AgentWithdrawCallback, ManagerExecuteMsg as ExecuteMsg,
-    ManagerInstantiateMsg as InstantiateMsg, ManagerQueryMsg as QueryMsg,
-    ManagerReceiveMsg as ReceiveMsg,
+    ManagerInstantiateMsg as InstantiateMsg, ManagerMigrateMsg as MigrateMsg,
+    ManagerQueryMsg as QueryMsg, ManagerReceiveMsg as ReceiveMsg, ManagerSudoMsg as SudoMsg,
 };
@@ -0,0 +1,94 @@
+//! Minimal protobuf encoding of the stargate messages sent by the manager
+//! and decoding of the stargate responses
+
+pub(crate) fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Length-delimited field, empty value is skipped as a default one
+pub(crate) fn encode_bytes_field(field: u32, value: &[u8], buf: &mut Vec<u8>) {
+    if value.is_empty() {
+        return;
+    }
+    encode_varint(((field << 3) | 2) as u64, buf);
+    encode_varint(value.len() as u64, buf);
+    buf.extend_from_slice(value);
+}
+
+pub(crate) fn encode_string_field(field: u32, value: &str, buf: &mut Vec<u8>) {
+    encode_bytes_field(field, value.as_bytes(), buf)
+}
+
+/// Varint field, zero value is skipped as a default one
+pub(crate) fn encode_uint64_field(field: u32, value: u64, buf: &mut Vec<u8>) {
+    if value == 0 {
+        return;
+    }
+    encode_varint((field << 3) as u64, buf);
+    encode_varint(value, buf);
+}
+
+fn decode_varint(data: &mut &[u8]) -> Option<u64> {
+    let bytes: &[u8] = data;
+    let mut value = 0u64;
+    for (idx, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * idx);
+        if byte & 0x80 == 0 {
+            *data = &bytes[idx + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+enum FieldValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Reads the field of the encoded message, skipping the other fields
+fn decode_field(field: u32, mut data: &[u8]) -> Option<FieldValue<'_>> {
+    while !data.is_empty() {
+        let key = decode_varint(&mut data)?;
+        let value = match key & 0x7 {
+            0 => FieldValue::Varint(decode_varint(&mut data)?),
+            1 => {
+                data = data.get(8..)?;
+                continue;
+            }
+            2 => {
+                let len = decode_varint(&mut data)? as usize;
+                let value = data.get(..len)?;
+                data = &data[len..];
+                FieldValue::Bytes(value)
+            }
+            5 => {
+                data = data.get(4..)?;
+                continue;
+            }
+            _ => return None,
+        };
+        if key >> 3 == field as u64 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+pub(crate) fn decode_uint64_field(field: u32, data: &[u8]) -> Option<u64> {
+    match decode_field(field, data)? {
+        FieldValue::Varint(value) => Some(value),
+        FieldValue::Bytes(_) => None,
+    }
+}
+
+pub(crate) fn decode_string_field(field: u32, data: &[u8]) -> Option<String> {
+    match decode_field(field, data)? {
+        FieldValue::Bytes(value) => String::from_utf8(value.to_vec()).ok(),
+        FieldValue::Varint(_) => None,
+    }
+}
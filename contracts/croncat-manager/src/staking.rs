@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    coin, Addr, BankMsg, CosmosMsg, Decimal, DepsMut, DistributionMsg, Env, MessageInfo, Order,
+    QuerierWrapper, Response, StakingMsg, StdResult, Storage, SubMsg, Timestamp, Uint128,
+};
+use croncat_sdk_manager::types::{Config, TaskBalance};
+use croncat_sdk_tasks::types::TaskInfo;
+
+use crate::{
+    contract::UNDELEGATE_REPLY,
+    helpers::check_ready_for_execution,
+    state::{
+        QueueItem, TaskDelegation, Unbonding, CONFIG, PAUSED, TASKS_BALANCES, TASK_DELEGATIONS,
+        UNBONDINGS, UNBONDINGS_NEXT_ID, VALIDATOR_STAKES,
+    },
+    ContractError,
+};
+
+/// Stake moved by the staking message: source validator, destination validator and amount
+fn stake_move(msg: &CosmosMsg) -> Option<(Option<&str>, Option<&str>, Uint128)> {
+    match msg {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            Some((None, Some(validator), amount.amount))
+        }
+        CosmosMsg::Staking(StakingMsg::Undelegate { validator, amount }) => {
+            Some((Some(validator), None, amount.amount))
+        }
+        CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator,
+            dst_validator,
+            amount,
+        }) => Some((Some(src_validator), Some(dst_validator), amount.amount)),
+        _ => None,
+    }
+}
+
+/// Validators the message withdraws the rewards from,
+/// every change of the delegation withdraws its rewards
+fn reward_validators(msg: &CosmosMsg) -> Vec<&str> {
+    match msg {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, .. })
+        | CosmosMsg::Staking(StakingMsg::Undelegate { validator, .. })
+        | CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward { validator }) => {
+            vec![validator]
+        }
+        CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator,
+            dst_validator,
+            ..
+        }) => vec![src_validator, dst_validator],
+        _ => vec![],
+    }
+}
+
+/// Checks that task undelegates and redelegates only the stake it delegated,
+/// counting the stake delegated by the earlier actions of this execution
+pub(crate) fn has_enough_delegations(storage: &dyn Storage, task: &TaskInfo) -> StdResult<bool> {
+    let task_hash = task.task_hash.as_bytes();
+    let mut delegations: BTreeMap<&str, Uint128> = BTreeMap::new();
+    for action in task.actions.iter() {
+        let Some((src, dst, amount)) = stake_move(&action.msg) else {
+            continue;
+        };
+        if let Some(src) = src {
+            let delegated = match delegations.get(src) {
+                Some(delegated) => *delegated,
+                None => TASK_DELEGATIONS
+                    .may_load(storage, (task_hash, src))?
+                    .map_or(Uint128::zero(), |delegation| delegation.amount),
+            };
+            match delegated.checked_sub(amount) {
+                Ok(left) => delegations.insert(src, left),
+                Err(_) => return Ok(false),
+            };
+        }
+        if let Some(dst) = dst {
+            let delegated = match delegations.get(dst) {
+                Some(delegated) => *delegated,
+                None => TASK_DELEGATIONS
+                    .may_load(storage, (task_hash, dst))?
+                    .map_or(Uint128::zero(), |delegation| delegation.amount),
+            };
+            delegations.insert(dst, delegated.checked_add(amount)?);
+        }
+    }
+    Ok(true)
+}
+
+/// Rewards accumulated on the validators of the task, before its actions withdraw them.
+/// Rewards belong to all the tasks staked on the validator, not only to this one
+pub(crate) fn query_task_rewards(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+    task: &TaskInfo,
+    staking_denom: &str,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let mut rewards: Vec<(String, Uint128)> = vec![];
+    for action in task.actions.iter() {
+        for validator in reward_validators(&action.msg) {
+            if rewards.iter().any(|(queried, _)| queried == validator)
+                || !VALIDATOR_STAKES.has(storage, validator)
+            {
+                continue;
+            }
+            let Some(delegation) = querier.query_delegation(contract_addr, validator)? else {
+                continue;
+            };
+            let amount = delegation
+                .accumulated_rewards
+                .iter()
+                .filter(|reward| reward.denom == staking_denom)
+                .map(|reward| reward.amount)
+                .sum();
+            rewards.push((validator.to_owned(), amount));
+        }
+    }
+    Ok(rewards)
+}
+
+/// Splits the rewards withdrawn from the validator between the tasks staked on it
+/// and returns the share of the task, including the shares of the earlier withdrawals
+fn withdraw_task_rewards(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    validator: &str,
+    withdrawn: Uint128,
+) -> StdResult<Uint128> {
+    let Some(mut validator_stake) = VALIDATOR_STAKES.may_load(storage, validator)? else {
+        return Ok(Uint128::zero());
+    };
+    if !withdrawn.is_zero() {
+        validator_stake.reward_per_stake += Decimal::from_ratio(withdrawn, validator_stake.amount);
+        VALIDATOR_STAKES.save(storage, validator, &validator_stake)?;
+    }
+    let Some(mut delegation) = TASK_DELEGATIONS.may_load(storage, (task_hash, validator))? else {
+        return Ok(Uint128::zero());
+    };
+    let rewards =
+        delegation.amount * (validator_stake.reward_per_stake - delegation.reward_per_stake);
+    delegation.reward_per_stake = validator_stake.reward_per_stake;
+    TASK_DELEGATIONS.save(storage, (task_hash, validator), &delegation)?;
+    Ok(rewards)
+}
+
+/// Adds the stake of the task on the validator, rewards of the task are withdrawn before
+fn add_task_stake(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    validator: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut validator_stake = VALIDATOR_STAKES
+        .may_load(storage, validator)?
+        .unwrap_or_default();
+    let mut delegation = TASK_DELEGATIONS
+        .may_load(storage, (task_hash, validator))?
+        .unwrap_or(TaskDelegation {
+            amount: Uint128::zero(),
+            reward_per_stake: validator_stake.reward_per_stake,
+        });
+    validator_stake.amount = validator_stake.amount.checked_add(amount)?;
+    delegation.amount = delegation.amount.checked_add(amount)?;
+    VALIDATOR_STAKES.save(storage, validator, &validator_stake)?;
+    TASK_DELEGATIONS.save(storage, (task_hash, validator), &delegation)
+}
+
+/// Subtracts the stake of the task on the validator, rewards of the task are withdrawn before
+fn sub_task_stake(
+    storage: &mut dyn Storage,
+    task_hash: &[u8],
+    validator: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut validator_stake = VALIDATOR_STAKES.load(storage, validator)?;
+    let mut delegation = TASK_DELEGATIONS.load(storage, (task_hash, validator))?;
+    validator_stake.amount = validator_stake.amount.checked_sub(amount)?;
+    delegation.amount = delegation.amount.checked_sub(amount)?;
+    if validator_stake.amount.is_zero() {
+        VALIDATOR_STAKES.remove(storage, validator);
+    } else {
+        VALIDATOR_STAKES.save(storage, validator, &validator_stake)?;
+    }
+    if delegation.amount.is_zero() {
+        TASK_DELEGATIONS.remove(storage, (task_hash, validator));
+    } else {
+        TASK_DELEGATIONS.save(storage, (task_hash, validator), &delegation)?;
+    }
+    Ok(())
+}
+
+/// Updates the stake of the task by its successful staking actions.
+/// Task share of the withdrawn rewards is credited to the task balance,
+/// undelegated stake gets back to it after the unbonding period
+pub(crate) fn update_task_delegations(
+    storage: &mut dyn Storage,
+    config: &Config,
+    block_time: Timestamp,
+    queue_item: &QueueItem,
+    task_balance: &mut TaskBalance,
+) -> StdResult<()> {
+    let task_hash = queue_item.task.task_hash.as_bytes();
+    let mut rewards = queue_item.staking_rewards.clone();
+    for (idx, action) in queue_item.task.actions.iter().enumerate() {
+        if queue_item
+            .failures
+            .iter()
+            .any(|(failed_idx, _)| *failed_idx as usize == idx)
+        {
+            continue;
+        }
+        for validator in reward_validators(&action.msg) {
+            // Rewards are withdrawn by the first action on the validator
+            let withdrawn = match rewards.iter().position(|(queried, _)| queried == validator) {
+                Some(pos) => rewards.swap_remove(pos).1,
+                None => Uint128::zero(),
+            };
+            let task_rewards = withdraw_task_rewards(storage, task_hash, validator, withdrawn)?;
+            task_balance.native_balance = task_balance.native_balance.checked_add(task_rewards)?;
+        }
+        let Some((src, dst, amount)) = stake_move(&action.msg) else {
+            continue;
+        };
+        if let Some(src) = src {
+            sub_task_stake(storage, task_hash, src, amount)?;
+        }
+        match dst {
+            Some(dst) => add_task_stake(storage, task_hash, dst, amount)?,
+            None => {
+                start_unbonding(
+                    storage,
+                    config,
+                    block_time,
+                    &queue_item.task.task_hash,
+                    &queue_item.task.owner_addr,
+                    amount,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Moves the stake of the task to its new hash, after the owner updated the task
+pub(crate) fn move_task_delegations(
+    storage: &mut dyn Storage,
+    old_task_hash: &[u8],
+    new_task_hash: &[u8],
+) -> StdResult<()> {
+    let delegations = TASK_DELEGATIONS
+        .prefix(old_task_hash)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, TaskDelegation)>>>()?;
+    for (validator, delegation) in delegations {
+        TASK_DELEGATIONS.remove(storage, (old_task_hash, &validator));
+        TASK_DELEGATIONS.save(storage, (new_task_hash, &validator), &delegation)?;
+    }
+    Ok(())
+}
+
+/// Undelegates all the stake of the task, before its balance is refunded or moved.
+/// Stake, and the task share of the rewards, goes to the task owner after the unbonding period.
+/// Slashed stake is undelegated in proportion to the task stake on the validator,
+/// failed undelegation doesn't revert the task removal
+pub(crate) fn undelegate_task_stake(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    config: &Config,
+    task_hash: &[u8],
+    owner_addr: &Addr,
+) -> StdResult<Vec<SubMsg>> {
+    let delegations = TASK_DELEGATIONS
+        .prefix(task_hash)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, TaskDelegation)>>>()?;
+    let mut msgs = Vec::with_capacity(delegations.len());
+    for (validator, task_delegation) in delegations {
+        let validator_stake = VALIDATOR_STAKES.load(storage, &validator)?;
+        let delegation = querier.query_delegation(&env.contract.address, &validator)?;
+        let stake = delegation.as_ref().map_or(Uint128::zero(), |delegation| {
+            task_delegation.amount.multiply_ratio(
+                delegation.amount.amount.min(validator_stake.amount),
+                validator_stake.amount,
+            )
+        });
+        // Undelegation withdraws the rewards of all the tasks on the validator
+        let withdrawn = match &delegation {
+            Some(delegation) if !stake.is_zero() => delegation
+                .accumulated_rewards
+                .iter()
+                .filter(|reward| reward.denom == config.native_denom)
+                .map(|reward| reward.amount)
+                .sum(),
+            _ => Uint128::zero(),
+        };
+        let rewards = withdraw_task_rewards(storage, task_hash, &validator, withdrawn)?;
+        sub_task_stake(storage, task_hash, &validator, task_delegation.amount)?;
+        if stake.is_zero() && rewards.is_zero() {
+            continue;
+        }
+        let id = start_unbonding(
+            storage,
+            config,
+            env.block.time,
+            &String::from_utf8_lossy(task_hash),
+            owner_addr,
+            stake.checked_add(rewards)?,
+        )?;
+        if stake.is_zero() {
+            continue;
+        }
+        msgs.push(SubMsg::reply_on_error(
+            StakingMsg::Undelegate {
+                validator,
+                amount: coin(stake.u128(), &config.native_denom),
+            },
+            UNDELEGATE_REPLY | id,
+        ));
+    }
+    Ok(msgs)
+}
+
+/// Saves the undelegated stake of the task, until its unbonding period ends
+fn start_unbonding(
+    storage: &mut dyn Storage,
+    config: &Config,
+    block_time: Timestamp,
+    task_hash: &str,
+    owner_addr: &Addr,
+    amount: Uint128,
+) -> StdResult<u64> {
+    let id = UNBONDINGS_NEXT_ID.may_load(storage)?.unwrap_or_default();
+    UNBONDINGS_NEXT_ID.save(storage, &(id + 1))?;
+    UNBONDINGS.save(
+        storage,
+        id,
+        &Unbonding {
+            task_hash: task_hash.to_owned(),
+            owner_addr: owner_addr.clone(),
+            amount: coin(amount.u128(), &config.native_denom),
+            release_at: block_time.plus_seconds(config.unbonding_period),
+        },
+    )?;
+    Ok(id)
+}
+
+/// Undelegation failed and stake stays with the validator, nothing to release
+pub(crate) fn drop_unbonding(storage: &mut dyn Storage, reply_id: u64) -> Option<Unbonding> {
+    let id = reply_id & u64::from(u32::MAX);
+    let unbonding = UNBONDINGS.may_load(storage, id).ok().flatten();
+    UNBONDINGS.remove(storage, id);
+    unbonding
+}
+
+/// Execute: ReleaseUnbondings
+/// Releases the unbondings in the order they started, until the first one still unbonding.
+/// Stake of the task that ended, or got updated or migrated, goes to the task owner
+pub fn execute_release_unbondings(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    let paused = PAUSED.load(deps.storage)?;
+    check_ready_for_execution(&info, paused)?;
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(config.limit);
+    let unbondings = UNBONDINGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit as usize)
+        .take_while(|unbonding| {
+            unbonding.as_ref().map_or(true, |(_, unbonding)| {
+                unbonding.release_at <= env.block.time
+            })
+        })
+        .collect::<StdResult<Vec<(u64, Unbonding)>>>()?;
+    if unbondings.is_empty() {
+        return Err(ContractError::NoUnbondingsReleased {});
+    }
+
+    let released = unbondings.len();
+    let mut msgs = vec![];
+    for (id, unbonding) in unbondings {
+        UNBONDINGS.remove(deps.storage, id);
+        match TASKS_BALANCES.may_load(deps.storage, unbonding.task_hash.as_bytes())? {
+            Some(mut task_balance)
+                if task_balance.gas_denom(&config.native_denom) == unbonding.amount.denom =>
+            {
+                task_balance.add_coin(&unbonding.amount, &config.native_denom)?;
+                TASKS_BALANCES.save(deps.storage, unbonding.task_hash.as_bytes(), &task_balance)?;
+            }
+            _ => msgs.push(BankMsg::Send {
+                to_address: unbonding.owner_addr.into_string(),
+                amount: vec![unbonding.amount],
+            }),
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "release_unbondings")
+        .add_attribute("released", released.to_string())
+        .add_messages(msgs))
+}
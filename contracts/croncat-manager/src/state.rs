@@ -1,6 +1,6 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use croncat_sdk_manager::types::TaskBalance;
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Timestamp, Uint128};
+use croncat_sdk_manager::types::{TaskBalance, TaskExecutionRecord};
 use cw_storage_plus::{Item, Map};
 
 pub use croncat_sdk_manager::types::Config;
@@ -12,11 +12,11 @@ pub const CONFIG: Item<Config> = Item::new("config");
 /// the pause_admin, can only be unpaused by DAO/owner_addr
 pub const PAUSED: Item<bool> = Item::new("paused");
 
-// Accrued Treasury reward balance in native coin
-pub const TREASURY_BALANCE: Item<Uint128> = Item::new("treasury_balance");
+// Accrued Treasury reward balance, keyed by gas denom
+pub const TREASURY_BALANCE: Map<&str, Uint128> = Map::new("treasury_denom_balances");
 
-// Accrued Agent reward balance in native coin
-pub const AGENT_REWARDS: Map<&Addr, Uint128> = Map::new("agent_rewards");
+// Accrued Agent reward balance, keyed by agent address and gas denom
+pub const AGENT_REWARDS: Map<(&Addr, &str), Uint128> = Map::new("agent_denom_rewards");
 
 // Temporary balances of users before task creation.
 // Please do not store your coins for any other use.
@@ -26,6 +26,43 @@ pub const TASKS_BALANCES: Map<&[u8], TaskBalance> = Map::new("tasks_balances");
 
 pub const REPLY_QUEUE: Item<QueueItem> = Item::new("reply_queue");
 
+/// Executed task waiting for the reschedule, kept only if task has callbacks
+pub const RESCHEDULE_QUEUE: Item<QueueItem> = Item::new("reschedule_queue");
+
+/// Latest executions of the tasks, keyed by task hash and execution index.
+/// Only the last [`TASK_EXECUTION_HISTORY_LIMIT`](croncat_sdk_manager::types::TASK_EXECUTION_HISTORY_LIMIT) executions are kept
+pub const TASK_EXECUTION_HISTORY: Map<(&[u8], u64), TaskExecutionRecord> =
+    Map::new("task_execution_history");
+
+/// Index of the next execution record of the task
+pub const TASK_EXECUTION_HISTORY_NEXT_IDX: Map<&[u8], u64> =
+    Map::new("task_execution_history_next_idx");
+
+/// Stake delegated by the task actions, keyed by task hash and validator.
+/// Task can only undelegate or redelegate the stake it delegated
+pub const TASK_DELEGATIONS: Map<(&[u8], &str), TaskDelegation> = Map::new("task_delegations");
+
+/// Stake of all the tasks on the validator, keyed by validator.
+/// Rewards of the validator are split between the tasks by their stake
+pub const VALIDATOR_STAKES: Map<&str, ValidatorStake> = Map::new("validator_stakes");
+
+/// Undelegated stake of the tasks waiting for the end of the unbonding period,
+/// keyed by unbonding id
+pub const UNBONDINGS: Map<u64, Unbonding> = Map::new("unbondings");
+
+/// Id of the next unbonding
+pub const UNBONDINGS_NEXT_ID: Item<u64> = Item::new("unbondings_next_id");
+
+/// Ibc transfers of the tasks waiting for the ack or timeout,
+/// keyed by source channel and packet sequence
+pub const IBC_TRANSFERS: Map<(&str, u64), IbcTransfer> = Map::new("ibc_transfers");
+
+/// Rewards paid out to the agent, restored if the payout fails
+pub const PAYOUT_QUEUE: Item<PayoutItem> = Item::new("payout_queue");
+
+/// Tasks funded by the sponsor, keyed by sponsor address and task hash
+pub const SPONSORED_TASKS: Map<(&Addr, &[u8]), Empty> = Map::new("sponsored_tasks");
+
 pub const LAST_TASK_EXECUTION_INFO: Item<TaskExecutionInfo> =
     croncat_sdk_manager::state::LAST_TASK_EXECUTION_INFO;
 
@@ -36,4 +73,50 @@ pub struct QueueItem {
     pub agent_addr: Addr,
     /// Storing any errors that happened to return
     pub failures: Vec<(u8, String)>,
+    /// Indexes of the actions skipped by their conditions, in the original task
+    #[serde(default)]
+    pub skipped_actions: Vec<u64>,
+    /// Rewards accumulated on the validators of the task before the execution
+    #[serde(default)]
+    pub staking_rewards: Vec<(String, Uint128)>,
+}
+
+/// Ibc transfer made by the task action
+#[cw_serde]
+pub struct IbcTransfer {
+    pub task_hash: String,
+    pub owner_addr: Addr,
+    pub amount: Coin,
+}
+
+#[cw_serde]
+pub struct TaskDelegation {
+    pub amount: Uint128,
+    /// Rewards per stake of the validator already credited to the task
+    pub reward_per_stake: Decimal,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ValidatorStake {
+    pub amount: Uint128,
+    /// Rewards per stake withdrawn from the validator since the first delegation
+    pub reward_per_stake: Decimal,
+}
+
+/// Stake undelegated by the task
+#[cw_serde]
+pub struct Unbonding {
+    pub task_hash: String,
+    pub owner_addr: Addr,
+    /// Undelegated stake with the task share of the rewards withdrawn by the undelegation
+    pub amount: Coin,
+    pub release_at: Timestamp,
+}
+
+/// Automatic payout of the agent rewards waiting for the result
+#[cw_serde]
+pub struct PayoutItem {
+    pub agent_addr: Addr,
+    pub rewards: Vec<Coin>,
 }
@@ -1,6 +1,13 @@
 #![allow(unused)]
 
-use cosmwasm_std::Empty;
+use cosmwasm_std::{
+    from_slice, to_binary, to_vec, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult,
+};
+use croncat_sdk_manager::msg::{
+    GasPriceOracleQueryMsg, TaskCallbackMsg, TaskEndedCallback, TreasuryExecuteMsg,
+    TreasuryQueryMsg,
+};
 use cw_multi_test::{Contract, ContractWrapper};
 
 pub(crate) fn croncat_manager_contract() -> Box<dyn Contract<Empty>> {
@@ -9,6 +16,7 @@ pub(crate) fn croncat_manager_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::instantiate,
         crate::contract::query,
     )
+    .with_migrate(crate::contract::migrate)
     .with_reply(crate::contract::reply);
     Box::new(contract)
 }
@@ -76,3 +84,98 @@ pub(crate) fn cw_boolean_contract() -> Box<dyn Contract<Empty>> {
     );
     Box::new(contract)
 }
+
+const LAST_CALLBACK_KEY: &[u8] = b"last_callback";
+
+/// Saves the last task callback, rejects callbacks of failed tasks
+pub(crate) fn task_callback_contract() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: TaskCallbackMsg,
+    ) -> StdResult<Response> {
+        let TaskCallbackMsg::CroncatTaskEnded(callback) = msg;
+        if callback.end_reason.is_failure() {
+            return Err(StdError::generic_err("Task failed"));
+        }
+        deps.storage
+            .set(LAST_CALLBACK_KEY, &to_binary(&Some(callback))?);
+        Ok(Response::new())
+    }
+
+    fn query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        match deps.storage.get(LAST_CALLBACK_KEY) {
+            Some(callback) => Ok(Binary(callback)),
+            None => to_binary(&None::<TaskEndedCallback>),
+        }
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+const GAS_PRICE_KEY: &[u8] = b"gas_price";
+
+/// Gas price oracle, instantiated and updated with the gas price
+pub(crate) fn gas_price_oracle_contract() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: Decimal,
+    ) -> StdResult<Response> {
+        deps.storage.set(GAS_PRICE_KEY, &to_vec(&msg)?);
+        Ok(Response::new())
+    }
+
+    fn execute(deps: DepsMut, _env: Env, _info: MessageInfo, msg: Decimal) -> StdResult<Response> {
+        deps.storage.set(GAS_PRICE_KEY, &to_vec(&msg)?);
+        Ok(Response::new())
+    }
+
+    fn query(deps: Deps, _env: Env, msg: GasPriceOracleQueryMsg) -> StdResult<Binary> {
+        let GasPriceOracleQueryMsg::GasPrice { .. } = msg;
+        let price: Decimal = from_slice(&deps.storage.get(GAS_PRICE_KEY).unwrap_or_default())?;
+        to_binary(&price)
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+/// Treasury supporting cw20 payouts of every cw20, but failing to pay out
+pub(crate) fn failing_treasury_contract() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: TreasuryExecuteMsg,
+    ) -> StdResult<Response> {
+        let TreasuryExecuteMsg::AgentPayout { .. } = msg;
+        Err(StdError::generic_err("Payout failed"))
+    }
+
+    fn query(_deps: Deps, _env: Env, msg: TreasuryQueryMsg) -> StdResult<Binary> {
+        let TreasuryQueryMsg::SupportsCw20Payout { .. } = msg;
+        to_binary(&true)
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
@@ -172,6 +172,10 @@ pub(crate) fn init_agents(app: &mut App, factory_addr: &Addr) -> Addr {
         min_active_agent_count: None,
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
     let module_instantiate_info = ModuleInstantiateInfo {
         code_id,
@@ -290,6 +294,7 @@ pub(crate) fn activate_agent(app: &mut App, agents_contract: &Addr) {
         agents_contract.clone(),
         &croncat_agents::msg::ExecuteMsg::RegisterAgent {
             payable_account_id: None,
+            payout: None,
         },
         &[],
     )
@@ -345,7 +350,7 @@ pub(crate) fn query_manager_config(app: &App, manager: &Addr) -> Config {
 
 pub(crate) fn query_manager_balances(app: &App, manager: &Addr) -> Uint128 {
     app.wrap()
-        .query_wasm_smart(manager, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap()
 }
 
@@ -387,10 +392,13 @@ pub(crate) fn support_new_cw20(
                     agent_fee: None,
                     treasury_fee: None,
                     gas_price: None,
+                    gas_price_source: None,
                     croncat_tasks_key: None,
                     croncat_agents_key: None,
                     treasury_addr: None,
                     cw20_whitelist: Some(vec![new_cw20_addr.to_owned()]),
+                    gas_denoms: None,
+                    unbonding_period: None,
                 })))
                 .unwrap(),
                 funds: vec![],
@@ -3,24 +3,32 @@ use cosmwasm_std::Attribute;
 use cosmwasm_std::BlockInfo;
 use cosmwasm_std::WasmQuery;
 use cosmwasm_std::{
-    coins, from_slice, to_binary, Addr, BankMsg, Binary, Coin, TransactionInfo, Uint128, WasmMsg,
+    coins, from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, DistributionMsg,
+    StakingMsg, SubMsg, TransactionInfo, Uint128, Validator, WasmMsg,
 };
 use croncat_mod_balances::types::HasBalanceComparator;
 use croncat_mod_generic::types::PathToValue;
 use croncat_mod_generic::types::ValueIndex;
+use croncat_mod_generic::value_ordering::ValueOrdering;
 use croncat_sdk_agents::msg::ExecuteMsg::RegisterAgent;
+use croncat_sdk_agents::types::AgentPayout;
 use croncat_sdk_core::internal_messages::agents::AgentWithdrawOnRemovalArgs;
 use croncat_sdk_factory::msg::ContractMetadataResponse;
 use croncat_sdk_manager::{
-    msg::AgentWithdrawCallback,
-    types::{Config, TaskBalance, TaskBalanceResponse, UpdateConfig},
+    msg::{AgentWithdrawCallback, IbcLifecycleComplete, TaskEndedCallback},
+    types::{
+        ActionResult, Config, GasDenom, GasPriceLimits, GasPriceSource, SponsoredTask, TaskBalance,
+        TaskBalanceResponse, TaskEndReason, TaskExecutionRecord, UpdateConfig,
+        DEFAULT_UNBONDING_PERIOD, TASK_EXECUTION_HISTORY_LIMIT,
+    },
 };
 use croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask;
 use croncat_sdk_tasks::types::CosmosQuery;
 use croncat_sdk_tasks::types::TaskExecutionInfo;
 use croncat_sdk_tasks::types::TaskRequest;
 use croncat_sdk_tasks::types::{
-    Action, Boundary, BoundaryHeight, BoundaryTime, CroncatQuery, Interval, TaskResponse, Transform,
+    Action, ActionCondition, Boundary, BoundaryHeight, BoundaryTime, CroncatQuery, Interval,
+    QueryRule, TaskCallbacks, TaskResponse, Transform,
 };
 use cw20::{Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg, Cw20QueryMsg};
 use cw_multi_test::AppResponse;
@@ -30,7 +38,8 @@ use crate::tests::PARTICIPANT3;
 use crate::tests::PAUSE_ADMIN;
 use crate::{
     contract::DEFAULT_FEE,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg},
+    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, SudoMsg},
+    state::{IbcTransfer, IBC_TRANSFERS},
     tests::{
         helpers::{
             default_app, default_instantiate_message, init_manager, init_mod_balances,
@@ -46,7 +55,7 @@ use croncat_mod_balances::msg::QueryMsg as BalancesQueryMsg;
 use croncat_sdk_core::types::{AmountForOneTask, GasPrice};
 use croncat_sdk_manager::msg::ManagerExecuteMsg::{ProxyBatch, ProxyCall};
 use cw_boolean_contract::msgs::execute_msg::ExecuteMsg::Toggle;
-use cw_multi_test::{BankSudo, Executor};
+use cw_multi_test::{App, BankSudo, Executor, StakingInfo, StakingSudo};
 
 use super::{
     contracts,
@@ -55,7 +64,7 @@ use super::{
 };
 use super::{
     helpers::{activate_agent, add_little_time, init_cw20, query_users_manager},
-    AGENT0,
+    AGENT0, AGENT_BENEFICIARY, PARTICIPANT6,
 };
 
 mod instantiate_tests {
@@ -83,8 +92,11 @@ mod instantiate_tests {
             agent_fee: DEFAULT_FEE,
             treasury_fee: DEFAULT_FEE,
             gas_price: Default::default(),
+            gas_price_source: GasPriceSource::Frozen {},
             cw20_whitelist: vec![],
             native_denom: DENOM.to_owned(),
+            gas_denoms: vec![],
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
             limit: 100,
             treasury_addr: None,
         };
@@ -166,8 +178,11 @@ mod instantiate_tests {
                 denominator: 20,
                 gas_adjustment_numerator: 30,
             },
+            gas_price_source: GasPriceSource::Frozen {},
             cw20_whitelist: vec![Addr::unchecked(PARTICIPANT3)],
             native_denom: DENOM.to_string(),
+            gas_denoms: vec![],
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
             limit: 100,
             treasury_addr: Some(Addr::unchecked(AGENT2)),
         };
@@ -256,10 +271,13 @@ fn update_config() {
             denominator: 666,
             gas_adjustment_numerator: 777,
         }),
+        gas_price_source: None,
         croncat_tasks_key: Some(("new_key_tasks".to_owned(), [0, 1])),
         croncat_agents_key: Some(("new_key_agents".to_owned(), [0, 1])),
         treasury_addr: Some(ANYONE.to_owned()),
         cw20_whitelist: Some(vec!["randomcw20".to_owned()]),
+        gas_denoms: None,
+        unbonding_period: None,
     };
 
     app.execute_contract(
@@ -289,8 +307,11 @@ fn update_config() {
             denominator: 666,
             gas_adjustment_numerator: 777,
         },
+        gas_price_source: GasPriceSource::Frozen {},
         cw20_whitelist: vec![Addr::unchecked("randomcw20")],
         native_denom: DENOM.to_owned(),
+        gas_denoms: vec![],
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
         limit: 100,
         treasury_addr: Some(Addr::unchecked(ANYONE)),
     };
@@ -324,10 +345,13 @@ fn invalid_updates_config() {
             denominator: 666,
             gas_adjustment_numerator: 777,
         }),
+        gas_price_source: None,
         croncat_tasks_key: Some(("new_key_tasks".to_owned(), [0, 1])),
         croncat_agents_key: Some(("new_key_agents".to_owned(), [0, 1])),
         treasury_addr: Some(ANYONE.to_owned()),
         cw20_whitelist: Some(vec!["randomcw20".to_owned()]),
+        gas_denoms: None,
+        unbonding_period: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -350,10 +374,13 @@ fn invalid_updates_config() {
             denominator: 0,
             gas_adjustment_numerator: 777,
         }),
+        gas_price_source: None,
         croncat_tasks_key: Some(("new_key_tasks".to_owned(), [0, 1])),
         croncat_agents_key: Some(("new_key_agents".to_owned(), [0, 1])),
         treasury_addr: Some(ANYONE.to_owned()),
         cw20_whitelist: Some(vec!["randomcw20".to_owned()]),
+        gas_denoms: None,
+        unbonding_period: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -766,10 +793,17 @@ fn simple_bank_transfers_block() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -839,6 +873,7 @@ fn simple_bank_transfers_block() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -851,7 +886,10 @@ fn simple_bank_transfers_block() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 + 1));
 
@@ -886,6 +924,9 @@ fn simple_bank_transfers_block() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -894,6 +935,9 @@ fn simple_bank_transfers_block() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -902,11 +946,18 @@ fn simple_bank_transfers_block() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -983,6 +1034,7 @@ fn simple_bank_transfers_block() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -995,7 +1047,10 @@ fn simple_bank_transfers_block() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128));
 
@@ -1047,10 +1102,17 @@ fn simple_bank_transfers_cron() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -1144,6 +1206,7 @@ fn simple_bank_transfers_cron() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -1158,7 +1221,10 @@ fn simple_bank_transfers_cron() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(
         treasury_balance,
@@ -1209,6 +1275,9 @@ fn simple_bank_transfers_cron() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -1217,6 +1286,9 @@ fn simple_bank_transfers_cron() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -1225,11 +1297,18 @@ fn simple_bank_transfers_cron() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -1329,6 +1408,7 @@ fn simple_bank_transfers_cron() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -1342,7 +1422,10 @@ fn simple_bank_transfers_cron() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(
         treasury_balance,
@@ -1402,6 +1485,9 @@ fn multi_coin_bank_transfers() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -1410,11 +1496,18 @@ fn multi_coin_bank_transfers() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let attach_funds = vec![coin(600_000, DENOM), coin(2400, "denom")];
     app.sudo(
@@ -1501,6 +1594,7 @@ fn multi_coin_bank_transfers() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -1512,7 +1606,7 @@ fn multi_coin_bank_transfers() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
     assert_eq!(
         treasury_balance,
@@ -1569,6 +1663,9 @@ fn cw20_action_transfer() {
                 }
                 .into(),
                 gas_limit: Some(250_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: WasmMsg::Execute {
@@ -1582,6 +1679,9 @@ fn cw20_action_transfer() {
                 }
                 .into(),
                 gas_limit: Some(250_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
@@ -1590,6 +1690,10 @@ fn cw20_action_transfer() {
             address: cw20_addr.to_string(),
             amount: Uint128::new(555),
         }),
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let res = app
@@ -1729,6 +1833,7 @@ fn cw20_action_transfer() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -1741,7 +1846,7 @@ fn cw20_action_transfer() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 + 1));
 }
@@ -1770,6 +1875,9 @@ fn task_with_query() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![CosmosQuery::Croncat(CroncatQuery {
             contract_addr: mod_balances.to_string(),
@@ -1785,6 +1893,10 @@ fn task_with_query() {
         })]),
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -1879,6 +1991,7 @@ fn task_with_query() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -1891,7 +2004,10 @@ fn task_with_query() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 + 1));
 
@@ -1923,6 +2039,9 @@ fn task_with_query() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![CosmosQuery::Croncat(CroncatQuery {
             contract_addr: mod_balances.to_string(),
@@ -1938,6 +2057,10 @@ fn task_with_query() {
         })]),
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2010,6 +2133,7 @@ fn task_with_query() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -2022,7 +2146,7 @@ fn task_with_query() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128));
 }
@@ -2055,6 +2179,9 @@ fn recurring_task_block_immediate() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -2063,11 +2190,18 @@ fn recurring_task_block_immediate() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     // pre action
@@ -2161,6 +2295,7 @@ fn recurring_task_block_immediate() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -2173,7 +2308,10 @@ fn recurring_task_block_immediate() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance { denom: None },
+        )
         .unwrap();
     assert_eq!(
         treasury_balance,
@@ -2213,6 +2351,9 @@ fn recurring_task_block_immediate() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -2221,11 +2362,18 @@ fn recurring_task_block_immediate() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let res = app
@@ -2312,6 +2460,7 @@ fn recurring_task_block_immediate() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -2324,7 +2473,7 @@ fn recurring_task_block_immediate() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
     assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 * 2));
 }
@@ -2357,6 +2506,9 @@ fn recurring_task_block_block_interval() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -2365,11 +2517,18 @@ fn recurring_task_block_block_interval() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let res = app
@@ -2480,6 +2639,7 @@ fn recurring_task_block_block_interval() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
@@ -2492,7 +2652,7 @@ fn recurring_task_block_block_interval() {
     // Check treasury reward
     let treasury_balance: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
     assert_eq!(
         treasury_balance,
@@ -2501,7 +2661,7 @@ fn recurring_task_block_block_interval() {
 }
 
 #[test]
-fn recurring_task_cron() {
+fn recurring_task_max_executions() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
@@ -2513,34 +2673,28 @@ fn recurring_task_cron() {
     activate_agent(&mut app, &agents_addr);
 
     let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Cron("* * * * * *".to_owned()),
-        // repeat it two times
-        boundary: Some(Boundary::Time(BoundaryTime {
-            start: Some(app.block_info().time),
-            end: Some(app.block_info().time.plus_seconds(40)),
-        })),
+        interval: Interval::Block(1),
+        boundary: None,
         stop_on_fail: false,
-        actions: vec![
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "alice".to_owned(),
-                    amount: coins(123, DENOM),
-                }
-                .into(),
-                gas_limit: None,
-            },
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(321, DENOM),
-                }
-                .into(),
-                gas_limit: None,
-            },
-        ],
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(123, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
         queries: None,
         transforms: None,
         cw20: None,
+        // run it exactly two times
+        max_executions: Some(2),
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let res = app
@@ -2550,7 +2704,7 @@ fn recurring_task_cron() {
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
                 task: Box::new(task),
             },
-            &coins(75000, DENOM),
+            &coins(600_000, DENOM),
         )
         .unwrap();
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
@@ -2564,18 +2718,11 @@ fn recurring_task_cron() {
             },
         )
         .unwrap();
-
-    let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
-    let expected_gone_amount = {
-        let gas_fees = gas_needed * (DEFAULT_FEE + DEFAULT_FEE) as f64 / 100.0;
-        let amount_for_task = gas_needed * 0.04;
-        let amount_for_fees = gas_fees * 0.04;
-        amount_for_task + amount_for_fees + 321.0 + 123.0
-    } as u128;
+    let task_info = task_response.task.unwrap();
+    assert_eq!(task_info.executions, 0);
+    assert_eq!(task_info.remaining_executions, Some(2));
 
     app.update_block(add_little_time);
-
-    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
@@ -2584,220 +2731,298 @@ fn recurring_task_cron() {
     )
     .unwrap();
 
-    // action done
-    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
-    assert_eq!(bob_balances, vec![coin(321, DENOM)]);
-    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
-    assert_eq!(alice_balances, coins(123, DENOM));
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_tasks::msg::QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let task_info = task_response.task.unwrap();
+    assert_eq!(task_info.executions, 1);
+    assert_eq!(task_info.remaining_executions, Some(1));
 
+    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     app.update_block(add_little_time);
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ExecuteMsg::ProxyCall { task_hash: None },
-        &[],
-    )
-    .unwrap();
+    let res = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "lifecycle" && attr.value == "task_ended")));
 
-    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
-    assert_eq!(bob_balances, vec![coin(321 * 2, DENOM)]);
     let alice_balances = app.wrap().query_all_balances("alice").unwrap();
     assert_eq!(alice_balances, coins(123 * 2, DENOM));
 
-    // check task got unregistered
+    // Task ended and the rest of the balance refunded
     let task_response: TaskResponse = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_tasks::msg::QueryMsg::Task { task_hash },
+            tasks_addr,
+            &croncat_tasks::msg::QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
         )
         .unwrap();
     assert!(task_response.task.is_none());
-
-    let after_unregister_participant_balance =
-        app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
-    assert_eq!(
-        75000 - expected_gone_amount * 2,
-        after_unregister_participant_balance.amount.u128() - participant_balance.amount.u128()
-    );
-
-    // Check agent reward
-    let agent_reward: Uint128 = app
+    let task_balance: TaskBalanceResponse = app
         .wrap()
-        .query_wasm_smart(
-            manager_addr.clone(),
-            &QueryMsg::AgentRewards {
-                agent_id: AGENT0.to_owned(),
-            },
-        )
+        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TaskBalance { task_hash })
         .unwrap();
-    let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
-    let amount_for_task = gas_needed * 0.04;
-    let amount_for_fees = gas_fees * 0.04;
-    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128 * 2;
-    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
+    assert!(task_balance.balance.is_none());
+    let after_end_participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
+    assert!(after_end_participant_balance.amount > participant_balance.amount);
 
-    // Check treasury reward
-    let treasury_balance: Uint128 = app
-        .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TreasuryBalance {})
+    // Run no more
+    app.update_block(add_little_time);
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr,
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
         .unwrap();
-    assert_eq!(
-        treasury_balance,
-        Uint128::new((amount_for_fees as u128 * 2) + 1)
-    );
+    assert_eq!(err, ContractError::NoTaskForAgent {});
+}
 
-    // repeat to check contract state is progressing as expected
-    // first clear up rewards
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ExecuteMsg::AgentWithdraw(None),
-        &[],
-    )
-    .unwrap();
-    app.execute_contract(
-        factory_addr.clone(),
-        manager_addr.clone(),
-        &ExecuteMsg::OwnerWithdraw {},
-        &[],
-    )
-    .unwrap();
+#[test]
+fn task_execution_history() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
 
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    activate_agent(&mut app, &agents_addr);
+
+    let executions = TASK_EXECUTION_HISTORY_LIMIT + 2;
     let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Cron("* * * * * *".to_owned()),
-        // repeat it two times
-        boundary: Some(Boundary::Time(BoundaryTime {
-            start: Some(app.block_info().time),
-            end: Some(app.block_info().time.plus_seconds(40)),
-        })),
+        interval: Interval::Block(1),
+        boundary: None,
         stop_on_fail: false,
         actions: vec![
             Action {
                 msg: BankMsg::Send {
                     to_address: "alice".to_owned(),
-                    amount: coins(123, DENOM),
+                    amount: coins(1, DENOM),
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
+            // Not a contract, fails every time
             Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(321, DENOM),
+                msg: WasmMsg::Execute {
+                    contract_addr: PARTICIPANT3.to_owned(),
+                    msg: to_binary(&"ping").unwrap(),
+                    funds: vec![],
                 }
                 .into(),
-                gas_limit: None,
+                gas_limit: Some(100_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: Some(executions),
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
-
     let res = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
-            tasks_addr.clone(),
+            tasks_addr,
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
                 task: Box::new(task),
             },
-            &coins(75000, DENOM),
+            &coins(1_500_000, DENOM),
         )
         .unwrap();
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
-    let task_response: TaskResponse = app
+
+    let mut execution_heights = vec![];
+    for _ in 0..executions {
+        app.update_block(add_little_time);
+        app.execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+        execution_heights.push(app.block_info().height);
+    }
+
+    // Only the latest executions kept, newest first
+    let history: Vec<TaskExecutionRecord> = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_tasks::msg::QueryMsg::Task {
+            manager_addr.clone(),
+            &QueryMsg::TaskExecutionHistory {
                 task_hash: task_hash.clone(),
+                from_index: None,
+                limit: Some(executions),
             },
         )
         .unwrap();
+    assert_eq!(history.len() as u64, TASK_EXECUTION_HISTORY_LIMIT);
+    let expected_heights: Vec<u64> = execution_heights
+        .into_iter()
+        .rev()
+        .take(TASK_EXECUTION_HISTORY_LIMIT as usize)
+        .collect();
+    assert_eq!(
+        history
+            .iter()
+            .map(|record| record.block_height)
+            .collect::<Vec<u64>>(),
+        expected_heights
+    );
+    for record in history.iter() {
+        assert_eq!(record.agent_addr, Addr::unchecked(AGENT0));
+        assert_eq!(record.gas_charged.denom, DENOM);
+        assert!(!record.gas_charged.amount.is_zero());
+        assert_eq!(record.action_results.len(), 2);
+        assert_eq!(record.action_results[0], ActionResult::Success);
+        assert!(matches!(record.action_results[1], ActionResult::Failure(_)));
+    }
+    assert_eq!(history[0].end_reason, Some(TaskEndReason::MaxExecutions));
+    assert!(history[1..]
+        .iter()
+        .all(|record| record.end_reason.is_none()));
 
-    let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
-    let expected_gone_amount = {
-        let gas_fees = gas_needed * (DEFAULT_FEE + DEFAULT_FEE) as f64 / 100.0;
-        let amount_for_task = gas_needed * 0.04;
-        let amount_for_fees = gas_fees * 0.04;
-        amount_for_task + amount_for_fees + 321.0 + 123.0
-    } as u128;
+    // Paginated
+    let history_page: Vec<TaskExecutionRecord> = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr,
+            &QueryMsg::TaskExecutionHistory {
+                task_hash,
+                from_index: Some(TASK_EXECUTION_HISTORY_LIMIT - 1),
+                limit: Some(5),
+            },
+        )
+        .unwrap();
+    assert_eq!(history_page, history[history.len() - 1..].to_vec());
+}
 
-    app.update_block(add_little_time);
-    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ExecuteMsg::ProxyCall { task_hash: None },
-        &[],
-    )
-    .unwrap();
+#[test]
+fn failure_of_not_last_action_recorded() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
 
-    // action done
-    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
-    assert_eq!(bob_balances, vec![coin(321 * 3, DENOM)]);
-    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
-    assert_eq!(alice_balances, coins(123 * 3, DENOM));
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    app.update_block(add_little_time);
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ExecuteMsg::ProxyCall { task_hash: None },
-        &[],
-    )
-    .unwrap();
-
-    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
-    assert_eq!(bob_balances, vec![coin(321 * 4, DENOM)]);
-    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
-    assert_eq!(alice_balances, coins(123 * 4, DENOM));
+    activate_agent(&mut app, &agents_addr);
 
-    // check task got unregistered
-    let task_response: TaskResponse = app
-        .wrap()
-        .query_wasm_smart(
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            // Not a contract, fails
+            Action {
+                msg: WasmMsg::Execute {
+                    contract_addr: PARTICIPANT3.to_owned(),
+                    msg: to_binary(&"ping").unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: Some(100_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "alice".to_owned(),
+                    amount: coins(1, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
             tasks_addr,
-            &croncat_tasks::msg::QueryMsg::Task { task_hash },
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
         )
         .unwrap();
-    assert!(task_response.task.is_none());
-
-    let after_unregister_participant_balance =
-        app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
-    assert_eq!(
-        75000 - expected_gone_amount * 2,
-        after_unregister_participant_balance.amount.u128() - participant_balance.amount.u128()
-    );
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
 
-    // Check agent reward
-    let agent_reward: Uint128 = app
-        .wrap()
-        .query_wasm_smart(
+    app.update_block(add_little_time);
+    let res = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &QueryMsg::AgentRewards {
-                agent_id: AGENT0.to_owned(),
-            },
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
         )
         .unwrap();
-    let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
-    let amount_for_task = gas_needed * 0.04;
-    let amount_for_fees = gas_fees * 0.04;
-    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128 * 2;
-    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action0_failure")));
 
-    // Check treasury reward
-    let treasury_balance: Uint128 = app
+    let history: Vec<TaskExecutionRecord> = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance {})
+        .query_wasm_smart(
+            manager_addr,
+            &QueryMsg::TaskExecutionHistory {
+                task_hash: task_data.task_hash,
+                from_index: None,
+                limit: None,
+            },
+        )
         .unwrap();
-    assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 * 2));
+    assert_eq!(history.len(), 1);
+    assert!(matches!(
+        history[0].action_results[0],
+        ActionResult::Failure(_)
+    ));
+    assert_eq!(history[0].action_results[1], ActionResult::Success);
 }
 
 #[test]
-fn negative_proxy_call() {
+fn paused_scheduled_task_not_executed() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
@@ -2805,35 +3030,71 @@ fn negative_proxy_call() {
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
     let agents_addr = init_agents(&mut app, &factory_addr);
     let tasks_addr = init_tasks(&mut app, &factory_addr);
-    let mod_balances = init_mod_balances(&mut app, &factory_addr);
 
-    let cw20_addr = init_cw20(&mut app);
-    support_new_cw20(
-        &mut app,
-        factory_addr.clone(),
-        &manager_addr,
-        cw20_addr.as_str(),
-    );
+    activate_agent(&mut app, &agents_addr);
+
+    // Both tasks land in the same slot, the paused one goes first
+    let mut task_hashes = vec![];
+    for to_address in ["bob", "alice"] {
+        let task = croncat_sdk_tasks::types::TaskRequest {
+            interval: Interval::Once,
+            boundary: None,
+            stop_on_fail: false,
+            actions: vec![Action {
+                msg: BankMsg::Send {
+                    to_address: to_address.to_owned(),
+                    amount: coins(5, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            }],
+            queries: None,
+            transforms: None,
+            cw20: None,
+            max_executions: None,
+            callbacks: None,
+            gas_denom: None,
+            query_rule: None,
+        };
+        let res = app
+            .execute_contract(
+                Addr::unchecked(PARTICIPANT0),
+                tasks_addr.clone(),
+                &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                    task: Box::new(task),
+                },
+                &coins(300_000, DENOM),
+            )
+            .unwrap();
+        let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+        task_hashes.push(task_data.task_hash);
+    }
 
     app.execute_contract(
         Addr::unchecked(PARTICIPANT0),
-        cw20_addr.clone(),
-        &cw20::Cw20ExecuteMsg::Send {
-            contract: manager_addr.to_string(),
-            amount: Uint128::new(555),
-            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
+        tasks_addr,
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::PauseTask {
+            task_hash: task_hashes[0].clone(),
         },
         &[],
     )
     .unwrap();
 
-    activate_agent(&mut app, &agents_addr);
-
-    // no task for this agent
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(AGENT0),
-            manager_addr.clone(),
+            manager_addr,
             &ExecuteMsg::ProxyCall { task_hash: None },
             &[],
         )
@@ -2842,60 +3103,64 @@ fn negative_proxy_call() {
         .unwrap();
     assert_eq!(err, ContractError::NoTaskForAgent {});
 
-    // not registered agent
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT1),
-            manager_addr.clone(),
-            &ExecuteMsg::ProxyCall { task_hash: None },
+    let alice_balance = app.wrap().query_balance("alice", DENOM).unwrap();
+    assert_eq!(alice_balance.amount, Uint128::new(5));
+    let bob_balance = app.wrap().query_balance("bob", DENOM).unwrap();
+    assert!(bob_balance.amount.is_zero());
+}
+
+#[test]
+fn task_callbacks() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    activate_agent(&mut app, &agents_addr);
+
+    let callback_code_id = app.store_code(contracts::task_callback_contract());
+    let callback_addr = app
+        .instantiate_contract(
+            callback_code_id,
+            Addr::unchecked(ADMIN),
+            &cosmwasm_std::Empty {},
             &[],
+            "task_callback",
+            None,
         )
-        .unwrap_err()
-        .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::NoTaskForAgent {});
+    let callbacks = TaskCallbacks {
+        on_complete: Some(callback_addr.to_string()),
+        on_failure: Some(callback_addr.to_string()),
+        gas_limit: None,
+    };
 
-    // agent not registered before proxy call with queries
-    // Creating task itself
+    // Completed task calls on_complete
     let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Once,
+        interval: Interval::Block(1),
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
-            msg: WasmMsg::Execute {
-                contract_addr: cw20_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: "alice".to_owned(),
-                    amount: Uint128::new(1),
-                })
-                .unwrap(),
-                funds: Default::default(),
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(123, DENOM),
             }
             .into(),
-            gas_limit: Some(250_000),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        queries: Some(vec![CosmosQuery::Croncat(CroncatQuery {
-            contract_addr: mod_balances.to_string(),
-            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::HasBalanceComparator(
-                HasBalanceComparator {
-                    address: "lucy".to_owned(),
-                    required_balance: coins(10, "denom").into(),
-                    comparator: croncat_mod_balances::types::BalanceComparator::Eq,
-                },
-            ))
-            .unwrap(),
-            check_result: true,
-        })]),
-        transforms: Some(vec![Transform {
-            action_idx: 0,
-            query_idx: 0,
-            action_path: vec!["transfer".to_owned().into(), "amount".to_owned().into()].into(),
-            query_response_path: vec!["amount".to_owned().into()].into(),
-        }]),
-        cw20: Some(Cw20Coin {
-            address: cw20_addr.to_string(),
-            amount: Uint128::new(1),
-        }),
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: Some(1),
+        callbacks: Some(callbacks.clone()),
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2910,90 +3175,86 @@ fn negative_proxy_call() {
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
 
+    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     app.update_block(add_little_time);
-
-    // Agent not registered
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT1),
-            manager_addr.clone(),
-            &ExecuteMsg::ProxyCall {
-                task_hash: Some(task_hash.clone()),
-            },
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::AgentNotActive {});
-
-    // Agent not active
-    // register agent1 first
     app.execute_contract(
-        Addr::unchecked(AGENT1),
-        agents_addr.clone(),
-        &croncat_sdk_agents::msg::ExecuteMsg::RegisterAgent {
-            payable_account_id: None,
-        },
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
         &[],
     )
     .unwrap();
-    let err: ContractError = app
+    let after_end_participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
+
+    let callback: Option<TaskEndedCallback> = app
+        .wrap()
+        .query_wasm_smart(callback_addr, &cosmwasm_std::Empty {})
+        .unwrap();
+    assert_eq!(
+        callback,
+        Some(TaskEndedCallback {
+            task_hash,
+            end_reason: TaskEndReason::MaxExecutions,
+            refunded: coins(
+                (after_end_participant_balance.amount - participant_balance.amount).u128(),
+                DENOM
+            ),
+            cw20_refunded: None,
+        })
+    );
+
+    // Failed callback doesn't revert the task execution
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Block(1),
+        boundary: None,
+        stop_on_fail: true,
+        actions: vec![Action {
+            msg: WasmMsg::Execute {
+                contract_addr: PARTICIPANT3.to_owned(),
+                msg: to_binary(&"ping").unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: Some(100_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: Some(callbacks),
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
         .execute_contract(
-            Addr::unchecked(AGENT1),
-            manager_addr.clone(),
-            &ExecuteMsg::ProxyCall {
-                task_hash: Some(task_hash.clone()),
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
             },
-            &[],
+            &coins(600_000, DENOM),
         )
-        .unwrap_err()
-        .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::AgentNotActive {});
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
 
-    // active agent(agent0), but task not ready
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr.clone(),
-            &ExecuteMsg::ProxyCall {
-                task_hash: Some(task_hash.clone()),
-            },
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::TaskQueryResultFalse {});
-
-    // making task ready
-    app.sudo(
-        BankSudo::Mint {
-            to_address: "lucy".to_owned(),
-            amount: coins(10, "denom"),
-        }
-        .into(),
-    )
-    .unwrap();
-
-    let res = app
+    app.update_block(add_little_time);
+    let res = app
         .execute_contract(
             Addr::unchecked(AGENT0),
             manager_addr,
-            &ExecuteMsg::ProxyCall {
-                task_hash: Some(task_hash.clone()),
-            },
+            &ExecuteMsg::ProxyCall { task_hash: None },
             &[],
         )
         .unwrap();
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "lifecycle" && attr.value == "task_invalidated")
-    }));
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "callback_failure")));
 
-    // make sure it's gone after invalidation
     let task_response: TaskResponse = app
         .wrap()
         .query_wasm_smart(
@@ -3003,97 +3264,8 @@ fn negative_proxy_call() {
         .unwrap();
     assert!(task_response.task.is_none());
 }
-
-#[test]
-fn test_withdraw_agent_fail() {
-    let mut app = default_app();
-    let factory_addr = init_factory(&mut app);
-
-    let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agents_addr = init_agents(&mut app, &factory_addr);
-    let _tasks_addr = init_tasks(&mut app, &factory_addr);
-
-    // Agent isn't registered
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(None),
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::NoRewardsOwnerAgentFound {});
-
-    activate_agent(&mut app, &agents_addr);
-
-    // No available rewards for withdraw
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(None),
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::NoWithdrawRewardsAvailable {});
-
-    // Unauthorized to withdraw, only agent contracts can call AgentWithdraw with args
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
-                agent_id: AGENT0.to_owned(),
-                payable_account_id: PARTICIPANT0.to_owned(),
-            })),
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::Unauthorized {});
-
-    // Shouldn't attach funds
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(None),
-            &[coin(1, DENOM)],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::RedundantFunds {});
-
-    // Paused
-    app.execute_contract(
-        Addr::unchecked(PAUSE_ADMIN),
-        manager_addr.clone(),
-        &ExecuteMsg::PauseContract {},
-        &[],
-    )
-    .unwrap();
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
-            manager_addr,
-            &ExecuteMsg::AgentWithdraw(None),
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::ContractPaused {});
-}
-
 #[test]
-fn test_withdraw_agent_success() {
+fn recurring_task_cron() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
@@ -3104,49 +3276,95 @@ fn test_withdraw_agent_success() {
 
     activate_agent(&mut app, &agents_addr);
 
-    // Create a task
     let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Once,
-        boundary: None,
+        interval: Interval::Cron("* * * * * *".to_owned()),
+        // repeat it two times
+        boundary: Some(Boundary::Time(BoundaryTime {
+            start: Some(app.block_info().time),
+            end: Some(app.block_info().time.plus_seconds(40)),
+        })),
         stop_on_fail: false,
-        actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: "bob".to_owned(),
-                amount: coins(45, DENOM),
-            }
-            .into(),
-            gas_limit: None,
-        }],
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "alice".to_owned(),
+                    amount: coins(123, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(321, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
+
     let res = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             tasks_addr.clone(),
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-                task: Box::new(task.clone()),
+                task: Box::new(task),
             },
-            &coins(600_000, DENOM),
+            &coins(75000, DENOM),
         )
         .unwrap();
-
-    // Get task info
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
     let task_response: TaskResponse = app
         .wrap()
         .query_wasm_smart(
             tasks_addr.clone(),
-            &croncat_tasks::msg::QueryMsg::Task { task_hash },
+            &croncat_tasks::msg::QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
         )
         .unwrap();
+
     let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
+    let expected_gone_amount = {
+        let gas_fees = gas_needed * (DEFAULT_FEE + DEFAULT_FEE) as f64 / 100.0;
+        let amount_for_task = gas_needed * 0.04;
+        let amount_for_fees = gas_fees * 0.04;
+        amount_for_task + amount_for_fees + 321.0 + 123.0
+    } as u128;
 
     app.update_block(add_little_time);
 
-    // Agent executes a task
+    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+
+    // action done
+    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
+    assert_eq!(bob_balances, vec![coin(321, DENOM)]);
+    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
+    assert_eq!(alice_balances, coins(123, DENOM));
+
+    app.update_block(add_little_time);
     app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
@@ -3155,6 +3373,28 @@ fn test_withdraw_agent_success() {
     )
     .unwrap();
 
+    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
+    assert_eq!(bob_balances, vec![coin(321 * 2, DENOM)]);
+    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
+    assert_eq!(alice_balances, coins(123 * 2, DENOM));
+
+    // check task got unregistered
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_tasks::msg::QueryMsg::Task { task_hash },
+        )
+        .unwrap();
+    assert!(task_response.task.is_none());
+
+    let after_unregister_participant_balance =
+        app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
+    assert_eq!(
+        75000 - expected_gone_amount * 2,
+        after_unregister_participant_balance.amount.u128() - participant_balance.amount.u128()
+    );
+
     // Check agent reward
     let agent_reward: Uint128 = app
         .wrap()
@@ -3162,94 +3402,134 @@ fn test_withdraw_agent_success() {
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
     let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
     let amount_for_task = gas_needed * 0.04;
     let amount_for_fees = gas_fees * 0.04;
-    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128;
+    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128 * 2;
     assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
 
-    let agent_balance_before_withdraw = app.wrap().query_balance(AGENT0, DENOM).unwrap().amount;
-
-    // withdraw rewards
-    let res = app
-        .execute_contract(
-            Addr::unchecked(AGENT0),
+    // Check treasury reward
+    let treasury_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
             manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(None),
-            &[],
+            &QueryMsg::TreasuryBalance { denom: None },
         )
         .unwrap();
-
-    let agent_balance_after_withdraw = app.wrap().query_balance(AGENT0, DENOM).unwrap().amount;
-
-    // Check agent balance
     assert_eq!(
-        agent_balance_before_withdraw
-            .checked_add(agent_reward)
-            .unwrap(),
-        agent_balance_after_withdraw
+        treasury_balance,
+        Uint128::new((amount_for_fees as u128 * 2) + 1)
     );
 
-    // Check attributes
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "payment_account_id" && attr.value == *AGENT0)
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "rewards" && attr.value == agent_reward.to_string())
-    }));
-    // Check data
-    assert_eq!(
-        res.data,
-        Some(
-            to_binary(&AgentWithdrawCallback {
-                agent_id: AGENT0.to_string(),
-                amount: agent_reward,
-                payable_account_id: AGENT0.to_string(),
-            })
-            .unwrap()
-        )
-    );
+    // repeat to check contract state is progressing as expected
+    // first clear up rewards
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::AgentWithdraw(None),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        factory_addr.clone(),
+        manager_addr.clone(),
+        &ExecuteMsg::OwnerWithdraw {},
+        &[],
+    )
+    .unwrap();
 
-    // Agent balance in manager contract is zero after withdraw
-    let agent_reward: Uint128 = app
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Cron("* * * * * *".to_owned()),
+        // repeat it two times
+        boundary: Some(Boundary::Time(BoundaryTime {
+            start: Some(app.block_info().time),
+            end: Some(app.block_info().time.plus_seconds(40)),
+        })),
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "alice".to_owned(),
+                    amount: coins(123, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(321, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(75000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+    let task_response: TaskResponse = app
         .wrap()
         .query_wasm_smart(
-            manager_addr.clone(),
-            &QueryMsg::AgentRewards {
-                agent_id: AGENT0.to_owned(),
+            tasks_addr.clone(),
+            &croncat_tasks::msg::QueryMsg::Task {
+                task_hash: task_hash.clone(),
             },
         )
         .unwrap();
-    assert_eq!(agent_reward, Uint128::zero());
 
-    // Do the same again to check AgentWithdraw with args (when agent contract calls withdraw)
+    let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
+    let expected_gone_amount = {
+        let gas_fees = gas_needed * (DEFAULT_FEE + DEFAULT_FEE) as f64 / 100.0;
+        let amount_for_task = gas_needed * 0.04;
+        let amount_for_fees = gas_fees * 0.04;
+        amount_for_task + amount_for_fees + 321.0 + 123.0
+    } as u128;
 
-    // Create a task
+    app.update_block(add_little_time);
+    let participant_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     app.execute_contract(
-        Addr::unchecked(PARTICIPANT0),
-        tasks_addr,
-        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-            task: Box::new(task),
-        },
-        &coins(600_000, DENOM),
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
     )
     .unwrap();
 
-    app.update_block(add_little_time);
+    // action done
+    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
+    assert_eq!(bob_balances, vec![coin(321 * 3, DENOM)]);
+    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
+    assert_eq!(alice_balances, coins(123 * 3, DENOM));
 
-    // Agent executes a task
+    app.update_block(add_little_time);
     app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
@@ -3258,192 +3538,162 @@ fn test_withdraw_agent_success() {
     )
     .unwrap();
 
-    // Check agent reward
-    // Don't calculate prices again, task is the same
-    let agent_reward: Uint128 = app
-        .wrap()
-        .query_wasm_smart(
-            manager_addr.clone(),
-            &QueryMsg::AgentRewards {
-                agent_id: AGENT0.to_owned(),
-            },
-        )
-        .unwrap();
-    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
+    let bob_balances = app.wrap().query_all_balances("bob").unwrap();
+    assert_eq!(bob_balances, vec![coin(321 * 4, DENOM)]);
+    let alice_balances = app.wrap().query_all_balances("alice").unwrap();
+    assert_eq!(alice_balances, coins(123 * 4, DENOM));
 
-    // Agent contract calls withdraw for this agent
-    let payable_account_balance_before_withdraw = app
+    // check task got unregistered
+    let task_response: TaskResponse = app
         .wrap()
-        .query_balance(PARTICIPANT2, DENOM)
-        .unwrap()
-        .amount;
-    let res = app
-        .execute_contract(
-            Addr::unchecked(agents_addr.clone()),
-            manager_addr.clone(),
-            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
-                agent_id: AGENT0.to_owned(),
-                payable_account_id: PARTICIPANT2.to_owned(),
-            })),
-            &[],
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_tasks::msg::QueryMsg::Task { task_hash },
         )
         .unwrap();
-    let payable_account_balance_after_withdraw = app
-        .wrap()
-        .query_balance(PARTICIPANT2, DENOM)
-        .unwrap()
-        .amount;
-
-    // Check payable_account balance
-    assert_eq!(
-        payable_account_balance_before_withdraw
-            .checked_add(agent_reward)
-            .unwrap(),
-        payable_account_balance_after_withdraw
-    );
+    assert!(task_response.task.is_none());
 
-    // Check attributes
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "payment_account_id" && attr.value == *PARTICIPANT2)
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "rewards" && attr.value == agent_reward.to_string())
-    }));
-    // Check data
+    let after_unregister_participant_balance =
+        app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     assert_eq!(
-        res.data,
-        Some(
-            to_binary(&AgentWithdrawCallback {
-                agent_id: AGENT0.to_string(),
-                amount: agent_reward,
-                payable_account_id: PARTICIPANT2.to_string(),
-            })
-            .unwrap()
-        )
+        75000 - expected_gone_amount * 2,
+        after_unregister_participant_balance.amount.u128() - participant_balance.amount.u128()
     );
 
-    // Agent balance in manager contract is zero after withdraw
+    // Check agent reward
     let agent_reward: Uint128 = app
         .wrap()
         .query_wasm_smart(
             manager_addr.clone(),
             &QueryMsg::AgentRewards {
                 agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
-    assert_eq!(agent_reward, Uint128::zero());
+    let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
+    let amount_for_task = gas_needed * 0.04;
+    let amount_for_fees = gas_fees * 0.04;
+    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128 * 2;
+    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
 
-    // Agent contract can call AgentWithdraw even if the reward is zero
-    let payable_account_balance_before_withdraw = app
+    // Check treasury reward
+    let treasury_balance: Uint128 = app
         .wrap()
-        .query_balance(PARTICIPANT2, DENOM)
-        .unwrap()
-        .amount;
-    // Agent contract calls withdraw for the agent
-    let res = app
-        .execute_contract(
-            Addr::unchecked(agents_addr),
-            manager_addr,
-            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
-                agent_id: AGENT0.to_owned(),
-                payable_account_id: PARTICIPANT2.to_owned(),
-            })),
-            &[],
-        )
+        .query_wasm_smart(manager_addr, &QueryMsg::TreasuryBalance { denom: None })
         .unwrap();
-    let payable_account_balance_after_withdraw = app
-        .wrap()
-        .query_balance(PARTICIPANT2, DENOM)
-        .unwrap()
-        .amount;
-
-    // Check payable_account balance
-    assert_eq!(
-        payable_account_balance_before_withdraw,
-        payable_account_balance_after_withdraw
-    );
-
-    // Check attributes
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "payment_account_id" && attr.value == *PARTICIPANT2)
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "rewards" && attr.value == *"0")
-    }));
-    // Check data
-    assert_eq!(
-        res.data,
-        Some(
-            to_binary(&AgentWithdrawCallback {
-                agent_id: AGENT0.to_string(),
-                amount: Uint128::zero(),
-                payable_account_id: PARTICIPANT2.to_string(),
-            })
-            .unwrap()
-        )
-    );
+    assert_eq!(treasury_balance, Uint128::new(amount_for_fees as u128 * 2));
 }
 
 #[test]
-fn refill_task_balance_fail() {
+fn negative_proxy_call() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let _agents_addr = init_agents(&mut app, &factory_addr);
+    let agents_addr = init_agents(&mut app, &factory_addr);
     let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let mod_balances = init_mod_balances(&mut app, &factory_addr);
 
-    // RefillTaskBalance with wrong hash
+    let cw20_addr = init_cw20(&mut app);
+    support_new_cw20(
+        &mut app,
+        factory_addr.clone(),
+        &manager_addr,
+        cw20_addr.as_str(),
+    );
+
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        cw20_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: manager_addr.to_string(),
+            amount: Uint128::new(555),
+            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    activate_agent(&mut app, &agents_addr);
+
+    // no task for this agent
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: "hash".to_owned(),
-            },
-            &coins(100_000, DENOM),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::NoTaskHash {});
+    assert_eq!(err, ContractError::NoTaskForAgent {});
 
-    // Create a task
+    // not registered agent
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT1),
+            manager_addr.clone(),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoTaskForAgent {});
+
+    // agent not registered before proxy call with queries
+    // Creating task itself
     let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: "bob".to_owned(),
-                amount: coins(45, DENOM),
+            msg: WasmMsg::Execute {
+                contract_addr: cw20_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "alice".to_owned(),
+                    amount: Uint128::new(1),
+                })
+                .unwrap(),
+                funds: Default::default(),
             }
             .into(),
-            gas_limit: None,
+            gas_limit: Some(250_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        queries: None,
-        transforms: None,
-        cw20: None,
+        queries: Some(vec![CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: mod_balances.to_string(),
+            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::HasBalanceComparator(
+                HasBalanceComparator {
+                    address: "lucy".to_owned(),
+                    required_balance: coins(10, "denom").into(),
+                    comparator: croncat_mod_balances::types::BalanceComparator::Eq,
+                },
+            ))
+            .unwrap(),
+            check_result: true,
+        })]),
+        transforms: Some(vec![Transform {
+            action_idx: 0,
+            query_idx: 0,
+            action_path: vec!["transfer".to_owned().into(), "amount".to_owned().into()].into(),
+            query_response_path: vec!["amount".to_owned().into()].into(),
+            operations: vec![],
+            result_type: None,
+        }]),
+        cw20: Some(Cw20Coin {
+            address: cw20_addr.to_string(),
+            amount: Uint128::new(1),
+        }),
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -3460,200 +3710,167 @@ fn refill_task_balance_fail() {
 
     app.update_block(add_little_time);
 
-    // RefillTaskBalance called by the wrong address
+    // Agent not registered
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT2),
+            Addr::unchecked(AGENT1),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
+            &ExecuteMsg::ProxyCall {
+                task_hash: Some(task_hash.clone()),
             },
-            &coins(100_000, DENOM),
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::Unauthorized {});
+    assert_eq!(err, ContractError::AgentNotActive {});
 
-    // RefillTaskBalance with 3 coins
-    let attach_funds = vec![coin(100_000, "ujuno"), coin(100_000, "ibc")];
-    app.sudo(
-        BankSudo::Mint {
-            to_address: PARTICIPANT0.to_owned(),
-            amount: attach_funds,
-        }
-        .into(),
+    // Agent not active
+    // register agent1 first
+    app.execute_contract(
+        Addr::unchecked(AGENT1),
+        agents_addr.clone(),
+        &croncat_sdk_agents::msg::ExecuteMsg::RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
     )
     .unwrap();
-    let mut participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[
-            coin(4_400_000, DENOM),
-            coin(100_000, "ibc"),
-            coin(100_000, "ujuno")
-        ]
-    );
-
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(AGENT1),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
+            &ExecuteMsg::ProxyCall {
+                task_hash: Some(task_hash.clone()),
             },
-            &[
-                coin(100_000, DENOM),
-                coin(10_000, "ujuno".to_owned()),
-                coin(10_000, "ibc".to_owned()),
-            ],
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::InvalidAttachedCoins {});
+    assert_eq!(err, ContractError::AgentNotActive {});
 
-    // RefillTaskBalance with wrong denom, task doesn't have ibc coins
+    // active agent(agent0), but task not ready
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
+            &ExecuteMsg::ProxyCall {
+                task_hash: Some(task_hash.clone()),
             },
-            &coins(10_000, "ibc".to_owned()),
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::InvalidAttachedCoins {});
+    assert_eq!(err, ContractError::TaskQueryResultFalse {});
 
-    // Get task balance
-    let task_balance: TaskBalanceResponse = app
+    // making task ready
+    app.sudo(
+        BankSudo::Mint {
+            to_address: "lucy".to_owned(),
+            amount: coins(10, "denom"),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr,
+            &ExecuteMsg::ProxyCall {
+                task_hash: Some(task_hash.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "lifecycle" && attr.value == "task_invalidated")
+    }));
+
+    // make sure it's gone after invalidation
+    let task_response: TaskResponse = app
         .wrap()
-        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TaskBalance { task_hash })
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_tasks::msg::QueryMsg::Task { task_hash },
+        )
         .unwrap();
-    assert_eq!(
-        task_balance.balance.unwrap(),
-        TaskBalance {
-            native_balance: 600_000u64.into(),
-            cw20_balance: None,
-            ibc_balance: None
-        }
-    );
+    assert!(task_response.task.is_none());
+}
 
-    // Check that PARTICIPANT0 balance didn't change
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[
-            coin(4_400_000, DENOM),
-            coin(100_000, "ibc"),
-            coin(100_000, "ujuno")
-        ]
-    );
+#[test]
+fn test_withdraw_agent_fail() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
 
-    // Create task without ibc balance, but attach some, should fail
-    let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Once,
-        boundary: None,
-        stop_on_fail: false,
-        actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: "bob".to_owned(),
-                amount: coins(46, DENOM),
-            }
-            .into(),
-            gas_limit: None,
-        }],
-        queries: None,
-        transforms: None,
-        cw20: None,
-    };
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let _tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    // Agent isn't registered
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-                task: Box::new(task),
-            },
-            &[coin(600_000, DENOM), coin(50_000, "ibc")],
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::AgentWithdraw(None),
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(
-        err,
-        ContractError::Sdk(croncat_sdk_manager::SdkError::NonRequiredDenom {})
-    );
+    assert_eq!(err, ContractError::NoRewardsOwnerAgentFound {});
 
-    // Create task with ibc balance
-    let task = croncat_sdk_tasks::types::TaskRequest {
-        interval: Interval::Once,
-        boundary: None,
-        stop_on_fail: false,
-        actions: vec![
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(46, DENOM),
-                }
-                .into(),
-                gas_limit: None,
-            },
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(5_000, "ibc"),
-                }
-                .into(),
-                gas_limit: None,
-            },
-        ],
-        queries: None,
-        transforms: None,
-        cw20: None,
-    };
-    let res = app
+    activate_agent(&mut app, &agents_addr);
+
+    // No available rewards for withdraw
+    let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
-            tasks_addr,
-            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-                task: Box::new(task),
-            },
-            &[coin(600_000, DENOM), coin(50_000, "ibc")],
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::AgentWithdraw(None),
+            &[],
         )
+        .unwrap_err()
+        .downcast()
         .unwrap();
-    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
-    let task_hash = task_data.task_hash;
+    assert_eq!(err, ContractError::NoWithdrawRewardsAvailable {});
 
-    // Check PARTICIPANT0 balance
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[
-            coin(3_800_000, DENOM),
-            coin(50_000, "ibc"),
-            coin(100_000, "ujuno")
-        ]
-    );
+    // Unauthorized to withdraw, only agent contracts can call AgentWithdraw with args
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
+                agent_id: AGENT0.to_owned(),
+                payable_account_id: PARTICIPANT0.to_owned(),
+            })),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    // RefillTaskBalance with wrong denom, task has ibc coins
+    // Shouldn't attach funds
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
-            },
-            &coins(10_000, "ujuno".to_owned()),
+            &ExecuteMsg::AgentWithdraw(None),
+            &[coin(1, DENOM)],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::InvalidAttachedCoins {});
+    assert_eq!(err, ContractError::RedundantFunds {});
 
-    // Pause
+    // Paused
     app.execute_contract(
         Addr::unchecked(PAUSE_ADMIN),
         manager_addr.clone(),
@@ -3661,210 +3878,343 @@ fn refill_task_balance_fail() {
         &[],
     )
     .unwrap();
-
-    // RefillTaskBalance when contract is paused
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT2),
-            manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
-            },
-            &coins(100_000, DENOM),
+            Addr::unchecked(AGENT0),
+            manager_addr,
+            &ExecuteMsg::AgentWithdraw(None),
+            &[],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
     assert_eq!(err, ContractError::ContractPaused {});
-
-    // Check task balance
-    let task_balance: TaskBalanceResponse = app
-        .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TaskBalance { task_hash })
-        .unwrap();
-    assert_eq!(
-        task_balance.balance.unwrap(),
-        TaskBalance {
-            native_balance: 600_000u64.into(),
-            cw20_balance: None,
-            ibc_balance: Some(coin(50_000, "ibc".to_owned()))
-        }
-    );
-
-    // Check that PARTICIPANT0 balance didn't change
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[
-            coin(3_800_000, DENOM),
-            coin(50_000, "ibc"),
-            coin(100_000, "ujuno")
-        ]
-    );
 }
 
 #[test]
-fn refill_task_balance_success() {
+fn test_withdraw_agent_success() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let _agents_addr = init_agents(&mut app, &factory_addr);
+    let agents_addr = init_agents(&mut app, &factory_addr);
     let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    // Create task with ibc balance
-    let attach_funds = vec![coin(100_000, "ibc")];
-    app.sudo(
-        BankSudo::Mint {
-            to_address: PARTICIPANT0.to_owned(),
-            amount: attach_funds,
-        }
-        .into(),
-    )
-    .unwrap();
-    let mut participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[coin(5_000_000, DENOM), coin(100_000, "ibc")]
-    );
+    activate_agent(&mut app, &agents_addr);
 
+    // Create a task
     let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
-        actions: vec![
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(45, DENOM),
-                }
-                .into(),
-                gas_limit: None,
-            },
-            Action {
-                msg: BankMsg::Send {
-                    to_address: "bob".to_owned(),
-                    amount: coins(5_000, "ibc"),
-                }
-                .into(),
-                gas_limit: None,
-            },
-        ],
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "bob".to_owned(),
+                amount: coins(45, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
-            tasks_addr,
+            tasks_addr.clone(),
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-                task: Box::new(task),
+                task: Box::new(task.clone()),
             },
-            &[coin(600_000, DENOM), coin(50_000, "ibc")],
+            &coins(600_000, DENOM),
         )
         .unwrap();
+
+    // Get task info
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
-
-    // PARTICIPANT0 balances
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[coin(4_400_000, DENOM), coin(50_000, "ibc")]
-    );
-
-    app.update_block(add_little_time);
-
-    // RefillTaskBalance with native coins
-    let res = app
-        .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
-            manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
-            },
-            &coins(100_000, DENOM),
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_tasks::msg::QueryMsg::Task { task_hash },
         )
         .unwrap();
+    let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
 
-    // Check attributes
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "action" && attr.value == "refill_native_balance")
-    }));
+    app.update_block(add_little_time);
 
-    // Check task balance
-    let task_balance: TaskBalanceResponse = app
+    // Agent executes a task
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+
+    // Check agent reward
+    let agent_reward: Uint128 = app
         .wrap()
         .query_wasm_smart(
             manager_addr.clone(),
-            &QueryMsg::TaskBalance {
-                task_hash: task_hash.to_owned(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
-    assert_eq!(
-        task_balance.balance.unwrap(),
-        TaskBalance {
-            native_balance: 700_000u64.into(),
-            cw20_balance: None,
-            ibc_balance: Some(coin(50_000, "ibc".to_owned()))
-        }
-    );
+    let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
+    let amount_for_task = gas_needed * 0.04;
+    let amount_for_fees = gas_fees * 0.04;
+    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128;
+    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
 
-    // Check PARTICIPANT0 balances
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
-    assert_eq!(
-        participant_balances,
-        &[coin(4_300_000, DENOM), coin(50_000, "ibc")]
-    );
+    let agent_balance_before_withdraw = app.wrap().query_balance(AGENT0, DENOM).unwrap().amount;
 
-    // RefillTaskBalance with ibc coins
+    // withdraw rewards
     let res = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskBalance {
-                task_hash: task_hash.to_owned(),
-            },
-            &coins(30_000, "ibc"),
+            &ExecuteMsg::AgentWithdraw(None),
+            &[],
         )
         .unwrap();
 
-    // Check attributes
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            .any(|attr| attr.key == "action" && attr.value == "refill_native_balance")
-    }));
+    let agent_balance_after_withdraw = app.wrap().query_balance(AGENT0, DENOM).unwrap().amount;
 
-    // Check task balance
-    let task_balance: TaskBalanceResponse = app
+    // Check agent balance
+    assert_eq!(
+        agent_balance_before_withdraw
+            .checked_add(agent_reward)
+            .unwrap(),
+        agent_balance_after_withdraw
+    );
+
+    // Check attributes
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "payment_account_id" && attr.value == *AGENT0)
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "rewards" && attr.value == format!("{agent_reward}{DENOM}"))
+    }));
+    // Check data
+    assert_eq!(
+        res.data,
+        Some(
+            to_binary(&AgentWithdrawCallback {
+                agent_id: AGENT0.to_string(),
+                amount: coins(agent_reward.u128(), DENOM),
+                payable_account_id: AGENT0.to_string(),
+            })
+            .unwrap()
+        )
+    );
+
+    // Agent balance in manager contract is zero after withdraw
+    let agent_reward: Uint128 = app
         .wrap()
-        .query_wasm_smart(manager_addr, &QueryMsg::TaskBalance { task_hash })
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_reward, Uint128::zero());
+
+    // Do the same again to check AgentWithdraw with args (when agent contract calls withdraw)
+
+    // Create a task
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        tasks_addr,
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+            task: Box::new(task),
+        },
+        &coins(600_000, DENOM),
+    )
+    .unwrap();
+
+    app.update_block(add_little_time);
+
+    // Agent executes a task
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+
+    // Check agent reward
+    // Don't calculate prices again, task is the same
+    let agent_reward: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_reward, Uint128::from(expected_agent_reward));
+
+    // Agent contract calls withdraw for this agent
+    let payable_account_balance_before_withdraw = app
+        .wrap()
+        .query_balance(PARTICIPANT2, DENOM)
+        .unwrap()
+        .amount;
+    let res = app
+        .execute_contract(
+            Addr::unchecked(agents_addr.clone()),
+            manager_addr.clone(),
+            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
+                agent_id: AGENT0.to_owned(),
+                payable_account_id: PARTICIPANT2.to_owned(),
+            })),
+            &[],
+        )
         .unwrap();
+    let payable_account_balance_after_withdraw = app
+        .wrap()
+        .query_balance(PARTICIPANT2, DENOM)
+        .unwrap()
+        .amount;
+
+    // Check payable_account balance
     assert_eq!(
-        task_balance.balance.unwrap(),
-        TaskBalance {
-            native_balance: 700_000u64.into(),
-            cw20_balance: None,
-            ibc_balance: Some(coin(80_000, "ibc".to_owned()))
-        }
+        payable_account_balance_before_withdraw
+            .checked_add(agent_reward)
+            .unwrap(),
+        payable_account_balance_after_withdraw
     );
 
-    // Check PARTICIPANT0 balances
-    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
+    // Check attributes
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "payment_account_id" && attr.value == *PARTICIPANT2)
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "rewards" && attr.value == format!("{agent_reward}{DENOM}"))
+    }));
+    // Check data
     assert_eq!(
-        participant_balances,
-        &[coin(4_300_000, DENOM), coin(20_000, "ibc")]
+        res.data,
+        Some(
+            to_binary(&AgentWithdrawCallback {
+                agent_id: AGENT0.to_string(),
+                amount: coins(agent_reward.u128(), DENOM),
+                payable_account_id: PARTICIPANT2.to_string(),
+            })
+            .unwrap()
+        )
+    );
+
+    // Agent balance in manager contract is zero after withdraw
+    let agent_reward: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_reward, Uint128::zero());
+
+    // Agent contract can call AgentWithdraw even if the reward is zero
+    let payable_account_balance_before_withdraw = app
+        .wrap()
+        .query_balance(PARTICIPANT2, DENOM)
+        .unwrap()
+        .amount;
+    // Agent contract calls withdraw for the agent
+    let res = app
+        .execute_contract(
+            Addr::unchecked(agents_addr),
+            manager_addr,
+            &ExecuteMsg::AgentWithdraw(Some(AgentWithdrawOnRemovalArgs {
+                agent_id: AGENT0.to_owned(),
+                payable_account_id: PARTICIPANT2.to_owned(),
+            })),
+            &[],
+        )
+        .unwrap();
+    let payable_account_balance_after_withdraw = app
+        .wrap()
+        .query_balance(PARTICIPANT2, DENOM)
+        .unwrap()
+        .amount;
+
+    // Check payable_account balance
+    assert_eq!(
+        payable_account_balance_before_withdraw,
+        payable_account_balance_after_withdraw
+    );
+
+    // Check attributes
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "withdraw_rewards")
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "payment_account_id" && attr.value == *PARTICIPANT2)
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "rewards" && attr.value == *"0")
+    }));
+    // Check data
+    assert_eq!(
+        res.data,
+        Some(
+            to_binary(&AgentWithdrawCallback {
+                agent_id: AGENT0.to_string(),
+                amount: vec![],
+                payable_account_id: PARTICIPANT2.to_string(),
+            })
+            .unwrap()
+        )
     );
 }
 
 #[test]
-fn refill_task_cw20_fail() {
+fn refill_task_balance_fail() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
@@ -3873,29 +4223,15 @@ fn refill_task_cw20_fail() {
     let _agents_addr = init_agents(&mut app, &factory_addr);
     let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    let cw20_addr = init_cw20(&mut app);
-    support_new_cw20(
-        &mut app,
-        factory_addr.clone(),
-        &manager_addr,
-        cw20_addr.as_str(),
-    );
-
-    let cw20 = Cw20Coin {
-        address: cw20_addr.to_string(),
-        amount: 100u64.into(),
-    };
-
-    // RefillTaskCw20Balance with wrong hash
+    // RefillTaskBalance with wrong hash
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: "hash".to_owned(),
-                cw20: cw20.clone(),
             },
-            &[],
+            &coins(100_000, DENOM),
         )
         .unwrap_err()
         .downcast()
@@ -3914,10 +4250,17 @@ fn refill_task_cw20_fail() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -3934,85 +4277,68 @@ fn refill_task_cw20_fail() {
 
     app.update_block(add_little_time);
 
-    // RefillTaskCw20Balance with funds
+    // RefillTaskBalance called by the wrong address
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(PARTICIPANT2),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20: cw20.clone(),
             },
             &coins(100_000, DENOM),
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::RedundantFunds {});
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    // RefillTaskCw20Balance called by the wrong address
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(PARTICIPANT2),
-            manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
-                task_hash: task_hash.to_owned(),
-                cw20: cw20.clone(),
-            },
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::Unauthorized {});
+    // RefillTaskBalance with 3 coins
+    let attach_funds = vec![coin(100_000, "ujuno"), coin(100_000, "ibc")];
+    app.sudo(
+        BankSudo::Mint {
+            to_address: PARTICIPANT0.to_owned(),
+            amount: attach_funds,
+        }
+        .into(),
+    )
+    .unwrap();
+    let mut participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
+    assert_eq!(
+        participant_balances,
+        &[
+            coin(4_400_000, DENOM),
+            coin(100_000, "ibc"),
+            coin(100_000, "ujuno")
+        ]
+    );
 
-    // RefillTaskBalance fails because PARTICIPANT0 doesn't have cw20 deposit
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20: cw20.clone(),
             },
-            &[],
+            &[
+                coin(100_000, DENOM),
+                coin(10_000, "ujuno".to_owned()),
+                coin(10_000, "ibc".to_owned()),
+            ],
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::EmptyBalance {});
-
-    // PARTICIPANT0 deposits some cw20 coins
-    app.execute_contract(
-        Addr::unchecked(PARTICIPANT0),
-        cw20_addr.clone(),
-        &cw20::Cw20ExecuteMsg::Send {
-            contract: manager_addr.to_string(),
-            amount: Uint128::new(555),
-            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
-        },
-        &[],
-    )
-    .unwrap();
-    let mut wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
-    assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(555),
-        }]
-    );
+    assert_eq!(err, ContractError::InvalidAttachedCoins {});
 
-    // RefillTaskCw20Balance fails because the task balance doesn't have cw20
+    // RefillTaskBalance with wrong denom, task doesn't have ibc coins
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20: cw20.clone(),
             },
-            &[],
+            &coins(10_000, "ibc".to_owned()),
         )
         .unwrap_err()
         .downcast()
@@ -4029,21 +4355,24 @@ fn refill_task_cw20_fail() {
         TaskBalance {
             native_balance: 600_000u64.into(),
             cw20_balance: None,
-            ibc_balance: None
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }
     );
 
-    // PARTICIPANT0 cw20 deposit didn't change
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    // Check that PARTICIPANT0 balance didn't change
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(555),
-        }]
+        participant_balances,
+        &[
+            coin(4_400_000, DENOM),
+            coin(100_000, "ibc"),
+            coin(100_000, "ujuno")
+        ]
     );
 
-    // Create a task without a cw20, attach some anyway
+    // Create task without ibc balance, but attach some, should fail
     let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
         boundary: None,
@@ -4055,10 +4384,17 @@ fn refill_task_cw20_fail() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
-        cw20: Some(cw20.clone()),
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -4067,7 +4403,7 @@ fn refill_task_cw20_fail() {
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
                 task: Box::new(task),
             },
-            &coins(600_000, DENOM),
+            &[coin(600_000, DENOM), coin(50_000, "ibc")],
         )
         .unwrap_err()
         .downcast()
@@ -4077,7 +4413,7 @@ fn refill_task_cw20_fail() {
         ContractError::Sdk(croncat_sdk_manager::SdkError::NonRequiredDenom {})
     );
 
-    // Create a task with cw20
+    // Create task with ibc balance
     let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
         boundary: None,
@@ -4090,25 +4426,29 @@ fn refill_task_cw20_fail() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
-                msg: WasmMsg::Execute {
-                    contract_addr: cw20_addr.clone().to_string(),
-                    msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
-                        contract: manager_addr.to_string(),
-                        amount: Uint128::new(55),
-                        msg: Binary::default(),
-                    })
-                    .unwrap(),
-                    funds: vec![],
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(5_000, "ibc"),
                 }
                 .into(),
-                gas_limit: Some(90_000),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
-        cw20: Some(cw20.clone()),
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -4117,71 +4457,32 @@ fn refill_task_cw20_fail() {
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
                 task: Box::new(task),
             },
-            &coins(600_000, DENOM),
+            &[coin(600_000, DENOM), coin(50_000, "ibc")],
         )
         .unwrap();
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
 
-    // PARTICIPANT0 spent 100 coins
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
-    assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(455),
-        }]
-    );
-
-    app.update_block(add_little_time);
-
-    // Try RefillTaskCw20Balance with wrong cw20 address
-    let new_cw20_addr = init_cw20(&mut app);
-    support_new_cw20(
-        &mut app,
-        factory_addr.clone(),
-        &manager_addr,
-        new_cw20_addr.as_str(),
-    );
-    app.execute_contract(
-        Addr::unchecked(PARTICIPANT0),
-        new_cw20_addr.clone(),
-        &cw20::Cw20ExecuteMsg::Send {
-            contract: manager_addr.to_string(),
-            amount: Uint128::new(555),
-            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
-        },
-        &[],
-    )
-    .unwrap();
-
-    // Check PARTICIPANT0 cw20 deposit on manager contract
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    // Check PARTICIPANT0 balance
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![
-            Cw20CoinVerified {
-                address: cw20_addr.to_owned(),
-                amount: Uint128::new(455),
-            },
-            Cw20CoinVerified {
-                address: new_cw20_addr.to_owned(),
-                amount: Uint128::new(555),
-            }
+        participant_balances,
+        &[
+            coin(3_800_000, DENOM),
+            coin(50_000, "ibc"),
+            coin(100_000, "ujuno")
         ]
     );
+
+    // RefillTaskBalance with wrong denom, task has ibc coins
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20: Cw20Coin {
-                    address: new_cw20_addr.to_string(),
-                    amount: 1u64.into(),
-                },
             },
-            &[],
+            &coins(10_000, "ujuno".to_owned()),
         )
         .unwrap_err()
         .downcast()
@@ -4200,58 +4501,48 @@ fn refill_task_cw20_fail() {
     // RefillTaskBalance when contract is paused
     let err: ContractError = app
         .execute_contract(
-            Addr::unchecked(PARTICIPANT0),
+            Addr::unchecked(PARTICIPANT2),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20,
             },
-            &[],
+            &coins(100_000, DENOM),
         )
         .unwrap_err()
         .downcast()
         .unwrap();
     assert_eq!(err, ContractError::ContractPaused {});
 
-    // Get task balance
+    // Check task balance
     let task_balance: TaskBalanceResponse = app
         .wrap()
-        .query_wasm_smart(
-            manager_addr.to_owned(),
-            &QueryMsg::TaskBalance { task_hash },
-        )
+        .query_wasm_smart(manager_addr, &QueryMsg::TaskBalance { task_hash })
         .unwrap();
     assert_eq!(
         task_balance.balance.unwrap(),
         TaskBalance {
             native_balance: 600_000u64.into(),
-            cw20_balance: Some(Cw20CoinVerified {
-                address: cw20_addr.to_owned(),
-                amount: 100u64.into()
-            }),
-            ibc_balance: None
+            cw20_balance: None,
+            ibc_balance: Some(coin(50_000, "ibc".to_owned())),
+            sponsor: None,
+            gas_denom: None,
         }
     );
 
-    // Check that PARTICIPANT0 cw20 balance didn't change
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    // Check that PARTICIPANT0 balance didn't change
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![
-            Cw20CoinVerified {
-                address: cw20_addr,
-                amount: Uint128::new(455),
-            },
-            Cw20CoinVerified {
-                address: new_cw20_addr,
-                amount: Uint128::new(555),
-            }
+        participant_balances,
+        &[
+            coin(3_800_000, DENOM),
+            coin(50_000, "ibc"),
+            coin(100_000, "ujuno")
         ]
     );
 }
 
 #[test]
-fn refill_task_cw20_success() {
+fn refill_task_balance_success() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
@@ -4260,43 +4551,22 @@ fn refill_task_cw20_success() {
     let _agents_addr = init_agents(&mut app, &factory_addr);
     let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    let cw20_addr = init_cw20(&mut app);
-    support_new_cw20(
-        &mut app,
-        factory_addr.clone(),
-        &manager_addr,
-        cw20_addr.as_str(),
-    );
-
-    let cw20 = Cw20Coin {
-        address: cw20_addr.to_string(),
-        amount: 100u64.into(),
-    };
-
-    // PARTICIPANT0 deposits some cw20 coins
-    app.execute_contract(
-        Addr::unchecked(PARTICIPANT0),
-        cw20_addr.clone(),
-        &cw20::Cw20ExecuteMsg::Send {
-            contract: manager_addr.to_string(),
-            amount: Uint128::new(555),
-            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
-        },
-        &[],
+    // Create task with ibc balance
+    let attach_funds = vec![coin(100_000, "ibc")];
+    app.sudo(
+        BankSudo::Mint {
+            to_address: PARTICIPANT0.to_owned(),
+            amount: attach_funds,
+        }
+        .into(),
     )
     .unwrap();
-
-    // Check the deposit
-    let mut wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    let mut participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(555),
-        }]
+        participant_balances,
+        &[coin(5_000_000, DENOM), coin(100_000, "ibc")]
     );
 
-    // Create a task with cw20
     let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
         boundary: None,
@@ -4309,25 +4579,29 @@ fn refill_task_cw20_success() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
-                msg: WasmMsg::Execute {
-                    contract_addr: cw20_addr.clone().to_string(),
-                    msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
-                        contract: manager_addr.to_string(),
-                        amount: Uint128::new(55),
-                        msg: Binary::default(),
-                    })
-                    .unwrap(),
-                    funds: vec![],
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(5_000, "ibc"),
                 }
                 .into(),
-                gas_limit: Some(90_000),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
-        cw20: Some(cw20.clone()),
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -4336,254 +4610,2060 @@ fn refill_task_cw20_success() {
             &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
                 task: Box::new(task),
             },
-            &coins(600_000, DENOM),
+            &[coin(600_000, DENOM), coin(50_000, "ibc")],
         )
         .unwrap();
     let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
     let task_hash = task_data.task_hash;
 
-    // PARTICIPANT0 spent 455 coins
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    // PARTICIPANT0 balances
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(455),
-        }]
+        participant_balances,
+        &[coin(4_400_000, DENOM), coin(50_000, "ibc")]
     );
 
     app.update_block(add_little_time);
 
-    // Refill task balance
+    // RefillTaskBalance with native coins
     let res = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             manager_addr.clone(),
-            &ExecuteMsg::RefillTaskCw20Balance {
+            &ExecuteMsg::RefillTaskBalance {
                 task_hash: task_hash.to_owned(),
-                cw20,
             },
-            &[],
+            &coins(100_000, DENOM),
         )
         .unwrap();
 
-    // Get task balance, cw20_balance increased
+    // Check attributes
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "refill_native_balance")
+    }));
+
+    // Check task balance
     let task_balance: TaskBalanceResponse = app
         .wrap()
         .query_wasm_smart(
-            manager_addr.to_owned(),
-            &QueryMsg::TaskBalance { task_hash },
+            manager_addr.clone(),
+            &QueryMsg::TaskBalance {
+                task_hash: task_hash.to_owned(),
+            },
         )
         .unwrap();
     assert_eq!(
         task_balance.balance.unwrap(),
         TaskBalance {
-            native_balance: 600_000u64.into(),
-            cw20_balance: Some(Cw20CoinVerified {
-                address: cw20_addr.clone(),
-                amount: 200u64.into()
-            }),
-            ibc_balance: None
+            native_balance: 700_000u64.into(),
+            cw20_balance: None,
+            ibc_balance: Some(coin(50_000, "ibc".to_owned())),
+            sponsor: None,
+            gas_denom: None,
         }
     );
 
-    // PARTICIPANT0 balance decreased
-    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    // Check PARTICIPANT0 balances
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
     assert_eq!(
-        wallet_balances,
-        vec![Cw20CoinVerified {
-            address: cw20_addr.to_owned(),
-            amount: Uint128::new(355),
-        }]
+        participant_balances,
+        &[coin(4_300_000, DENOM), coin(50_000, "ibc")]
     );
 
+    // RefillTaskBalance with ibc coins
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskBalance {
+                task_hash: task_hash.to_owned(),
+            },
+            &coins(30_000, "ibc"),
+        )
+        .unwrap();
+
     // Check attributes
     assert!(res.events.iter().any(|ev| {
         ev.attributes
             .iter()
-            .any(|attr| attr.key == "action" && attr.value == "refill_task_cw20")
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes.iter().any(|attr| {
-            attr.key == "cw20_refilled"
-                && attr.value
-                    == Cw20CoinVerified {
-                        address: cw20_addr.clone(),
-                        amount: 100u64.into(),
-                    }
-                    .to_string()
-        })
-    }));
-    assert!(res.events.iter().any(|ev| {
-        ev.attributes.iter().any(|attr| {
-            attr.key == "task_cw20_balance"
-                && attr.value
-                    == Cw20CoinVerified {
-                        address: cw20_addr.clone(),
-                        amount: 200u64.into(),
-                    }
-                    .to_string()
-        })
+            .any(|attr| attr.key == "action" && attr.value == "refill_native_balance")
     }));
+
+    // Check task balance
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(manager_addr, &QueryMsg::TaskBalance { task_hash })
+        .unwrap();
+    assert_eq!(
+        task_balance.balance.unwrap(),
+        TaskBalance {
+            native_balance: 700_000u64.into(),
+            cw20_balance: None,
+            ibc_balance: Some(coin(80_000, "ibc".to_owned())),
+            sponsor: None,
+            gas_denom: None,
+        }
+    );
+
+    // Check PARTICIPANT0 balances
+    participant_balances = app.wrap().query_all_balances(PARTICIPANT0).unwrap();
+    assert_eq!(
+        participant_balances,
+        &[coin(4_300_000, DENOM), coin(20_000, "ibc")]
+    );
 }
 
 #[test]
-fn scheduled_task_with_boundary_issue() {
+fn refill_task_cw20_fail() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
+    let _agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    // Register an agent
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        agent_addr,
-        &RegisterAgent {
-            payable_account_id: None,
-        },
-        &[],
-    )
-    .expect("Could not register agent");
+    let cw20_addr = init_cw20(&mut app);
+    support_new_cw20(
+        &mut app,
+        factory_addr.clone(),
+        &manager_addr,
+        cw20_addr.as_str(),
+    );
 
-    // Create a Once task with a Boundary that is soon
-    let task = TaskRequest {
+    let cw20 = Cw20Coin {
+        address: cw20_addr.to_string(),
+        amount: 100u64.into(),
+    };
+
+    // RefillTaskCw20Balance with wrong hash
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: "hash".to_owned(),
+                cw20: cw20.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoTaskHash {});
+
+    // Create a task
+    let task = croncat_sdk_tasks::types::TaskRequest {
         interval: Interval::Once,
-        boundary: Some(Boundary::Height(BoundaryHeight {
-            start: None,
-            end: Some((app.block_info().height + 10).into()),
-        })),
+        boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
+                to_address: "bob".to_owned(),
+                amount: coins(45, DENOM),
             }
             .into(),
-            gas_limit: Some(50_000),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
-    app.execute_contract(
-        Addr::unchecked(ANYONE),
-        tasks_addr.clone(),
-        &CreateTask {
-            task: Box::new(task),
-        },
-        &coins(50_000, DENOM),
-    )
-    .expect("Couldn't create task");
-
-    app.update_block(|block| add_seconds_to_block(block, 120));
-    app.update_block(|block| increment_block_height(block, Some(20)));
-
-    // Have agent call proxy call, and check how it went
-
-    let proxy_call_res = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr,
-        &ProxyCall { task_hash: None },
-        &[], // Attach no funds
-    );
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
 
-    // The ProxyCall with "succeed" but the task should be ended,
-    // and when it's ended, we can look for a specific Attribute to
-    // be included in the response.
-    let target_attribute = Attribute::new("lifecycle", "task_ended");
-    let has_task_ended_attr = proxy_call_res
-        .unwrap()
-        .events
-        .iter()
-        .any(|event| event.attributes.contains(&target_attribute));
-    assert!(
-        has_task_ended_attr,
-        "Did not see the lifecycle returned explaining that the task ended"
-    );
+    app.update_block(add_little_time);
 
-    // Check number of regular tasks (should be zero since our task should be ended and gone)
-    let tasks_for_agent: Vec<croncat_sdk_tasks::types::TaskInfo> = app
-        .wrap()
-        .query_wasm_smart(
-            tasks_addr,
-            &croncat_sdk_tasks::msg::TasksQueryMsg::Tasks {
-                from_index: None,
-                limit: None,
+    // RefillTaskCw20Balance with funds
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20: cw20.clone(),
             },
+            &coins(100_000, DENOM),
         )
-        .expect("Error unwrapping regular task query");
-    assert_eq!(
-        tasks_for_agent.len(),
-        0usize,
-        "Should have no regular tasks since it ended"
-    );
-}
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::RedundantFunds {});
 
-#[test]
-fn event_task_with_boundary_issue() {
+    // RefillTaskCw20Balance called by the wrong address
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT2),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20: cw20.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // RefillTaskBalance fails because PARTICIPANT0 doesn't have cw20 deposit
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20: cw20.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::EmptyBalance {});
+
+    // PARTICIPANT0 deposits some cw20 coins
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        cw20_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: manager_addr.to_string(),
+            amount: Uint128::new(555),
+            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let mut wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(555),
+        }]
+    );
+
+    // RefillTaskCw20Balance fails because the task balance doesn't have cw20
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20: cw20.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAttachedCoins {});
+
+    // Get task balance
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(manager_addr.clone(), &QueryMsg::TaskBalance { task_hash })
+        .unwrap();
+    assert_eq!(
+        task_balance.balance.unwrap(),
+        TaskBalance {
+            native_balance: 600_000u64.into(),
+            cw20_balance: None,
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        }
+    );
+
+    // PARTICIPANT0 cw20 deposit didn't change
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(555),
+        }]
+    );
+
+    // Create a task without a cw20, attach some anyway
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "bob".to_owned(),
+                amount: coins(46, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: Some(cw20.clone()),
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Sdk(croncat_sdk_manager::SdkError::NonRequiredDenom {})
+    );
+
+    // Create a task with cw20
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(46, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: WasmMsg::Execute {
+                    contract_addr: cw20_addr.clone().to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+                        contract: manager_addr.to_string(),
+                        amount: Uint128::new(55),
+                        msg: Binary::default(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: Some(90_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: Some(cw20.clone()),
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+
+    // PARTICIPANT0 spent 100 coins
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(455),
+        }]
+    );
+
+    app.update_block(add_little_time);
+
+    // Try RefillTaskCw20Balance with wrong cw20 address
+    let new_cw20_addr = init_cw20(&mut app);
+    support_new_cw20(
+        &mut app,
+        factory_addr.clone(),
+        &manager_addr,
+        new_cw20_addr.as_str(),
+    );
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        new_cw20_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: manager_addr.to_string(),
+            amount: Uint128::new(555),
+            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Check PARTICIPANT0 cw20 deposit on manager contract
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![
+            Cw20CoinVerified {
+                address: cw20_addr.to_owned(),
+                amount: Uint128::new(455),
+            },
+            Cw20CoinVerified {
+                address: new_cw20_addr.to_owned(),
+                amount: Uint128::new(555),
+            }
+        ]
+    );
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20: Cw20Coin {
+                    address: new_cw20_addr.to_string(),
+                    amount: 1u64.into(),
+                },
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAttachedCoins {});
+
+    // Pause
+    app.execute_contract(
+        Addr::unchecked(PAUSE_ADMIN),
+        manager_addr.clone(),
+        &ExecuteMsg::PauseContract {},
+        &[],
+    )
+    .unwrap();
+
+    // RefillTaskBalance when contract is paused
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20,
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::ContractPaused {});
+
+    // Get task balance
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.to_owned(),
+            &QueryMsg::TaskBalance { task_hash },
+        )
+        .unwrap();
+    assert_eq!(
+        task_balance.balance.unwrap(),
+        TaskBalance {
+            native_balance: 600_000u64.into(),
+            cw20_balance: Some(Cw20CoinVerified {
+                address: cw20_addr.to_owned(),
+                amount: 100u64.into()
+            }),
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        }
+    );
+
+    // Check that PARTICIPANT0 cw20 balance didn't change
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![
+            Cw20CoinVerified {
+                address: cw20_addr,
+                amount: Uint128::new(455),
+            },
+            Cw20CoinVerified {
+                address: new_cw20_addr,
+                amount: Uint128::new(555),
+            }
+        ]
+    );
+}
+
+#[test]
+fn refill_task_cw20_success() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let _agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    let cw20_addr = init_cw20(&mut app);
+    support_new_cw20(
+        &mut app,
+        factory_addr.clone(),
+        &manager_addr,
+        cw20_addr.as_str(),
+    );
+
+    let cw20 = Cw20Coin {
+        address: cw20_addr.to_string(),
+        amount: 100u64.into(),
+    };
+
+    // PARTICIPANT0 deposits some cw20 coins
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        cw20_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: manager_addr.to_string(),
+            amount: Uint128::new(555),
+            msg: to_binary(&ReceiveMsg::RefillTempBalance {}).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Check the deposit
+    let mut wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(555),
+        }]
+    );
+
+    // Create a task with cw20
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: "bob".to_owned(),
+                    amount: coins(45, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: WasmMsg::Execute {
+                    contract_addr: cw20_addr.clone().to_string(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+                        contract: manager_addr.to_string(),
+                        amount: Uint128::new(55),
+                        msg: Binary::default(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: Some(90_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: Some(cw20.clone()),
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+
+    // PARTICIPANT0 spent 455 coins
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(455),
+        }]
+    );
+
+    app.update_block(add_little_time);
+
+    // Refill task balance
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            manager_addr.clone(),
+            &ExecuteMsg::RefillTaskCw20Balance {
+                task_hash: task_hash.to_owned(),
+                cw20,
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Get task balance, cw20_balance increased
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.to_owned(),
+            &QueryMsg::TaskBalance { task_hash },
+        )
+        .unwrap();
+    assert_eq!(
+        task_balance.balance.unwrap(),
+        TaskBalance {
+            native_balance: 600_000u64.into(),
+            cw20_balance: Some(Cw20CoinVerified {
+                address: cw20_addr.clone(),
+                amount: 200u64.into()
+            }),
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        }
+    );
+
+    // PARTICIPANT0 balance decreased
+    wallet_balances = query_users_manager(&app, &manager_addr, PARTICIPANT0);
+    assert_eq!(
+        wallet_balances,
+        vec![Cw20CoinVerified {
+            address: cw20_addr.to_owned(),
+            amount: Uint128::new(355),
+        }]
+    );
+
+    // Check attributes
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "refill_task_cw20")
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes.iter().any(|attr| {
+            attr.key == "cw20_refilled"
+                && attr.value
+                    == Cw20CoinVerified {
+                        address: cw20_addr.clone(),
+                        amount: 100u64.into(),
+                    }
+                    .to_string()
+        })
+    }));
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes.iter().any(|attr| {
+            attr.key == "task_cw20_balance"
+                && attr.value
+                    == Cw20CoinVerified {
+                        address: cw20_addr.clone(),
+                        amount: 200u64.into(),
+                    }
+                    .to_string()
+        })
+    }));
+}
+
+#[test]
+fn scheduled_task_with_boundary_issue() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+
+    // Register an agent
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agent_addr,
+        &RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .expect("Could not register agent");
+
+    // Create a Once task with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: Some(Boundary::Height(BoundaryHeight {
+            start: None,
+            end: Some((app.block_info().height + 10).into()),
+        })),
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        tasks_addr.clone(),
+        &CreateTask {
+            task: Box::new(task),
+        },
+        &coins(50_000, DENOM),
+    )
+    .expect("Couldn't create task");
+
+    app.update_block(|block| add_seconds_to_block(block, 120));
+    app.update_block(|block| increment_block_height(block, Some(20)));
+
+    // Have agent call proxy call, and check how it went
+
+    let proxy_call_res = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ProxyCall { task_hash: None },
+        &[], // Attach no funds
+    );
+
+    // The ProxyCall with "succeed" but the task should be ended,
+    // and when it's ended, we can look for a specific Attribute to
+    // be included in the response.
+    let target_attribute = Attribute::new("lifecycle", "task_ended");
+    let has_task_ended_attr = proxy_call_res
+        .unwrap()
+        .events
+        .iter()
+        .any(|event| event.attributes.contains(&target_attribute));
+    assert!(
+        has_task_ended_attr,
+        "Did not see the lifecycle returned explaining that the task ended"
+    );
+
+    // Check number of regular tasks (should be zero since our task should be ended and gone)
+    let tasks_for_agent: Vec<croncat_sdk_tasks::types::TaskInfo> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksQueryMsg::Tasks {
+                from_index: None,
+                limit: None,
+            },
+        )
+        .expect("Error unwrapping regular task query");
+    assert_eq!(
+        tasks_for_agent.len(),
+        0usize,
+        "Should have no regular tasks since it ended"
+    );
+}
+
+#[test]
+fn event_task_with_boundary_issue() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+    let mod_balances_addr = init_mod_balances(&mut app, &factory_addr);
+
+    // Register an agent
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agent_addr,
+        &RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .expect("Could not register agent");
+
+    let queries = vec![
+        CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: mod_balances_addr.to_string(),
+            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::GetBalance {
+                address: Addr::unchecked(PARTICIPANT1).to_string(),
+                denom: DENOM.to_string(),
+            })
+            .unwrap(),
+            check_result: true,
+        }),
+        CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: mod_balances_addr.to_string(),
+            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::GetBalance {
+                address: Addr::unchecked(PARTICIPANT2).to_string(),
+                denom: DENOM.to_string(),
+            })
+            .unwrap(),
+            check_result: true,
+        }),
+    ];
+    let transforms = vec![Transform {
+        action_idx: 0,
+        query_idx: 0,
+        query_response_path: PathToValue::from(vec![ValueIndex::Key("amount".to_string())]),
+        action_path: PathToValue::from(vec![
+            ValueIndex::Key("bank".to_string()),
+            ValueIndex::Key("send".to_string()),
+            ValueIndex::Key("amount".to_string()),
+            ValueIndex::Index(0),
+            ValueIndex::Key("amount".to_string()),
+        ]),
+        operations: vec![],
+        result_type: None,
+    }];
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: Some(Boundary::Height(BoundaryHeight {
+            start: None,
+            end: Some((app.block_info().height + 10).into()),
+        })),
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: Some(queries),
+        transforms: Some(transforms),
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        tasks_addr,
+        &CreateTask {
+            task: Box::new(task),
+        },
+        &coins(500_000, DENOM),
+    )
+    .expect("Couldn't create task");
+
+    app.update_block(|block| add_seconds_to_block(block, 120));
+    app.update_block(|block| increment_block_height(block, Some(20)));
+
+    // Have agent call proxy call, and check how it went
+    let proxy_call_res = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ProxyCall { task_hash: None },
+        &[], // Attach no funds
+    );
+    assert!(
+        proxy_call_res.is_err(),
+        "Expecting proxy_call to error because task is no longer valid"
+    );
+    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
+    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
+}
+
+pub(crate) fn add_seconds_to_block(block: &mut BlockInfo, seconds: u64) {
+    block.time = block.time.plus_seconds(seconds);
+}
+pub(crate) fn increment_block_height(block: &mut BlockInfo, inc_value: Option<u64>) {
+    block.height += inc_value.unwrap_or(1);
+}
+
+/// Checks that the creation of a task with a query (that has check_result = true)
+/// and evaluates false, will not be executed by proxy call if the agent
+/// provides the task_hash.
+#[test]
+fn event_task_with_failed_check_result() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+    let boolean_addr = init_boolean(&mut app);
+
+    // Register an agent
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agent_addr,
+        &RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .expect("Could not register agent");
+
+    let queries = vec![CosmosQuery::Croncat(CroncatQuery {
+        contract_addr: boolean_addr.to_string(),
+        // Calls `get_value` on boolean contract, which defaults to false
+        msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {}).unwrap(),
+        // It's important to set this to true
+        check_result: true,
+    })];
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        // queries: None,
+        queries: Some(queries),
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        tasks_addr.clone(),
+        &CreateTask {
+            task: Box::new(task),
+        },
+        &coins(500_000, DENOM),
+    )
+    .expect("Couldn't create task");
+
+    // Agent checks to see if there are tasks for them to do.
+    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
+    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should only have one evented task"
+    );
+
+    // Have agent call proxy call (without hash), and check how it went
+    let mut proxy_call_res = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyCall { task_hash: None },
+        &[], // Attach no funds
+    );
+    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
+    // Should be no "regular" tasks, only the one evented one
+    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
+
+    // Now check that the evented one returns false and doesn't complete
+    let task_hash: String = evented_task_info[0].clone().task_hash;
+    proxy_call_res = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ProxyCall {
+            task_hash: Some(task_hash),
+        },
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        proxy_call_res.is_err(),
+        "Proxy call should fail since the check_return comes back false"
+    );
+    let proxy_call_err: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
+    assert_eq!(proxy_call_err, ContractError::TaskQueryResultFalse {});
+
+    // Check that the one tasks still exists
+    tasks_for_agent = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    evented_task_info =
+        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should still have one evented task"
+    );
+}
+
+/// Check all the different query types within a task
+#[test]
+fn event_task_with_all_query_types() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+    let balances_addr = init_mod_balances(&mut app, &factory_addr);
+    let cw20_addr = init_cw20(&mut app);
+
+    // Register an agent
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agent_addr,
+        &RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .expect("Could not register agent");
+
+    // These queries cover all the supported types of dynamic
+    let queries = vec![
+        CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: balances_addr.to_string(),
+            msg: to_binary(&BalancesQueryMsg::GetBalance {
+                address: Addr::unchecked(ANYONE).to_string(),
+                denom: DENOM.to_string(),
+            })
+            .unwrap(),
+            check_result: true,
+        }),
+        CosmosQuery::Wasm(WasmQuery::Smart {
+            contract_addr: cw20_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {}).unwrap(),
+        }),
+        CosmosQuery::Wasm(WasmQuery::Raw {
+            contract_addr: tasks_addr.to_string(),
+            key: Binary::from("config".to_string().into_bytes()),
+        }),
+        CosmosQuery::Wasm(WasmQuery::ContractInfo {
+            contract_addr: tasks_addr.to_string(),
+        }),
+    ];
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Immediate,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        // queries: None,
+        queries: Some(queries),
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        tasks_addr.clone(),
+        &CreateTask {
+            task: Box::new(task),
+        },
+        &coins(500_000, DENOM),
+    )
+    .expect("Couldn't create task");
+
+    // Agent checks to see if there are tasks for them to do.
+    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
+    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should only have one evented task"
+    );
+
+    // Have agent call proxy call (without hash), and check how it went
+    let proxy_call_res = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyCall { task_hash: None },
+        &[], // Attach no funds
+    );
+    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
+    // Should be no "regular" tasks, only the one evented one
+    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
+
+    // Now check that the evented one returns true and completez
+    let task_hash: String = evented_task_info[0].clone().task_hash;
+    let pc_result = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ProxyCall {
+            task_hash: Some(task_hash),
+        },
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        pc_result.is_ok(),
+        "Proxy call should succeed since the check_return comes back true"
+    );
+    let pc_res: AppResponse = pc_result.unwrap();
+    // we expect the queries to pass and the task to reschedule after success
+    assert!(pc_res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
+            .any(|attr| attr.key == "action" && attr.value == "continue_task")
+    }));
+
+    // Check that the one tasks still exists
+    tasks_for_agent = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    evented_task_info =
+        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should still have one evented task"
+    );
+}
+
+/// Checks that the creation of a task with an Immediate interval
+/// is able to execute multiple times when given sufficient funds
+#[test]
+fn immediate_event_task_has_multiple_executions() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+    let boolean_addr = init_boolean(&mut app);
+
+    // Set boolean contract to return true
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        boolean_addr.clone(),
+        &Toggle {},
+        &[],
+    )
+    .expect("Toggling boolean contract from false to true failed");
+
+    // Register an agent
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agent_addr,
+        &RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .expect("Could not register agent");
+
+    let queries = vec![CosmosQuery::Croncat(CroncatQuery {
+        contract_addr: boolean_addr.to_string(),
+        // Calls `get_value` on boolean contract, which defaults to false
+        msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {}).unwrap(),
+        // It's important to set this to true
+        check_result: true,
+    })];
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Immediate,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: Some(queries),
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    let _res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &CreateTask {
+                task: Box::new(task),
+            },
+            &coins(126_740, DENOM),
+        )
+        .expect("Couldn't create task");
+
+    // Agent checks to see if there are tasks for them to do.
+    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
+    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
+    assert_eq!(
+        &evented_task_info.len(),
+        &1usize,
+        "Should have one evented task"
+    );
+    let task_hash = evented_task_info[0].clone().task_hash;
+
+    let proxy_call_msg = ProxyCall {
+        task_hash: Some(task_hash),
+    };
+    // Have agent call proxy call (without hash), and check how it went
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &proxy_call_msg,
+        &[], // Attach no funds
+    )
+    .expect("First proxy call should succeed");
+
+    // Check that the one Immediate tasks still exists
+    tasks_for_agent = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    evented_task_info =
+        tasks_for_agent.expect("Error unwrapping evented task info after successful proxy_call");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should still have one evented task"
+    );
+
+    // Call ProxyCall again, expecting it to succeed
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &proxy_call_msg,
+        &[], // Attach no funds
+    )
+    .expect("Second proxy call should succeed");
+}
+
+#[test]
+fn config_invalid_percentage_updates() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let manager_addr = init_manager(&mut app, &default_instantiate_message(), &factory_addr, &[]);
+
+    // Check that agent_fee of 101 (above 100%) is invalid
+    let mut update_cfg_msg = UpdateConfig {
+        agent_fee: Some(10_001), // Above 10_000
+        treasury_fee: Some(0),
+        gas_price: Some(GasPrice {
+            numerator: 555,
+            denominator: 666,
+            gas_adjustment_numerator: 777,
+        }),
+        gas_price_source: None,
+        croncat_tasks_key: Some(("new_key_tasks".to_owned(), [0, 1])),
+        croncat_agents_key: Some(("new_key_agents".to_owned(), [0, 1])),
+        treasury_addr: Some(ANYONE.to_owned()),
+        cw20_whitelist: Some(vec!["randomcw20".to_owned()]),
+        gas_denoms: None,
+        unbonding_period: None,
+    };
+
+    let mut err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+                msg: WasmMsg::Execute {
+                    contract_addr: manager_addr.to_string(),
+                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(update_cfg_msg.clone())))
+                        .unwrap(),
+                    funds: vec![],
+                },
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidPercentage {
+            field: "agent_fee".to_string()
+        }
+    );
+
+    // Now check the same for the treasury_fee
+    update_cfg_msg.agent_fee = Some(5);
+    update_cfg_msg.treasury_fee = Some(22_222); // Above 10_000
+
+    err = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+                msg: WasmMsg::Execute {
+                    contract_addr: manager_addr.to_string(),
+                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(update_cfg_msg))).unwrap(),
+                    funds: vec![],
+                },
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidPercentage {
+            field: "treasury_fee".to_string()
+        }
+    );
+}
+
+/// Check for instantiate pause admin scenarios of pass/fail
+/// Check for pause & unpause scenarios of pass/fail
+#[test]
+fn pause_admin_cases() {
+    let mut app = default_app();
+
+    let factory_code_id = app.store_code(contracts::croncat_factory_contract());
+    let manager_code_id = app.store_code(contracts::croncat_manager_contract());
+
+    let init_msg = croncat_sdk_factory::msg::FactoryInstantiateMsg {
+        owner_addr: Some(ADMIN.to_owned()),
+    };
+    let croncat_factory_addr = app
+        .instantiate_contract(
+            factory_code_id,
+            Addr::unchecked(ADMIN),
+            &init_msg,
+            &[],
+            "factory",
+            None,
+        )
+        .unwrap();
+
+    let init_manager_contract_msg = InstantiateMsg {
+        version: Some("0.1".to_owned()),
+        croncat_tasks_key: (AGENT1.to_owned(), [0, 1]),
+        croncat_agents_key: (AGENT2.to_owned(), [0, 1]),
+        pause_admin: Addr::unchecked(PAUSE_ADMIN),
+        gas_price: Some(GasPrice {
+            numerator: 10,
+            denominator: 20,
+            gas_adjustment_numerator: 30,
+        }),
+        treasury_addr: Some(AGENT2.to_owned()),
+        cw20_whitelist: Some(vec![PARTICIPANT3.to_owned()]),
+    };
+    // Attempt to initialize with short address for pause_admin
+    let mut init_manager_contract_msg_short_addr = init_manager_contract_msg.clone();
+    init_manager_contract_msg_short_addr.pause_admin = Addr::unchecked(ANYONE);
+    // Attempt to initialize with same owner address for pause_admin
+    let mut init_manager_contract_msg_same_owner = init_manager_contract_msg.clone();
+    init_manager_contract_msg_same_owner.pause_admin = Addr::unchecked(ADMIN);
+
+    // Should fail: shorty addr
+    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
+        code_id: manager_code_id,
+        version: [0, 1],
+        commit_id: "some".to_owned(),
+        checksum: "qwe123".to_owned(),
+        changelog_url: None,
+        schema: None,
+        msg: to_binary(&init_manager_contract_msg_short_addr).unwrap(),
+        contract_name: "manager".to_owned(),
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            croncat_factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
+                kind: croncat_sdk_factory::msg::VersionKind::Manager,
+                module_instantiate_info: manager_module_instantiate_info,
+            },
+            &[get_manager_instantiate_denom_fee()],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidPauseAdmin {});
+
+    // Should fail: same as owner
+    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
+        code_id: manager_code_id,
+        version: [0, 1],
+        commit_id: "some".to_owned(),
+        checksum: "qwe123".to_owned(),
+        changelog_url: None,
+        schema: None,
+        msg: to_binary(&init_manager_contract_msg_same_owner).unwrap(),
+        contract_name: "manager".to_owned(),
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            croncat_factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
+                kind: croncat_sdk_factory::msg::VersionKind::Manager,
+                module_instantiate_info: manager_module_instantiate_info,
+            },
+            &[get_manager_instantiate_denom_fee()],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidPauseAdmin {});
+
+    // Now, we do a working furr shurr case
+    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
+        code_id: manager_code_id,
+        version: [0, 1],
+        commit_id: "some".to_owned(),
+        checksum: "qwe123".to_owned(),
+        changelog_url: None,
+        schema: None,
+        msg: to_binary(&init_manager_contract_msg).unwrap(),
+        contract_name: "manager".to_owned(),
+    };
+
+    // Successfully deploy agents contract
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        croncat_factory_addr.clone(),
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
+            kind: croncat_sdk_factory::msg::VersionKind::Manager,
+            module_instantiate_info: manager_module_instantiate_info,
+        },
+        &[get_manager_instantiate_denom_fee()],
+    )
+    .unwrap();
+
+    // Get agents contract address
+    let manager_contracts: ContractMetadataResponse = app
+        .wrap()
+        .query_wasm_smart(
+            croncat_factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryQueryMsg::LatestContract {
+                contract_name: "manager".to_string(),
+            },
+        )
+        .unwrap();
+    assert!(
+        manager_contracts.metadata.is_some(),
+        "Should be contract metadata"
+    );
+    let manager_metadata = manager_contracts.metadata.unwrap();
+    let croncat_manager_addr = manager_metadata.contract_addr;
+
+    // Owner Should not be able to pause, not pause_admin
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            croncat_manager_addr.clone(),
+            &ExecuteMsg::PauseContract {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(error, ContractError::Unauthorized {});
+    // Anyone Should not be able to pause, not pause_admin
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            croncat_manager_addr.clone(),
+            &ExecuteMsg::PauseContract {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(error, ContractError::Unauthorized {});
+
+    // Pause admin should be able to pause
+    let res = app.execute_contract(
+        Addr::unchecked(PAUSE_ADMIN),
+        croncat_manager_addr.clone(),
+        &ExecuteMsg::PauseContract {},
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Check the pause query is valid
+    let is_paused: bool = app
+        .wrap()
+        .query_wasm_smart(croncat_manager_addr.clone(), &QueryMsg::Paused {})
+        .unwrap();
+    assert!(is_paused);
+
+    // Pause Admin Should not be able to unpause
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PAUSE_ADMIN),
+            croncat_manager_addr.clone(),
+            &ExecuteMsg::UnpauseContract {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(error, ContractError::Unauthorized {});
+    // Anyone Should not be able to unpause
+    let error: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            croncat_manager_addr.clone(),
+            &ExecuteMsg::UnpauseContract {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(error, ContractError::Unauthorized {});
+
+    // Owner should be able to unpause
+    let res = app.execute_contract(
+        Addr::unchecked(ADMIN),
+        croncat_factory_addr,
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: croncat_manager_addr.to_string(),
+                msg: to_binary(&ExecuteMsg::UnpauseContract {}).unwrap(),
+                funds: vec![],
+            },
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Confirm unpaused
+    let is_paused: bool = app
+        .wrap()
+        .query_wasm_smart(croncat_manager_addr, &QueryMsg::Paused {})
+        .unwrap();
+    assert!(!is_paused);
+}
+
+#[test]
+fn last_task_execution_info_simple() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let manager_addr = init_manager(&mut app, &default_instantiate_message(), &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    // Right after instantiation, expect it to return all default vals
+    let raw_task_execution_info_res = app
+        .wrap()
+        .query_wasm_raw(manager_addr.clone(), b"last_task_execution_info".as_slice())
+        .unwrap();
+    let mut raw_task_execution_info: TaskExecutionInfo =
+        from_slice(raw_task_execution_info_res.unwrap().as_slice()).unwrap();
+    assert_eq!(
+        raw_task_execution_info,
+        TaskExecutionInfo {
+            block_height: u64::default(),
+            tx_info: None,
+            task_hash: String::default(),
+            owner_addr: Addr::unchecked(""),
+            amount_for_one_task: AmountForOneTask::default(),
+            version: String::default(),
+        },
+        "After instantiate, should have all default values"
+    );
+
+    activate_agent(&mut app, &agents_addr);
+
+    // Create a task
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "frob".to_owned(),
+                amount: coins(19, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let create_task_res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap()
+        .data
+        .unwrap();
+
+    // This contains info about the task after creation
+    let mut task_execution_info_creation: TaskExecutionInfo =
+        from_binary(&create_task_res).unwrap();
+
+    app.update_block(add_little_time);
+
+    // Proxy call
+    assert!(app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ProxyCall { task_hash: None },
+            &[],
+        )
+        .is_ok());
+
+    // Check the block height that proxy_call happened on
+    let proxy_call_height = app.block_info().height;
+
+    // Now we compare the saved value with the state key last_task_execution_info
+    raw_task_execution_info = crate::state::LAST_TASK_EXECUTION_INFO
+        .query(&app.wrap(), manager_addr)
+        .unwrap();
+
+    // We must modify the data we received upon task creation
+    // since the block height returned was for the creation time,
+    // not representing task execution that happens a block later.
+    task_execution_info_creation.block_height = proxy_call_height;
+
+    assert_eq!(
+        task_execution_info_creation,
+        TaskExecutionInfo {
+            block_height: proxy_call_height,
+            tx_info: Some(TransactionInfo { index: 0 }),
+            task_hash: raw_task_execution_info.task_hash,
+            owner_addr: raw_task_execution_info.owner_addr,
+            amount_for_one_task: raw_task_execution_info.amount_for_one_task,
+            version: raw_task_execution_info.version,
+        }
+    );
+
+    // TODO: this test is somewhat incomplete in the sense that at the time of
+    // this writing, the TransactionInfo doesn't seem to return a non-zero
+    // index. This curious behavior is captured here:
+    // https://github.com/CosmWasm/cosmwasm/issues/1651
+    // If this issue gets ironed out, please modify this test such that
+    // We perform multiple proxy_call executions in the same block and
+    // can confirm that the TransactionInfo's index increases.
+}
+
+/// Check all the different query types within a task
+#[test]
+fn proxy_batch_cases() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
-    let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
-    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
-    let mod_balances_addr = init_mod_balances(&mut app, &factory_addr);
+    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agent_addr = init_agents(&mut app, &factory_addr);
+    let balances_addr = init_mod_balances(&mut app, &factory_addr);
+    let cw20_addr = init_cw20(&mut app);
+
+    // Register an agent
+    activate_agent(&mut app, &agent_addr);
+    app.update_block(add_little_time);
+
+    // These queries cover all the supported types of dynamic
+    let queries = vec![
+        CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: balances_addr.to_string(),
+            msg: to_binary(&BalancesQueryMsg::GetBalance {
+                address: Addr::unchecked(ANYONE).to_string(),
+                denom: DENOM.to_string(),
+            })
+            .unwrap(),
+            check_result: true,
+        }),
+        CosmosQuery::Wasm(WasmQuery::Smart {
+            contract_addr: cw20_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {}).unwrap(),
+        }),
+    ];
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Immediate,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        // queries: None,
+        queries: Some(queries),
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        tasks_addr.clone(),
+        &CreateTask {
+            task: Box::new(task),
+        },
+        &coins(500_000, DENOM),
+    )
+    .expect("Couldn't create task");
+
+    app.update_block(add_little_time);
+
+    // Agent checks to see if there are tasks for them to do.
+    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
+    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should only have one evented task"
+    );
+
+    // Now check that the evented one returns true and completez
+    let task_hash: String = evented_task_info[0].clone().task_hash;
+    let pc_result = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyBatch(vec![Some(task_hash.clone())]),
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        pc_result.is_ok(),
+        "Proxy call should succeed since the check_return comes back true"
+    );
+    let pc_res: AppResponse = pc_result.unwrap();
+    // we expect the queries to pass and the task to reschedule after success
+    assert!(pc_res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
+            .any(|attr| attr.key == "action" && attr.value == "continue_task")
+    }));
+
+    // app.update_block(add_little_time);
+
+    // Check that the one tasks still exists
+    tasks_for_agent = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
+                start: None,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    evented_task_info =
+        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
+    assert_eq!(
+        evented_task_info.len(),
+        1usize,
+        "Should still have one evented task"
+    );
+
+    // Attempt many moar
+    let pc_result = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyBatch(vec![
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash.clone()),
+            Some(task_hash),
+        ]),
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        pc_result.is_ok(),
+        "Proxy call should succeed since the check_return comes back true"
+    );
+    let pc_res: AppResponse = pc_result.unwrap();
+    // we expect the queries to pass and the task to reschedule after success
+    assert!(pc_res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
+            .any(|attr| attr.key == "action" && attr.value == "continue_task")
+    }));
+
+    // Attempt none (no tasks for agent, but try anyway to sneakz)
+    let pc_result = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyBatch(vec![None]),
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        pc_result.is_err(),
+        "Proxy call should not succeed since we didnt have scheduled tasks yet"
+    );
+
+    // Create a task (queries and transforms) with a Boundary that is soon
+    let task = TaskRequest {
+        interval: Interval::Block(1),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
 
-    // Register an agent
     app.execute_contract(
-        Addr::unchecked(AGENT0),
-        agent_addr,
-        &RegisterAgent {
-            payable_account_id: None,
+        Addr::unchecked(PARTICIPANT0),
+        tasks_addr.clone(),
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+            task: Box::new(task),
         },
-        &[],
+        &coins(600_000, DENOM),
     )
-    .expect("Could not register agent");
+    .unwrap();
 
-    let queries = vec![
-        CosmosQuery::Croncat(CroncatQuery {
-            contract_addr: mod_balances_addr.to_string(),
-            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::GetBalance {
-                address: Addr::unchecked(PARTICIPANT1).to_string(),
-                denom: DENOM.to_string(),
-            })
-            .unwrap(),
-            check_result: true,
-        }),
-        CosmosQuery::Croncat(CroncatQuery {
-            contract_addr: mod_balances_addr.to_string(),
-            msg: to_binary(&croncat_mod_balances::msg::QueryMsg::GetBalance {
-                address: Addr::unchecked(PARTICIPANT2).to_string(),
-                denom: DENOM.to_string(),
-            })
-            .unwrap(),
-            check_result: true,
-        }),
-    ];
-    let transforms = vec![Transform {
-        action_idx: 0,
-        query_idx: 0,
-        query_response_path: PathToValue::from(vec![ValueIndex::Key("amount".to_string())]),
-        action_path: PathToValue::from(vec![
-            ValueIndex::Key("bank".to_string()),
-            ValueIndex::Key("send".to_string()),
-            ValueIndex::Key("amount".to_string()),
-            ValueIndex::Index(0),
-            ValueIndex::Key("amount".to_string()),
-        ]),
-    }];
+    app.update_block(add_little_time);
+
+    // Attempt none (no tasks for agent, but try anyway to sneakz)
+    let pc_result = app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyBatch(vec![None]),
+        &[], // Attach no funds ofc
+    );
+    assert!(
+        pc_result.is_ok(),
+        "Proxy call should succeed since we have scheduled task"
+    );
 
     // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Once,
+    let task1 = TaskRequest {
+        interval: Interval::Block(1),
         boundary: Some(Boundary::Height(BoundaryHeight {
             start: None,
+            end: Some((app.block_info().height + 1).into()),
+        })),
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None, // No transforms in this task
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let task2 = TaskRequest {
+        interval: Interval::Block(1),
+        boundary: Some(Boundary::Height(BoundaryHeight {
+            start: Some((app.block_info().height).into()),
             end: Some((app.block_info().height + 10).into()),
         })),
         stop_on_fail: false,
@@ -4594,1150 +6674,1901 @@ fn event_task_with_boundary_issue() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        queries: Some(queries),
-        transforms: Some(transforms),
+        queries: None,
+        transforms: None, // No transforms in this task
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
-    app.execute_contract(
-        Addr::unchecked(ANYONE),
-        tasks_addr,
-        &CreateTask {
-            task: Box::new(task),
-        },
-        &coins(500_000, DENOM),
-    )
-    .expect("Couldn't create task");
+    let tasks = vec![task1, task2];
 
-    app.update_block(|block| add_seconds_to_block(block, 120));
-    app.update_block(|block| increment_block_height(block, Some(20)));
+    for t in tasks {
+        app.execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask { task: Box::new(t) },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    }
 
-    // Have agent call proxy call, and check how it went
-    let proxy_call_res = app.execute_contract(
+    app.update_block(add_little_time);
+
+    // Attempt none (no tasks for agent, but try anyway to sneakz)
+    let pc_result = app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr,
-        &ProxyCall { task_hash: None },
-        &[], // Attach no funds
+        &ProxyBatch(vec![None, None, None]),
+        &[], // Attach no funds ofc
     );
     assert!(
-        proxy_call_res.is_err(),
-        "Expecting proxy_call to error because task is no longer valid"
+        pc_result.is_ok(),
+        "Proxy call should succeed since we have scheduled task"
     );
-    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
-    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
 }
 
-pub(crate) fn add_seconds_to_block(block: &mut BlockInfo, seconds: u64) {
-    block.time = block.time.plus_seconds(seconds);
+#[test]
+fn ibc_transfer_timeout_relative_to_execution() {
+    let env = cosmwasm_std::testing::mock_env();
+    let manager_addr = Addr::unchecked("manager");
+    let msg = crate::ibc::ibc_transfer_msg(
+        &manager_addr,
+        &env.block,
+        "channel-0",
+        "osmo1receiver",
+        &coin(100, DENOM),
+        Some(600),
+    )
+    .unwrap();
+    let CosmosMsg::Stargate { type_url, value } = msg else {
+        panic!("Expected stargate message");
+    };
+    assert_eq!(type_url, "/ibc.applications.transfer.v1.MsgTransfer");
+    assert_eq!(
+        crate::proto::decode_uint64_field(7, &value),
+        Some(env.block.time.plus_seconds(600).nanos())
+    );
+
+    // Ibc transfer can't be sent without the timeout
+    let err = crate::ibc::ibc_transfer_msg(
+        &manager_addr,
+        &env.block,
+        "channel-0",
+        "osmo1receiver",
+        &coin(100, DENOM),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TaskNoLongerValid {});
+
+    // MsgTransferResponse { sequence: 300 }
+    let mut response = vec![];
+    crate::proto::encode_uint64_field(1, 300, &mut response);
+    assert_eq!(response, vec![8, 172, 2]);
+    assert_eq!(crate::proto::decode_uint64_field(1, &response), Some(300));
 }
-pub(crate) fn increment_block_height(block: &mut BlockInfo, inc_value: Option<u64>) {
-    block.height += inc_value.unwrap_or(1);
+
+#[test]
+fn ibc_transfer_refunds() {
+    let mut deps = cosmwasm_std::testing::mock_dependencies();
+    let env = cosmwasm_std::testing::mock_env();
+    crate::state::CONFIG
+        .save(
+            deps.as_mut().storage,
+            &Config {
+                owner_addr: Addr::unchecked(ADMIN),
+                pause_admin: Addr::unchecked(PAUSE_ADMIN),
+                croncat_factory_addr: Addr::unchecked("factory"),
+                croncat_tasks_key: ("tasks".to_owned(), [0, 1]),
+                croncat_agents_key: ("agents".to_owned(), [0, 1]),
+                agent_fee: DEFAULT_FEE,
+                treasury_fee: DEFAULT_FEE,
+                gas_price: Default::default(),
+                gas_price_source: GasPriceSource::Frozen {},
+                cw20_whitelist: vec![],
+                native_denom: DENOM.to_owned(),
+                gas_denoms: vec![],
+                unbonding_period: DEFAULT_UNBONDING_PERIOD,
+                limit: 100,
+                treasury_addr: None,
+            },
+        )
+        .unwrap();
+    crate::state::TASKS_BALANCES
+        .save(
+            deps.as_mut().storage,
+            b"task_hash",
+            &TaskBalance {
+                native_balance: Uint128::new(1000),
+                cw20_balance: None,
+                ibc_balance: None,
+                sponsor: None,
+                gas_denom: None,
+            },
+        )
+        .unwrap();
+    let transfer = IbcTransfer {
+        task_hash: "task_hash".to_owned(),
+        owner_addr: Addr::unchecked(PARTICIPANT0),
+        amount: coin(100, DENOM),
+    };
+    for sequence in 1..=3 {
+        IBC_TRANSFERS
+            .save(deps.as_mut().storage, ("channel-0", sequence), &transfer)
+            .unwrap();
+    }
+
+    // Successful transfer keeps the task balance
+    crate::contract::sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: "channel-0".to_owned(),
+            sequence: 1,
+            ack: String::new(),
+            success: true,
+        }),
+    )
+    .unwrap();
+    assert!(!IBC_TRANSFERS.has(deps.as_ref().storage, ("channel-0", 1)));
+
+    // Timed out transfer gets back to the task balance
+    let res = crate::contract::sudo(
+        deps.as_mut(),
+        env.clone(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: "channel-0".to_owned(),
+            sequence: 2,
+        }),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    let task_balance = crate::state::TASKS_BALANCES
+        .load(deps.as_ref().storage, b"task_hash")
+        .unwrap();
+    assert_eq!(task_balance.native_balance, Uint128::new(1100));
+
+    // Failed transfer of the removed task goes to the owner
+    crate::state::TASKS_BALANCES.remove(deps.as_mut().storage, b"task_hash");
+    let res = crate::contract::sudo(
+        deps.as_mut(),
+        env,
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: "channel-0".to_owned(),
+            sequence: 3,
+            ack: String::new(),
+            success: false,
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: PARTICIPANT0.to_owned(),
+            amount: coins(100, DENOM),
+        })]
+    );
 }
 
-/// Checks that the creation of a task with a query (that has check_result = true)
-/// and evaluates false, will not be executed by proxy call if the agent
-/// provides the task_hash.
 #[test]
-fn event_task_with_failed_check_result() {
+fn authz_exec_encoding() {
+    let msg = crate::authz::authz_exec_msg(
+        &Addr::unchecked("manager"),
+        &Addr::unchecked("owner"),
+        &BankMsg::Send {
+            to_address: "alice".to_owned(),
+            amount: coins(100, DENOM),
+        }
+        .into(),
+    )
+    .unwrap();
+    // MsgExec { grantee, msgs: [Any { type_url, value: MsgSend { from, to, amount } }] }
+    let expected: Vec<u8> = [
+        &[10, 7][..],
+        b"manager",
+        &[18, 60, 10, 28],
+        b"/cosmos.bank.v1beta1.MsgSend",
+        &[18, 28, 10, 5],
+        b"owner",
+        &[18, 5],
+        b"alice",
+        &[26, 12, 10, 5],
+        b"TOKEN",
+        &[18, 3],
+        b"100",
+    ]
+    .concat();
+    assert_eq!(
+        msg,
+        CosmosMsg::Stargate {
+            type_url: "/cosmos.authz.v1beta1.MsgExec".to_owned(),
+            value: Binary(expected),
+        }
+    );
+
+    // Only bank sends and contract executions can be done through authz
+    let err = crate::authz::authz_exec_msg(
+        &Addr::unchecked("manager"),
+        &Addr::unchecked("owner"),
+        &WasmMsg::ClearAdmin {
+            contract_addr: "alice".to_owned(),
+        }
+        .into(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TaskNoLongerValid {});
+}
+
+#[test]
+fn sponsored_task_gas() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
-    let boolean_addr = init_boolean(&mut app);
-
-    // Register an agent
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        agent_addr,
-        &RegisterAgent {
-            payable_account_id: None,
-        },
-        &[],
-    )
-    .expect("Could not register agent");
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    let queries = vec![CosmosQuery::Croncat(CroncatQuery {
-        contract_addr: boolean_addr.to_string(),
-        // Calls `get_value` on boolean contract, which defaults to false
-        msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {}).unwrap(),
-        // It's important to set this to true
-        check_result: true,
-    })];
+    activate_agent(&mut app, &agents_addr);
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Once,
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Block(1),
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
+                to_address: "alice".to_owned(),
+                amount: coins(123, DENOM),
             }
             .into(),
-            gas_limit: Some(50_000),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        // queries: None,
-        queries: Some(queries),
-        transforms: None, // No transforms in this task
+        queries: None,
+        transforms: None,
         cw20: None,
+        max_executions: Some(2),
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
 
+    // Sponsor has to be approved by the task owner
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT2),
+            manager_addr.clone(),
+            &ExecuteMsg::SponsorTask {
+                task_hash: task_hash.clone(),
+            },
+            &coins(100_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::SponsorNotApproved {});
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT2),
+            manager_addr.clone(),
+            &ExecuteMsg::ApproveSponsor {
+                task_hash: task_hash.clone(),
+                sponsor_addr: PARTICIPANT2.to_owned(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
     app.execute_contract(
-        Addr::unchecked(ANYONE),
-        tasks_addr.clone(),
-        &CreateTask {
-            task: Box::new(task),
+        Addr::unchecked(PARTICIPANT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ApproveSponsor {
+            task_hash: task_hash.clone(),
+            sponsor_addr: PARTICIPANT2.to_owned(),
         },
-        &coins(500_000, DENOM),
+        &[],
     )
-    .expect("Couldn't create task");
+    .unwrap();
 
-    // Agent checks to see if there are tasks for them to do.
-    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
-    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+    let sponsor_balance = app.wrap().query_balance(PARTICIPANT2, DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT2),
+        manager_addr.clone(),
+        &ExecuteMsg::SponsorTask {
+            task_hash: task_hash.clone(),
+        },
+        &coins(100_000, DENOM),
+    )
+    .unwrap();
+    let sponsored_tasks: Vec<SponsoredTask> = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
+            manager_addr.clone(),
+            &QueryMsg::SponsoredTasks {
+                sponsor_addr: PARTICIPANT2.to_owned(),
                 from_index: None,
                 limit: None,
             },
         )
         .unwrap();
-    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
     assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should only have one evented task"
+        sponsored_tasks,
+        vec![SponsoredTask {
+            task_hash: task_hash.clone(),
+            gas_balance: Uint128::new(100_000),
+        }]
     );
 
-    // Have agent call proxy call (without hash), and check how it went
-    let mut proxy_call_res = app.execute_contract(
+    // Only one sponsor per task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT3),
+            manager_addr.clone(),
+            &ExecuteMsg::SponsorTask {
+                task_hash: task_hash.clone(),
+            },
+            &coins(100_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::SponsorNotApproved {});
+
+    // Gas charged from the sponsor, task balance only pays for the send
+    app.update_block(add_little_time);
+    app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
-        &ProxyCall { task_hash: None },
-        &[], // Attach no funds
-    );
-    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
-    // Should be no "regular" tasks, only the one evented one
-    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TaskBalance {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let task_balance = task_balance.balance.unwrap();
+    assert_eq!(task_balance.native_balance, Uint128::new(600_000 - 123));
+    let gas_charged = Uint128::new(100_000) - task_balance.sponsor.unwrap().gas_balance;
+    assert!(!gas_charged.is_zero());
 
-    // Now check that the evented one returns false and doesn't complete
-    let task_hash: String = evented_task_info[0].clone().task_hash;
-    proxy_call_res = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr,
-        &ProxyCall {
-            task_hash: Some(task_hash),
+    // Unused gas balance goes back to the sponsor when the owner replaces it
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ApproveSponsor {
+            task_hash,
+            sponsor_addr: PARTICIPANT3.to_owned(),
         },
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        proxy_call_res.is_err(),
-        "Proxy call should fail since the check_return comes back false"
+        &[],
+    )
+    .unwrap();
+    let new_sponsor_balance = app.wrap().query_balance(PARTICIPANT2, DENOM).unwrap();
+    assert_eq!(
+        new_sponsor_balance.amount,
+        sponsor_balance.amount - gas_charged
     );
-    let proxy_call_err: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
-    assert_eq!(proxy_call_err, ContractError::TaskQueryResultFalse {});
-
-    // Check that the one tasks still exists
-    tasks_for_agent = app
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    // Task ended, sponsor is taken off
+    let sponsored_tasks: Vec<SponsoredTask> = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr,
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
+            manager_addr,
+            &QueryMsg::SponsoredTasks {
+                sponsor_addr: PARTICIPANT3.to_owned(),
                 from_index: None,
                 limit: None,
             },
         )
         .unwrap();
-    evented_task_info =
-        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
+    assert!(sponsored_tasks.is_empty());
+}
+
+#[test]
+fn dynamic_gas_price_from_oracle() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    activate_agent(&mut app, &agents_addr);
+
+    let code_id = app.store_code(contracts::gas_price_oracle_contract());
+    let oracle_addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &Decimal::percent(5),
+            &[],
+            "gas_price_oracle",
+            None,
+        )
+        .unwrap();
+    let update_gas_price_source = |app: &mut App, min_price, tolerance| {
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            factory_addr.clone(),
+            &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+                msg: WasmMsg::Execute {
+                    contract_addr: manager_addr.to_string(),
+                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(UpdateConfig {
+                        agent_fee: None,
+                        treasury_fee: None,
+                        gas_price: None,
+                        gas_price_source: Some(GasPriceSource::Oracle {
+                            contract_addr: oracle_addr.to_string(),
+                            limits: GasPriceLimits {
+                                min_price,
+                                max_price: Decimal::percent(10),
+                                tolerance,
+                            },
+                        }),
+                        croncat_tasks_key: None,
+                        croncat_agents_key: None,
+                        treasury_addr: None,
+                        cw20_whitelist: None,
+                        gas_denoms: None,
+                        unbonding_period: None,
+                    })))
+                    .unwrap(),
+                    funds: vec![],
+                },
+            },
+            &[],
+        )
+    };
+
+    // Task can't agree to pay less than frozen gas price
+    let err: ContractError = update_gas_price_source(&mut app, Decimal::percent(1), 90)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidGasPrice {});
+    update_gas_price_source(&mut app, Decimal::percent(1), 150).unwrap();
+
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Block(1),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(123, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(task_data.amount_for_one_task.gas_price_tolerance, Some(150));
+    let task_hash = task_data.task_hash;
+    let gas_with_fees = task_data
+        .amount_for_one_task
+        .gas_with_fees(task_data.amount_for_one_task.gas)
+        .unwrap();
+    let last_execution = |app: &App| {
+        let history: Vec<TaskExecutionRecord> = app
+            .wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &QueryMsg::TaskExecutionHistory {
+                    task_hash: task_hash.clone(),
+                    from_index: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        history[0].clone()
+    };
+
+    // Oracle gas price is paid instead of the frozen one
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let expected = GasPrice {
+        numerator: 5,
+        denominator: 100,
+        gas_adjustment_numerator: 150,
+    }
+    .calculate(gas_with_fees)
+    .unwrap();
+    assert_eq!(last_execution(&app).gas_charged, coin(expected, DENOM));
+
+    // Gas price capped by the max price and the tolerance of the task
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        oracle_addr.clone(),
+        &Decimal::percent(50),
+        &[],
+    )
+    .unwrap();
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let expected = GasPrice {
+        numerator: 6,
+        denominator: 100,
+        gas_adjustment_numerator: 150,
+    }
+    .calculate(gas_with_fees)
+    .unwrap();
+    assert_eq!(last_execution(&app).gas_charged, coin(expected, DENOM));
+
+    // Task ends once the minimum price is above what it agreed to pay
+    update_gas_price_source(&mut app, Decimal::percent(7), 150).unwrap();
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
     assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should still have one evented task"
+        last_execution(&app).end_reason,
+        Some(TaskEndReason::GasPriceTooLow)
     );
+    let task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(manager_addr, &QueryMsg::TaskBalance { task_hash })
+        .unwrap();
+    assert!(task_balance.balance.is_none());
 }
 
-/// Check all the different query types within a task
 #[test]
-fn event_task_with_all_query_types() {
+fn agent_auto_payout() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
-    let balances_addr = init_mod_balances(&mut app, &factory_addr);
-    let cw20_addr = init_cw20(&mut app);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    // Register an agent
+    // Payout in cw20 requires the treasury
+    let err: croncat_agents::error::ContractError = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            agents_addr.clone(),
+            &RegisterAgent {
+                payable_account_id: Some(AGENT_BENEFICIARY.to_owned()),
+                payout: Some(AgentPayout {
+                    thresholds: coins(1, DENOM),
+                    cw20_addr: Some(Addr::unchecked(PARTICIPANT6)),
+                }),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        croncat_agents::error::ContractError::UnsupportedPayoutCw20 {
+            cw20_addr: PARTICIPANT6.to_owned()
+        }
+    );
+
+    // Threshold of the other denom isn't reached by the rewards of the task
     app.execute_contract(
         Addr::unchecked(AGENT0),
-        agent_addr,
+        agents_addr.clone(),
         &RegisterAgent {
-            payable_account_id: None,
+            payable_account_id: Some(AGENT_BENEFICIARY.to_owned()),
+            payout: Some(AgentPayout {
+                thresholds: coins(1, "ibc"),
+                cw20_addr: None,
+            }),
         },
         &[],
     )
-    .expect("Could not register agent");
-
-    // These queries cover all the supported types of dynamic
-    let queries = vec![
-        CosmosQuery::Croncat(CroncatQuery {
-            contract_addr: balances_addr.to_string(),
-            msg: to_binary(&BalancesQueryMsg::GetBalance {
-                address: Addr::unchecked(ANYONE).to_string(),
-                denom: DENOM.to_string(),
-            })
-            .unwrap(),
-            check_result: true,
-        }),
-        CosmosQuery::Wasm(WasmQuery::Smart {
-            contract_addr: cw20_addr.to_string(),
-            msg: to_binary(&Cw20QueryMsg::TokenInfo {}).unwrap(),
-        }),
-        CosmosQuery::Wasm(WasmQuery::Raw {
-            contract_addr: tasks_addr.to_string(),
-            key: Binary::from("config".to_string().into_bytes()),
-        }),
-        CosmosQuery::Wasm(WasmQuery::ContractInfo {
-            contract_addr: tasks_addr.to_string(),
-        }),
-    ];
+    .unwrap();
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Immediate,
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
+                to_address: "bob".to_owned(),
+                amount: coins(45, DENOM),
             }
             .into(),
-            gas_limit: Some(50_000),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        // queries: None,
-        queries: Some(queries),
-        transforms: None, // No transforms in this task
+        queries: None,
+        transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
-
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
+            },
+            &coins(600_000, DENOM),
+        )
+        .unwrap();
     app.execute_contract(
-        Addr::unchecked(ANYONE),
+        Addr::unchecked(PARTICIPANT1),
         tasks_addr.clone(),
-        &CreateTask {
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
             task: Box::new(task),
         },
-        &coins(500_000, DENOM),
+        &coins(600_000, DENOM),
     )
-    .expect("Couldn't create task");
-
-    // Agent checks to see if there are tasks for them to do.
-    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
-    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
+    .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_response: TaskResponse = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
-                from_index: None,
-                limit: None,
+            tasks_addr,
+            &croncat_tasks::msg::QueryMsg::Task {
+                task_hash: task_data.task_hash,
             },
         )
         .unwrap();
-    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
-    assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should only have one evented task"
-    );
+    let gas_needed = task_response.task.unwrap().amount_for_one_task.gas as f64 * 1.5;
 
-    // Have agent call proxy call (without hash), and check how it went
-    let proxy_call_res = app.execute_contract(
+    app.update_block(add_little_time);
+
+    let beneficiary_balance = app.wrap().query_balance(AGENT_BENEFICIARY, DENOM).unwrap();
+    app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
-        &ProxyCall { task_hash: None },
-        &[], // Attach no funds
-    );
-    let contract_error: ContractError = proxy_call_res.unwrap_err().downcast().unwrap();
-    // Should be no "regular" tasks, only the one evented one
-    assert_eq!(contract_error, ContractError::NoTaskForAgent {});
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let gas_fees = gas_needed * DEFAULT_FEE as f64 / 100.0;
+    let amount_for_task = gas_needed * 0.04;
+    let amount_for_fees = gas_fees * 0.04;
+    let expected_agent_reward = (amount_for_task + amount_for_fees) as u128;
+    let agent_reward: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(agent_reward, Uint128::new(expected_agent_reward));
 
-    // Now check that the evented one returns true and completez
-    let task_hash: String = evented_task_info[0].clone().task_hash;
-    let pc_result = app.execute_contract(
+    app.execute_contract(
         Addr::unchecked(AGENT0),
-        manager_addr,
-        &ProxyCall {
-            task_hash: Some(task_hash),
+        agents_addr,
+        &croncat_sdk_agents::msg::ExecuteMsg::UpdateAgent {
+            payable_account_id: AGENT_BENEFICIARY.to_owned(),
+            payout: Some(AgentPayout {
+                thresholds: coins(1, DENOM),
+                cw20_addr: None,
+            }),
         },
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        pc_result.is_ok(),
-        "Proxy call should succeed since the check_return comes back true"
-    );
-    let pc_res: AppResponse = pc_result.unwrap();
-    // we expect the queries to pass and the task to reschedule after success
-    assert!(pc_res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
-            .any(|attr| attr.key == "action" && attr.value == "continue_task")
-    }));
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
 
-    // Check that the one tasks still exists
-    tasks_for_agent = app
+    // Rewards crossed the threshold and got paid out in the same proxy call
+    let new_beneficiary_balance = app.wrap().query_balance(AGENT_BENEFICIARY, DENOM).unwrap();
+    assert_eq!(
+        new_beneficiary_balance.amount,
+        beneficiary_balance.amount + Uint128::new(expected_agent_reward * 2)
+    );
+    let agent_reward: Uint128 = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr,
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
-                from_index: None,
-                limit: None,
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
-    evented_task_info =
-        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
-    assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should still have one evented task"
-    );
+    assert!(agent_reward.is_zero());
+    // Successful payout leaves nothing in the queue
+    let payout_queue = app
+        .wrap()
+        .query_wasm_raw(manager_addr, crate::state::PAYOUT_QUEUE.as_slice())
+        .unwrap();
+    assert!(payout_queue.is_none());
 }
 
-/// Checks that the creation of a task with an Immediate interval
-/// is able to execute multiple times when given sufficient funds
 #[test]
-fn immediate_event_task_has_multiple_executions() {
+fn failed_agent_payout_restores_rewards() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
-    let boolean_addr = init_boolean(&mut app);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let cw20_addr = init_cw20(&mut app);
 
-    // Set boolean contract to return true
+    let code_id = app.store_code(contracts::failing_treasury_contract());
+    let treasury_addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &cosmwasm_std::Empty {},
+            &[],
+            "treasury",
+            None,
+        )
+        .unwrap();
     app.execute_contract(
-        Addr::unchecked(ANYONE),
-        boolean_addr.clone(),
-        &Toggle {},
+        Addr::unchecked(ADMIN),
+        factory_addr,
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: manager_addr.to_string(),
+                msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(UpdateConfig {
+                    agent_fee: None,
+                    treasury_fee: None,
+                    gas_price: None,
+                    gas_price_source: None,
+                    croncat_tasks_key: None,
+                    croncat_agents_key: None,
+                    treasury_addr: Some(treasury_addr.to_string()),
+                    cw20_whitelist: Some(vec![cw20_addr.to_string()]),
+                    gas_denoms: None,
+                    unbonding_period: None,
+                })))
+                .unwrap(),
+                funds: vec![],
+            },
+        },
         &[],
     )
-    .expect("Toggling boolean contract from false to true failed");
+    .unwrap();
 
-    // Register an agent
     app.execute_contract(
         Addr::unchecked(AGENT0),
-        agent_addr,
+        agents_addr,
         &RegisterAgent {
-            payable_account_id: None,
+            payable_account_id: Some(AGENT_BENEFICIARY.to_owned()),
+            payout: Some(AgentPayout {
+                thresholds: coins(1, DENOM),
+                cw20_addr: Some(cw20_addr),
+            }),
         },
         &[],
     )
-    .expect("Could not register agent");
-
-    let queries = vec![CosmosQuery::Croncat(CroncatQuery {
-        contract_addr: boolean_addr.to_string(),
-        // Calls `get_value` on boolean contract, which defaults to false
-        msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {}).unwrap(),
-        // It's important to set this to true
-        check_result: true,
-    })];
+    .unwrap();
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Immediate,
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
+                to_address: "bob".to_owned(),
+                amount: coins(45, DENOM),
             }
             .into(),
-            gas_limit: Some(50_000),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        queries: Some(queries),
-        transforms: None, // No transforms in this task
+        queries: None,
+        transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        tasks_addr,
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+            task: Box::new(task),
+        },
+        &coins(600_000, DENOM),
+    )
+    .unwrap();
+    app.update_block(add_little_time);
 
-    let _res = app
+    // Failed payout doesn't revert the task execution
+    let beneficiary_balance = app.wrap().query_balance(AGENT_BENEFICIARY, DENOM).unwrap();
+    let res = app
         .execute_contract(
-            Addr::unchecked(ANYONE),
-            tasks_addr.clone(),
-            &CreateTask {
-                task: Box::new(task),
-            },
-            &coins(126_740, DENOM),
-        )
-        .expect("Couldn't create task");
-
-    // Agent checks to see if there are tasks for them to do.
-    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
-    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
-        .wrap()
-        .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
-                from_index: None,
-                limit: None,
-            },
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
         )
         .unwrap();
-    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
-    assert_eq!(
-        &evented_task_info.len(),
-        &1usize,
-        "Should have one evented task"
-    );
-    let task_hash = evented_task_info[0].clone().task_hash;
-
-    let proxy_call_msg = ProxyCall {
-        task_hash: Some(task_hash),
-    };
-    // Have agent call proxy call (without hash), and check how it went
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &proxy_call_msg,
-        &[], // Attach no funds
-    )
-    .expect("First proxy call should succeed");
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "payout_failure")));
 
-    // Check that the one Immediate tasks still exists
-    tasks_for_agent = app
+    // Rewards stay with the agent
+    let new_beneficiary_balance = app.wrap().query_balance(AGENT_BENEFICIARY, DENOM).unwrap();
+    assert_eq!(new_beneficiary_balance, beneficiary_balance);
+    let agent_reward: Uint128 = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr,
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
-                from_index: None,
-                limit: None,
+            manager_addr,
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
             },
         )
         .unwrap();
-    evented_task_info =
-        tasks_for_agent.expect("Error unwrapping evented task info after successful proxy_call");
-    assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should still have one evented task"
-    );
-
-    // Call ProxyCall again, expecting it to succeed
-    app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr,
-        &proxy_call_msg,
-        &[], // Attach no funds
-    )
-    .expect("Second proxy call should succeed");
+    assert!(!agent_reward.is_zero());
 }
 
 #[test]
-fn config_invalid_percentage_updates() {
+fn task_pays_gas_in_whitelisted_denom() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
-    let manager_addr = init_manager(&mut app, &default_instantiate_message(), &factory_addr, &[]);
-
-    // Check that agent_fee of 101 (above 100%) is invalid
-    let mut update_cfg_msg = UpdateConfig {
-        agent_fee: Some(10_001), // Above 10_000
-        treasury_fee: Some(0),
-        gas_price: Some(GasPrice {
-            numerator: 555,
-            denominator: 666,
-            gas_adjustment_numerator: 777,
-        }),
-        croncat_tasks_key: Some(("new_key_tasks".to_owned(), [0, 1])),
-        croncat_agents_key: Some(("new_key_agents".to_owned(), [0, 1])),
-        treasury_addr: Some(ANYONE.to_owned()),
-        cw20_whitelist: Some(vec!["randomcw20".to_owned()]),
-    };
 
-    let mut err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(ADMIN),
-            factory_addr.clone(),
-            &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
-                msg: WasmMsg::Execute {
-                    contract_addr: manager_addr.to_string(),
-                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(update_cfg_msg.clone())))
-                        .unwrap(),
-                    funds: vec![],
-                },
-            },
-            &[],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(
-        err,
-        ContractError::InvalidPercentage {
-            field: "agent_fee".to_string()
-        }
-    );
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    // Now check the same for the treasury_fee
-    update_cfg_msg.agent_fee = Some(5);
-    update_cfg_msg.treasury_fee = Some(22_222); // Above 10_000
+    activate_agent(&mut app, &agents_addr);
 
-    err = app
-        .execute_contract(
+    let gas_denom = "ibc/gas";
+    app.sudo(
+        BankSudo::Mint {
+            to_address: PARTICIPANT0.to_owned(),
+            amount: coins(1_000_000, gas_denom),
+        }
+        .into(),
+    )
+    .unwrap();
+    let gas_price = GasPrice {
+        numerator: 1,
+        denominator: 100,
+        gas_adjustment_numerator: 150,
+    };
+    let update_gas_denoms = |app: &mut App, gas_denoms| {
+        app.execute_contract(
             Addr::unchecked(ADMIN),
             factory_addr.clone(),
             &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
                 msg: WasmMsg::Execute {
                     contract_addr: manager_addr.to_string(),
-                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(update_cfg_msg))).unwrap(),
+                    msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(UpdateConfig {
+                        agent_fee: None,
+                        treasury_fee: None,
+                        gas_price: None,
+                        gas_price_source: None,
+                        croncat_tasks_key: None,
+                        croncat_agents_key: None,
+                        treasury_addr: None,
+                        cw20_whitelist: None,
+                        gas_denoms: Some(gas_denoms),
+                        unbonding_period: None,
+                    })))
+                    .unwrap(),
                     funds: vec![],
                 },
             },
             &[],
         )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(
-        err,
-        ContractError::InvalidPercentage {
-            field: "treasury_fee".to_string()
-        }
-    );
-}
-
-/// Check for instantiate pause admin scenarios of pass/fail
-/// Check for pause & unpause scenarios of pass/fail
-#[test]
-fn pause_admin_cases() {
-    let mut app = default_app();
-
-    let factory_code_id = app.store_code(contracts::croncat_factory_contract());
-    let manager_code_id = app.store_code(contracts::croncat_manager_contract());
-
-    let init_msg = croncat_sdk_factory::msg::FactoryInstantiateMsg {
-        owner_addr: Some(ADMIN.to_owned()),
-    };
-    let croncat_factory_addr = app
-        .instantiate_contract(
-            factory_code_id,
-            Addr::unchecked(ADMIN),
-            &init_msg,
-            &[],
-            "factory",
-            None,
-        )
-        .unwrap();
-
-    let init_manager_contract_msg = InstantiateMsg {
-        version: Some("0.1".to_owned()),
-        croncat_tasks_key: (AGENT1.to_owned(), [0, 1]),
-        croncat_agents_key: (AGENT2.to_owned(), [0, 1]),
-        pause_admin: Addr::unchecked(PAUSE_ADMIN),
-        gas_price: Some(GasPrice {
-            numerator: 10,
-            denominator: 20,
-            gas_adjustment_numerator: 30,
-        }),
-        treasury_addr: Some(AGENT2.to_owned()),
-        cw20_whitelist: Some(vec![PARTICIPANT3.to_owned()]),
     };
-    // Attempt to initialize with short address for pause_admin
-    let mut init_manager_contract_msg_short_addr = init_manager_contract_msg.clone();
-    init_manager_contract_msg_short_addr.pause_admin = Addr::unchecked(ANYONE);
-    // Attempt to initialize with same owner address for pause_admin
-    let mut init_manager_contract_msg_same_owner = init_manager_contract_msg.clone();
-    init_manager_contract_msg_same_owner.pause_admin = Addr::unchecked(ADMIN);
 
-    // Should fail: shorty addr
-    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
-        code_id: manager_code_id,
-        version: [0, 1],
-        commit_id: "some".to_owned(),
-        checksum: "qwe123".to_owned(),
-        changelog_url: None,
-        schema: None,
-        msg: to_binary(&init_manager_contract_msg_short_addr).unwrap(),
-        contract_name: "manager".to_owned(),
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "bob".to_owned(),
+                amount: coins(45, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: Some(gas_denom.to_owned()),
+        query_rule: None,
     };
-    let err: ContractError = app
-        .execute_contract(
-            Addr::unchecked(ADMIN),
-            croncat_factory_addr.clone(),
-            &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
-                kind: croncat_sdk_factory::msg::VersionKind::Manager,
-                module_instantiate_info: manager_module_instantiate_info,
-            },
-            &[get_manager_instantiate_denom_fee()],
-        )
-        .unwrap_err()
-        .downcast()
-        .unwrap();
-    assert_eq!(err, ContractError::InvalidPauseAdmin {});
+    let attach_funds = vec![coin(45, DENOM), coin(100_000, gas_denom)];
 
-    // Should fail: same as owner
-    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
-        code_id: manager_code_id,
-        version: [0, 1],
-        commit_id: "some".to_owned(),
-        checksum: "qwe123".to_owned(),
-        changelog_url: None,
-        schema: None,
-        msg: to_binary(&init_manager_contract_msg_same_owner).unwrap(),
-        contract_name: "manager".to_owned(),
-    };
-    let err: ContractError = app
+    // Denom has to be whitelisted first
+    let err: croncat_tasks::ContractError = app
         .execute_contract(
-            Addr::unchecked(ADMIN),
-            croncat_factory_addr.clone(),
-            &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
-                kind: croncat_sdk_factory::msg::VersionKind::Manager,
-                module_instantiate_info: manager_module_instantiate_info,
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
             },
-            &[get_manager_instantiate_denom_fee()],
+            &attach_funds,
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(err, ContractError::InvalidPauseAdmin {});
-
-    // Now, we do a working furr shurr case
-    let manager_module_instantiate_info = croncat_sdk_factory::msg::ModuleInstantiateInfo {
-        code_id: manager_code_id,
-        version: [0, 1],
-        commit_id: "some".to_owned(),
-        checksum: "qwe123".to_owned(),
-        changelog_url: None,
-        schema: None,
-        msg: to_binary(&init_manager_contract_msg).unwrap(),
-        contract_name: "manager".to_owned(),
-    };
+    assert_eq!(
+        err,
+        croncat_tasks::ContractError::UnsupportedGasDenom {
+            denom: gas_denom.to_owned()
+        }
+    );
 
-    // Successfully deploy agents contract
-    app.execute_contract(
-        Addr::unchecked(ADMIN),
-        croncat_factory_addr.clone(),
-        &croncat_sdk_factory::msg::FactoryExecuteMsg::Deploy {
-            kind: croncat_sdk_factory::msg::VersionKind::Manager,
-            module_instantiate_info: manager_module_instantiate_info,
-        },
-        &[get_manager_instantiate_denom_fee()],
+    // Native denom can't be in the list
+    let err: ContractError = update_gas_denoms(
+        &mut app,
+        vec![GasDenom {
+            denom: DENOM.to_owned(),
+            gas_price: gas_price.clone(),
+        }],
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidGasDenoms {});
+    update_gas_denoms(
+        &mut app,
+        vec![GasDenom {
+            denom: gas_denom.to_owned(),
+            gas_price: gas_price.clone(),
+        }],
     )
     .unwrap();
 
-    // Get agents contract address
-    let manager_contracts: ContractMetadataResponse = app
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &attach_funds,
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        task_data.amount_for_one_task.gas_denom,
+        Some(gas_denom.to_owned())
+    );
+    assert_eq!(task_data.amount_for_one_task.gas_price, gas_price);
+    let task_balance: TaskBalanceResponse = app
         .wrap()
         .query_wasm_smart(
-            croncat_factory_addr.clone(),
-            &croncat_sdk_factory::msg::FactoryQueryMsg::LatestContract {
-                contract_name: "manager".to_string(),
+            manager_addr.clone(),
+            &QueryMsg::TaskBalance {
+                task_hash: task_data.task_hash,
             },
         )
         .unwrap();
-    assert!(
-        manager_contracts.metadata.is_some(),
-        "Should be contract metadata"
+    assert_eq!(
+        task_balance.balance,
+        Some(TaskBalance {
+            native_balance: Uint128::new(100_000),
+            cw20_balance: None,
+            ibc_balance: Some(coin(45, DENOM)),
+            sponsor: None,
+            gas_denom: Some(gas_denom.to_owned()),
+        })
     );
-    let manager_metadata = manager_contracts.metadata.unwrap();
-    let croncat_manager_addr = manager_metadata.contract_addr;
 
-    // Owner Should not be able to pause, not pause_admin
-    let error: ContractError = app
-        .execute_contract(
-            Addr::unchecked(ADMIN),
-            croncat_manager_addr.clone(),
-            &ExecuteMsg::PauseContract {},
-            &[],
+    app.update_block(add_little_time);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+
+    // Rewards are tracked in the gas denom of the task
+    let gas = task_data.amount_for_one_task.gas;
+    let gas_fee = gas * DEFAULT_FEE as u64 / 100;
+    let expected_agent_reward = gas_price.calculate(gas + gas_fee).unwrap();
+    let expected_treasury_reward = gas_price.calculate(gas_fee).unwrap();
+    let agent_reward: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: Some(gas_denom.to_owned()),
+            },
         )
-        .unwrap_err()
-        .downcast()
         .unwrap();
-    assert_eq!(error, ContractError::Unauthorized {});
-    // Anyone Should not be able to pause, not pause_admin
-    let error: ContractError = app
-        .execute_contract(
-            Addr::unchecked(ANYONE),
-            croncat_manager_addr.clone(),
-            &ExecuteMsg::PauseContract {},
-            &[],
+    assert_eq!(agent_reward, Uint128::new(expected_agent_reward));
+    let agent_native_reward: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::AgentRewards {
+                agent_id: AGENT0.to_owned(),
+                denom: None,
+            },
         )
-        .unwrap_err()
-        .downcast()
         .unwrap();
-    assert_eq!(error, ContractError::Unauthorized {});
+    assert!(agent_native_reward.is_zero());
+    let treasury_balance: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &QueryMsg::TreasuryBalance {
+                denom: Some(gas_denom.to_owned()),
+            },
+        )
+        .unwrap();
+    assert_eq!(treasury_balance, Uint128::new(expected_treasury_reward));
 
-    // Pause admin should be able to pause
-    let res = app.execute_contract(
-        Addr::unchecked(PAUSE_ADMIN),
-        croncat_manager_addr.clone(),
-        &ExecuteMsg::PauseContract {},
-        &[],
+    // Unused gas balance refunded in the gas denom
+    let owner_balance = app.wrap().query_balance(PARTICIPANT0, gas_denom).unwrap();
+    let gas_with_fees = task_data.amount_for_one_task.gas_with_fees(gas).unwrap();
+    assert_eq!(
+        owner_balance.amount,
+        Uint128::new(1_000_000 - gas_price.calculate(gas_with_fees).unwrap())
     );
-    assert!(res.is_ok());
 
-    // Check the pause query is valid
-    let is_paused: bool = app
-        .wrap()
-        .query_wasm_smart(croncat_manager_addr.clone(), &QueryMsg::Paused {})
-        .unwrap();
-    assert!(is_paused);
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ExecuteMsg::AgentWithdraw(None),
+        &[],
+    )
+    .unwrap();
+    let agent_balance = app.wrap().query_balance(AGENT0, gas_denom).unwrap();
+    assert_eq!(agent_balance.amount, Uint128::new(expected_agent_reward));
+}
 
-    // Pause Admin Should not be able to unpause
-    let error: ContractError = app
+#[test]
+fn conditional_actions() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let boolean_addr = init_boolean(&mut app);
+
+    activate_agent(&mut app, &agents_addr);
+
+    // Boolean contract value defaults to false
+    let queries = vec![CosmosQuery::Wasm(WasmQuery::Smart {
+        contract_addr: boolean_addr.to_string(),
+        msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {}).unwrap(),
+    })];
+    let value_is = |value: bool| ActionCondition {
+        query_idx: 0,
+        query_response_path: PathToValue(vec![ValueIndex::Key("result".to_owned())]),
+        ordering: ValueOrdering::Equal,
+        value: to_binary(&value).unwrap(),
+    };
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: PARTICIPANT1.to_owned(),
+                    amount: coins(5, DENOM),
+                }
+                .into(),
+                gas_limit: Some(50_000),
+                authz: false,
+                condition: Some(value_is(true)),
+                ibc_timeout: None,
+            },
+            Action {
+                msg: BankMsg::Send {
+                    to_address: PARTICIPANT2.to_owned(),
+                    amount: coins(7, DENOM),
+                }
+                .into(),
+                gas_limit: Some(60_000),
+                authz: false,
+                condition: Some(value_is(false)),
+                ibc_timeout: None,
+            },
+        ],
+        queries: Some(queries),
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+
+    // Condition has to point to the existing query
+    let err: croncat_tasks::ContractError = app
         .execute_contract(
-            Addr::unchecked(PAUSE_ADMIN),
-            croncat_manager_addr.clone(),
-            &ExecuteMsg::UnpauseContract {},
-            &[],
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        condition: Some(ActionCondition {
+                            query_idx: 1,
+                            ..value_is(true)
+                        }),
+                        ..task.actions[0].clone()
+                    }],
+                    ..task.clone()
+                }),
+            },
+            &coins(500_000, DENOM),
         )
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(error, ContractError::Unauthorized {});
-    // Anyone Should not be able to unpause
-    let error: ContractError = app
+    assert_eq!(err, croncat_tasks::ContractError::InvalidCondition {});
+
+    let res = app
         .execute_contract(
-            Addr::unchecked(ANYONE),
-            croncat_manager_addr.clone(),
-            &ExecuteMsg::UnpauseContract {},
-            &[],
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr,
+            &CreateTask {
+                task: Box::new(task),
+            },
+            &coins(500_000, DENOM),
         )
-        .unwrap_err()
-        .downcast()
         .unwrap();
-    assert_eq!(error, ContractError::Unauthorized {});
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
 
-    // Owner should be able to unpause
-    let res = app.execute_contract(
-        Addr::unchecked(ADMIN),
-        croncat_factory_addr,
-        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
-            msg: WasmMsg::Execute {
-                contract_addr: croncat_manager_addr.to_string(),
-                msg: to_binary(&ExecuteMsg::UnpauseContract {}).unwrap(),
-                funds: vec![],
-            },
+    let participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    let participant2_balance = app.wrap().query_balance(PARTICIPANT2, DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ProxyCall {
+            task_hash: Some(task_data.task_hash.clone()),
         },
         &[],
-    );
-    assert!(res.is_ok());
-
-    // Confirm unpaused
-    let is_paused: bool = app
-        .wrap()
-        .query_wasm_smart(croncat_manager_addr, &QueryMsg::Paused {})
-        .unwrap();
-    assert!(!is_paused);
-}
+    )
+    .unwrap();
 
-#[test]
-fn last_task_execution_info_simple() {
-    let mut app = default_app();
-    let factory_addr = init_factory(&mut app);
-    let manager_addr = init_manager(&mut app, &default_instantiate_message(), &factory_addr, &[]);
-    let agents_addr = init_agents(&mut app, &factory_addr);
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    // Only the action with met condition executed
+    let new_participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    let new_participant2_balance = app.wrap().query_balance(PARTICIPANT2, DENOM).unwrap();
+    assert_eq!(new_participant1_balance, participant1_balance);
+    assert_eq!(
+        new_participant2_balance.amount,
+        participant2_balance.amount + Uint128::new(7)
+    );
 
-    // Right after instantiation, expect it to return all default vals
-    let raw_task_execution_info_res = app
+    // Gas of the skipped action is not charged
+    let history: Vec<TaskExecutionRecord> = app
         .wrap()
-        .query_wasm_raw(manager_addr.clone(), b"last_task_execution_info".as_slice())
-        .unwrap();
-    let mut raw_task_execution_info: TaskExecutionInfo =
-        from_slice(raw_task_execution_info_res.unwrap().as_slice()).unwrap();
-    assert_eq!(
-        raw_task_execution_info,
-        TaskExecutionInfo {
-            block_height: u64::default(),
-            tx_info: None,
-            task_hash: String::default(),
-            owner_addr: Addr::unchecked(""),
-            amount_for_one_task: AmountForOneTask::default(),
-            version: String::default(),
-        },
-        "After instantiate, should have all default values"
+        .query_wasm_smart(
+            manager_addr,
+            &QueryMsg::TaskExecutionHistory {
+                task_hash: task_data.task_hash,
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let gas_with_fees = task_data
+        .amount_for_one_task
+        .gas_with_fees(task_data.amount_for_one_task.gas - 50_000)
+        .unwrap();
+    let expected_gas_charged = task_data
+        .amount_for_one_task
+        .gas_price
+        .calculate(gas_with_fees)
+        .unwrap();
+    assert_eq!(history[0].gas_charged, coin(expected_gas_charged, DENOM));
+    assert_eq!(
+        history[0].action_results,
+        vec![ActionResult::Skipped, ActionResult::Success]
     );
+}
+
+#[test]
+fn evented_task_with_query_rule() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let proposal_passed_addr = init_boolean(&mut app);
+    let deadline_reached_addr = init_boolean(&mut app);
 
     activate_agent(&mut app, &agents_addr);
 
-    // Create a task
+    // Both values default to false
+    let queries = [&proposal_passed_addr, &deadline_reached_addr]
+        .into_iter()
+        .map(|boolean_addr| {
+            CosmosQuery::Croncat(CroncatQuery {
+                contract_addr: boolean_addr.to_string(),
+                msg: to_binary(&cw_boolean_contract::msgs::query_msg::QueryMsg::GetValue {})
+                    .unwrap(),
+                check_result: true,
+            })
+        })
+        .collect();
     let task = TaskRequest {
         interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: "frob".to_owned(),
-                amount: coins(19, DENOM),
+                to_address: PARTICIPANT1.to_owned(),
+                amount: coins(5, DENOM),
             }
             .into(),
-            gas_limit: None,
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        queries: None,
+        queries: Some(queries),
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: Some(QueryRule::Or(vec![
+            QueryRule::Query(0),
+            QueryRule::Query(1),
+        ])),
     };
-    let create_task_res = app
+    let res = app
         .execute_contract(
             Addr::unchecked(PARTICIPANT0),
             tasks_addr,
             &CreateTask {
                 task: Box::new(task),
             },
-            &coins(600_000, DENOM),
+            &coins(500_000, DENOM),
         )
-        .unwrap()
-        .data
         .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
 
-    // This contains info about the task after creation
-    let mut task_execution_info_creation: TaskExecutionInfo =
-        from_binary(&create_task_res).unwrap();
-
-    app.update_block(add_little_time);
-
-    // Proxy call
-    assert!(app
+    let err: ContractError = app
         .execute_contract(
             Addr::unchecked(AGENT0),
             manager_addr.clone(),
-            &ProxyCall { task_hash: None },
+            &ProxyCall {
+                task_hash: Some(task_data.task_hash.clone()),
+            },
             &[],
         )
-        .is_ok());
-
-    // Check the block height that proxy_call happened on
-    let proxy_call_height = app.block_info().height;
-
-    // Now we compare the saved value with the state key last_task_execution_info
-    raw_task_execution_info = crate::state::LAST_TASK_EXECUTION_INFO
-        .query(&app.wrap(), manager_addr)
+        .unwrap_err()
+        .downcast()
         .unwrap();
+    assert_eq!(err, ContractError::TaskQueryResultFalse {});
 
-    // We must modify the data we received upon task creation
-    // since the block height returned was for the creation time,
-    // not representing task execution that happens a block later.
-    task_execution_info_creation.block_height = proxy_call_height;
-
+    // One of the results is enough, even though both queries check the result
+    app.execute_contract(
+        Addr::unchecked(ANYONE),
+        deadline_reached_addr,
+        &Toggle {},
+        &[],
+    )
+    .unwrap();
+    let participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr,
+        &ProxyCall {
+            task_hash: Some(task_data.task_hash),
+        },
+        &[],
+    )
+    .unwrap();
+    let new_participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
     assert_eq!(
-        task_execution_info_creation,
-        TaskExecutionInfo {
-            block_height: proxy_call_height,
-            tx_info: Some(TransactionInfo { index: 0 }),
-            task_hash: raw_task_execution_info.task_hash,
-            owner_addr: raw_task_execution_info.owner_addr,
-            amount_for_one_task: raw_task_execution_info.amount_for_one_task,
-            version: raw_task_execution_info.version,
-        }
+        new_participant1_balance.amount,
+        participant1_balance.amount + Uint128::new(5)
     );
-
-    // TODO: this test is somewhat incomplete in the sense that at the time of
-    // this writing, the TransactionInfo doesn't seem to return a non-zero
-    // index. This curious behavior is captured here:
-    // https://github.com/CosmWasm/cosmwasm/issues/1651
-    // If this issue gets ironed out, please modify this test such that
-    // We perform multiple proxy_call executions in the same block and
-    // can confirm that the TransactionInfo's index increases.
 }
 
-/// Check all the different query types within a task
 #[test]
-fn proxy_batch_cases() {
+fn transformed_native_amounts_verified() {
     let mut app = default_app();
     let factory_addr = init_factory(&mut app);
 
-    // let instantiate_msg: InstantiateMsg = default_instantiate_msg();
     let instantiate_msg: InstantiateMsg = default_instantiate_message();
-    let tasks_addr = init_tasks(&mut app, &factory_addr);
     let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
-    let agent_addr = init_agents(&mut app, &factory_addr);
-    let balances_addr = init_mod_balances(&mut app, &factory_addr);
-    let cw20_addr = init_cw20(&mut app);
-
-    // Register an agent
-    activate_agent(&mut app, &agent_addr);
-    app.update_block(add_little_time);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+    let mod_balances_addr = init_mod_balances(&mut app, &factory_addr);
 
-    // These queries cover all the supported types of dynamic
-    let queries = vec![
-        CosmosQuery::Croncat(CroncatQuery {
-            contract_addr: balances_addr.to_string(),
-            msg: to_binary(&BalancesQueryMsg::GetBalance {
-                address: Addr::unchecked(ANYONE).to_string(),
-                denom: DENOM.to_string(),
-            })
-            .unwrap(),
-            check_result: true,
-        }),
-        CosmosQuery::Wasm(WasmQuery::Smart {
-            contract_addr: cw20_addr.to_string(),
-            msg: to_binary(&Cw20QueryMsg::TokenInfo {}).unwrap(),
-        }),
-    ];
+    activate_agent(&mut app, &agents_addr);
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Immediate,
+    // Task sends the amount equal to the balance of "lucy"
+    let task_with_transform = |query_response_key: &str| TaskRequest {
+        interval: Interval::Once,
         boundary: None,
         stop_on_fail: false,
         actions: vec![Action {
             msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                to_address: PARTICIPANT1.to_owned(),
                 amount: coins(5, DENOM),
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
-        // queries: None,
-        queries: Some(queries),
-        transforms: None, // No transforms in this task
+        queries: Some(vec![CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: mod_balances_addr.to_string(),
+            msg: to_binary(&BalancesQueryMsg::GetBalance {
+                address: "lucy".to_owned(),
+                denom: DENOM.to_owned(),
+            })
+            .unwrap(),
+            check_result: true,
+        })]),
+        transforms: Some(vec![Transform {
+            action_idx: 0,
+            query_idx: 0,
+            query_response_path: PathToValue::from(vec![ValueIndex::Key(
+                query_response_key.to_owned(),
+            )]),
+            action_path: PathToValue::from(vec![
+                ValueIndex::Key("bank".to_owned()),
+                ValueIndex::Key("send".to_owned()),
+                ValueIndex::Key("amount".to_owned()),
+                ValueIndex::Index(0),
+                ValueIndex::Key("amount".to_owned()),
+            ]),
+            operations: vec![],
+            result_type: None,
+        }]),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let create_and_execute = |app: &mut App, task: TaskRequest| {
+        let res = app
+            .execute_contract(
+                Addr::unchecked(PARTICIPANT0),
+                tasks_addr.clone(),
+                &CreateTask {
+                    task: Box::new(task),
+                },
+                &coins(500_000, DENOM),
+            )
+            .unwrap();
+        let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+        app.execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ProxyCall {
+                task_hash: Some(task_data.task_hash.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+        let history: Vec<TaskExecutionRecord> = app
+            .wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &QueryMsg::TaskExecutionHistory {
+                    task_hash: task_data.task_hash,
+                    from_index: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        history[0].clone()
     };
 
+    // Transformed amount is covered by the task balance
+    app.sudo(
+        BankSudo::Mint {
+            to_address: "lucy".to_owned(),
+            amount: coins(10, DENOM),
+        }
+        .into(),
+    )
+    .unwrap();
+    let participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    let record = create_and_execute(&mut app, task_with_transform("amount"));
+    assert_eq!(record.action_results, vec![ActionResult::Success]);
+    let new_participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    assert_eq!(
+        new_participant1_balance.amount,
+        participant1_balance.amount + Uint128::new(10)
+    );
+
+    // Transformed amount is above the task balance
+    app.sudo(
+        BankSudo::Mint {
+            to_address: "lucy".to_owned(),
+            amount: coins(1_000_000, DENOM),
+        }
+        .into(),
+    )
+    .unwrap();
+    let record = create_and_execute(&mut app, task_with_transform("amount"));
+    assert_eq!(record.end_reason, Some(TaskEndReason::Invalidated));
+    assert!(record.action_results.is_empty());
+
+    // Denom is not a valid amount, coin doesn't get dropped silently
+    let record = create_and_execute(&mut app, task_with_transform("denom"));
+    assert_eq!(record.end_reason, Some(TaskEndReason::TransformFailed));
+    assert!(record.action_results.is_empty());
+
+    let new_participant1_balance = app.wrap().query_balance(PARTICIPANT1, DENOM).unwrap();
+    assert_eq!(
+        new_participant1_balance.amount,
+        participant1_balance.amount + Uint128::new(10)
+    );
+}
+
+/// Adds the validator to the staking module and shortens the unbonding period of the manager
+fn setup_validator(app: &mut App, factory_addr: &Addr, manager_addr: &Addr, validator: &str) {
+    let block = app.block_info();
+    app.init_modules(|router, api, storage| {
+        router
+            .staking
+            .setup(
+                storage,
+                StakingInfo {
+                    bonded_denom: DENOM.to_owned(),
+                    unbonding_time: 60,
+                    apr: Decimal::percent(10),
+                },
+            )
+            .unwrap();
+        router
+            .staking
+            .add_validator(
+                api,
+                storage,
+                &block,
+                Validator {
+                    address: validator.to_owned(),
+                    commission: Decimal::zero(),
+                    max_commission: Decimal::one(),
+                    max_change_rate: Decimal::one(),
+                },
+            )
+            .unwrap();
+    });
     app.execute_contract(
-        Addr::unchecked(ANYONE),
-        tasks_addr.clone(),
-        &CreateTask {
-            task: Box::new(task),
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &croncat_sdk_factory::msg::FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: manager_addr.to_string(),
+                msg: to_binary(&ExecuteMsg::UpdateConfig(Box::new(UpdateConfig {
+                    agent_fee: None,
+                    treasury_fee: None,
+                    gas_price: None,
+                    gas_price_source: None,
+                    croncat_tasks_key: None,
+                    croncat_agents_key: None,
+                    treasury_addr: None,
+                    cw20_whitelist: None,
+                    gas_denoms: None,
+                    unbonding_period: Some(60),
+                })))
+                .unwrap(),
+                funds: vec![],
+            },
         },
-        &coins(500_000, DENOM),
+        &[],
     )
-    .expect("Couldn't create task");
+    .unwrap();
+}
 
-    app.update_block(add_little_time);
+#[test]
+fn task_stake_unbonds_before_release() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
 
-    // Agent checks to see if there are tasks for them to do.
-    // Note: we hit the Tasks contract for this one. Manager for regular tasks.
-    let mut tasks_for_agent: Option<Vec<croncat_sdk_tasks::types::TaskInfo>> = app
-        .wrap()
-        .query_wasm_smart(
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
+
+    activate_agent(&mut app, &agents_addr);
+
+    let validator = "validator1";
+    setup_validator(&mut app, &factory_addr, &manager_addr, validator);
+
+    // Delegate and claim the rewards every block
+    let task = croncat_sdk_tasks::types::TaskRequest {
+        interval: Interval::Block(1),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: StakingMsg::Delegate {
+                    validator: validator.to_owned(),
+                    amount: coin(100, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: DistributionMsg::WithdrawDelegatorReward {
+                    validator: validator.to_owned(),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
             tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
-                from_index: None,
-                limit: None,
+            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
             },
+            &coins(1_000_000, DENOM),
         )
         .unwrap();
-    let mut evented_task_info = tasks_for_agent.expect("Error unwrapping evented task info");
-    assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should only have one evented task"
-    );
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+    let query_native_balance = |app: &App| {
+        let res: TaskBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &QueryMsg::TaskBalance {
+                    task_hash: task_hash.clone(),
+                },
+            )
+            .unwrap();
+        res.balance.unwrap().native_balance
+    };
 
-    // Now check that the evented one returns true and completez
-    let task_hash: String = evented_task_info[0].clone().task_hash;
-    let pc_result = app.execute_contract(
+    app.update_block(add_little_time);
+    app.execute_contract(
         Addr::unchecked(AGENT0),
         manager_addr.clone(),
-        &ProxyBatch(vec![Some(task_hash.clone())]),
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        pc_result.is_ok(),
-        "Proxy call should succeed since the check_return comes back true"
-    );
-    let pc_res: AppResponse = pc_result.unwrap();
-    // we expect the queries to pass and the task to reschedule after success
-    assert!(pc_res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
-            .any(|attr| attr.key == "action" && attr.value == "continue_task")
-    }));
-
-    // app.update_block(add_little_time);
-
-    // Check that the one tasks still exists
-    tasks_for_agent = app
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let native_balance = query_native_balance(&app);
+
+    // Rewards of the year get credited to the task
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(365 * 24 * 60 * 60);
+    });
+    let rewards = app
+        .wrap()
+        .query_delegation(&manager_addr, validator)
+        .unwrap()
+        .unwrap()
+        .accumulated_rewards[0]
+        .amount;
+    assert!(!rewards.is_zero());
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let history: Vec<TaskExecutionRecord> = app
         .wrap()
         .query_wasm_smart(
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksQueryMsg::EventedTasks {
-                start: None,
+            manager_addr.clone(),
+            &QueryMsg::TaskExecutionHistory {
+                task_hash: task_hash.clone(),
                 from_index: None,
-                limit: None,
+                limit: Some(1),
             },
         )
         .unwrap();
-    evented_task_info =
-        tasks_for_agent.expect("Error unwrapping evented task info after failed proxy_call");
     assert_eq!(
-        evented_task_info.len(),
-        1usize,
-        "Should still have one evented task"
-    );
-
-    // Attempt many moar
-    let pc_result = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ProxyBatch(vec![
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash.clone()),
-            Some(task_hash),
-        ]),
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        pc_result.is_ok(),
-        "Proxy call should succeed since the check_return comes back true"
+        query_native_balance(&app),
+        native_balance - history[0].gas_charged.amount - Uint128::new(100) + rewards
     );
-    let pc_res: AppResponse = pc_result.unwrap();
-    // we expect the queries to pass and the task to reschedule after success
-    assert!(pc_res.events.iter().any(|ev| {
-        ev.attributes
-            .iter()
-            // NOTE: "continue_task" will appear only for evented tasks, so annotate incomplete but continuing
-            .any(|attr| attr.key == "action" && attr.value == "continue_task")
-    }));
 
-    // Attempt none (no tasks for agent, but try anyway to sneakz)
-    let pc_result = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ProxyBatch(vec![None]),
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        pc_result.is_err(),
-        "Proxy call should not succeed since we didnt have scheduled tasks yet"
-    );
+    // Slashed stake doesn't revert the removal
+    app.sudo(
+        StakingSudo::Slash {
+            validator: validator.to_owned(),
+            percentage: Decimal::percent(50),
+        }
+        .into(),
+    )
+    .unwrap();
+    let delegation = app
+        .wrap()
+        .query_delegation(&manager_addr, validator)
+        .unwrap()
+        .unwrap();
+    let unbonding = delegation.amount.amount
+        + delegation
+            .accumulated_rewards
+            .first()
+            .map_or(Uint128::zero(), |reward| reward.amount);
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        tasks_addr,
+        &croncat_sdk_tasks::msg::TasksExecuteMsg::RemoveTask { task_hash },
+        &[],
+    )
+    .unwrap();
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task = TaskRequest {
-        interval: Interval::Block(1),
-        boundary: None,
-        stop_on_fail: false,
-        actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
-            }
-            .into(),
-            gas_limit: Some(50_000),
-        }],
-        queries: None,
-        transforms: None, // No transforms in this task
-        cw20: None,
-    };
+    // Stake is released after the unbonding period
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            manager_addr.clone(),
+            &ExecuteMsg::ReleaseUnbondings { limit: None },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoUnbondingsReleased {});
 
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+    app.sudo(StakingSudo::ProcessQueue {}.into()).unwrap();
+    let owner_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
     app.execute_contract(
-        Addr::unchecked(PARTICIPANT0),
-        tasks_addr.clone(),
-        &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
-            task: Box::new(task),
-        },
-        &coins(600_000, DENOM),
+        Addr::unchecked(ANYONE),
+        manager_addr,
+        &ExecuteMsg::ReleaseUnbondings { limit: None },
+        &[],
     )
     .unwrap();
+    let new_owner_balance = app.wrap().query_balance(PARTICIPANT0, DENOM).unwrap();
+    assert_eq!(new_owner_balance.amount, owner_balance.amount + unbonding);
+}
 
-    app.update_block(add_little_time);
+#[test]
+fn staking_rewards_split_by_task_stake() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
 
-    // Attempt none (no tasks for agent, but try anyway to sneakz)
-    let pc_result = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr.clone(),
-        &ProxyBatch(vec![None]),
-        &[], // Attach no funds ofc
-    );
-    assert!(
-        pc_result.is_ok(),
-        "Proxy call should succeed since we have scheduled task"
-    );
+    let instantiate_msg: InstantiateMsg = default_instantiate_message();
+    let manager_addr = init_manager(&mut app, &instantiate_msg, &factory_addr, &[]);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+    let tasks_addr = init_tasks(&mut app, &factory_addr);
 
-    // Create a task (queries and transforms) with a Boundary that is soon
-    let task1 = TaskRequest {
-        interval: Interval::Block(1),
-        boundary: Some(Boundary::Height(BoundaryHeight {
-            start: None,
-            end: Some((app.block_info().height + 1).into()),
-        })),
-        stop_on_fail: false,
-        actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
-            }
-            .into(),
-            gas_limit: Some(50_000),
-        }],
-        queries: None,
-        transforms: None, // No transforms in this task
-        cw20: None,
+    activate_agent(&mut app, &agents_addr);
+
+    let validator = "validator1";
+    setup_validator(&mut app, &factory_addr, &manager_addr, validator);
+
+    // Both tasks delegate to the same validator and claim the rewards every block
+    let create_task = |app: &mut App, owner: &str, amount: u128| {
+        let task = croncat_sdk_tasks::types::TaskRequest {
+            interval: Interval::Block(1),
+            boundary: None,
+            stop_on_fail: false,
+            actions: vec![
+                Action {
+                    msg: StakingMsg::Delegate {
+                        validator: validator.to_owned(),
+                        amount: coin(amount, DENOM),
+                    }
+                    .into(),
+                    gas_limit: None,
+                    authz: false,
+                    condition: None,
+                    ibc_timeout: None,
+                },
+                Action {
+                    msg: DistributionMsg::WithdrawDelegatorReward {
+                        validator: validator.to_owned(),
+                    }
+                    .into(),
+                    gas_limit: None,
+                    authz: false,
+                    condition: None,
+                    ibc_timeout: None,
+                },
+            ],
+            queries: None,
+            transforms: None,
+            cw20: None,
+            max_executions: None,
+            callbacks: None,
+            gas_denom: None,
+            query_rule: None,
+        };
+        let res = app
+            .execute_contract(
+                Addr::unchecked(owner),
+                tasks_addr.clone(),
+                &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask {
+                    task: Box::new(task),
+                },
+                &coins(1_000_000, DENOM),
+            )
+            .unwrap();
+        let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+        task_data.task_hash
     };
-    let task2 = TaskRequest {
-        interval: Interval::Block(1),
-        boundary: Some(Boundary::Height(BoundaryHeight {
-            start: Some((app.block_info().height).into()),
-            end: Some((app.block_info().height + 10).into()),
-        })),
-        stop_on_fail: false,
-        actions: vec![Action {
-            msg: BankMsg::Send {
-                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
-                amount: coins(5, DENOM),
-            }
-            .into(),
-            gas_limit: Some(50_000),
-        }],
-        queries: None,
-        transforms: None, // No transforms in this task
-        cw20: None,
+    let task_hash1 = create_task(&mut app, PARTICIPANT0, 200);
+    let task_hash2 = create_task(&mut app, PARTICIPANT1, 100);
+    let query_native_balance = |app: &App, task_hash: &str| {
+        let res: TaskBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &QueryMsg::TaskBalance {
+                    task_hash: task_hash.to_owned(),
+                },
+            )
+            .unwrap();
+        res.balance.unwrap().native_balance
+    };
+    let query_gas_charged = |app: &App, task_hash: &str| {
+        let history: Vec<TaskExecutionRecord> = app
+            .wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &QueryMsg::TaskExecutionHistory {
+                    task_hash: task_hash.to_owned(),
+                    from_index: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        history[0].gas_charged.amount
     };
 
-    let tasks = vec![task1, task2];
-
-    for t in tasks {
+    app.update_block(add_little_time);
+    for _ in 0..2 {
         app.execute_contract(
-            Addr::unchecked(PARTICIPANT0),
-            tasks_addr.clone(),
-            &croncat_sdk_tasks::msg::TasksExecuteMsg::CreateTask { task: Box::new(t) },
-            &coins(600_000, DENOM),
+            Addr::unchecked(AGENT0),
+            manager_addr.clone(),
+            &ExecuteMsg::ProxyCall { task_hash: None },
+            &[],
         )
         .unwrap();
     }
 
-    app.update_block(add_little_time);
-
-    // Attempt none (no tasks for agent, but try anyway to sneakz)
-    let pc_result = app.execute_contract(
-        Addr::unchecked(AGENT0),
-        manager_addr,
-        &ProxyBatch(vec![None, None, None]),
-        &[], // Attach no funds ofc
+    // Rewards withdrawn by the first task are split by the stake
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(365 * 24 * 60 * 60);
+    });
+    let rewards = app
+        .wrap()
+        .query_delegation(&manager_addr, validator)
+        .unwrap()
+        .unwrap()
+        .accumulated_rewards[0]
+        .amount;
+    assert!(!rewards.is_zero());
+    let native_balance1 = query_native_balance(&app, &task_hash1);
+    let native_balance2 = query_native_balance(&app, &task_hash2);
+    for _ in 0..2 {
+        let res = app
+            .execute_contract(
+                Addr::unchecked(AGENT0),
+                manager_addr.clone(),
+                &ExecuteMsg::ProxyCall { task_hash: None },
+                &[],
+            )
+            .unwrap();
+        assert!(!res.events.iter().any(|ev| ev
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "lifecycle" && attr.value == "task_invalidated")));
+    }
+    let reward_per_stake = Decimal::from_ratio(rewards, 300u128);
+    assert_eq!(
+        query_native_balance(&app, &task_hash1),
+        native_balance1 - query_gas_charged(&app, &task_hash1) - Uint128::new(200)
+            + Uint128::new(200) * reward_per_stake
     );
-    assert!(
-        pc_result.is_ok(),
-        "Proxy call should succeed since we have scheduled task"
+    assert_eq!(
+        query_native_balance(&app, &task_hash2),
+        native_balance2 - query_gas_charged(&app, &task_hash2) - Uint128::new(100)
+            + Uint128::new(100) * reward_per_stake
     );
 }
@@ -4,14 +4,21 @@ mod tests {
         tests::{DENOM, PARTICIPANT1, PARTICIPANT2},
         ContractError,
     };
-    use cosmwasm_std::{coins, Addr, BankMsg, Binary, CosmosMsg, WasmMsg};
-    use croncat_mod_generic::types::{PathToValue, ValueIndex};
+    use cosmwasm_std::{
+        coin, coins, Addr, BankMsg, Binary, CosmosMsg, Decimal256, DistributionMsg, StakingMsg,
+        WasmMsg,
+    };
+    use croncat_mod_generic::{
+        types::{PathToValue, ValueIndex},
+        value_ordering::ValueOrdering,
+    };
     use croncat_sdk_core::types::AmountForOneTask;
     use croncat_sdk_tasks::types::{
-        Action, Boundary, BoundaryTime, CosmosQuery, CroncatQuery, Interval, TaskInfo, Transform,
+        Action, ActionCondition, Boundary, BoundaryTime, CosmosQuery, CroncatQuery, Interval,
+        TaskInfo, Transform, TransformOperand, TransformOperation, TransformResultType,
     };
 
-    use crate::helpers::replace_values;
+    use crate::helpers::{replace_values, skip_unmet_actions};
 
     fn create_query_response_data(json_str: &str) -> Vec<Option<Binary>> {
         let query_json_value = serde_json::from_str::<serde_json::Value>(json_str).unwrap();
@@ -36,6 +43,9 @@ mod tests {
                     funds: vec![],
                 }),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             }],
             queries: None,
             transforms: vec![Transform {
@@ -45,11 +55,18 @@ mod tests {
                 query_response_path: PathToValue::from(vec![ValueIndex::Key(
                     "query_key".to_string(),
                 )]),
+                operations: vec![],
+                result_type: None,
             }],
             version: "1.0".to_string(),
+            paused: false,
+            executions: 0,
+            remaining_executions: None,
+            callbacks: None,
             amount_for_one_task: AmountForOneTask::default(),
             task_hash: "atom:cc4909816ce7ff69f5804e2416d3c437d7367bc7751596845c658050df7"
                 .to_string(),
+            query_rule: None,
         }
     }
 
@@ -71,6 +88,9 @@ mod tests {
                 amount: coins(5, DENOM),
             }),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         });
         task.actions.push(Action {
             msg: CosmosMsg::Bank(BankMsg::Send {
@@ -78,6 +98,9 @@ mod tests {
                 amount: coins(5, DENOM),
             }),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         });
 
         // Add a new Transform
@@ -96,6 +119,8 @@ mod tests {
                 ValueIndex::Index(0),
                 ValueIndex::Key("amount".to_string()),
             ]),
+            operations: vec![],
+            result_type: None,
         });
 
         let query_response_data = create_query_response_data(
@@ -120,6 +145,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_staking_action_replace() {
+        let mut task = get_task();
+
+        task.queries = Some(vec![CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: "rewards_helper".to_string(),
+            msg: Binary::from(r#"{"pending_rewards": {}}"#.as_bytes()),
+            check_result: false,
+        })]);
+        task.actions = vec![
+            Action {
+                msg: CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                    validator: "validator".to_string(),
+                }),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: CosmosMsg::Staking(StakingMsg::Delegate {
+                    validator: "validator".to_string(),
+                    amount: coin(1, DENOM),
+                }),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ];
+        task.transforms = vec![Transform {
+            action_idx: 1,
+            query_idx: 0,
+            query_response_path: PathToValue::from(vec![ValueIndex::Key("amount".to_string())]),
+            action_path: PathToValue::from(vec![
+                ValueIndex::Key("staking".to_string()),
+                ValueIndex::Key("delegate".to_string()),
+                ValueIndex::Key("amount".to_string()),
+                ValueIndex::Key("amount".to_string()),
+            ]),
+            operations: vec![],
+            result_type: None,
+        }];
+
+        let query_response_data = create_query_response_data(r#"{"amount": "1234"}"#);
+        replace_values(&mut task, query_response_data).unwrap();
+
+        assert_eq!(
+            task.actions[1].msg,
+            CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: "validator".to_string(),
+                amount: coin(1234, DENOM),
+            })
+        );
+    }
+
     #[test]
     fn test_single_action_replace() {
         // Create the necessary instances
@@ -166,6 +247,9 @@ mod tests {
                 funds: vec![],
             }),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         });
 
         // Fill a query, but not really used in this test
@@ -181,6 +265,8 @@ mod tests {
             query_idx: 0,
             action_path: PathToValue::from(vec![ValueIndex::Key("action_key".to_string())]),
             query_response_path: PathToValue::from(vec![ValueIndex::Key("query_key".to_string())]),
+            operations: vec![],
+            result_type: None,
         });
 
         let query_response_data = create_query_response_data(r#"{"query_key": "new_value"}"#);
@@ -212,6 +298,9 @@ mod tests {
                 funds: vec![],
             }),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         });
 
         // Fill a query, but not really used in this test
@@ -229,6 +318,8 @@ mod tests {
             query_response_path: PathToValue::from(vec![ValueIndex::Key(
                 "another_query_key".to_string(),
             )]),
+            operations: vec![],
+            result_type: None,
         });
 
         let query_response_data = create_query_response_data(
@@ -457,4 +548,303 @@ mod tests {
         // Assert that there are no errors
         assert!(result.is_ok());
     }
+
+    fn get_bank_send_task(operations: Vec<TransformOperation>) -> TaskInfo {
+        let mut task = get_task();
+        let query = CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: "balance_contract".to_string(),
+            msg: Binary::from(r#"{"get_balance": {}}"#.as_bytes()),
+            check_result: false,
+        });
+        task.queries = Some(vec![query.clone(), query]);
+        task.actions[0].msg = CosmosMsg::Bank(BankMsg::Send {
+            to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+            amount: coins(5, DENOM),
+        });
+        task.transforms[0] = Transform {
+            action_idx: 0,
+            query_idx: 0,
+            query_response_path: PathToValue::from(vec![ValueIndex::Key("balance".to_string())]),
+            action_path: PathToValue::from(vec![
+                ValueIndex::Key("bank".to_string()),
+                ValueIndex::Key("send".to_string()),
+                ValueIndex::Key("amount".to_string()),
+                ValueIndex::Index(0),
+                ValueIndex::Key("amount".to_string()),
+            ]),
+            operations,
+            result_type: Some(TransformResultType::Uint128),
+        };
+        task
+    }
+
+    fn bank_send_amount(task: &TaskInfo) -> u128 {
+        if let CosmosMsg::Bank(BankMsg::Send { amount, .. }) = &task.actions[0].msg {
+            amount[0].amount.u128()
+        } else {
+            panic!("Unexpected message type");
+        }
+    }
+
+    #[test]
+    fn test_transform_operations_percent_and_reserve() {
+        // Send 10% of the balance, keeping 1000 as a reserve
+        let mut task = get_bank_send_task(vec![
+            TransformOperation::Percent(Decimal256::from_atomics(10u128, 0).unwrap()),
+            TransformOperation::Sub(TransformOperand::Constant(
+                Decimal256::from_atomics(1000u128, 0).unwrap(),
+            )),
+        ]);
+        let query_response_data = create_query_response_data(r#"{"balance": "1234567"}"#);
+
+        replace_values(&mut task, query_response_data).unwrap();
+        // 123456.7 gets floored
+        assert_eq!(bank_send_amount(&task), 122456);
+    }
+
+    #[test]
+    fn test_transform_operations_query_operand() {
+        // Balance multiplied by the price from the second query
+        let mut task = get_bank_send_task(vec![
+            TransformOperation::Mul(TransformOperand::Query {
+                query_idx: 1,
+                query_response_path: PathToValue::from(vec![ValueIndex::Key("price".to_string())]),
+            }),
+            TransformOperation::Div(TransformOperand::Constant(
+                Decimal256::from_atomics(2u128, 0).unwrap(),
+            )),
+        ]);
+        let mut query_response_data = create_query_response_data(r#"{"balance": "1000"}"#);
+        query_response_data.extend(create_query_response_data(r#"{"price": "1.5"}"#));
+
+        replace_values(&mut task, query_response_data).unwrap();
+        assert_eq!(bank_send_amount(&task), 750);
+    }
+
+    #[test]
+    fn test_transform_operations_clamp() {
+        let operations = vec![
+            TransformOperation::Max(TransformOperand::Constant(
+                Decimal256::from_atomics(100u128, 0).unwrap(),
+            )),
+            TransformOperation::Min(TransformOperand::Constant(
+                Decimal256::from_atomics(500u128, 0).unwrap(),
+            )),
+        ];
+
+        let mut task = get_bank_send_task(operations.clone());
+        replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "42"}"#),
+        )
+        .unwrap();
+        assert_eq!(bank_send_amount(&task), 100);
+
+        let mut task = get_bank_send_task(operations.clone());
+        replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "300"}"#),
+        )
+        .unwrap();
+        assert_eq!(bank_send_amount(&task), 300);
+
+        let mut task = get_bank_send_task(operations);
+        replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "9000"}"#),
+        )
+        .unwrap();
+        assert_eq!(bank_send_amount(&task), 500);
+    }
+
+    fn get_decimal_task(operations: Vec<TransformOperation>) -> TaskInfo {
+        let mut task = get_task();
+        task.queries = Some(vec![CosmosQuery::Croncat(CroncatQuery {
+            contract_addr: "price_contract".to_string(),
+            msg: Binary::from(r#"{"get_price": {}}"#.as_bytes()),
+            check_result: false,
+        })]);
+        task.transforms[0].operations = operations;
+        task.transforms[0].result_type = Some(TransformResultType::Decimal);
+        task
+    }
+
+    fn action_key_value(task: &TaskInfo) -> String {
+        if let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &task.actions[0].msg {
+            let msg_value: serde_json::Value = serde_json::from_slice(&msg.0).unwrap();
+            msg_value
+                .get("action_key")
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_owned()
+        } else {
+            panic!("Unexpected message type");
+        }
+    }
+
+    #[test]
+    fn test_transform_operations_decimal_value() {
+        let mut task = get_decimal_task(vec![TransformOperation::Add(TransformOperand::Constant(
+            Decimal256::from_atomics(25u128, 2).unwrap(),
+        ))]);
+        let query_response_data = create_query_response_data(r#"{"query_key": "1.5"}"#);
+
+        replace_values(&mut task, query_response_data).unwrap();
+        assert_eq!(action_key_value(&task), "1.75");
+    }
+
+    #[test]
+    fn test_transform_operations_integral_decimal_value() {
+        // Decimal without the fractional part isn't rounded down
+        let mut task = get_decimal_task(vec![TransformOperation::Mul(TransformOperand::Constant(
+            Decimal256::from_atomics(125u128, 2).unwrap(),
+        ))]);
+        let query_response_data = create_query_response_data(r#"{"query_key": "2"}"#);
+
+        replace_values(&mut task, query_response_data).unwrap();
+        assert_eq!(action_key_value(&task), "2.5");
+    }
+
+    #[test]
+    fn test_transform_operations_no_result_type() {
+        let mut task = get_decimal_task(vec![TransformOperation::Mul(TransformOperand::Constant(
+            Decimal256::from_atomics(125u128, 2).unwrap(),
+        ))]);
+        task.transforms[0].result_type = None;
+        let query_response_data = create_query_response_data(r#"{"query_key": "2"}"#);
+
+        assert!(replace_values(&mut task, query_response_data).is_err());
+    }
+
+    #[test]
+    fn test_transform_operations_checked() {
+        // Underflow
+        let mut task = get_bank_send_task(vec![TransformOperation::Sub(
+            TransformOperand::Constant(Decimal256::from_atomics(1000u128, 0).unwrap()),
+        )]);
+        let result = replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "10"}"#),
+        );
+        assert!(result.is_err());
+
+        // Division by zero from the query value
+        let mut task = get_bank_send_task(vec![TransformOperation::Div(TransformOperand::Query {
+            query_idx: 0,
+            query_response_path: PathToValue::from(vec![ValueIndex::Key("zero".to_string())]),
+        })]);
+        let result = replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "10", "zero": "0"}"#),
+        );
+        assert!(result.is_err());
+
+        // Result doesn't fit into Uint128
+        let mut task = get_bank_send_task(vec![TransformOperation::Mul(
+            TransformOperand::Constant(Decimal256::from_atomics(u128::MAX, 0).unwrap()),
+        )]);
+        let result = replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "340282366920938463463374607431768211455"}"#),
+        );
+        assert!(result.is_err());
+
+        // Operand query doesn't exist
+        let mut task = get_bank_send_task(vec![TransformOperation::Add(TransformOperand::Query {
+            query_idx: 5,
+            query_response_path: PathToValue::from(vec![ValueIndex::Key("balance".to_string())]),
+        })]);
+        let result = replace_values(
+            &mut task,
+            create_query_response_data(r#"{"balance": "10"}"#),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bank_send_multiple_coins() {
+        let mut task = get_bank_send_task(vec![]);
+        task.actions[0].msg = CosmosMsg::Bank(BankMsg::Send {
+            to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+            amount: vec![coin(5, DENOM), coin(7, "ibc/denom")],
+        });
+        let mut second_coin = task.transforms[0].clone();
+        second_coin.query_response_path =
+            PathToValue::from(vec![ValueIndex::Key("other_balance".to_string())]);
+        second_coin.action_path.0[3] = ValueIndex::Index(1);
+        task.transforms.push(second_coin);
+
+        let query_response_data =
+            create_query_response_data(r#"{"balance": "100", "other_balance": "200"}"#);
+        replace_values(&mut task, query_response_data).unwrap();
+
+        // Both coins are kept
+        if let CosmosMsg::Bank(BankMsg::Send { to_address, amount }) = &task.actions[0].msg {
+            assert_eq!(to_address, PARTICIPANT1);
+            assert_eq!(amount, &vec![coin(100, DENOM), coin(200, "ibc/denom")]);
+        } else {
+            panic!("Unexpected message type");
+        }
+    }
+
+    #[test]
+    fn test_bank_send_invalid_amount() {
+        let mut task = get_bank_send_task(vec![]);
+
+        // Not a number
+        let query_response_data = create_query_response_data(r#"{"balance": "lots"}"#);
+        let result = replace_values(&mut task, query_response_data);
+        assert_eq!(result.unwrap_err(), ContractError::TaskInvalidTransform {});
+
+        // Not a Uint128
+        let query_response_data = create_query_response_data(r#"{"balance": 100}"#);
+        let result = replace_values(&mut task, query_response_data);
+        assert_eq!(result.unwrap_err(), ContractError::TaskInvalidTransform {});
+
+        // Coin doesn't get dropped
+        assert_eq!(bank_send_amount(&task), 5);
+    }
+
+    #[test]
+    fn test_transform_cant_change_msg_kind() {
+        let mut task = get_bank_send_task(vec![]);
+        task.transforms[0].query_response_path = PathToValue::from(vec![]);
+        task.transforms[0].action_path =
+            PathToValue::from(vec![ValueIndex::Key("bank".to_string())]);
+
+        let query_response_data = create_query_response_data(
+            r#"{"burn": {"amount": [{"denom": "atom", "amount": "5"}]}}"#,
+        );
+        let result = replace_values(&mut task, query_response_data);
+        assert_eq!(result.unwrap_err(), ContractError::TaskInvalidTransform {});
+    }
+
+    #[test]
+    fn test_condition_evaluation_error_is_not_met() {
+        let mut task = get_task();
+        task.transforms.clear();
+        let action = task.actions[0].clone();
+        task.actions = vec![
+            Action {
+                gas_limit: Some(50_000),
+                condition: Some(ActionCondition {
+                    query_idx: 0,
+                    query_response_path: PathToValue::from(vec![ValueIndex::Key(
+                        "missing_key".to_string(),
+                    )]),
+                    ordering: ValueOrdering::Equal,
+                    value: Binary::from(r#""value""#.as_bytes()),
+                }),
+                ..action.clone()
+            },
+            action,
+        ];
+        let query_response_data = create_query_response_data(r#"{"query_key": "value"}"#);
+
+        // Action with the condition is skipped instead of failing the task
+        let skipped = skip_unmet_actions(&mut task, &query_response_data).unwrap();
+        assert_eq!(skipped, vec![0]);
+        assert_eq!(task.actions.len(), 1);
+    }
 }
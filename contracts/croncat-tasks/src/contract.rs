@@ -2,17 +2,23 @@ use crate::ContractError::InvalidZeroValue;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Attribute, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
-    Uint64,
+    coin, to_binary, Addr, Attribute, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, Uint128, Uint64, WasmMsg,
 };
 use croncat_sdk_core::internal_messages::agents::AgentOnTaskCreated;
-use croncat_sdk_core::internal_messages::manager::{ManagerCreateTaskBalance, ManagerRemoveTask};
+use croncat_sdk_core::internal_messages::manager::{
+    ManagerCreateTaskBalance, ManagerMigrateTaskBalance, ManagerRemoveTask,
+    ManagerUpdateTaskBalance,
+};
 use croncat_sdk_core::internal_messages::tasks::{TasksRemoveTaskByManager, TasksRescheduleTask};
 use croncat_sdk_core::types::{DEFAULT_PAGINATION_FROM_INDEX, DEFAULT_PAGINATION_LIMIT};
-use croncat_sdk_tasks::msg::UpdateConfigMsg;
+use croncat_sdk_core::version::{migrate_contract, StateMigration};
+use croncat_sdk_manager::msg::ManagerQueryMsg;
+use croncat_sdk_tasks::msg::{TasksImportTask, UpdateConfigMsg};
 use croncat_sdk_tasks::types::{
-    Config, CurrentTaskInfoResponse, Interval, SlotHashesResponse, SlotIdsResponse,
-    SlotTasksTotalResponse, SlotType, Task, TaskExecutionInfo, TaskInfo, TaskRequest, TaskResponse,
+    Boundary, Config, CurrentTaskInfoResponse, Interval, SimulateTaskResponse, SlotHashesResponse,
+    SlotIdsResponse, SlotTasksTotalResponse, SlotType, Task, TaskExecutionInfo, TaskInfo,
+    TaskRequest, TaskResponse, TaskTemplate, TaskTemplateResponse, TemplatePlaceholder,
 };
 use cw2::set_contract_version;
 use cw20::Cw20CoinVerified;
@@ -20,18 +26,23 @@ use cw_storage_plus::Bound;
 
 use crate::error::ContractError;
 use crate::helpers::{
-    check_if_sender_is_manager, get_agents_addr, get_manager_addr, remove_task, validate_boundary,
-    validate_msg_calculate_usage, validate_queries, validate_transforms,
+    check_if_sender_is_manager, check_if_sender_is_other_version, fill_template, get_agents_addr,
+    get_contract_name, get_latest_addr, get_manager_addr, remove_task, remove_task_from_slots,
+    validate_boundary, validate_callbacks, validate_conditions, validate_imported_boundary,
+    validate_msg_calculate_usage, validate_queries, validate_query_rule, validate_template,
+    validate_transforms,
 };
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::state::{
     tasks_map, BLOCK_SLOTS, CONFIG, EVENTED_TASKS_LOOKUP, LAST_TASK_CREATION, PAUSED, TASKS_TOTAL,
-    TIME_SLOTS,
+    TASK_HASH_UPDATES, TASK_TEMPLATES, TEMPLATE_NONCE, TIME_SLOTS,
 };
 
 const CONTRACT_NAME: &str = "crate:croncat-tasks";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const STATE_MIGRATIONS: &[(&str, StateMigration<ContractError>)] = &[];
+
 // Default value based on non-wasm operations, wasm ops seem impossible to predict
 // TODO: this values based of pre-split, need to recalculate GAS_BASE_FEE
 pub(crate) const GAS_BASE_FEE: u64 = 300_000;
@@ -39,6 +50,7 @@ pub(crate) const GAS_ACTION_FEE: u64 = 130_000;
 pub(crate) const GAS_QUERY_FEE: u64 = 130_000; // Load query module(~61_000) and query after that(~65_000+)
 pub(crate) const GAS_LIMIT: u64 = 3_000_000; // 10M is default for juno, but let's make sure we have space for block inclusivity guarantees
 pub(crate) const SLOT_GRANULARITY_TIME: u64 = 10_000_000_000; // 10 seconds
+pub(crate) const QUERY_RULE_MAX_DEPTH: usize = 8;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -112,7 +124,29 @@ pub fn execute(
         ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, info, msg),
         ExecuteMsg::CreateTask { task } => execute_create_task(deps, env, info, *task),
         ExecuteMsg::RemoveTask { task_hash } => execute_remove_task(deps, info, task_hash),
+        ExecuteMsg::UpdateTask { task_hash, task } => {
+            execute_update_task(deps, env, info, task_hash, *task)
+        }
+        ExecuteMsg::PauseTask { task_hash } => execute_pause_task(deps, info, task_hash),
+        ExecuteMsg::ResumeTask { task_hash } => execute_resume_task(deps, env, info, task_hash),
+        ExecuteMsg::MigrateTask {
+            task_hash,
+            to_version,
+        } => execute_migrate_task(deps, env, info, task_hash, to_version),
+        ExecuteMsg::RegisterTemplate {
+            name,
+            task,
+            placeholders,
+        } => execute_register_template(deps, info, name, *task, placeholders),
+        ExecuteMsg::RemoveTemplate { template_id } => {
+            execute_remove_template(deps, info, template_id)
+        }
+        ExecuteMsg::CreateTaskFromTemplate {
+            template_id,
+            params,
+        } => execute_create_task_from_template(deps, env, info, template_id, params),
         // Methods for other contracts
+        ExecuteMsg::ImportTask(import_msg) => execute_import_task(deps, env, info, *import_msg),
         ExecuteMsg::RemoveTaskByManager(remove_task_msg) => {
             execute_remove_task_by_manager(deps, info, remove_task_msg)
         }
@@ -202,6 +236,7 @@ fn execute_reschedule_task(
         // but we also dont want to remove unless it was Interval::Once
         if next_id != 0 && !task.is_evented() && task.interval != Interval::Once {
             res_attributes.push(Attribute::new("action", "reschedule_task"));
+            count_execution(deps.storage, &task, &task_hash)?;
             // Get previous task hashes in slot, add as needed
             let update_vec_data = |d: Option<Vec<Vec<u8>>>| -> StdResult<Vec<Vec<u8>>> {
                 match d {
@@ -273,6 +308,7 @@ fn execute_reschedule_task(
         } else if task.is_evented() {
             // Seems like overkill but super nice to know we didn't remove an evented task
             res_attributes.push(Attribute::new("action", "continue_task"));
+            count_execution(deps.storage, &task, &task_hash)?;
         }
         (next_id, slot_kind)
     } else {
@@ -355,19 +391,391 @@ fn execute_create_task(
     let config = CONFIG.load(deps.storage)?;
     let owner_addr = info.sender;
 
-    // Validate boundary and interval
     let boundary = validate_boundary(&env.block, task.boundary.clone(), &task.interval)?;
+    let (item, cw20) = build_task(
+        deps.as_ref(),
+        &env,
+        &config,
+        owner_addr.clone(),
+        task,
+        boundary,
+    )?;
+    let amount_for_one_task = item.amount_for_one_task.clone();
+
+    let hash_prefix = &config.chain_name;
+    let hash = item.to_hash(hash_prefix);
+
+    let recurring = item.recurring();
+    let hash_vec = hash.clone().into_bytes();
+    let attributes = slot_task(deps.storage, &env, &config, &item, &hash_vec)?;
+    save_task(deps.storage, &item, &hash_vec)?;
+
+    // Save the current timestamp as the last time a task was created
+    LAST_TASK_CREATION.save(deps.storage, &env.block.time)?;
+
+    let manager_addr = get_manager_addr(&deps.querier, &config)?;
+    let manager_create_task_balance_msg = ManagerCreateTaskBalance {
+        sender: owner_addr,
+        task_hash: hash_vec,
+        recurring,
+        cw20,
+        amount_for_one_task,
+    }
+    .into_cosmos_msg(manager_addr, info.funds)?;
+
+    let agent_addr = get_agents_addr(&deps.querier, &config)?;
+    let agent_new_task_msg = AgentOnTaskCreated {}.into_cosmos_msg(agent_addr)?;
+    let response_data = TaskExecutionInfo {
+        block_height: env.block.height,
+        tx_info: env.transaction,
+        task_hash: hash.clone(),
+        owner_addr: item.owner_addr,
+        amount_for_one_task: item.amount_for_one_task,
+        version: item.version.clone(),
+    };
+    Ok(Response::new()
+        .set_data(to_binary(&response_data)?)
+        .add_attribute("action", "create_task")
+        .add_attributes(attributes)
+        .add_attribute("task_hash", hash)
+        .add_attribute("task_version", item.version)
+        .add_message(manager_create_task_balance_msg)
+        .add_message(agent_new_task_msg))
+}
+
+fn execute_register_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    task: TaskRequest,
+    placeholders: Vec<TemplatePlaceholder>,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let template = TaskTemplate {
+        owner_addr: info.sender,
+        name,
+        task,
+        placeholders,
+    };
+    validate_template(&template)?;
+
+    let template_id = TEMPLATE_NONCE.may_load(deps.storage)?.unwrap_or_default();
+    TASK_TEMPLATES.save(deps.storage, template_id, &template)?;
+    TEMPLATE_NONCE.save(deps.storage, &(template_id + 1))?;
+
+    Ok(Response::new()
+        .set_data(to_binary(&template_id)?)
+        .add_attribute("action", "register_template")
+        .add_attribute("template_id", template_id.to_string()))
+}
+
+fn execute_remove_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template_id: u64,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let template = TASK_TEMPLATES
+        .may_load(deps.storage, template_id)?
+        .ok_or(ContractError::NoTemplateFound {})?;
+    if template.owner_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    TASK_TEMPLATES.remove(deps.storage, template_id);
 
-    let amount_for_one_task = validate_msg_calculate_usage(
+    Ok(Response::new()
+        .add_attribute("action", "remove_template")
+        .add_attribute("template_id", template_id.to_string()))
+}
+
+fn execute_create_task_from_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    template_id: u64,
+    params: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    let template = TASK_TEMPLATES
+        .may_load(deps.storage, template_id)?
+        .ok_or(ContractError::NoTemplateFound {})?;
+    let task = fill_template(&template, params)?;
+
+    let res = execute_create_task(deps, env, info, task)?;
+    Ok(res.add_attribute("template_id", template_id.to_string()))
+}
+
+/// Replaces the task with the updated one, owner keeps the task balance
+/// and can attach more funds if updated task requires more.
+/// Old task hash stays queryable via `UpdatedTaskHash` query
+fn execute_update_task(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    task_hash: String,
+    task: TaskRequest,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let old_hash_vec = task_hash.clone().into_bytes();
+    let old_task = tasks_map()
+        .may_load(deps.storage, &old_hash_vec)?
+        .ok_or(ContractError::NoTaskFound {})?;
+    if old_task.owner_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let boundary = validate_boundary(&env.block, task.boundary.clone(), &task.interval)?;
+    let (mut item, cw20) = build_task(
         deps.as_ref(),
-        &task,
-        &env.contract.address,
-        &owner_addr,
+        &env,
         &config,
+        info.sender.clone(),
+        task,
+        boundary,
     )?;
+    let amount_for_one_task = item.amount_for_one_task.clone();
+    // Paused task stays paused after the update
+    item.paused = old_task.paused;
+    // Executions count carries over, updated task can't be over its limit
+    item.executions = old_task.executions;
+    if item.remaining_executions() == Some(0) {
+        return Err(ContractError::InvalidMaxExecutions {});
+    }
+
+    let hash = item.to_hash(&config.chain_name);
+    let recurring = item.recurring();
+    let hash_vec = hash.clone().into_bytes();
+    let attributes = if hash == task_hash {
+        // `stop_on_fail` isn't part of the hash, task with only it changed stays in place
+        if item.stop_on_fail == old_task.stop_on_fail {
+            return Err(ContractError::TaskUnchanged {});
+        }
+        tasks_map().save(deps.storage, &hash_vec, &item)?;
+        vec![]
+    } else {
+        // Take old task out of the slots, before new one gets scheduled
+        remove_task(
+            deps.storage,
+            &old_hash_vec,
+            old_task.boundary.is_block(),
+            old_task.is_evented(),
+        )?;
+        let attributes = if item.paused {
+            vec![]
+        } else {
+            slot_task(deps.storage, &env, &config, &item, &hash_vec)?
+        };
+        save_task(deps.storage, &item, &hash_vec)?;
+        TASK_HASH_UPDATES.save(deps.storage, &old_hash_vec, &hash_vec)?;
+        attributes
+    };
+
+    let manager_addr = get_manager_addr(&deps.querier, &config)?;
+    let manager_update_task_balance_msg = ManagerUpdateTaskBalance {
+        sender: info.sender,
+        old_task_hash: old_hash_vec,
+        new_task_hash: hash_vec,
+        recurring,
+        cw20,
+        amount_for_one_task,
+    }
+    .into_cosmos_msg(manager_addr, info.funds)?;
+
+    let response_data = TaskExecutionInfo {
+        block_height: env.block.height,
+        tx_info: env.transaction,
+        task_hash: hash.clone(),
+        owner_addr: item.owner_addr,
+        amount_for_one_task: item.amount_for_one_task,
+        version: item.version.clone(),
+    };
+    Ok(Response::new()
+        .set_data(to_binary(&response_data)?)
+        .add_attribute("action", "update_task")
+        .add_attributes(attributes)
+        .add_attribute("old_task_hash", task_hash)
+        .add_attribute("task_hash", hash)
+        .add_attribute("task_version", item.version)
+        .add_message(manager_update_task_balance_msg))
+}
+
+/// Moves the task to the latest tasks contract of the given version,
+/// task balance moved to the latest manager contract of the same version.
+/// Whole migration reverted if any of the contracts fails
+fn execute_migrate_task(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    task_hash: String,
+    to_version: [u8; 2],
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let hash = task_hash.as_bytes();
+    let task = tasks_map()
+        .may_load(deps.storage, hash)?
+        .ok_or(ContractError::NoTaskFound {})?;
+    if task.owner_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let tasks_name = get_contract_name(&deps.querier, &config, &env.contract.address)?
+        .ok_or(ContractError::InvalidKey {})?;
+    let new_tasks_addr = get_latest_addr(&deps.querier, &config, &tasks_name, to_version)?;
+    let manager_addr = get_manager_addr(&deps.querier, &config)?;
+    let new_manager_addr = get_latest_addr(
+        &deps.querier,
+        &config,
+        &config.croncat_manager_key.0,
+        to_version,
+    )?;
+    if new_tasks_addr == env.contract.address || new_manager_addr == manager_addr {
+        return Err(ContractError::InvalidTaskMigration {});
+    }
+
+    remove_task(
+        deps.storage,
+        hash,
+        task.boundary.is_block(),
+        task.is_evented(),
+    )?;
+
+    // Balance goes first, so new tasks contract can verify it
+    let migrate_balance_msg = ManagerMigrateTaskBalance {
+        sender: info.sender,
+        task_hash: hash.to_vec(),
+        new_manager_addr: new_manager_addr.clone(),
+    }
+    .into_cosmos_msg(manager_addr)?;
+    let import_task_msg = WasmMsg::Execute {
+        contract_addr: new_tasks_addr.to_string(),
+        msg: to_binary(&ExecuteMsg::ImportTask(Box::new(TasksImportTask {
+            task_hash: task_hash.clone(),
+            task,
+        })))?,
+        funds: vec![],
+    };
+    Ok(Response::new()
+        .add_attribute("action", "migrate_task")
+        .add_attribute("task_hash", task_hash)
+        .add_attribute("new_tasks_addr", new_tasks_addr)
+        .add_attribute("new_manager_addr", new_manager_addr)
+        .add_message(migrate_balance_msg)
+        .add_message(import_task_msg))
+}
+
+/// Receives the task from the other version of the tasks contract.
+/// Task validated against this contract config and the imported balance should cover it
+fn execute_import_task(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    import_msg: TasksImportTask,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    check_if_sender_is_other_version(&deps.querier, &config, &env.contract.address, &info.sender)?;
+    let TasksImportTask {
+        task_hash: old_task_hash,
+        task,
+    } = import_msg;
+
+    let boundary = validate_imported_boundary(&env.block, task.boundary, &task.interval)?;
+    let task_request = TaskRequest {
+        interval: task.interval,
+        boundary: None,
+        stop_on_fail: task.stop_on_fail,
+        actions: task.actions,
+        queries: Some(task.queries),
+        transforms: Some(task.transforms),
+        cw20: None,
+        max_executions: task.max_executions,
+        callbacks: task.callbacks,
+        gas_denom: task.amount_for_one_task.gas_denom,
+        query_rule: task.query_rule,
+    };
+    let (mut item, _) = build_task(
+        deps.as_ref(),
+        &env,
+        &config,
+        task.owner_addr.clone(),
+        task_request,
+        boundary,
+    )?;
+    item.paused = task.paused;
+    item.executions = task.executions;
+    let amount_for_one_task = item.amount_for_one_task.clone();
+
+    let hash = item.to_hash(&config.chain_name);
+    let recurring = item.recurring();
+    let hash_vec = hash.clone().into_bytes();
+    let attributes = if item.paused {
+        vec![]
+    } else {
+        slot_task(deps.storage, &env, &config, &item, &hash_vec)?
+    };
+    save_task(deps.storage, &item, &hash_vec)?;
+
+    // Imported balance moved to the task hash of this contract and verified
+    let manager_addr = get_manager_addr(&deps.querier, &config)?;
+    let manager_update_task_balance_msg = ManagerUpdateTaskBalance {
+        sender: task.owner_addr,
+        old_task_hash: old_task_hash.clone().into_bytes(),
+        new_task_hash: hash_vec,
+        recurring,
+        cw20: None,
+        amount_for_one_task,
+    }
+    .into_cosmos_msg(manager_addr, vec![])?;
+
+    // Imported task is new for the agents of this version
+    let agent_addr = get_agents_addr(&deps.querier, &config)?;
+    let agent_new_task_msg = AgentOnTaskCreated {}.into_cosmos_msg(agent_addr)?;
+    let response_data = TaskExecutionInfo {
+        block_height: env.block.height,
+        tx_info: env.transaction,
+        task_hash: hash.clone(),
+        owner_addr: item.owner_addr,
+        amount_for_one_task: item.amount_for_one_task,
+        version: item.version.clone(),
+    };
+    Ok(Response::new()
+        .set_data(to_binary(&response_data)?)
+        .add_attribute("action", "import_task")
+        .add_attributes(attributes)
+        .add_attribute("old_task_hash", old_task_hash)
+        .add_attribute("task_hash", hash)
+        .add_attribute("task_version", item.version)
+        .add_message(manager_update_task_balance_msg)
+        .add_message(agent_new_task_msg))
+}
+
+/// Validates the task request and builds the task out of it, boundary is validated by the caller
+fn build_task(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    owner_addr: Addr,
+    task: TaskRequest,
+    boundary: Boundary,
+) -> Result<(Task, Option<Cw20CoinVerified>), ContractError> {
+    let amount_for_one_task =
+        validate_msg_calculate_usage(deps, &task, &env.contract.address, &owner_addr, config)?;
     if amount_for_one_task.gas > config.gas_limit {
         return Err(ContractError::InvalidGas {});
     }
+    validate_non_zero_value(task.max_executions, "max_executions")?;
+    let callbacks = validate_callbacks(deps, task.callbacks, &env.contract.address, config)?;
     let cw20 = task
         .cw20
         .map(|human| {
@@ -379,110 +787,184 @@ fn execute_create_task(
         .transpose()?;
 
     let item = Task {
-        owner_addr: owner_addr.clone(),
+        owner_addr,
         interval: task.interval,
         boundary,
         stop_on_fail: task.stop_on_fail,
-        amount_for_one_task: amount_for_one_task.clone(),
+        amount_for_one_task,
         actions: task.actions,
         // NOTE: See process_queries in manager contract for details on limitations of malformed queries
         queries: task.queries.unwrap_or_default(),
         transforms: task.transforms.unwrap_or_default(),
         version: config.version.clone(),
+        paused: false,
+        max_executions: task.max_executions,
+        executions: 0,
+        callbacks,
+        query_rule: task.query_rule,
     };
+    if let Interval::ZonedCron { timezone, .. } = &item.interval {
+        if !timezone.is_valid() {
+            return Err(ContractError::InvalidTimezone {});
+        }
+    }
     if !item.interval.is_valid() {
         return Err(ContractError::InvalidInterval {});
     }
-    if !validate_queries(&deps.as_ref(), &item.queries) {
+    if !validate_queries(&deps, &item.queries) {
         return Err(ContractError::InvalidQueries {});
     }
     if !validate_transforms(&item) {
         return Err(ContractError::InvalidTransform {});
     }
+    if !validate_conditions(&item) {
+        return Err(ContractError::InvalidCondition {});
+    }
+    if !validate_query_rule(&item) {
+        return Err(ContractError::InvalidQueryRule {
+            max_depth: QUERY_RULE_MAX_DEPTH,
+        });
+    }
+    Ok((item, cw20))
+}
 
-    let hash_prefix = &config.chain_name;
-    let hash = item.to_hash(hash_prefix);
+/// Increments executions count of the task, that stays scheduled
+fn count_execution(storage: &mut dyn Storage, task: &Task, hash_vec: &[u8]) -> StdResult<()> {
+    let executed_task = Task {
+        executions: task.executions + 1,
+        ..task.clone()
+    };
+    tasks_map().save(storage, hash_vec, &executed_task)
+}
+
+/// Saves the task, slots are handled separately by `slot_task`
+fn save_task(storage: &mut dyn Storage, item: &Task, hash_vec: &[u8]) -> Result<(), ContractError> {
+    // Update query totals and map
+    TASKS_TOTAL.update(storage, |amt| -> StdResult<_> { Ok(amt + 1) })?;
+    tasks_map().update(storage, hash_vec, |old| match old {
+        Some(_) => Err(ContractError::TaskExists {}),
+        None => Ok(item.clone()),
+    })?;
+    // Live task hash shouldn't point to any other task
+    TASK_HASH_UPDATES.remove(storage, hash_vec);
+    Ok(())
+}
 
+/// Puts the task into the next slot, or evented lookup
+/// Returns attributes with the slot info
+fn slot_task(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    item: &Task,
+    hash_vec: &[u8],
+) -> Result<Vec<Attribute>, ContractError> {
     let (next_id, slot_kind) =
         item.interval
-            .next(&env, &item.boundary, config.slot_granularity_time);
+            .next(env, &item.boundary, config.slot_granularity_time);
     if next_id == 0 {
         return Err(ContractError::TaskEnded {});
     }
 
-    let recurring = item.recurring();
-    let hash_vec = hash.clone().into_bytes();
     let mut attributes: Vec<Attribute> = vec![];
 
-    // Update query totals and map
-    TASKS_TOTAL.update(deps.storage, |amt| -> StdResult<_> { Ok(amt + 1) })?;
-    tasks_map().update(deps.storage, &hash_vec, |old| match old {
-        Some(_) => Err(ContractError::TaskExists {}),
-        None => Ok(item.clone()),
-    })?;
-
     // Get previous task hashes in slot, add as needed
     let update_vec_data = |d: Option<Vec<Vec<u8>>>| -> StdResult<Vec<Vec<u8>>> {
         match d {
             // has some data, simply push new hash
             Some(data) => {
                 let mut s = data;
-                s.push(hash_vec.clone());
+                s.push(hash_vec.to_vec());
                 Ok(s)
             }
             // No data, push new vec & hash
-            None => Ok(vec![hash_vec.clone()]),
+            None => Ok(vec![hash_vec.to_vec()]),
         }
     };
 
     if item.is_evented() {
-        EVENTED_TASKS_LOOKUP.update(deps.storage, next_id, update_vec_data)?;
+        EVENTED_TASKS_LOOKUP.update(storage, next_id, update_vec_data)?;
         attributes.push(Attribute::new("evented_id", next_id.to_string()));
     } else {
         // Only scheduled tasks get put into slots
         match slot_kind {
             SlotType::Block => {
-                BLOCK_SLOTS.update(deps.storage, next_id, update_vec_data)?;
+                BLOCK_SLOTS.update(storage, next_id, update_vec_data)?;
             }
             SlotType::Cron => {
-                TIME_SLOTS.update(deps.storage, next_id, update_vec_data)?;
+                TIME_SLOTS.update(storage, next_id, update_vec_data)?;
             }
         }
         attributes.push(Attribute::new("slot_id", next_id.to_string()));
         attributes.push(Attribute::new("slot_kind", slot_kind.to_string()));
     }
+    Ok(attributes)
+}
 
-    // Save the current timestamp as the last time a task was created
-    LAST_TASK_CREATION.save(deps.storage, &env.block.time)?;
+/// Takes the task out of the slots, task balance stays in the manager
+fn execute_pause_task(
+    deps: DepsMut,
+    info: MessageInfo,
+    task_hash: String,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let hash = task_hash.as_bytes();
+    let mut task = tasks_map()
+        .may_load(deps.storage, hash)?
+        .ok_or(ContractError::NoTaskFound {})?;
+    if task.owner_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if task.paused {
+        return Err(ContractError::TaskPaused {});
+    }
 
-    let manager_addr = get_manager_addr(&deps.querier, &config)?;
-    let manager_create_task_balance_msg = ManagerCreateTaskBalance {
-        sender: owner_addr,
-        task_hash: hash_vec,
-        recurring,
-        cw20,
-        amount_for_one_task,
+    remove_task_from_slots(
+        deps.storage,
+        hash,
+        task.boundary.is_block(),
+        task.is_evented(),
+    )?;
+    task.paused = true;
+    tasks_map().save(deps.storage, hash, &task)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause_task")
+        .add_attribute("task_hash", task_hash))
+}
+
+/// Puts paused task back into the next slot
+fn execute_resume_task(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    task_hash: String,
+) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::ContractPaused);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let hash = task_hash.as_bytes();
+    let mut task = tasks_map()
+        .may_load(deps.storage, hash)?
+        .ok_or(ContractError::NoTaskFound {})?;
+    if task.owner_addr != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !task.paused {
+        return Err(ContractError::TaskNotPaused {});
     }
-    .into_cosmos_msg(manager_addr, info.funds)?;
 
-    let agent_addr = get_agents_addr(&deps.querier, &config)?;
-    let agent_new_task_msg = AgentOnTaskCreated {}.into_cosmos_msg(agent_addr)?;
-    let response_data = TaskExecutionInfo {
-        block_height: env.block.height,
-        tx_info: env.transaction,
-        task_hash: hash.clone(),
-        owner_addr: item.owner_addr,
-        amount_for_one_task: item.amount_for_one_task,
-        version: item.version.clone(),
-    };
+    task.paused = false;
+    let attributes = slot_task(deps.storage, &env, &config, &task, hash)?;
+    tasks_map().save(deps.storage, hash, &task)?;
+
     Ok(Response::new()
-        .set_data(to_binary(&response_data)?)
-        .add_attribute("action", "create_task")
+        .add_attribute("action", "resume_task")
         .add_attributes(attributes)
-        .add_attribute("task_hash", hash)
-        .add_attribute("task_version", item.version)
-        .add_message(manager_create_task_balance_msg)
-        .add_message(agent_new_task_msg))
+        .add_attribute("task_hash", task_hash))
 }
 
 pub fn execute_pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
@@ -509,6 +991,18 @@ pub fn execute_unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, Con
     Ok(Response::new().add_attribute("action", "unpause_contract"))
 }
 
+/// Migrate
+/// See [`migrate_contract`] for the allowed migrations
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_contract(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        STATE_MIGRATIONS,
+    )
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -538,6 +1032,21 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         } => to_binary(&query_tasks_by_owner(deps, owner_addr, from_index, limit)?),
         QueryMsg::Task { task_hash } => to_binary(&query_task(deps, task_hash)?),
         QueryMsg::TaskHash { task } => to_binary(&query_task_hash(deps, *task)?),
+        QueryMsg::SimulateTask {
+            task,
+            executions,
+            sender,
+        } => to_binary(
+            &query_simulate_task(deps, env, *task, executions, sender)
+                .map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
+        QueryMsg::UpdatedTaskHash { task_hash } => {
+            to_binary(&query_updated_task_hash(deps, task_hash)?)
+        }
+        QueryMsg::Template { template_id } => to_binary(&query_template(deps, template_id)?),
+        QueryMsg::Templates { from_index, limit } => {
+            to_binary(&query_templates(deps, from_index, limit)?)
+        }
         QueryMsg::SlotHashes { slot } => to_binary(&query_slot_hashes(deps, slot)?),
         QueryMsg::SlotIds { from_index, limit } => {
             to_binary(&query_slot_ids(deps, from_index, limit)?)
@@ -872,6 +1381,163 @@ fn query_task_hash(deps: Deps, task: Task) -> StdResult<String> {
     Ok(task.to_hash(&config.chain_name))
 }
 
+/// Runs the checks of the task creation, returning the error instead of failing,
+/// and calculates the deposit that covers given amount of executions.
+/// Task is simulated as created by the `sender`, contract itself if not set
+fn query_simulate_task(
+    deps: Deps,
+    env: Env,
+    task: TaskRequest,
+    executions: Option<u64>,
+    sender: Option<String>,
+) -> Result<SimulateTaskResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut errors: Vec<String> = vec![];
+    if PAUSED.load(deps.storage)? {
+        errors.push(ContractError::ContractPaused.to_string());
+    }
+    let owner_addr = match sender {
+        Some(sender) => deps.api.addr_validate(&sender)?,
+        None => env.contract.address.clone(),
+    };
+
+    let build_result = validate_boundary(&env.block, task.boundary.clone(), &task.interval)
+        .and_then(|boundary| build_task(deps, &env, &config, owner_addr, task, boundary));
+    let item = match build_result {
+        Ok((item, _)) => {
+            let hash = item.to_hash(&config.chain_name);
+            if tasks_map().has(deps.storage, hash.as_bytes()) {
+                errors.push(ContractError::TaskExists {}.to_string());
+            }
+            if item
+                .interval
+                .next(&env, &item.boundary, config.slot_granularity_time)
+                .0
+                == 0
+            {
+                errors.push(ContractError::TaskEnded {}.to_string());
+            }
+            Some(item)
+        }
+        Err(err) => {
+            errors.push(err.to_string());
+            None
+        }
+    };
+    let amount_for_one_task = item.as_ref().map(|item| item.amount_for_one_task.clone());
+
+    let manager_addr = get_manager_addr(&deps.querier, &config)?;
+    let manager_config: croncat_sdk_manager::types::Config = deps
+        .querier
+        .query_wasm_smart(manager_addr, &ManagerQueryMsg::Config {})?;
+    // Gas and its fees are paid in the gas denom of the task
+    let native_denom = amount_for_one_task
+        .as_ref()
+        .map(|amount| amount.gas_denom(&manager_config.native_denom).to_owned())
+        .unwrap_or(manager_config.native_denom);
+    // Recurring task has to cover at least two executions on creation
+    let recurring = item.as_ref().is_some_and(|item| item.recurring());
+    let min_executions = if recurring { 2 } else { 1 };
+    let executions = Uint128::from(executions.unwrap_or_default().max(min_executions));
+
+    let mut native_per_execution = coin(0, &native_denom);
+    let mut max_native_per_execution = coin(0, &native_denom);
+    let mut ibc_per_execution = None;
+    let mut cw20_per_execution = None;
+    if let Some(amount) = amount_for_one_task.as_ref() {
+        let gas_with_fees = amount
+            .gas_with_fees(amount.gas)
+            .map_err(|_| ContractError::InvalidGas {})?;
+        native_per_execution.amount = amount
+            .gas_price
+            .calculate(gas_with_fees)
+            .map_err(|_| ContractError::InvalidGas {})?
+            .into();
+        // Gas price source can raise the frozen price up to the tolerance of the task
+        let tolerance = amount.gas_price_tolerance.unwrap_or(100);
+        max_native_per_execution.amount = native_per_execution
+            .amount
+            .checked_multiply_ratio(tolerance, 100u16)
+            .map_err(|_| ContractError::InvalidGas {})?;
+        for coin in amount.coin.iter().flatten() {
+            if coin.denom == native_denom {
+                native_per_execution.amount += coin.amount;
+                max_native_per_execution.amount += coin.amount;
+            } else {
+                ibc_per_execution = Some(coin.clone());
+            }
+        }
+        cw20_per_execution = amount.cw20.clone();
+    }
+
+    let mut min_deposit = vec![];
+    if !native_per_execution.amount.is_zero() {
+        min_deposit.push(coin(
+            (native_per_execution.amount * executions).u128(),
+            &native_denom,
+        ));
+    }
+    if let Some(ibc) = &ibc_per_execution {
+        min_deposit.push(coin((ibc.amount * executions).u128(), &ibc.denom));
+    }
+    let min_cw20_deposit = cw20_per_execution.clone().map(|cw20| Cw20CoinVerified {
+        address: cw20.address,
+        amount: cw20.amount * executions,
+    });
+    Ok(SimulateTaskResponse {
+        amount_for_one_task,
+        native_per_execution,
+        max_native_per_execution,
+        ibc_per_execution,
+        cw20_per_execution,
+        min_deposit,
+        min_cw20_deposit,
+        errors,
+    })
+}
+
+/// Follows the chain of updates, returns the latest hash of the task, if task was ever updated
+fn query_updated_task_hash(deps: Deps, task_hash: String) -> StdResult<Option<String>> {
+    let mut latest_hash = None;
+    let mut current_hash = task_hash.into_bytes();
+    while let Some(new_hash) = TASK_HASH_UPDATES.may_load(deps.storage, &current_hash)? {
+        latest_hash = Some(new_hash.clone());
+        current_hash = new_hash;
+    }
+    latest_hash
+        .map(|hash| String::from_utf8(hash).map_err(StdError::invalid_utf8))
+        .transpose()
+}
+
+fn query_template(deps: Deps, template_id: u64) -> StdResult<Option<TaskTemplateResponse>> {
+    let template = TASK_TEMPLATES.may_load(deps.storage, template_id)?;
+    Ok(template.map(|template| TaskTemplateResponse {
+        template_id,
+        template,
+    }))
+}
+
+fn query_templates(
+    deps: Deps,
+    from_index: Option<u64>,
+    limit: Option<u64>,
+) -> StdResult<Vec<TaskTemplateResponse>> {
+    let from_index = from_index.unwrap_or(DEFAULT_PAGINATION_FROM_INDEX);
+    let limit = limit.unwrap_or(DEFAULT_PAGINATION_LIMIT);
+
+    TASK_TEMPLATES
+        .range(deps.storage, None, None, Order::Ascending)
+        .skip(from_index as usize)
+        .take(limit as usize)
+        .map(|template_res| {
+            template_res.map(|(template_id, template)| TaskTemplateResponse {
+                template_id,
+                template,
+            })
+        })
+        .collect()
+}
+
 fn query_slot_hashes(deps: Deps, slot: Option<u64>) -> StdResult<SlotHashesResponse> {
     let mut block_id: u64 = 0;
     let mut block_hashes: Vec<Vec<u8>> = Vec::new();
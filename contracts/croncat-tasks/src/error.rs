@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use croncat_sdk_core::error::MigrationError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,6 +13,15 @@ pub enum ContractError {
     #[error("Invalid interval")]
     InvalidInterval {},
 
+    #[error("Invalid timezone")]
+    InvalidTimezone {},
+
+    #[error("Max executions must be greater than the executions count")]
+    InvalidMaxExecutions {},
+
+    #[error("Task callback can't be a croncat contract")]
+    InvalidCallback {},
+
     #[error("Empty balance, must attach funds")]
     MustAttach {},
 
@@ -27,6 +37,12 @@ pub enum ContractError {
     #[error("Task transform is either looking at wrong indices or has malformed pointers")]
     InvalidTransform {},
 
+    #[error("Action condition must point to an existing query and conditional action must have a gas limit")]
+    InvalidCondition {},
+
+    #[error("Query rule must point to croncat queries and nest at most {max_depth} levels deep")]
+    InvalidQueryRule { max_depth: usize },
+
     #[error("Supplied address is not valid address")]
     InvalidAddress {},
 
@@ -48,9 +64,27 @@ pub enum ContractError {
     #[error("Task already exists")]
     TaskExists {},
 
+    #[error("Task is paused")]
+    TaskPaused {},
+
+    #[error("Task is not paused")]
+    TaskNotPaused {},
+
+    #[error("Updated task is identical to the existing one")]
+    TaskUnchanged {},
+
     #[error("No task found by hash")]
     NoTaskFound {},
 
+    #[error("No template found by id")]
+    NoTemplateFound {},
+
+    #[error("Template must have placeholders pointing to existing values of the task")]
+    InvalidTemplate {},
+
+    #[error("Params must be valid json values, one for each of the template placeholders")]
+    InvalidTemplateParams {},
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -63,6 +97,15 @@ pub enum ContractError {
     #[error("Invalid version key, please update it")]
     InvalidKey {},
 
+    #[error("Task can only be migrated to the latest tasks and manager contracts")]
+    InvalidTaskMigration {},
+
     #[error("Field must be non-zero: {field}")]
     InvalidZeroValue { field: String },
+
+    #[error("Gas can't be paid in {denom}, denom is not whitelisted")]
+    UnsupportedGasDenom { denom: String },
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
 }
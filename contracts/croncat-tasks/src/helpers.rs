@@ -1,15 +1,17 @@
 use cosmwasm_std::{
-    Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, Empty, Order, QuerierWrapper, StdError,
-    StdResult, Storage, WasmMsg, WasmQuery,
+    Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, DistributionMsg, Empty, IbcMsg, Order,
+    QuerierWrapper, StakingMsg, StdError, StdResult, Storage, WasmMsg, WasmQuery,
 };
 use croncat_sdk_tasks::types::{
-    AmountForOneTask, Boundary, BoundaryHeight, BoundaryTime, Config, CosmosQuery, Interval, Task,
-    TaskRequest,
+    AmountForOneTask, Boundary, BoundaryHeight, BoundaryTime, Config, CosmosQuery, Interval,
+    QueryRule, Task, TaskCallbacks, TaskRequest, TaskTemplate, TransformOperand,
+    TransformOperation,
 };
 use cw20::{Cw20CoinVerified, Cw20ExecuteMsg};
 use serde_cw_value::Value;
 
 use crate::{
+    contract::QUERY_RULE_MAX_DEPTH,
     state::{tasks_map, BLOCK_SLOTS, EVENTED_TASKS_LOOKUP, TASKS_TOTAL, TIME_SLOTS},
     ContractError,
 };
@@ -20,7 +22,14 @@ pub(crate) fn validate_boundary(
     interval: &Interval,
 ) -> Result<Boundary, ContractError> {
     match (interval, boundary) {
-        (Interval::Cron(_), Some(Boundary::Time(boundary_time))) => {
+        (
+            Interval::Cron(_) | Interval::ZonedCron { .. } | Interval::Seconds(_),
+            Some(Boundary::Time(mut boundary_time)),
+        ) => {
+            // Periods of the seconds interval are counted from the start, creation time by default
+            if matches!(interval, Interval::Seconds(_)) && boundary_time.start.is_none() {
+                boundary_time.start = Some(block_info.time);
+            }
             let starting_time = boundary_time.start.unwrap_or(block_info.time);
             if boundary_time.end.map_or(false, |e| e <= starting_time) {
                 Err(ContractError::InvalidBoundary {})
@@ -45,8 +54,14 @@ pub(crate) fn validate_boundary(
                 Ok(Boundary::Height(boundary_height))
             }
         }
-        (Interval::Cron(_), None) => Ok(Boundary::Time(BoundaryTime {
-            start: None,
+        (Interval::Cron(_) | Interval::ZonedCron { .. }, None) => {
+            Ok(Boundary::Time(BoundaryTime {
+                start: None,
+                end: None,
+            }))
+        }
+        (Interval::Seconds(_), None) => Ok(Boundary::Time(BoundaryTime {
+            start: Some(block_info.time),
             end: None,
         })),
         (_, None) => Ok(Boundary::Height(BoundaryHeight {
@@ -57,6 +72,36 @@ pub(crate) fn validate_boundary(
     }
 }
 
+/// Imported task could have started already, so its boundary isn't checked against the current block
+pub(crate) fn validate_imported_boundary(
+    block_info: &BlockInfo,
+    boundary: Boundary,
+    interval: &Interval,
+) -> Result<Boundary, ContractError> {
+    match (interval, boundary) {
+        (
+            Interval::Cron(_) | Interval::ZonedCron { .. } | Interval::Seconds(_),
+            Boundary::Time(mut boundary_time),
+        ) => {
+            if matches!(interval, Interval::Seconds(_)) && boundary_time.start.is_none() {
+                boundary_time.start = Some(block_info.time);
+            }
+            match (boundary_time.start, boundary_time.end) {
+                (Some(start), Some(end)) if end <= start => Err(ContractError::InvalidBoundary {}),
+                _ => Ok(Boundary::Time(boundary_time)),
+            }
+        }
+        (
+            Interval::Block(_) | Interval::Once | Interval::Immediate,
+            Boundary::Height(boundary_height),
+        ) => match (boundary_height.start, boundary_height.end) {
+            (Some(start), Some(end)) if end <= start => Err(ContractError::InvalidBoundary {}),
+            _ => Ok(Boundary::Height(boundary_height)),
+        },
+        _ => Err(ContractError::InvalidBoundary {}),
+    }
+}
+
 /// Query against all to validate the query is possible, rather than open ended failures
 /// This does NOT evaluate the contents which could change, allowing reactivity later.
 /// Errors are assessed against contract and method availability
@@ -142,6 +187,30 @@ pub(crate) fn validate_transforms(task: &Task) -> bool {
             return false;
         }
 
+        // Operations need the type of the result
+        if !transform.operations.is_empty() && transform.result_type.is_none() {
+            return false;
+        }
+
+        // Validate operands point to the existing queries and there is no division by zero
+        for operation in transform.operations.iter() {
+            match (operation, operation.operand()) {
+                (TransformOperation::Div(TransformOperand::Constant(divisor)), _)
+                    if divisor.is_zero() =>
+                {
+                    return false;
+                }
+                (
+                    _,
+                    Some(TransformOperand::Query {
+                        query_idx: operand_query_idx,
+                        ..
+                    }),
+                ) if *operand_query_idx as usize >= task.queries.len() => return false,
+                _ => (),
+            }
+        }
+
         // Validate action path
         if let Some(action) = task.actions.get(transform.action_idx as usize) {
             // NOTE: This only covers the supported methods known to valid task actions!
@@ -165,6 +234,15 @@ pub(crate) fn validate_transforms(task: &Task) -> bool {
                         return false;
                     }
                 }
+                CosmosMsg::Staking(_) | CosmosMsg::Distribution(_) | CosmosMsg::Ibc(_) => {
+                    let mut action_value = serde_json_wasm::from_str::<Value>(
+                        &serde_json_wasm::to_string(&action.msg).unwrap(),
+                    )
+                    .unwrap();
+                    if transform.action_path.find_value(&mut action_value).is_err() {
+                        return false;
+                    }
+                }
                 _ => return false,
             }
         }
@@ -172,6 +250,130 @@ pub(crate) fn validate_transforms(task: &Task) -> bool {
     true
 }
 
+/// Validate the action conditions point to the existing queries
+/// Conditional actions need the gas limit, so skipped action gas is known
+/// NOTE: Cannot validate the query response path, as it is determined at time of execution
+pub(crate) fn validate_conditions(task: &Task) -> bool {
+    task.actions.iter().all(|action| match &action.condition {
+        Some(condition) => {
+            (condition.query_idx as usize) < task.queries.len() && action.gas_limit.is_some()
+        }
+        None => true,
+    })
+}
+
+/// Validate the query rule points only to the croncat queries and doesn't nest too deep
+pub(crate) fn validate_query_rule(task: &Task) -> bool {
+    fn validate(rule: &QueryRule, queries: &[CosmosQuery], depth: usize) -> bool {
+        if depth > QUERY_RULE_MAX_DEPTH {
+            return false;
+        }
+        match rule {
+            QueryRule::Query(idx) => {
+                matches!(queries.get(*idx as usize), Some(CosmosQuery::Croncat(_)))
+            }
+            QueryRule::And(rules) | QueryRule::Or(rules) => {
+                !rules.is_empty() && rules.iter().all(|rule| validate(rule, queries, depth + 1))
+            }
+            QueryRule::Not(rule) => validate(rule, queries, depth + 1),
+        }
+    }
+
+    match &task.query_rule {
+        Some(rule) => validate(rule, &task.queries, 1),
+        None => true,
+    }
+}
+
+/// Messages of the wasm execute actions in the json value of the task
+fn wasm_execute_msgs(task_value: &mut Value) -> Vec<&mut Value> {
+    fn field<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+        match value {
+            Value::Map(map) => map.get_mut(&Value::String(key.to_owned())),
+            _ => None,
+        }
+    }
+
+    match field(task_value, "actions") {
+        Some(Value::Seq(actions)) => actions
+            .iter_mut()
+            .filter_map(|action| {
+                field(action, "msg")
+                    .and_then(|msg| field(msg, "wasm"))
+                    .and_then(|wasm| field(wasm, "execute"))
+                    .and_then(|execute| field(execute, "msg"))
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Json value of the template task, placeholders point into it.
+/// Json messages of the wasm execute actions are decoded,
+/// so placeholders can point inside them, like into the cw20 transfers
+fn template_task_value(task: &TaskRequest) -> Result<Value, ContractError> {
+    let mut task_value: Value = serde_json_wasm::to_vec(task)
+        .ok()
+        .and_then(|json| serde_json_wasm::from_slice(&json).ok())
+        .ok_or(ContractError::InvalidTemplate {})?;
+    for msg in wasm_execute_msgs(&mut task_value) {
+        let decoded = match msg {
+            Value::String(encoded) => Binary::from_base64(encoded)
+                .ok()
+                .and_then(|binary| cosmwasm_std::from_binary(&binary).ok()),
+            _ => None,
+        };
+        if let Some(decoded @ Value::Map(_)) = decoded {
+            *msg = decoded;
+        }
+    }
+    Ok(task_value)
+}
+
+/// Template needs at least one placeholder and every placeholder has to point at the existing value
+pub(crate) fn validate_template(template: &TaskTemplate) -> Result<(), ContractError> {
+    if template.placeholders.is_empty() {
+        return Err(ContractError::InvalidTemplate {});
+    }
+    let mut task_value = template_task_value(&template.task)?;
+    for placeholder in template.placeholders.iter() {
+        placeholder
+            .path
+            .find_value(&mut task_value)
+            .map_err(|_| ContractError::InvalidTemplate {})?;
+    }
+    Ok(())
+}
+
+/// Replaces the placeholders of the template with the given params
+/// NOTE: Resulting task goes through the regular task validation on creation
+pub(crate) fn fill_template(
+    template: &TaskTemplate,
+    params: Vec<Binary>,
+) -> Result<TaskRequest, ContractError> {
+    if params.len() != template.placeholders.len() {
+        return Err(ContractError::InvalidTemplateParams {});
+    }
+    let mut task_value = template_task_value(&template.task)?;
+    for (placeholder, param) in template.placeholders.iter().zip(params) {
+        let param_value: Value = cosmwasm_std::from_binary(&param)
+            .map_err(|_| ContractError::InvalidTemplateParams {})?;
+        *placeholder.path.find_value(&mut task_value)? = param_value;
+    }
+    // Encode the decoded messages back
+    for msg in wasm_execute_msgs(&mut task_value) {
+        if let Value::Map(_) = msg {
+            let encoded = serde_json_wasm::to_vec(msg)
+                .map_err(|_| ContractError::InvalidTemplateParams {})?;
+            *msg = Value::String(Binary(encoded).to_base64());
+        }
+    }
+    serde_json_wasm::to_vec(&task_value)
+        .ok()
+        .and_then(|json| serde_json_wasm::from_slice(&json).ok())
+        .ok_or(ContractError::InvalidTemplateParams {})
+}
+
 /// Check for calls of our contracts
 pub(crate) fn check_for_self_calls(
     tasks_addr: &Addr,
@@ -217,13 +419,42 @@ pub(crate) fn validate_msg_calculate_usage(
         &croncat_sdk_manager::msg::ManagerQueryMsg::Config {},
     )?;
 
+    // Native denom is not stored, gas price source reads the price of the native denom only
+    let gas_denom = task
+        .gas_denom
+        .clone()
+        .filter(|denom| denom != &manager_config.native_denom);
+    let (gas_price, gas_price_tolerance) = match &gas_denom {
+        Some(denom) => {
+            let gas_price = manager_config.gas_price_of(denom).cloned().ok_or_else(|| {
+                ContractError::UnsupportedGasDenom {
+                    denom: denom.clone(),
+                }
+            })?;
+            (gas_price, None)
+        }
+        None => (
+            manager_config.gas_price.clone(),
+            manager_config
+                .gas_price_source
+                .limits()
+                .map(|limits| limits.tolerance),
+        ),
+    };
+    // Undelegated stake returns to the gas balance of the task, so it has to be in native coins
+    let staking_denom = gas_denom
+        .is_none()
+        .then_some(manager_config.native_denom.as_str());
+
     let mut amount_for_one_task = AmountForOneTask {
         cw20: None,
         coin: [None, None],
         gas: config.gas_base_fee,
         agent_fee: manager_config.agent_fee,
         treasury_fee: manager_config.treasury_fee,
-        gas_price: manager_config.gas_price,
+        gas_price,
+        gas_price_tolerance,
+        gas_denom,
     };
 
     if task.actions.is_empty() {
@@ -232,6 +463,53 @@ pub(crate) fn validate_msg_calculate_usage(
     for action in task.actions.iter() {
         amount_for_one_task.add_gas(action.gas_limit.unwrap_or(config.gas_action_fee));
 
+        // Only ibc transfers have a timeout, authz actions can't be ibc transfers
+        if action.ibc_timeout.is_some()
+            && (action.authz || !matches!(action.msg, CosmosMsg::Ibc(IbcMsg::Transfer { .. })))
+        {
+            return Err(ContractError::InvalidAction {});
+        }
+
+        // Coins of the authz actions are taken from the owner's wallet, no deposit needed
+        if action.authz {
+            match &action.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr,
+                    funds,
+                    msg,
+                }) => {
+                    if action.gas_limit.is_none() {
+                        return Err(ContractError::NoGasLimit {});
+                    }
+                    if funds.iter().any(|coin| coin.amount.is_zero()) {
+                        return Err(ContractError::InvalidAction {});
+                    }
+                    check_for_self_calls(
+                        &deps.api.addr_validate(self_addr.as_str())?,
+                        &deps.api.addr_validate(manager_addr.as_str())?,
+                        &deps.api.addr_validate(agents_addr.as_str())?,
+                        &deps.api.addr_validate(config.owner_addr.as_str())?,
+                        &deps.api.addr_validate(sender.as_str())?,
+                        &deps.api.addr_validate(contract_addr.as_str())?,
+                        msg,
+                    )?;
+                }
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    if deps.api.addr_validate(to_address).is_err() {
+                        return Err(ContractError::InvalidAddress {});
+                    }
+                    if amount.is_empty() || amount.iter().any(|coin| coin.amount.is_zero()) {
+                        return Err(ContractError::InvalidAction {});
+                    }
+                }
+                // Only messages manager can encode for `MsgExec`
+                _ => {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
+            continue;
+        }
+
         match &action.msg {
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr,
@@ -297,6 +575,59 @@ pub(crate) fn validate_msg_calculate_usage(
                     }
                 }
             }
+            CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+                // Delegated coins leave the task balance, same as bank sends
+                if validator.is_empty()
+                    || amount.amount.is_zero()
+                    || Some(amount.denom.as_str()) != staking_denom
+                    || !amount_for_one_task.add_coin(amount.clone())?
+                {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
+            // Manager only lets the task move or return the stake it delegated
+            CosmosMsg::Staking(StakingMsg::Undelegate { validator, amount }) => {
+                if validator.is_empty()
+                    || amount.amount.is_zero()
+                    || Some(amount.denom.as_str()) != staking_denom
+                {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
+            CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator,
+                dst_validator,
+                amount,
+            }) => {
+                if src_validator.is_empty()
+                    || src_validator == dst_validator
+                    || amount.amount.is_zero()
+                    || Some(amount.denom.as_str()) != staking_denom
+                {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward { validator }) => {
+                if validator.is_empty() {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
+            // Timeout is set by the action, as it's relative to the execution
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id,
+                to_address,
+                amount,
+                ..
+            }) => {
+                if channel_id.is_empty()
+                    || to_address.is_empty()
+                    || amount.amount.is_zero()
+                    || matches!(action.ibc_timeout, None | Some(0))
+                    || !amount_for_one_task.add_coin(amount.clone())?
+                {
+                    return Err(ContractError::InvalidAction {});
+                }
+            }
             // Disallow unknown messages
             _ => {
                 return Err(ContractError::InvalidAction {});
@@ -310,6 +641,46 @@ pub(crate) fn validate_msg_calculate_usage(
     Ok(amount_for_one_task)
 }
 
+/// Validates callback contracts of the task, callbacks can't call croncat contracts
+/// Returns callbacks with normalized addresses and gas limit
+pub(crate) fn validate_callbacks(
+    deps: Deps,
+    callbacks: Option<TaskCallbacks>,
+    self_addr: &Addr,
+    config: &Config,
+) -> Result<Option<TaskCallbacks>, ContractError> {
+    let Some(callbacks) = callbacks else {
+        return Ok(None);
+    };
+    if callbacks.on_complete.is_none() && callbacks.on_failure.is_none() {
+        return Ok(None);
+    }
+    let gas_limit = callbacks.gas_limit.unwrap_or(config.gas_action_fee);
+    if gas_limit == 0 || gas_limit > config.gas_limit {
+        return Err(ContractError::InvalidGas {});
+    }
+
+    let manager_addr = get_manager_addr(&deps.querier, config)?;
+    let agents_addr = get_agents_addr(&deps.querier, config)?;
+    let validate_callback = |callback: Option<String>| -> Result<Option<String>, ContractError> {
+        callback
+            .map(|addr| {
+                let addr = deps.api.addr_validate(&addr)?;
+                if addr == *self_addr || addr == manager_addr || addr == agents_addr {
+                    Err(ContractError::InvalidCallback {})
+                } else {
+                    Ok(addr.into_string())
+                }
+            })
+            .transpose()
+    };
+    Ok(Some(TaskCallbacks {
+        on_complete: validate_callback(callbacks.on_complete)?,
+        on_failure: validate_callback(callbacks.on_failure)?,
+        gas_limit: Some(gas_limit),
+    }))
+}
+
 pub(crate) fn remove_task(
     storage: &mut dyn Storage,
     hash: &[u8],
@@ -318,17 +689,24 @@ pub(crate) fn remove_task(
 ) -> StdResult<()> {
     tasks_map().remove(storage, hash)?;
     TASKS_TOTAL.update(storage, |total| StdResult::Ok(total - 1))?;
+    remove_task_from_slots(storage, hash, is_block, is_evented)
+}
+
+/// Takes task hash out of the slots or evented lookup, task itself stays untouched
+pub(crate) fn remove_task_from_slots(
+    storage: &mut dyn Storage,
+    hash: &[u8],
+    is_block: bool,
+    is_evented: bool,
+) -> StdResult<()> {
     if is_evented {
         let hashes = EVENTED_TASKS_LOOKUP
             .range(storage, None, None, Order::Ascending)
             .collect::<StdResult<Vec<_>>>()?;
         for (hid, mut all_hashes) in hashes {
-            let mut found = false;
-            all_hashes.retain(|h| {
-                found = h == hash;
-                !found
-            });
-            if found {
+            let len = all_hashes.len();
+            all_hashes.retain(|h| h != hash);
+            if all_hashes.len() != len {
                 if all_hashes.is_empty() {
                     EVENTED_TASKS_LOOKUP.remove(storage, hid);
                 } else {
@@ -342,12 +720,9 @@ pub(crate) fn remove_task(
             .range(storage, None, None, Order::Ascending)
             .collect::<StdResult<Vec<_>>>()?;
         for (bid, mut block_hashes) in blocks {
-            let mut found = false;
-            block_hashes.retain(|h| {
-                found = h == hash;
-                !found
-            });
-            if found {
+            let len = block_hashes.len();
+            block_hashes.retain(|h| h != hash);
+            if block_hashes.len() != len {
                 if block_hashes.is_empty() {
                     BLOCK_SLOTS.remove(storage, bid);
                 } else {
@@ -361,12 +736,9 @@ pub(crate) fn remove_task(
             .range(storage, None, None, Order::Ascending)
             .collect::<StdResult<Vec<_>>>()?;
         for (tid, mut time_hashes) in time_buckets {
-            let mut found = false;
-            time_hashes.retain(|h| {
-                found = h == hash;
-                !found
-            });
-            if found {
+            let len = time_hashes.len();
+            time_hashes.retain(|h| h != hash);
+            if time_hashes.len() != len {
                 if time_hashes.is_empty() {
                     TIME_SLOTS.remove(storage, tid);
                 } else {
@@ -421,6 +793,62 @@ pub(crate) fn get_agents_addr(
         .ok_or(ContractError::InvalidKey {})
 }
 
+/// Get address of the given contract version, only if it's the latest version in the factory
+pub(crate) fn get_latest_addr(
+    deps_queries: &QuerierWrapper<Empty>,
+    config: &Config,
+    contract_name: &str,
+    version: [u8; 2],
+) -> Result<Addr, ContractError> {
+    let addr = croncat_sdk_factory::state::CONTRACT_ADDRS
+        .query(
+            deps_queries,
+            config.croncat_factory_addr.clone(),
+            (contract_name, &version),
+        )?
+        .ok_or(ContractError::InvalidKey {})?;
+    let latest_addr = croncat_sdk_factory::state::LATEST_ADDRS.query(
+        deps_queries,
+        config.croncat_factory_addr.clone(),
+        contract_name,
+    )?;
+    if latest_addr.as_ref() != Some(&addr) {
+        return Err(ContractError::InvalidTaskMigration {});
+    }
+
+    Ok(addr)
+}
+
+/// Get name of the given contract, registered in the factory
+pub(crate) fn get_contract_name(
+    deps_queries: &QuerierWrapper<Empty>,
+    config: &Config,
+    contract_addr: &Addr,
+) -> Result<Option<String>, ContractError> {
+    let contract_name = croncat_sdk_factory::state::CONTRACT_ADDRS_LOOKUP.query(
+        deps_queries,
+        config.croncat_factory_addr.clone(),
+        contract_addr.clone(),
+    )?;
+    Ok(contract_name)
+}
+
+/// Check if sender is the other version of this contract, registered in the factory
+pub(crate) fn check_if_sender_is_other_version(
+    deps_queries: &QuerierWrapper<Empty>,
+    config: &Config,
+    contract_addr: &Addr,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let contract_name = get_contract_name(deps_queries, config, contract_addr)?;
+    let sender_name = get_contract_name(deps_queries, config, sender)?;
+    if contract_name.is_none() || contract_name != sender_name {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{Timestamp, Uint64};
@@ -775,6 +1203,63 @@ mod tests {
                 Timestamp::from_nanos(123456),
                 Err(ContractError::InvalidBoundary {}),
             ),
+            // Seconds interval, starts at the creation time if start not set
+            (
+                Interval::Seconds(90),
+                None,
+                123,
+                Timestamp::from_nanos(123456),
+                Ok(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(123456)),
+                    end: None,
+                })),
+            ),
+            (
+                Interval::Seconds(90),
+                Some(Boundary::Time(BoundaryTime {
+                    start: None,
+                    end: Some(Timestamp::from_nanos(1234567)),
+                })),
+                123,
+                Timestamp::from_nanos(123456),
+                Ok(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(123456)),
+                    end: Some(Timestamp::from_nanos(1234567)),
+                })),
+            ),
+            (
+                Interval::Seconds(90),
+                Some(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(123456)),
+                    end: Some(Timestamp::from_nanos(1234567)),
+                })),
+                123,
+                Timestamp::from_nanos(123456),
+                Ok(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(123456)),
+                    end: Some(Timestamp::from_nanos(1234567)),
+                })),
+            ),
+            (
+                Interval::Seconds(90),
+                Some(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(123456)),
+                    end: Some(Timestamp::from_nanos(123456)),
+                })),
+                123,
+                Timestamp::from_nanos(123456),
+                Err(ContractError::InvalidBoundary {}),
+            ),
+            (
+                Interval::Seconds(90),
+                Some(Boundary::Height(BoundaryHeight {
+                    start: None,
+                    end: None,
+                })),
+                123,
+                Timestamp::from_nanos(123456),
+                Err(ContractError::InvalidBoundary {}),
+            ),
         ];
         for (interval, boundary, height, time, expected_res) in cases {
             let block_info = BlockInfo {
@@ -786,4 +1271,69 @@ mod tests {
             assert_eq!(res, expected_res)
         }
     }
+
+    #[test]
+    fn validate_imported_boundary_cases() {
+        let block_info = BlockInfo {
+            height: 1000,
+            time: Timestamp::from_seconds(1000),
+            chain_id: "cron".to_owned(),
+        };
+        let cases: Vec<(Interval, Boundary, Result<Boundary, ContractError>)> = vec![
+            // Task ended before the import, but it's not rejected here
+            (
+                Interval::Block(5),
+                Boundary::Height(BoundaryHeight {
+                    start: None,
+                    end: Some(Uint64::new(500)),
+                }),
+                Ok(Boundary::Height(BoundaryHeight {
+                    start: None,
+                    end: Some(Uint64::new(500)),
+                })),
+            ),
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_seconds(100)),
+                    end: Some(Timestamp::from_seconds(500)),
+                }),
+                Ok(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_seconds(100)),
+                    end: Some(Timestamp::from_seconds(500)),
+                })),
+            ),
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: None,
+                    end: None,
+                }),
+                Ok(Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_seconds(1000)),
+                    end: None,
+                })),
+            ),
+            (
+                Interval::Once,
+                Boundary::Height(BoundaryHeight {
+                    start: Some(Uint64::new(500)),
+                    end: Some(Uint64::new(500)),
+                }),
+                Err(ContractError::InvalidBoundary {}),
+            ),
+            (
+                Interval::Cron("0 * * * * *".to_owned()),
+                Boundary::Height(BoundaryHeight {
+                    start: None,
+                    end: None,
+                }),
+                Err(ContractError::InvalidBoundary {}),
+            ),
+        ];
+        for (interval, boundary, expected_res) in cases {
+            let res = validate_imported_boundary(&block_info, boundary, &interval);
+            assert_eq!(res, expected_res)
+        }
+    }
 }
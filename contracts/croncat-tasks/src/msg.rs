@@ -1,3 +1,4 @@
 pub use croncat_sdk_tasks::msg::{
-    TasksExecuteMsg as ExecuteMsg, TasksInstantiateMsg as InstantiateMsg, TasksQueryMsg as QueryMsg,
+    TasksExecuteMsg as ExecuteMsg, TasksInstantiateMsg as InstantiateMsg,
+    TasksMigrateMsg as MigrateMsg, TasksQueryMsg as QueryMsg,
 };
@@ -1,5 +1,5 @@
 use cosmwasm_std::{Addr, Timestamp, Uint64};
-use croncat_sdk_tasks::types::{Boundary, Config, Task};
+use croncat_sdk_tasks::types::{Boundary, Config, Task, TaskTemplate};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -22,9 +22,19 @@ pub const BLOCK_SLOTS: Map<u64, Vec<Vec<u8>>> = Map::new("block_slots");
 /// key: Boundary Start - either height or time :: defaults to 0
 pub const EVENTED_TASKS_LOOKUP: Map<u64, Vec<Vec<u8>>> = Map::new("evented_task_lookup");
 
+/// Old task hash to the new one, saved every time owner updates the task
+/// Hashes of live tasks never point anywhere, so following the chain always ends
+pub const TASK_HASH_UPDATES: Map<&[u8], Vec<u8>> = Map::new("task_hash_updates");
+
 /// Last task creation timestamp
 pub const LAST_TASK_CREATION: Item<Timestamp> = Item::new("last_task_creation");
 
+/// Registered task templates by id
+pub const TASK_TEMPLATES: Map<u64, TaskTemplate> = Map::new("task_templates");
+
+/// Id of the next registered template
+pub const TEMPLATE_NONCE: Item<u64> = Item::new("template_nonce");
+
 // TODO: make IndexedMap's const as soon as cw_storage_plus new version arrives
 pub fn tasks_map<'a>() -> IndexedMap<'a, &'a [u8], Task, TaskIndexes<'a>> {
     let indexes = TaskIndexes {
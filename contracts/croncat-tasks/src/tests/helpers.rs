@@ -146,6 +146,10 @@ pub(crate) fn init_agents(app: &mut App, factory_addr: &Addr) -> Addr {
         min_active_agent_count: None,
         allowed_agents: Some(vec![]),
         public_registration: true,
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     };
     let module_instantiate_info = ModuleInstantiateInfo {
         code_id,
@@ -210,6 +214,7 @@ pub(crate) fn activate_agent(app: &mut App, agents_contract: &Addr) {
         agents_contract.clone(),
         &croncat_agents::msg::ExecuteMsg::RegisterAgent {
             payable_account_id: None,
+            payout: None,
         },
         &[],
     )
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use crate::{
-        helpers::validate_transforms,
+        helpers::{fill_template, validate_template, validate_transforms},
         msg::InstantiateMsg,
         tests::{
             helpers::{
@@ -11,15 +11,18 @@ mod tests {
             ANYONE, DENOM, PARTICIPANT1,
         },
     };
-    use cosmwasm_std::{coins, to_binary, Addr, BankMsg, Binary, CosmosMsg, WasmMsg, WasmQuery};
+    use cosmwasm_std::{
+        coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal256, Uint128,
+        WasmMsg, WasmQuery,
+    };
     use croncat_mod_balances::msg::QueryMsg as BalancesQueryMsg;
     use croncat_mod_generic::types::{PathToValue, ValueIndex};
     use croncat_sdk_core::types::AmountForOneTask;
     use croncat_sdk_tasks::types::{
         Action, Boundary, BoundaryTime, CosmosQuery, CroncatQuery, Interval, Task, TaskRequest,
-        Transform,
+        TaskTemplate, TemplatePlaceholder, Transform, TransformOperation, TransformResultType,
     };
-    use cw20::Cw20QueryMsg;
+    use cw20::{Cw20ExecuteMsg, Cw20QueryMsg};
     use cw_multi_test::Executor;
 
     #[test]
@@ -121,10 +124,17 @@ mod tests {
                     }
                     .into(),
                     gas_limit: Some(50_000),
+                    authz: false,
+                    condition: None,
+                    ibc_timeout: None,
                 }],
                 queries: Some(qs.clone()),
                 transforms: None,
                 cw20: None,
+                max_executions: None,
+                callbacks: None,
+                gas_denom: None,
+                query_rule: None,
             };
 
             let res = app.execute_contract(
@@ -158,6 +168,9 @@ mod tests {
                 funds: vec![],
             }),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         };
 
         let query = CosmosQuery::Wasm(WasmQuery::Smart {
@@ -180,6 +193,8 @@ mod tests {
             query_idx: 0,
             query_response_path,
             action_path,
+            operations: vec![],
+            result_type: None,
         };
 
         let mut task = Task {
@@ -194,7 +209,12 @@ mod tests {
             }),
             stop_on_fail: true,
             version: "1.0".to_string(),
+            paused: false,
+            max_executions: None,
+            executions: 0,
+            callbacks: None,
             amount_for_one_task: AmountForOneTask::default(),
+            query_rule: None,
         };
 
         assert!(validate_transforms(&task));
@@ -209,6 +229,15 @@ mod tests {
         assert!(!validate_transforms(&task));
         task.transforms[0].query_idx = 0;
 
+        // Test operations without the result type
+        task.transforms[0].operations =
+            vec![TransformOperation::Percent(Decimal256::percent(5000))];
+        assert!(!validate_transforms(&task));
+        task.transforms[0].result_type = Some(TransformResultType::Uint128);
+        assert!(validate_transforms(&task));
+        task.transforms[0].operations = vec![];
+        task.transforms[0].result_type = None;
+
         // Test invalid action path
         task.transforms[0]
             .action_path
@@ -216,4 +245,77 @@ mod tests {
             .push(ValueIndex::from("invalid_key".to_string()));
         assert!(!validate_transforms(&task));
     }
+
+    #[test]
+    fn test_fill_cw20_template() {
+        let transfer = |recipient: &str, amount: u128| Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_owned(),
+            amount: Uint128::new(amount),
+        };
+        let task_with_msg = |msg: Binary| TaskRequest {
+            interval: Interval::Once,
+            boundary: None,
+            stop_on_fail: false,
+            actions: vec![Action {
+                msg: WasmMsg::Execute {
+                    contract_addr: "cw20".to_owned(),
+                    msg,
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: Some(150_000),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            }],
+            queries: None,
+            transforms: None,
+            cw20: None,
+            max_executions: None,
+            callbacks: None,
+            gas_denom: None,
+            query_rule: None,
+        };
+        let transfer_path = |key: &str| {
+            PathToValue::from(vec![
+                ValueIndex::Key("actions".to_string()),
+                ValueIndex::Index(0),
+                ValueIndex::Key("msg".to_string()),
+                ValueIndex::Key("wasm".to_string()),
+                ValueIndex::Key("execute".to_string()),
+                ValueIndex::Key("msg".to_string()),
+                ValueIndex::Key("transfer".to_string()),
+                ValueIndex::Key(key.to_string()),
+            ])
+        };
+        let template = TaskTemplate {
+            owner_addr: Addr::unchecked(ANYONE),
+            name: "Cw20 transfer".to_owned(),
+            task: task_with_msg(to_binary(&transfer("receiver", 1)).unwrap()),
+            placeholders: vec![
+                TemplatePlaceholder {
+                    name: "Receiver".to_owned(),
+                    path: transfer_path("recipient"),
+                },
+                TemplatePlaceholder {
+                    name: "Amount".to_owned(),
+                    path: transfer_path("amount"),
+                },
+            ],
+        };
+        // Placeholders point inside the wasm message
+        validate_template(&template).unwrap();
+
+        let task = fill_template(
+            &template,
+            vec![to_binary("bob").unwrap(), to_binary("20").unwrap()],
+        )
+        .unwrap();
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = &task.actions[0].msg else {
+            panic!("Filled action has to stay wasm execute");
+        };
+        let filled_msg: Cw20ExecuteMsg = from_binary(msg).unwrap();
+        assert_eq!(filled_msg, transfer("bob", 20));
+        assert_eq!(task, task_with_msg(msg.clone()));
+    }
 }
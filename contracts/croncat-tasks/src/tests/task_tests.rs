@@ -3,28 +3,34 @@ use crate::tests::{
     PARTICIPANT1, PARTICIPANT2, PAUSE_ADMIN,
 };
 use cosmwasm_std::{
-    coin, coins, from_binary, to_binary, Addr, BankMsg, StakingMsg, StdError, Timestamp, Uint128,
-    Uint64, WasmMsg,
+    coin, coins, from_binary, to_binary, Addr, BankMsg, Decimal, DistributionMsg, IbcMsg,
+    IbcTimeout, IbcTimeoutBlock, StakingMsg, StdError, Timestamp, Uint128, Uint64, WasmMsg,
+    WasmQuery,
 };
 use croncat_mod_generic::types::{PathToValue, ValueIndex};
+use croncat_sdk_core::error::MigrationError;
 use croncat_sdk_core::types::{AmountForOneTask, GasPrice};
 use croncat_sdk_factory::msg::{
     ContractMetadataResponse, FactoryExecuteMsg, ModuleInstantiateInfo, VersionKind,
 };
 use croncat_sdk_manager::{
     msg::ManagerExecuteMsg,
-    types::{TaskBalance, TaskBalanceResponse},
+    types::{
+        GasPriceLimits, GasPriceSource, TaskBalance, TaskBalanceResponse, TaskExecutionRecord,
+        UpdateConfig,
+    },
 };
 use croncat_sdk_tasks::{
     msg::UpdateConfigMsg,
     types::{
         Action, Boundary, BoundaryHeight, BoundaryTime, Config, CosmosQuery, CroncatQuery,
-        CurrentTaskInfoResponse, Interval, SlotHashesResponse, SlotTasksTotalResponse, Task,
-        TaskExecutionInfo, TaskInfo, TaskRequest, TaskResponse, Transform,
+        CurrentTaskInfoResponse, Interval, QueryRule, SimulateTaskResponse, SlotHashesResponse,
+        SlotTasksTotalResponse, Task, TaskCallbacks, TaskExecutionInfo, TaskInfo, TaskRequest,
+        TaskResponse, TaskTemplate, TaskTemplateResponse, TemplatePlaceholder, Timezone, Transform,
     },
 };
 use cw20::Cw20ExecuteMsg;
-use cw_multi_test::{AppResponse, BankSudo, Executor};
+use cw_multi_test::{App, AppResponse, BankSudo, Executor};
 
 use super::{
     contracts,
@@ -35,7 +41,10 @@ use super::{
     ADMIN, DENOM,
 };
 use crate::{
-    contract::{GAS_ACTION_FEE, GAS_BASE_FEE, GAS_LIMIT, GAS_QUERY_FEE, SLOT_GRANULARITY_TIME},
+    contract::{
+        GAS_ACTION_FEE, GAS_BASE_FEE, GAS_LIMIT, GAS_QUERY_FEE, QUERY_RULE_MAX_DEPTH,
+        SLOT_GRANULARITY_TIME,
+    },
     msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
     state::TASKS_TOTAL,
     tests::{helpers::add_little_time, ANYONE},
@@ -159,6 +168,9 @@ fn create_task_without_query() {
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let action2 = Action {
@@ -168,6 +180,9 @@ fn create_task_without_query() {
         }
         .into(),
         gas_limit: Some(100_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let task = TaskRequest {
@@ -181,6 +196,10 @@ fn create_task_without_query() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -238,11 +257,18 @@ fn create_task_without_query() {
                     denominator: 100,
                     gas_adjustment_numerator: 150,
                 },
+                gas_price_tolerance: None,
+                gas_denom: None,
             },
             actions: vec![action1, action2],
             queries: None,
             transforms: vec![],
             version: "0.1".to_owned(),
+            paused: false,
+            executions: 0,
+            remaining_executions: None,
+            callbacks: None,
+            query_rule: None,
         }),
     };
     assert_eq!(task_response.task, expected_block_task_response.task);
@@ -268,6 +294,8 @@ fn create_task_without_query() {
             native_balance: Uint128::new(30000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -325,6 +353,9 @@ fn create_task_without_query() {
         }
         .into(),
         gas_limit: Some(60_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Cron("* * * * * *".to_owned()),
@@ -337,6 +368,10 @@ fn create_task_without_query() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -399,11 +434,18 @@ fn create_task_without_query() {
                     denominator: 100,
                     gas_adjustment_numerator: 150,
                 },
+                gas_price_tolerance: None,
+                gas_denom: None,
             },
             actions: vec![action],
             queries: None,
             transforms: vec![],
             version: "0.1".to_owned(),
+            paused: false,
+            executions: 0,
+            remaining_executions: None,
+            callbacks: None,
+            query_rule: None,
         }),
     };
     assert_eq!(task_response.task, expected_cron_task_response.task);
@@ -455,6 +497,8 @@ fn create_task_without_query() {
             native_balance: Uint128::new(60000),
             cw20_balance: None,
             ibc_balance: Some(coin(10, "test_coins")),
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -484,6 +528,9 @@ fn check_task_timestamp() {
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let action2 = Action {
@@ -493,6 +540,9 @@ fn check_task_timestamp() {
         }
         .into(),
         gas_limit: Some(100_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let mut task = TaskRequest {
@@ -506,6 +556,10 @@ fn check_task_timestamp() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -580,6 +634,9 @@ fn create_task_with_wasm() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let task = TaskRequest {
@@ -593,6 +650,10 @@ fn create_task_with_wasm() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ADMIN),
@@ -649,6 +710,8 @@ fn create_task_with_wasm() {
             native_balance: Uint128::new(30000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 }
@@ -671,6 +734,9 @@ fn create_tasks_with_queries_and_transforms() {
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let queries = vec![
         CosmosQuery::Croncat(CroncatQuery {
@@ -703,6 +769,8 @@ fn create_tasks_with_queries_and_transforms() {
             ValueIndex::Index(0),
             ValueIndex::Key("amount".to_string()),
         ]),
+        operations: vec![],
+        result_type: None,
     }];
 
     let task = TaskRequest {
@@ -716,6 +784,10 @@ fn create_tasks_with_queries_and_transforms() {
         queries: Some(queries.clone()),
         transforms: Some(transforms.clone()),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -771,11 +843,18 @@ fn create_tasks_with_queries_and_transforms() {
                     denominator: 100,
                     gas_adjustment_numerator: 150,
                 },
+                gas_price_tolerance: None,
+                gas_denom: None,
             },
             actions: vec![action],
             queries: Some(queries),
             transforms,
             version: "0.1".to_owned(),
+            paused: false,
+            executions: 0,
+            remaining_executions: None,
+            callbacks: None,
+            query_rule: None,
         }),
     };
     assert_eq!(task_response.task, expected_block_task_response.task);
@@ -800,6 +879,8 @@ fn create_tasks_with_queries_and_transforms() {
             native_balance: Uint128::new(50000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -866,6 +947,9 @@ fn remove_tasks_fail() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![
             CosmosQuery::Croncat(CroncatQuery {
@@ -898,8 +982,14 @@ fn remove_tasks_fail() {
                 ValueIndex::Index(0),
                 ValueIndex::Key("amount".to_string()),
             ]),
+            operations: vec![],
+            result_type: None,
         }]),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -926,10 +1016,17 @@ fn remove_tasks_fail() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -1041,6 +1138,9 @@ fn remove_tasks_with_queries_success() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![
             CosmosQuery::Croncat(CroncatQuery {
@@ -1073,8 +1173,14 @@ fn remove_tasks_with_queries_success() {
                 ValueIndex::Index(0),
                 ValueIndex::Key("amount".to_string()),
             ]),
+            operations: vec![],
+            result_type: None,
         }]),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let task_raw = Task {
@@ -1089,6 +1195,10 @@ fn remove_tasks_with_queries_success() {
         queries: task.queries.clone().unwrap(),
         transforms: task.transforms.clone().unwrap(),
         version: "0.1".to_string(),
+        paused: false,
+        max_executions: None,
+        executions: 0,
+        callbacks: None,
         amount_for_one_task: AmountForOneTask {
             cw20: None,
             coin: [Some(coin(5, DENOM)), None],
@@ -1096,7 +1206,10 @@ fn remove_tasks_with_queries_success() {
             agent_fee: u16::default(),
             treasury_fee: u16::default(),
             gas_price: GasPrice::default(),
+            gas_price_tolerance: None,
+            gas_denom: None,
         },
+        query_rule: None,
     };
     assert!(task_raw.is_evented());
     assert!(task_raw.is_evented() && task_raw.boundary.is_block());
@@ -1121,6 +1234,10 @@ fn remove_tasks_with_queries_success() {
         })],
         transforms: task.transforms.clone().unwrap(),
         version: "0.1".to_string(),
+        paused: false,
+        max_executions: None,
+        executions: 0,
+        callbacks: None,
         amount_for_one_task: AmountForOneTask {
             cw20: None,
             coin: [Some(coin(5, DENOM)), None],
@@ -1128,7 +1245,10 @@ fn remove_tasks_with_queries_success() {
             agent_fee: u16::default(),
             treasury_fee: u16::default(),
             gas_price: GasPrice::default(),
+            gas_price_tolerance: None,
+            gas_denom: None,
         },
+        query_rule: None,
     };
     assert!(!task_raw_non_evented.is_evented());
     assert!(!task_raw_non_evented.is_evented() && task_raw.boundary.is_block());
@@ -1175,6 +1295,8 @@ fn remove_tasks_with_queries_success() {
             native_balance: Uint128::new(50000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -1192,6 +1314,9 @@ fn remove_tasks_with_queries_success() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![
             CosmosQuery::Croncat(CroncatQuery {
@@ -1224,8 +1349,14 @@ fn remove_tasks_with_queries_success() {
                 ValueIndex::Index(0),
                 ValueIndex::Key("amount".to_string()),
             ]),
+            operations: vec![],
+            result_type: None,
         }]),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let task_no_evented = TaskRequest {
@@ -1242,6 +1373,9 @@ fn remove_tasks_with_queries_success() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: Some(vec![
             CosmosQuery::Croncat(CroncatQuery {
@@ -1274,8 +1408,14 @@ fn remove_tasks_with_queries_success() {
                 ValueIndex::Index(0),
                 ValueIndex::Key("amount".to_string()),
             ]),
+            operations: vec![],
+            result_type: None,
         }]),
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     // Make sure to test task with Cron interval and queries works
@@ -1503,10 +1643,17 @@ fn remove_tasks_without_queries_success() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -1537,6 +1684,8 @@ fn remove_tasks_without_queries_success() {
             native_balance: Uint128::new(50000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -1554,10 +1703,17 @@ fn remove_tasks_without_queries_success() {
             }
             .into(),
             gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     let res = app
@@ -1589,6 +1745,8 @@ fn remove_tasks_without_queries_success() {
             native_balance: Uint128::new(50000),
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         }),
     );
 
@@ -1767,6 +1925,83 @@ fn update_cfg() {
     assert_eq!(not_updated_config, expected_config);
 }
 
+#[test]
+fn migrate_tasks() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let new_code_id = app.store_code(contracts::croncat_tasks_contract());
+
+    // Only the owner(factory) can migrate
+    app.migrate_contract(
+        Addr::unchecked(ADMIN),
+        tasks_addr.clone(),
+        &crate::msg::MigrateMsg {},
+        new_code_id,
+    )
+    .unwrap_err();
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Migrate {
+                contract_addr: tasks_addr.to_string(),
+                new_code_id,
+                msg: to_binary(&crate::msg::MigrateMsg {}).unwrap(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let contract_version = cw2::CONTRACT
+        .query(&app.wrap(), tasks_addr.clone())
+        .unwrap();
+    assert_eq!(contract_version.version, env!("CARGO_PKG_VERSION"));
+    // Version of the new tasks stays the version of the tasks contract in the factory
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(tasks_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.version, "0.1");
+
+    // Downgrade not allowed
+    let instantiate_msg = InstantiateMsg {
+        version: Some("99.0".to_owned()),
+        ..default_instantiate_msg()
+    };
+    let newer_tasks_addr = app
+        .instantiate_contract(
+            new_code_id,
+            factory_addr,
+            &instantiate_msg,
+            &[],
+            "tasks",
+            Some(ADMIN.to_owned()),
+        )
+        .unwrap();
+    let err: ContractError = app
+        .migrate_contract(
+            Addr::unchecked(ADMIN),
+            newer_tasks_addr,
+            &crate::msg::MigrateMsg {},
+            new_code_id,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Migration(MigrationError::InvalidMigrationVersion {
+            from: "99.0".to_owned(),
+            to: env!("CARGO_PKG_VERSION").to_owned()
+        })
+    );
+}
+
 #[test]
 fn negative_create_task() {
     let mut app = default_app();
@@ -1785,6 +2020,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
 
     let task = TaskRequest {
@@ -1795,6 +2033,10 @@ fn negative_create_task() {
         transforms: None,
         cw20: None,
         queries: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -1810,6 +2052,73 @@ fn negative_create_task() {
         .unwrap();
 
     assert_eq!(err, ContractError::InvalidInterval {});
+
+    // zero max executions
+    let task = TaskRequest {
+        interval: Interval::Block(5),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        transforms: None,
+        cw20: None,
+        queries: None,
+        max_executions: Some(0),
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
+            },
+            &coins(30000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidZeroValue {
+            field: "max_executions".to_owned()
+        }
+    );
+
+    // callback to the croncat contract
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    max_executions: None,
+                    callbacks: Some(TaskCallbacks {
+                        on_complete: None,
+                        on_failure: Some(tasks_addr.to_string()),
+                        gas_limit: None,
+                    }),
+                    ..task
+                }),
+            },
+            &coins(30000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidCallback {});
+
     // invalid gas limit
     let action1 = Action {
         msg: BankMsg::Send {
@@ -1818,6 +2127,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(GAS_LIMIT / 2),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let action2 = Action {
         msg: BankMsg::Send {
@@ -1826,6 +2138,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(GAS_LIMIT / 2 + 1),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -1838,6 +2153,10 @@ fn negative_create_task() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -1861,6 +2180,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(25_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -1873,6 +2195,10 @@ fn negative_create_task() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -1896,6 +2222,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(25_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -1905,6 +2234,10 @@ fn negative_create_task() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -1938,6 +2271,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(25_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -1955,6 +2291,10 @@ fn negative_create_task() {
         })]),
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -1997,6 +2337,9 @@ fn negative_create_task() {
         }
         .into(),
         gas_limit: Some(25_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2006,6 +2349,10 @@ fn negative_create_task() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2040,6 +2387,9 @@ fn remove_task_negative() {
         }
         .into(),
         gas_limit: Some(25_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2049,6 +2399,10 @@ fn remove_task_negative() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2094,6 +2448,10 @@ fn remove_task_negative() {
         })]),
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2183,6 +2541,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2208,6 +2570,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2217,6 +2582,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2240,6 +2609,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2249,6 +2621,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2273,6 +2649,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2282,6 +2661,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2306,6 +2689,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2315,6 +2701,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2338,6 +2728,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2347,6 +2740,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2363,7 +2760,7 @@ fn is_valid_msg_negative_tests() {
 
     assert_eq!(err, ContractError::InvalidAction {});
 
-    // not supported message
+    // delegation not in the native denom
     let action = Action {
         msg: StakingMsg::Delegate {
             validator: "alice".to_owned(),
@@ -2371,6 +2768,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2380,13 +2780,46 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
             Addr::unchecked(ANYONE),
             tasks_addr.clone(),
             &ExecuteMsg::CreateTask {
-                task: Box::new(task),
+                task: Box::new(task.clone()),
+            },
+            &coins(30000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+
+    assert_eq!(err, ContractError::InvalidAction {});
+
+    // not supported message
+    let action = Action {
+        msg: DistributionMsg::SetWithdrawAddress {
+            address: "alice".to_owned(),
+        }
+        .into(),
+        gas_limit: None,
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![action],
+                    ..task
+                }),
             },
             &coins(30000, DENOM),
         )
@@ -2405,6 +2838,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2414,6 +2850,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2439,6 +2879,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2448,6 +2891,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2477,6 +2924,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2486,6 +2936,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2516,6 +2970,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2525,6 +2982,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2555,6 +3016,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let action2 = Action {
         msg: WasmMsg::Execute {
@@ -2569,6 +3033,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2578,6 +3045,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2607,6 +3078,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let action2 = Action {
         msg: WasmMsg::Execute {
@@ -2620,6 +3094,9 @@ fn is_valid_msg_negative_tests() {
         }
         .into(),
         gas_limit: Some(150_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let task = TaskRequest {
         interval: Interval::Once,
@@ -2629,6 +3106,10 @@ fn is_valid_msg_negative_tests() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let err: ContractError = app
         .execute_contract(
@@ -2695,6 +3176,9 @@ fn query_slot_hashes_test() {
         }
         .into(),
         gas_limit: Some(100_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let current_block: Uint64 = app.block_info().height.into(); // 12_345
 
@@ -2710,6 +3194,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2735,6 +3223,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2760,6 +3252,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2785,6 +3281,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2807,6 +3307,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2829,6 +3333,10 @@ fn query_slot_hashes_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -2949,6 +3457,9 @@ fn query_slot_tasks_total_test() {
         }
         .into(),
         gas_limit: Some(100_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
     };
     let current_block: Uint64 = (app.block_info().height + 2).into(); // 12_345 + 2 = 12_347
 
@@ -2964,6 +3475,10 @@ fn query_slot_tasks_total_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -2986,6 +3501,10 @@ fn query_slot_tasks_total_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -3008,6 +3527,10 @@ fn query_slot_tasks_total_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -3030,6 +3553,10 @@ fn query_slot_tasks_total_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -3051,6 +3578,10 @@ fn query_slot_tasks_total_test() {
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     app.execute_contract(
         Addr::unchecked(ANYONE),
@@ -3159,6 +3690,7 @@ fn poc_case1_case2() {
         agents_addr.clone(),
         &croncat_agents::msg::ExecuteMsg::RegisterAgent {
             payable_account_id: None,
+            payout: None,
         },
         &[],
     )
@@ -3190,10 +3722,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let _res = app
         .execute_contract(
@@ -3217,10 +3756,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let _res = app
         .execute_contract(
@@ -3244,10 +3790,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let _res = app
         .execute_contract(
@@ -3271,10 +3824,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let _res = app
         .execute_contract(
@@ -3303,6 +3863,9 @@ fn poc_case1_case2() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -3311,11 +3874,18 @@ fn poc_case1_case2() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -3436,6 +4006,9 @@ fn poc_case1_case2() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
             Action {
                 msg: BankMsg::Send {
@@ -3444,11 +4017,18 @@ fn poc_case1_case2() {
                 }
                 .into(),
                 gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             },
         ],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -3486,10 +4066,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: Some(250_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     // passing message with uppercase manager address
@@ -3517,10 +4104,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: Some(250_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     // Need this to fail to check correct coverage
@@ -3555,10 +4149,17 @@ fn poc_case1_case2() {
             }
             .into(),
             gas_limit: Some(250_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
 
     // Need this to fail to check correct coverage
@@ -3996,10 +4597,17 @@ fn check_task_reschedule_interval() {
             }
             .into(),
             gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
         }],
         queries: None,
         transforms: None,
         cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
     };
     let res = app
         .execute_contract(
@@ -4069,3 +4677,2019 @@ fn check_task_reschedule_interval() {
         }
     }
 }
+
+#[test]
+fn check_task_reschedule_seconds_interval() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+
+    activate_agent(&mut app, &agents_addr);
+
+    let mut task = TaskRequest {
+        interval: Interval::Seconds(0),
+        boundary: Some(Boundary::Time(BoundaryTime {
+            start: Some(app.block_info().time),
+            end: None,
+        })),
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(1, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
+            },
+            &coins(700_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidInterval {});
+
+    // Seconds interval can't be used with height boundary
+    task.interval = Interval::Seconds(90);
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    boundary: Some(Boundary::Height(BoundaryHeight {
+                        start: None,
+                        end: None,
+                    })),
+                    ..task.clone()
+                }),
+            },
+            &coins(700_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidBoundary {});
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr,
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(700_000, DENOM),
+        )
+        .unwrap();
+
+    fn get_slot_attributes(res: &AppResponse) -> (String, String) {
+        let mut slot_id = String::default();
+        let mut slot_kind = String::default();
+        for ev in res.events.iter() {
+            for attr in ev.attributes.iter() {
+                if attr.key == "slot_id" {
+                    slot_id = attr.value.clone();
+                }
+                if attr.key == "slot_kind" {
+                    slot_kind = attr.value.clone();
+                }
+            }
+        }
+        (slot_id, slot_kind)
+    }
+
+    // Created at 1571797419879305533, next run 90 seconds later, rounded to the slot granularity
+    assert_eq!(
+        get_slot_attributes(&res),
+        ("1571797500000000000".to_owned(), "cron".to_owned())
+    );
+
+    // Period counted from the boundary start, so it doesn't drift after execution
+    app.update_block(|block| {
+        add_seconds_to_block(block, 90);
+        increment_block_height(block, None);
+    });
+    let res = app
+        .execute_contract(
+            Addr::unchecked(AGENT0),
+            manager_addr,
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        get_slot_attributes(&res),
+        ("1571797590000000000".to_owned(), "cron".to_owned())
+    );
+}
+
+#[test]
+fn create_task_zoned_cron_interval() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let _ = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    let task = TaskRequest {
+        interval: Interval::ZonedCron {
+            crontab: "0 0 9 * * *".to_owned(),
+            timezone: Timezone::Iana("Europe/Atlantis".to_owned()),
+        },
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(1, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
+            },
+            &coins(700_000, DENOM),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidTimezone {});
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr,
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    interval: Interval::ZonedCron {
+                        crontab: "0 0 9 * * *".to_owned(),
+                        timezone: Timezone::Iana("Europe/Berlin".to_owned()),
+                    },
+                    ..task
+                }),
+            },
+            &coins(700_000, DENOM),
+        )
+        .unwrap();
+
+    // Created at 2019-10-23 02:23:39 UTC, next run at 09:00 CEST, which is 07:00 UTC
+    let slot_id = res
+        .events
+        .iter()
+        .flat_map(|ev| ev.attributes.iter())
+        .find(|attr| attr.key == "slot_id")
+        .map(|attr| attr.value.clone())
+        .unwrap();
+    assert_eq!(slot_id, "1571814000000000000");
+}
+#[test]
+fn update_task() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+
+    let action = Action {
+        msg: BankMsg::Send {
+            to_address: Addr::unchecked(PARTICIPANT2).to_string(),
+            amount: coins(5, DENOM),
+        }
+        .into(),
+        gas_limit: Some(50_000),
+        authz: false,
+        condition: None,
+        ibc_timeout: None,
+    };
+    let task = TaskRequest {
+        interval: Interval::Block(5),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![action.clone()],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task.clone()),
+            },
+            &coins(80000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let old_task_hash = task_data.task_hash;
+
+    // Task executed once before the update
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        agents_addr,
+        &croncat_agents::msg::ExecuteMsg::RegisterAgent {
+            payable_account_id: None,
+            payout: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| increment_block_height(block, Some(5)));
+    app.execute_contract(
+        Addr::unchecked(AGENT0),
+        manager_addr.clone(),
+        &ManagerExecuteMsg::ProxyCall { task_hash: None },
+        &[],
+    )
+    .unwrap();
+    let task_history = |app: &App, task_hash: &str| -> Vec<TaskExecutionRecord> {
+        app.wrap()
+            .query_wasm_smart(
+                manager_addr.clone(),
+                &croncat_manager::msg::QueryMsg::TaskExecutionHistory {
+                    task_hash: task_hash.to_owned(),
+                    from_index: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+    };
+    let history = task_history(&app, &old_task_hash);
+    assert_eq!(history.len(), 1);
+
+    let updated_task = TaskRequest {
+        interval: Interval::Block(10),
+        actions: vec![action.clone(), action.clone(), action],
+        ..task.clone()
+    };
+
+    // Only owner can update the task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(updated_task.clone()),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Can't update unknown task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: "atom:unknown".to_owned(),
+                task: Box::new(updated_task.clone()),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoTaskFound {});
+
+    // Nothing to update
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(task.clone()),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::TaskUnchanged {});
+
+    // Task with only `stop_on_fail` changed keeps its hash
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(TaskRequest {
+                    stop_on_fail: true,
+                    ..task
+                }),
+            },
+            &[],
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(task_data.task_hash, old_task_hash);
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: old_task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(task_response.task.unwrap().stop_on_fail);
+
+    // Updated task re-validated
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(TaskRequest {
+                    interval: Interval::Cron("invalid".to_owned()),
+                    ..updated_task.clone()
+                }),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidInterval {});
+
+    // Updated task needs more balance than attached before
+    let err: croncat_manager::ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(updated_task.clone()),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert!(matches!(err, croncat_manager::ContractError::Sdk(_)));
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::UpdateTask {
+                task_hash: old_task_hash.clone(),
+                task: Box::new(updated_task),
+            },
+            &coins(30000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let new_task_hash = task_data.task_hash;
+    assert_ne!(old_task_hash, new_task_hash);
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "update_task")
+    }));
+
+    // Old task is gone, new one is in place
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: old_task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(task_response.task.is_none());
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: new_task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let task_info = task_response.task.unwrap();
+    assert_eq!(task_info.interval, Interval::Block(10));
+    assert_eq!(task_info.actions.len(), 3);
+
+    let tasks_total: Uint64 = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::TasksTotal {})
+        .unwrap();
+    assert_eq!(tasks_total, Uint64::new(1));
+
+    // Slots re-scheduled
+    let slot_hashes: SlotHashesResponse = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::SlotHashes { slot: None })
+        .unwrap();
+    assert_eq!(slot_hashes.block_task_hash, vec![new_task_hash.clone()]);
+
+    // Old hash points to the new one
+    let updated_hash: Option<String> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::UpdatedTaskHash {
+                task_hash: old_task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(updated_hash, Some(new_task_hash.clone()));
+    let updated_hash: Option<String> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &QueryMsg::UpdatedTaskHash {
+                task_hash: new_task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(updated_hash, None);
+
+    // Execution history moved to the new hash
+    assert!(task_history(&app, &old_task_hash).is_empty());
+    assert_eq!(task_history(&app, &new_task_hash), history);
+
+    // Balance moved to the new hash, with extra funds
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &croncat_manager::msg::QueryMsg::TaskBalance {
+                task_hash: old_task_hash,
+            },
+        )
+        .unwrap();
+    assert!(manager_task_balance.balance.is_none());
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr,
+            &croncat_manager::msg::QueryMsg::TaskBalance {
+                task_hash: new_task_hash,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        manager_task_balance.balance,
+        Some(TaskBalance {
+            native_balance: Uint128::new(110000 - 5) - history[0].gas_charged.amount,
+            cw20_balance: None,
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        }),
+    );
+}
+
+#[test]
+fn pause_and_resume_task() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    let task = TaskRequest {
+        interval: Interval::Block(5),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(53000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+
+    // Only owner can pause
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::PauseTask {
+                task_hash: task_hash.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Can't resume not paused task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::ResumeTask {
+                task_hash: task_hash.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::TaskNotPaused {});
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::PauseTask {
+                task_hash: task_hash.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "pause_task")
+    }));
+
+    // Can't pause twice
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::PauseTask {
+                task_hash: task_hash.clone(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::TaskPaused {});
+
+    // Task is still there, but flagged
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(task_response.task.unwrap().paused);
+
+    // Not scheduled anymore
+    let slot_hashes: SlotHashesResponse = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::SlotHashes { slot: None })
+        .unwrap();
+    assert!(slot_hashes.block_task_hash.is_empty());
+    app.update_block(|block| increment_block_height(block, Some(10)));
+    let current_task: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::CurrentTask {})
+        .unwrap();
+    assert!(current_task.task.is_none());
+
+    // Balance stays locked in manager
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr,
+            &croncat_manager::msg::QueryMsg::TaskBalance {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        manager_task_balance.balance,
+        Some(TaskBalance {
+            native_balance: Uint128::new(53000),
+            cw20_balance: None,
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        }),
+    );
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::ResumeTask {
+                task_hash: task_hash.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "resume_task")
+    }));
+
+    // Re-slotted from the current block
+    let slot_hashes: SlotHashesResponse = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::SlotHashes { slot: None })
+        .unwrap();
+    assert_eq!(slot_hashes.block_task_hash, vec![task_hash.clone()]);
+    assert!(slot_hashes.block_id > app.block_info().height);
+
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(tasks_addr, &QueryMsg::Task { task_hash })
+        .unwrap();
+    assert!(!task_response.task.unwrap().paused);
+}
+
+#[test]
+fn migrate_task() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    let task = TaskRequest {
+        interval: Interval::Block(5),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(task),
+            },
+            &coins(53000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task_hash = task_data.task_hash;
+
+    // No newer version yet
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::MigrateTask {
+                task_hash: task_hash.clone(),
+                to_version: [0, 2],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidKey {});
+
+    // Deploy newer set of tasks, manager and agents
+    let new_tasks_code_id = app.store_code(contracts::croncat_tasks_contract());
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &FactoryExecuteMsg::Deploy {
+            kind: VersionKind::Tasks,
+            module_instantiate_info: ModuleInstantiateInfo {
+                code_id: new_tasks_code_id,
+                version: [0, 2],
+                commit_id: "commit2".to_owned(),
+                checksum: "checksum3".to_owned(),
+                changelog_url: None,
+                schema: None,
+                msg: to_binary(&InstantiateMsg {
+                    version: Some("0.2".to_owned()),
+                    croncat_manager_key: ("manager".to_owned(), [0, 2]),
+                    croncat_agents_key: ("agents".to_owned(), [0, 2]),
+                    ..default_instantiate_msg()
+                })
+                .unwrap(),
+                contract_name: "tasks".to_owned(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let new_manager_code_id = app.store_code(contracts::croncat_manager_contract());
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &FactoryExecuteMsg::Deploy {
+            kind: VersionKind::Manager,
+            module_instantiate_info: ModuleInstantiateInfo {
+                code_id: new_manager_code_id,
+                version: [0, 2],
+                commit_id: "commit2".to_owned(),
+                checksum: "checksum3".to_owned(),
+                changelog_url: None,
+                schema: None,
+                msg: to_binary(&croncat_manager::msg::InstantiateMsg {
+                    version: Some("0.2".to_owned()),
+                    croncat_tasks_key: ("tasks".to_owned(), [0, 2]),
+                    croncat_agents_key: ("agents".to_owned(), [0, 2]),
+                    pause_admin: Addr::unchecked(PAUSE_ADMIN),
+                    gas_price: None,
+                    treasury_addr: None,
+                    cw20_whitelist: None,
+                })
+                .unwrap(),
+                contract_name: "manager".to_owned(),
+            },
+        },
+        &[crate::tests::get_manager_instantiate_denom_fee()],
+    )
+    .unwrap();
+    let new_agents_code_id = app.store_code(contracts::croncat_agents_contract());
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &FactoryExecuteMsg::Deploy {
+            kind: VersionKind::Agents,
+            module_instantiate_info: ModuleInstantiateInfo {
+                code_id: new_agents_code_id,
+                version: [0, 2],
+                commit_id: "commit2".to_owned(),
+                checksum: "checksum3".to_owned(),
+                changelog_url: None,
+                schema: None,
+                msg: to_binary(&croncat_agents::msg::InstantiateMsg {
+                    version: Some("0.2".to_owned()),
+                    croncat_manager_key: ("manager".to_owned(), [0, 2]),
+                    croncat_tasks_key: ("tasks".to_owned(), [0, 2]),
+                    pause_admin: Addr::unchecked(PAUSE_ADMIN),
+                    agent_nomination_duration: None,
+                    min_tasks_per_agent: None,
+                    min_coins_for_agent_registration: None,
+                    agents_eject_threshold: None,
+                    min_active_agent_count: None,
+                    allowed_agents: Some(vec![]),
+                    public_registration: true,
+                    min_agent_stake: None,
+                    agent_slash_percentage: None,
+                    agent_unbonding_period: None,
+                    task_distribution: None,
+                })
+                .unwrap(),
+                contract_name: "agents".to_owned(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let latest_addr = |app: &App, contract_name: &str| {
+        let metadata: ContractMetadataResponse = app
+            .wrap()
+            .query_wasm_smart(
+                factory_addr.clone(),
+                &croncat_factory::msg::QueryMsg::LatestContract {
+                    contract_name: contract_name.to_owned(),
+                },
+            )
+            .unwrap();
+        metadata.metadata.unwrap().contract_addr
+    };
+    let new_tasks_addr = latest_addr(&app, "tasks");
+    let new_manager_addr = latest_addr(&app, "manager");
+    let new_agents_addr = latest_addr(&app, "agents");
+
+    // Only owner can migrate the task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::MigrateTask {
+                task_hash: task_hash.clone(),
+                to_version: [0, 2],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Can't migrate to the same version
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::MigrateTask {
+                task_hash: task_hash.clone(),
+                to_version: [0, 1],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidTaskMigration {});
+
+    // Only other version of tasks contract can import tasks
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            new_tasks_addr.clone(),
+            &ExecuteMsg::ImportTask(Box::new(croncat_sdk_tasks::msg::TasksImportTask {
+                task_hash: task_hash.clone(),
+                task: Task {
+                    owner_addr: Addr::unchecked(ANYONE),
+                    interval: Interval::Once,
+                    boundary: Boundary::Height(BoundaryHeight {
+                        start: None,
+                        end: None,
+                    }),
+                    stop_on_fail: false,
+                    actions: vec![],
+                    queries: vec![],
+                    transforms: vec![],
+                    version: "0.1".to_owned(),
+                    amount_for_one_task: Default::default(),
+                    paused: false,
+                    max_executions: None,
+                    executions: 0,
+                    callbacks: None,
+                    query_rule: None,
+                },
+            })),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Everything reverted if new contracts fail
+    app.execute_contract(
+        Addr::unchecked(PAUSE_ADMIN),
+        new_tasks_addr.clone(),
+        &ExecuteMsg::PauseContract {},
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::MigrateTask {
+                task_hash: task_hash.clone(),
+                to_version: [0, 2],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::ContractPaused);
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(task_response.task.is_some());
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &croncat_manager::msg::QueryMsg::TaskBalance {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(manager_task_balance.balance.is_some());
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr,
+        &FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: new_tasks_addr.to_string(),
+                msg: to_binary(&ExecuteMsg::UnpauseContract {}).unwrap(),
+                funds: vec![],
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(ANYONE),
+            tasks_addr.clone(),
+            &ExecuteMsg::MigrateTask {
+                task_hash: task_hash.clone(),
+                to_version: [0, 2],
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|ev| ev
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "import_task")));
+    // Agents of the new version are notified about the task
+    assert!(res.events.iter().any(|ev| {
+        ev.attributes
+            .iter()
+            .any(|attr| attr.key == "_contract_addr" && attr.value == new_agents_addr.as_str())
+            && ev
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "on_task_created")
+    }));
+
+    // Task and its balance moved
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(task_response.task.is_none());
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            manager_addr.clone(),
+            &croncat_manager::msg::QueryMsg::TaskBalance {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert!(manager_task_balance.balance.is_none());
+    assert_eq!(
+        app.wrap()
+            .query_balance(manager_addr, DENOM)
+            .unwrap()
+            .amount,
+        Uint128::new(1)
+    );
+
+    let task_response: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            new_tasks_addr,
+            &QueryMsg::Task {
+                task_hash: task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let task = task_response.task.unwrap();
+    assert_eq!(task.version, "0.2");
+    assert_eq!(task.owner_addr, Addr::unchecked(ANYONE));
+    let manager_task_balance: TaskBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            new_manager_addr.clone(),
+            &croncat_manager::msg::QueryMsg::TaskBalance { task_hash },
+        )
+        .unwrap();
+    assert_eq!(
+        manager_task_balance.balance,
+        Some(TaskBalance {
+            native_balance: Uint128::new(53000),
+            cw20_balance: None,
+            ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
+        })
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(new_manager_addr, DENOM)
+            .unwrap()
+            .amount,
+        Uint128::new(53001)
+    );
+}
+
+#[test]
+fn simulate_task() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let agents_addr = init_agents(&mut app, &factory_addr);
+
+    let task = TaskRequest {
+        interval: Interval::Block(3),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "alice".to_owned(),
+                amount: coins(15, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    // (300_000 base + 130_000 action) * 1.1 fees * 1.5 adjustment * 0.04 price + 15 sent
+    let native_per_execution = 28_395;
+    assert!(res.errors.is_empty());
+    assert_eq!(
+        res.amount_for_one_task.unwrap().gas,
+        GAS_BASE_FEE + GAS_ACTION_FEE
+    );
+    assert_eq!(res.native_per_execution, coin(native_per_execution, DENOM));
+    // Frozen gas price can't go up
+    assert_eq!(res.max_native_per_execution, res.native_per_execution);
+    assert_eq!(res.ibc_per_execution, None);
+    assert_eq!(res.cw20_per_execution, None);
+    // Recurring task covers two executions on creation
+    assert_eq!(res.min_deposit, coins(native_per_execution * 2, DENOM));
+    assert_eq!(res.min_cw20_deposit, None);
+
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: Some(5),
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.min_deposit, coins(native_per_execution * 5, DENOM));
+
+    // Gas price source can charge up to the tolerance of the task
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        factory_addr.clone(),
+        &FactoryExecuteMsg::Proxy {
+            msg: WasmMsg::Execute {
+                contract_addr: manager_addr.to_string(),
+                msg: to_binary(&ManagerExecuteMsg::UpdateConfig(Box::new(UpdateConfig {
+                    agent_fee: None,
+                    treasury_fee: None,
+                    gas_price: None,
+                    gas_price_source: Some(GasPriceSource::Oracle {
+                        contract_addr: "gas_price_oracle".to_owned(),
+                        limits: GasPriceLimits {
+                            min_price: Decimal::percent(1),
+                            max_price: Decimal::percent(10),
+                            tolerance: 150,
+                        },
+                    }),
+                    croncat_tasks_key: None,
+                    croncat_agents_key: None,
+                    treasury_addr: None,
+                    cw20_whitelist: None,
+                    gas_denoms: None,
+                    unbonding_period: None,
+                })))
+                .unwrap(),
+                funds: vec![],
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.native_per_execution, coin(native_per_execution, DENOM));
+    // (28_395 - 15 sent) * 1.5 tolerance + 15 sent
+    assert_eq!(res.max_native_per_execution, coin(42_585, DENOM));
+
+    // Simulated deposit is enough for the real task creation
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        tasks_addr.clone(),
+        &ExecuteMsg::CreateTask {
+            task: Box::new(task.clone()),
+        },
+        &coins(native_per_execution * 2, DENOM),
+    )
+    .unwrap();
+
+    // Same task can't be created twice
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: Some(ADMIN.to_owned()),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.errors, vec![ContractError::TaskExists {}.to_string()]);
+
+    // Invalid task reports the creation error
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(TaskRequest {
+                    boundary: Some(Boundary::Time(BoundaryTime {
+                        start: None,
+                        end: None,
+                    })),
+                    ..task.clone()
+                }),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.amount_for_one_task, None);
+    assert_eq!(res.min_deposit, vec![]);
+    assert_eq!(
+        res.errors,
+        vec![ContractError::InvalidBoundary {}.to_string()]
+    );
+
+    // Tick is allowed only for the owner of the contracts
+    let tick_task = TaskRequest {
+        actions: vec![Action {
+            msg: WasmMsg::Execute {
+                contract_addr: agents_addr.to_string(),
+                msg: to_binary(&croncat_sdk_agents::msg::ExecuteMsg::Tick {}).unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: Some(150_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        ..task
+    };
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(tick_task.clone()),
+                executions: None,
+                sender: Some(ADMIN.to_owned()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.errors,
+        vec![ContractError::InvalidAction {}.to_string()]
+    );
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &QueryMsg::SimulateTask {
+                task: Box::new(tick_task),
+                executions: None,
+                sender: Some(factory_addr.to_string()),
+            },
+        )
+        .unwrap();
+    assert!(res.errors.is_empty());
+    assert!(res.amount_for_one_task.is_some());
+}
+
+#[test]
+fn create_task_with_staking_actions() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let _ = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    // Claim rewards and delegate them daily
+    let task = TaskRequest {
+        interval: Interval::Cron("0 0 0 * * *".to_owned()),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: DistributionMsg::WithdrawDelegatorReward {
+                    validator: "validator1".to_owned(),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: StakingMsg::Delegate {
+                    validator: "validator1".to_owned(),
+                    amount: coin(100, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: StakingMsg::Redelegate {
+                    src_validator: "validator1".to_owned(),
+                    dst_validator: "validator2".to_owned(),
+                    amount: coin(50, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert!(res.errors.is_empty());
+    let amount_for_one_task = res.amount_for_one_task.unwrap();
+    assert_eq!(amount_for_one_task.gas, GAS_BASE_FEE + 3 * GAS_ACTION_FEE);
+    // Only delegated coins leave the task balance
+    assert_eq!(amount_for_one_task.coin, [Some(coin(100, DENOM)), None]);
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        tasks_addr.clone(),
+        &ExecuteMsg::CreateTask {
+            task: Box::new(task.clone()),
+        },
+        &res.min_deposit,
+    )
+    .unwrap();
+
+    // Redelegation to the same validator
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr,
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        msg: StakingMsg::Redelegate {
+                            src_validator: "validator1".to_owned(),
+                            dst_validator: "validator1".to_owned(),
+                            amount: coin(50, DENOM),
+                        }
+                        .into(),
+                        gas_limit: None,
+                        authz: false,
+                        condition: None,
+                        ibc_timeout: None,
+                    }],
+                    ..task
+                }),
+            },
+            &res.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAction {});
+}
+
+#[test]
+fn create_task_with_ibc_transfer() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let _ = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    // Timeout is 10 minutes after every execution
+    let task = TaskRequest {
+        interval: Interval::Block(100),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: IbcMsg::Transfer {
+                channel_id: "channel-0".to_owned(),
+                to_address: "osmo1receiver".to_owned(),
+                amount: coin(100, DENOM),
+                timeout: IbcTimeout::with_timestamp(Timestamp::from_seconds(0)),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: Some(600),
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert!(res.errors.is_empty());
+    let amount_for_one_task = res.amount_for_one_task.unwrap();
+    assert_eq!(amount_for_one_task.gas, GAS_BASE_FEE + GAS_ACTION_FEE);
+    assert_eq!(amount_for_one_task.coin, [Some(coin(100, DENOM)), None]);
+
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        tasks_addr.clone(),
+        &ExecuteMsg::CreateTask {
+            task: Box::new(task.clone()),
+        },
+        &res.min_deposit,
+    )
+    .unwrap();
+
+    // Timeout of the transfer itself doesn't count, action has to set it
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        msg: IbcMsg::Transfer {
+                            channel_id: "channel-0".to_owned(),
+                            to_address: "osmo1receiver".to_owned(),
+                            amount: coin(100, DENOM),
+                            timeout: IbcTimeout::with_block(IbcTimeoutBlock {
+                                revision: 1,
+                                height: 12345,
+                            }),
+                        }
+                        .into(),
+                        gas_limit: None,
+                        authz: false,
+                        condition: None,
+                        ibc_timeout: None,
+                    }],
+                    ..task.clone()
+                }),
+            },
+            &res.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAction {});
+
+    // Only ibc transfers have a timeout
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr,
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        msg: BankMsg::Send {
+                            to_address: ANYONE.to_owned(),
+                            amount: coins(100, DENOM),
+                        }
+                        .into(),
+                        gas_limit: None,
+                        authz: false,
+                        condition: None,
+                        ibc_timeout: Some(600),
+                    }],
+                    ..task
+                }),
+            },
+            &res.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAction {});
+}
+
+#[test]
+fn create_task_with_authz_actions() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let manager_addr = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    // Coins and cw20 are moved from the owner's wallet
+    let task = TaskRequest {
+        interval: Interval::Block(100),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![
+            Action {
+                msg: BankMsg::Send {
+                    to_address: ANYONE.to_owned(),
+                    amount: coins(1_000_000, DENOM),
+                }
+                .into(),
+                gas_limit: None,
+                authz: true,
+                condition: None,
+                ibc_timeout: None,
+            },
+            Action {
+                msg: WasmMsg::Execute {
+                    contract_addr: ANYONE.to_owned(),
+                    msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                        recipient: ANYONE.to_owned(),
+                        amount: Uint128::new(1000),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }
+                .into(),
+                gas_limit: Some(150_000),
+                authz: true,
+                condition: None,
+                ibc_timeout: None,
+            },
+        ],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let res: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert!(res.errors.is_empty());
+    let amount_for_one_task = res.amount_for_one_task.unwrap();
+    assert_eq!(
+        amount_for_one_task.gas,
+        GAS_BASE_FEE + GAS_ACTION_FEE + 150_000
+    );
+    assert_eq!(amount_for_one_task.coin, [None, None]);
+    assert_eq!(amount_for_one_task.cw20, None);
+
+    // Only gas is deposited
+    app.execute_contract(
+        Addr::unchecked(ADMIN),
+        tasks_addr.clone(),
+        &ExecuteMsg::CreateTask {
+            task: Box::new(task.clone()),
+        },
+        &res.min_deposit,
+    )
+    .unwrap();
+
+    // Self-calls are still checked
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        msg: WasmMsg::Execute {
+                            contract_addr: manager_addr.to_string(),
+                            msg: to_binary(
+                                &croncat_sdk_manager::msg::ManagerExecuteMsg::ProxyCall {
+                                    task_hash: None,
+                                },
+                            )
+                            .unwrap(),
+                            funds: vec![],
+                        }
+                        .into(),
+                        gas_limit: Some(150_000),
+                        authz: true,
+                        condition: None,
+                        ibc_timeout: None,
+                    }],
+                    ..task.clone()
+                }),
+            },
+            &res.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAction {});
+
+    // Manager can't encode other messages for authz
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADMIN),
+            tasks_addr,
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    actions: vec![Action {
+                        msg: StakingMsg::Delegate {
+                            validator: "validator1".to_owned(),
+                            amount: coin(100, DENOM),
+                        }
+                        .into(),
+                        gas_limit: None,
+                        authz: true,
+                        condition: None,
+                        ibc_timeout: None,
+                    }],
+                    ..task
+                }),
+            },
+            &res.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidAction {});
+}
+
+#[test]
+fn create_task_from_template() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let _ = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+
+    let template_task = TaskRequest {
+        interval: Interval::Block(100),
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "receiver".to_owned(),
+                amount: coins(1, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: None,
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let bank_send_path = |key: &str| {
+        PathToValue::from(vec![
+            ValueIndex::Key("actions".to_string()),
+            ValueIndex::Index(0),
+            ValueIndex::Key("msg".to_string()),
+            ValueIndex::Key("bank".to_string()),
+            ValueIndex::Key("send".to_string()),
+            ValueIndex::Key(key.to_string()),
+        ])
+    };
+    let placeholders = vec![
+        TemplatePlaceholder {
+            name: "Receiver".to_owned(),
+            path: bank_send_path("to_address"),
+        },
+        TemplatePlaceholder {
+            name: "Amount".to_owned(),
+            path: bank_send_path("amount"),
+        },
+    ];
+
+    // Placeholders have to point to the values of the task
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::RegisterTemplate {
+                name: "Send".to_owned(),
+                task: Box::new(template_task.clone()),
+                placeholders: vec![TemplatePlaceholder {
+                    name: "Receiver".to_owned(),
+                    path: bank_send_path("recipient"),
+                }],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidTemplate {});
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::RegisterTemplate {
+                name: "Send".to_owned(),
+                task: Box::new(template_task.clone()),
+                placeholders: placeholders.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    let template_id: u64 = from_binary(&res.data.unwrap()).unwrap();
+    let templates: Vec<TaskTemplateResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Templates {
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let expected_template = TaskTemplateResponse {
+        template_id,
+        template: TaskTemplate {
+            owner_addr: Addr::unchecked(PARTICIPANT0),
+            name: "Send".to_owned(),
+            task: template_task.clone(),
+            placeholders,
+        },
+    };
+    assert_eq!(templates, vec![expected_template.clone()]);
+    let template: Option<TaskTemplateResponse> = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::Template { template_id })
+        .unwrap();
+    assert_eq!(template, Some(expected_template));
+
+    // Every placeholder needs a param
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT1),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTaskFromTemplate {
+                template_id,
+                params: vec![to_binary("bob").unwrap()],
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidTemplateParams {});
+
+    let expected_task = TaskRequest {
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: "bob".to_owned(),
+                amount: coins(20, DENOM),
+            }
+            .into(),
+            gas_limit: None,
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        ..template_task
+    };
+    let simulate: SimulateTaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::SimulateTask {
+                task: Box::new(expected_task.clone()),
+                executions: None,
+                sender: None,
+            },
+        )
+        .unwrap();
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT1),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTaskFromTemplate {
+                template_id,
+                params: vec![
+                    to_binary("bob").unwrap(),
+                    to_binary(&coins(20, DENOM)).unwrap(),
+                ],
+            },
+            &simulate.min_deposit,
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(task_data.owner_addr, Addr::unchecked(PARTICIPANT1));
+    let task: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr.clone(),
+            &QueryMsg::Task {
+                task_hash: task_data.task_hash,
+            },
+        )
+        .unwrap();
+    assert_eq!(task.task.unwrap().actions, expected_task.actions);
+
+    // Only owner can remove the template
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT1),
+            tasks_addr.clone(),
+            &ExecuteMsg::RemoveTemplate { template_id },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    app.execute_contract(
+        Addr::unchecked(PARTICIPANT0),
+        tasks_addr.clone(),
+        &ExecuteMsg::RemoveTemplate { template_id },
+        &[],
+    )
+    .unwrap();
+    let template: Option<TaskTemplateResponse> = app
+        .wrap()
+        .query_wasm_smart(tasks_addr.clone(), &QueryMsg::Template { template_id })
+        .unwrap();
+    assert_eq!(template, None);
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT1),
+            tasks_addr,
+            &ExecuteMsg::CreateTaskFromTemplate {
+                template_id,
+                params: vec![
+                    to_binary("bob").unwrap(),
+                    to_binary(&coins(20, DENOM)).unwrap(),
+                ],
+            },
+            &simulate.min_deposit,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoTemplateFound {});
+}
+
+#[test]
+fn create_task_with_query_rule() {
+    let mut app = default_app();
+    let factory_addr = init_factory(&mut app);
+    let instantiate_msg: InstantiateMsg = default_instantiate_msg();
+    let tasks_addr = init_tasks(&mut app, &instantiate_msg, &factory_addr);
+    let _ = init_manager(&mut app, &factory_addr);
+    let _ = init_agents(&mut app, &factory_addr);
+    let mod_balances_addr = init_mod_balances(&mut app, &factory_addr);
+
+    let get_balance_msg = to_binary(&croncat_mod_balances::msg::QueryMsg::GetBalance {
+        address: Addr::unchecked(PARTICIPANT1).to_string(),
+        denom: DENOM.to_string(),
+    })
+    .unwrap();
+    let task = TaskRequest {
+        interval: Interval::Once,
+        boundary: None,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: BankMsg::Send {
+                to_address: Addr::unchecked(PARTICIPANT1).to_string(),
+                amount: coins(5, DENOM),
+            }
+            .into(),
+            gas_limit: Some(50_000),
+            authz: false,
+            condition: None,
+            ibc_timeout: None,
+        }],
+        queries: Some(vec![
+            CosmosQuery::Croncat(CroncatQuery {
+                contract_addr: mod_balances_addr.to_string(),
+                msg: get_balance_msg.clone(),
+                check_result: true,
+            }),
+            CosmosQuery::Wasm(WasmQuery::Smart {
+                contract_addr: mod_balances_addr.to_string(),
+                msg: get_balance_msg,
+            }),
+        ]),
+        transforms: None,
+        cw20: None,
+        max_executions: None,
+        callbacks: None,
+        gas_denom: None,
+        query_rule: None,
+    };
+    let mut too_deep_rule = QueryRule::Query(0);
+    for _ in 0..QUERY_RULE_MAX_DEPTH {
+        too_deep_rule = QueryRule::Not(Box::new(too_deep_rule));
+    }
+    let invalid_rules = vec![
+        // Out of range
+        QueryRule::Query(2),
+        // Wasm query has no result
+        QueryRule::Or(vec![QueryRule::Query(0), QueryRule::Query(1)]),
+        QueryRule::And(vec![]),
+        too_deep_rule,
+    ];
+    for query_rule in invalid_rules {
+        let err: ContractError = app
+            .execute_contract(
+                Addr::unchecked(PARTICIPANT0),
+                tasks_addr.clone(),
+                &ExecuteMsg::CreateTask {
+                    task: Box::new(TaskRequest {
+                        query_rule: Some(query_rule),
+                        ..task.clone()
+                    }),
+                },
+                &coins(300_000, DENOM),
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(
+            err,
+            ContractError::InvalidQueryRule {
+                max_depth: QUERY_RULE_MAX_DEPTH
+            }
+        );
+    }
+
+    let query_rule = QueryRule::Not(Box::new(QueryRule::Query(0)));
+    let res = app
+        .execute_contract(
+            Addr::unchecked(PARTICIPANT0),
+            tasks_addr.clone(),
+            &ExecuteMsg::CreateTask {
+                task: Box::new(TaskRequest {
+                    query_rule: Some(query_rule.clone()),
+                    ..task
+                }),
+            },
+            &coins(300_000, DENOM),
+        )
+        .unwrap();
+    let task_data: TaskExecutionInfo = from_binary(&res.data.unwrap()).unwrap();
+    let task: TaskResponse = app
+        .wrap()
+        .query_wasm_smart(
+            tasks_addr,
+            &QueryMsg::Task {
+                task_hash: task_data.task_hash,
+            },
+        )
+        .unwrap();
+    assert_eq!(task.task.unwrap().query_rule, Some(query_rule));
+}
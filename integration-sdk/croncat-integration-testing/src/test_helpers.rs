@@ -202,6 +202,10 @@ pub fn default_agents_instantiate_message() -> croncat_sdk_agents::msg::Instanti
         public_registration: false,
         pause_admin: Addr::unchecked(PAUSE_ADMIN),
         allowed_agents: Some(vec![AGENT.to_string()]),
+        min_agent_stake: None,
+        agent_slash_percentage: None,
+        agent_unbonding_period: None,
+        task_distribution: None,
     }
 }
 
@@ -1,4 +1,4 @@
-use crate::types::AgentStatus;
+use crate::types::{AgentPayout, AgentStatus, TaskDistribution};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Timestamp, Uint128, Uint64};
 use croncat_sdk_core::internal_messages::agents::{AgentOnTaskCompleted, AgentOnTaskCreated};
@@ -47,8 +47,24 @@ pub struct InstantiateMsg {
 
     /// If public registration is false, this provides initial, approved agent addresses
     pub allowed_agents: Option<Vec<String>>,
+
+    /// Native coins agent has to bond on registration
+    pub min_agent_stake: Option<u64>,
+
+    /// Percentage of the stake slashed when agent gets ejected for the missed slots
+    pub agent_slash_percentage: Option<u16>,
+
+    /// Seconds the stake of the unregistered agent stays bonded
+    pub agent_unbonding_period: Option<u64>,
+
+    /// How the task slots are split between the active agents
+    pub task_distribution: Option<TaskDistribution>,
 }
 
+/// Migrate message for agent contract, contract always migrates to its package version
+#[cw_serde]
+pub struct MigrateMsg {}
+
 /// Execute messages for agent contract
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -57,14 +73,26 @@ pub enum ExecuteMsg {
     /// Removes an agent from the whitelist
     /// Note: this does not kick the agent, but instead means they will not be able to re-register
     RemoveAgentFromWhitelist { agent_address: String },
-    /// Action registers new agent
-    RegisterAgent { payable_account_id: Option<String> },
-    /// Action for updating agents
-    UpdateAgent { payable_account_id: String },
+    /// Action registers new agent, attached native coins are bonded as the agent stake
+    RegisterAgent {
+        payable_account_id: Option<String>,
+        payout: Option<AgentPayout>,
+    },
+    /// Action for updating agents, `None` payout disables automatic payouts
+    UpdateAgent {
+        payable_account_id: String,
+        payout: Option<AgentPayout>,
+    },
     /// Action moves agent from pending to active list
     CheckInAgent {},
-    /// Actions for removing agent from the system
+    /// Actions for removing agent from the system, agent stake starts unbonding
     UnregisterAgent { from_behind: Option<bool> },
+    /// Withdraws the stake of the unregistered agent after the unbonding period
+    WithdrawUnbondedStake {},
+    /// Bonds attached native coins on top of the agent stake.
+    /// Agents registered before the stake was required have to bond up to `min_agent_stake`,
+    /// otherwise they can't check in and tick removes them from the active agents
+    BondStake {},
     /// Task contract will send message when task is created
     OnTaskCreated(AgentOnTaskCreated),
     /// Task contract will send message when task is completed
@@ -102,6 +130,9 @@ pub enum QueryMsg {
     /// Gets the specified agent tasks
     #[returns[AgentTaskResponse]]
     GetAgentTasks { account_id: String },
+    /// Gets the unbonding stake of the unregistered agent
+    #[returns[Option<crate::types::AgentUnbonding>]]
+    GetAgentUnbonding { account_id: String },
     /// Gets the agent contract configuration
     #[returns[crate::types::Config]]
     Config {},
@@ -131,12 +162,16 @@ pub struct AgentInfo {
     pub status: AgentStatus,
     /// Account where agent will move all his rewards
     pub payable_account_id: Addr,
-    /// Agent reward balance
+    /// Agent reward balance in the native denom
     pub balance: Uint128,
     /// Last executed slot number
     pub last_executed_slot: u64,
     /// Registration time
     pub register_start: Timestamp,
+    /// Bonded native coins
+    pub stake: Uint128,
+    /// Automatic payout of the rewards
+    pub payout: Option<AgentPayout>,
     /// Execution Totals - helpful for alerting & displays
     pub completed_block_tasks: Uint64,
     pub completed_cron_tasks: Uint64,
@@ -189,4 +224,16 @@ pub struct UpdateConfig {
 
     /// Determines whether agent registration is public or uses the whitelist (APPROVED_AGENTS Map)
     pub public_registration: Option<bool>,
+
+    /// Native coins agent has to bond on registration
+    pub min_agent_stake: Option<u64>,
+
+    /// Percentage of the stake slashed when agent gets ejected for the missed slots
+    pub agent_slash_percentage: Option<u16>,
+
+    /// Seconds the stake of the unregistered agent stays bonded
+    pub agent_unbonding_period: Option<u64>,
+
+    /// How the task slots are split between the active agents
+    pub task_distribution: Option<TaskDistribution>,
 }
@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Timestamp};
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint128};
 use std::fmt;
 
 #[cw_serde]
@@ -40,6 +40,31 @@ pub struct Agent {
     // Agent will be responsible to constantly monitor when it is their turn to join in active agent set (done as part of agent code loops)
     // Example data: 1633890060000000000 or 0
     pub register_start: Timestamp,
+
+    // Native coins bonded on registration, slashed for the missed slots
+    #[serde(default)]
+    pub stake: Uint128,
+
+    // Rewards get paid out automatically once they reach the threshold
+    #[serde(default)]
+    pub payout: Option<AgentPayout>,
+}
+
+/// Automatic payout of the agent rewards, done by the manager during the task execution
+#[cw_serde]
+pub struct AgentPayout {
+    /// Amounts the rewards have to reach, per gas denom.
+    /// Rewards of every denom get paid out once any of the thresholds is reached
+    pub thresholds: Vec<Coin>,
+    /// Pays out in this whitelisted cw20 through the treasury, instead of native coins
+    pub cw20_addr: Option<Addr>,
+}
+
+/// Stake of the unregistered agent, withdrawable after the unbonding period
+#[cw_serde]
+pub struct AgentUnbonding {
+    pub amount: Uint128,
+    pub release_time: Timestamp,
 }
 
 #[cw_serde]
@@ -53,6 +78,22 @@ pub struct AgentStats {
     // If agent does a task, this number is set to the current block.
     pub last_executed_slot: u64,
 }
+/// Tasks the agent got assigned by the last tick, compared to its completed tasks on the next one
+#[cw_serde]
+#[derive(Default)]
+pub struct AgentSlotAssignment {
+    /// Block height of the assignment
+    pub slot: u64,
+    pub block_tasks: u64,
+    pub cron_tasks: u64,
+    /// Completed tasks of the agent at the time of the assignment
+    pub completed_block_tasks: u64,
+    pub completed_cron_tasks: u64,
+    /// Slots missed since the agent last served all of its assigned tasks,
+    /// counted in proportion to the missed part of the assigned tasks
+    pub missed_slots: u64,
+}
+
 /// Contract configuration state
 #[cw_serde]
 pub struct Config {
@@ -83,7 +124,8 @@ pub struct Config {
     pub agent_nomination_block_duration: u16,
     /// Min coins that should be attached to register an agent
     pub min_coins_for_agent_registration: u64,
-    /// How many slots an agent can miss before being removed from the active queue
+    /// How many slots an agent can miss before being removed from the active queue.
+    /// Slot is missed if it passed before the agent served the tasks assigned to it
     pub agents_eject_threshold: u64,
     /// Minimum agent count in active queue to be untouched by bad agent verifier
     pub min_active_agent_count: u16,
@@ -95,6 +137,31 @@ pub struct Config {
     /// Note that once this becomes true, it's intentionally meant to be true forever,
     /// since this is an aspect of progressive decentralization
     pub public_registration: bool,
+    /// Native coins agent has to bond on registration.
+    /// Zero means no stake is required and no funds could be attached
+    #[serde(default)]
+    pub min_agent_stake: u64,
+    /// Percentage of the stake slashed to the treasury when agent gets ejected for the missed slots
+    #[serde(default)]
+    pub agent_slash_percentage: u16,
+    /// Seconds the stake of the unregistered agent stays bonded
+    #[serde(default)]
+    pub agent_unbonding_period: u64,
+    /// How the task slots are split between the active agents
+    #[serde(default)]
+    pub task_distribution: TaskDistribution,
+}
+
+/// Strategy of splitting the task slots between the active agents
+#[cw_serde]
+#[derive(Default)]
+pub enum TaskDistribution {
+    /// Slots are split evenly, agents with fewer completed tasks go first
+    #[default]
+    RoundRobin,
+    /// Slots are split by the agent success rate and stake,
+    /// so agents that miss slots get a smaller share
+    Weighted,
 }
 
 #[cfg(test)]
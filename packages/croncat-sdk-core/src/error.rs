@@ -5,3 +5,12 @@ pub enum SdkError {
     #[error("Invalid gas input")]
     InvalidGas {},
 }
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MigrationError {
+    #[error("Can't migrate from a different contract: {contract}")]
+    InvalidMigrationContract { contract: String },
+
+    #[error("Can't migrate from version {from} to {to}")]
+    InvalidMigrationVersion { from: String, to: String },
+}
@@ -72,3 +72,114 @@ impl ManagerCreateTaskBalance {
 enum CreateTaskBalanceMsg {
     CreateTaskBalance(ManagerCreateTaskBalance),
 }
+
+// Note: sender and cw20 validated on the tasks contract
+#[cw_serde]
+pub struct ManagerUpdateTaskBalance {
+    pub sender: Addr,
+    pub old_task_hash: Vec<u8>,
+    pub new_task_hash: Vec<u8>,
+    pub recurring: bool,
+    pub cw20: Option<Cw20CoinVerified>,
+    pub amount_for_one_task: AmountForOneTask,
+}
+
+impl ManagerUpdateTaskBalance {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = UpdateTaskBalanceMsg::UpdateTaskBalance(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(
+        self,
+        contract_addr: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds,
+        };
+        Ok(execute.into())
+    }
+}
+
+#[cw_serde]
+pub(crate) enum UpdateTaskBalanceMsg {
+    UpdateTaskBalance(ManagerUpdateTaskBalance),
+}
+
+/// Sends task balance to the manager of the newer version
+#[cw_serde]
+pub struct ManagerMigrateTaskBalance {
+    /// Owner of the task, validated on the tasks contract.
+    /// Unbonding stake of the task is left to the owner, as it can't be moved to the other manager
+    pub sender: Addr,
+    pub task_hash: Vec<u8>,
+    pub new_manager_addr: Addr,
+}
+
+impl ManagerMigrateTaskBalance {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = MigrateTaskBalanceMsg::MigrateTaskBalance(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+#[cw_serde]
+pub(crate) enum MigrateTaskBalanceMsg {
+    MigrateTaskBalance(ManagerMigrateTaskBalance),
+}
+
+// Note: natives attached as funds, cw20 transferred before this message
+#[cw_serde]
+pub struct ManagerImportTaskBalance {
+    pub task_hash: Vec<u8>,
+    pub cw20: Option<Cw20CoinVerified>,
+    /// Denom the task pays the gas in, native denom of the manager if not set
+    #[serde(default)]
+    pub gas_denom: Option<String>,
+}
+
+impl ManagerImportTaskBalance {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = ImportTaskBalanceMsg::ImportTaskBalance(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(
+        self,
+        contract_addr: T,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds,
+        };
+        Ok(execute.into())
+    }
+}
+
+#[cw_serde]
+pub(crate) enum ImportTaskBalanceMsg {
+    ImportTaskBalance(ManagerImportTaskBalance),
+}
@@ -5,3 +5,4 @@ pub mod internal_messages;
 #[cfg(test)]
 mod tests;
 pub mod types;
+pub mod version;
@@ -0,0 +1,116 @@
+use cosmwasm_std::to_binary;
+use cosmwasm_std::Addr;
+use cosmwasm_std::StdError;
+use cosmwasm_std::WasmMsg;
+use cosmwasm_std::{coins, Uint128};
+use cw20::Cw20CoinVerified;
+
+use crate::internal_messages::manager::{
+    ImportTaskBalanceMsg, ManagerImportTaskBalance, ManagerMigrateTaskBalance,
+    ManagerUpdateTaskBalance, MigrateTaskBalanceMsg, UpdateTaskBalanceMsg,
+};
+use crate::types::AmountForOneTask;
+
+#[test]
+fn manager_update_task_balance() -> Result<(), StdError> {
+    let update_balance = ManagerUpdateTaskBalance {
+        sender: Addr::unchecked("owner"),
+        old_task_hash: "23743450d67e0182ac1c2ace859151e92123bb8b4e3a490a2c0ff8a7b01b0391".into(),
+        new_task_hash: "5ff2ae1e41c2bf1e9ec7eb6df3c0b1c5bf1c8f9d7d8e1ad7be3f5d50e1ac1a39".into(),
+        recurring: true,
+        cw20: Some(Cw20CoinVerified {
+            address: Addr::unchecked("cw20"),
+            amount: Uint128::new(10),
+        }),
+        amount_for_one_task: AmountForOneTask {
+            gas: 300_000,
+            ..Default::default()
+        },
+    };
+
+    let msg = update_balance.clone().into_binary()?;
+    assert_eq!(
+        msg,
+        to_binary(&UpdateTaskBalanceMsg::UpdateTaskBalance(
+            update_balance.clone()
+        ))?
+    );
+
+    let cosmos_msg =
+        update_balance.into_cosmos_msg(Addr::unchecked("addr"), coins(100, "denom"))?;
+    assert_eq!(
+        cosmos_msg,
+        WasmMsg::Execute {
+            contract_addr: "addr".into(),
+            msg,
+            funds: coins(100, "denom"),
+        }
+        .into()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn manager_migrate_task_balance() -> Result<(), StdError> {
+    let migrate_balance = ManagerMigrateTaskBalance {
+        sender: Addr::unchecked("owner"),
+        task_hash: "23743450d67e0182ac1c2ace859151e92123bb8b4e3a490a2c0ff8a7b01b0391".into(),
+        new_manager_addr: Addr::unchecked("new_manager"),
+    };
+
+    let msg = migrate_balance.clone().into_binary()?;
+    assert_eq!(
+        msg,
+        to_binary(&MigrateTaskBalanceMsg::MigrateTaskBalance(
+            migrate_balance.clone()
+        ))?
+    );
+
+    let cosmos_msg = migrate_balance.into_cosmos_msg(Addr::unchecked("addr"))?;
+    assert_eq!(
+        cosmos_msg,
+        WasmMsg::Execute {
+            contract_addr: "addr".into(),
+            msg,
+            funds: vec![],
+        }
+        .into()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn manager_import_task_balance() -> Result<(), StdError> {
+    let import_balance = ManagerImportTaskBalance {
+        task_hash: "23743450d67e0182ac1c2ace859151e92123bb8b4e3a490a2c0ff8a7b01b0391".into(),
+        cw20: Some(Cw20CoinVerified {
+            address: Addr::unchecked("cw20"),
+            amount: Uint128::new(10),
+        }),
+        gas_denom: None,
+    };
+
+    let msg = import_balance.clone().into_binary()?;
+    assert_eq!(
+        msg,
+        to_binary(&ImportTaskBalanceMsg::ImportTaskBalance(
+            import_balance.clone()
+        ))?
+    );
+
+    let cosmos_msg =
+        import_balance.into_cosmos_msg(Addr::unchecked("addr"), coins(100, "denom"))?;
+    assert_eq!(
+        cosmos_msg,
+        WasmMsg::Execute {
+            contract_addr: "addr".into(),
+            msg,
+            funds: coins(100, "denom"),
+        }
+        .into()
+    );
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub mod manager;
 pub mod tasks;
 pub mod types;
+pub mod version;
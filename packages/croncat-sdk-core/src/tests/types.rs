@@ -21,6 +21,8 @@ fn amount_for_one_task_add_gas() {
         agent_fee: u16::default(),
         treasury_fee: u16::default(),
         gas_price: GasPrice::default(),
+        gas_price_tolerance: None,
+        gas_denom: None,
     };
 
     amount.add_gas(10);
@@ -42,6 +44,8 @@ fn amount_for_one_task_add_coin() {
         agent_fee: u16::default(),
         treasury_fee: u16::default(),
         gas_price: GasPrice::default(),
+        gas_price_tolerance: None,
+        gas_denom: None,
     };
 
     // Add the first coin
@@ -94,6 +98,8 @@ fn amount_for_one_task_add_cw20() {
         agent_fee: u16::default(),
         treasury_fee: u16::default(),
         gas_price: GasPrice::default(),
+        gas_price_tolerance: None,
+        gas_denom: None,
     };
 
     // Add cw20 coin
@@ -142,6 +148,8 @@ fn amount_for_one_task_sub_coin() {
         agent_fee: u16::default(),
         treasury_fee: u16::default(),
         gas_price: GasPrice::default(),
+        gas_price_tolerance: None,
+        gas_denom: None,
     };
 
     let coin1 = coin(10, "denom1".to_string());
@@ -155,6 +163,8 @@ fn amount_for_one_task_sub_coin() {
             agent_fee: u16::default(),
             treasury_fee: u16::default(),
             gas_price: GasPrice::default(),
+            gas_price_tolerance: None,
+            gas_denom: None,
         }
     );
 
@@ -233,6 +243,8 @@ fn amount_for_one_task_sub_cw20() {
         agent_fee: u16::default(),
         treasury_fee: u16::default(),
         gas_price: GasPrice::default(),
+        gas_price_tolerance: None,
+        gas_denom: None,
     };
 
     let cw20 = Cw20CoinVerified {
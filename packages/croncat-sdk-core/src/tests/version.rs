@@ -0,0 +1,69 @@
+use std::cmp::Ordering;
+
+use cosmwasm_std::testing::MockStorage;
+use cosmwasm_std::{StdError, Storage};
+
+use crate::version::{can_migrate, compare_versions, run_state_migrations, StateMigration};
+
+#[test]
+fn compare_versions_test() {
+    assert_eq!(compare_versions("0.1", "0.1"), Some(Ordering::Equal));
+    assert_eq!(compare_versions("1.0", "1.0.0"), Some(Ordering::Equal));
+    assert_eq!(compare_versions("0.1", "0.2"), Some(Ordering::Less));
+    assert_eq!(compare_versions("0.10", "0.9"), Some(Ordering::Greater));
+    assert_eq!(compare_versions("1.0.1", "1.0"), Some(Ordering::Greater));
+    assert_eq!(compare_versions("1.0.0", "2"), Some(Ordering::Less));
+
+    // Not numeric
+    assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), None);
+    assert_eq!(compare_versions("", "1.0.0"), None);
+    assert_eq!(compare_versions("1..0", "1.0.0"), None);
+}
+
+#[test]
+fn can_migrate_test() {
+    assert!(can_migrate("0.1", "0.1"));
+    assert!(can_migrate("0.1", "0.2"));
+    assert!(can_migrate("0.1", "1.0.0"));
+    assert!(!can_migrate("1.0.0", "0.1"));
+    assert!(!can_migrate("0.1", "latest"));
+}
+
+#[test]
+fn run_state_migrations_test() {
+    fn migrate_one(storage: &mut dyn Storage) -> Result<(), StdError> {
+        storage.set(b"one", b"1");
+        Ok(())
+    }
+    fn migrate_two(storage: &mut dyn Storage) -> Result<(), StdError> {
+        storage.set(b"two", b"2");
+        Ok(())
+    }
+    fn migrate_fail(_storage: &mut dyn Storage) -> Result<(), StdError> {
+        Err(StdError::generic_err("failed migration"))
+    }
+    let migrations: &[(&str, StateMigration<StdError>)] =
+        &[("0.2", migrate_one), ("0.3", migrate_two)];
+
+    // Only newer versions get migrated
+    let mut storage = MockStorage::new();
+    run_state_migrations(&mut storage, "0.2", migrations).unwrap();
+    assert_eq!(storage.get(b"one"), None);
+    assert_eq!(storage.get(b"two"), Some(b"2".to_vec()));
+
+    let mut storage = MockStorage::new();
+    run_state_migrations(&mut storage, "0.1", migrations).unwrap();
+    assert_eq!(storage.get(b"one"), Some(b"1".to_vec()));
+    assert_eq!(storage.get(b"two"), Some(b"2".to_vec()));
+
+    // Already latest
+    let mut storage = MockStorage::new();
+    run_state_migrations(&mut storage, "0.3", migrations).unwrap();
+    assert_eq!(storage.get(b"one"), None);
+    assert_eq!(storage.get(b"two"), None);
+
+    // Error stops the migration
+    let migrations: &[(&str, StateMigration<StdError>)] = &[("0.2", migrate_fail)];
+    let err = run_state_migrations(&mut MockStorage::new(), "0.1", migrations).unwrap_err();
+    assert_eq!(err, StdError::generic_err("failed migration"));
+}
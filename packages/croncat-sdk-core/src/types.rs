@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Coin, StdError, StdResult};
+use cosmwasm_std::{Coin, Decimal, StdError, StdResult};
 use cw20::Cw20CoinVerified;
 
 use crate::error::SdkError;
@@ -48,6 +48,11 @@ impl GasPrice {
 
         Ok(price as u128)
     }
+
+    /// Price of one unit of gas, without the gas adjustment
+    pub fn price(&self) -> Decimal {
+        Decimal::from_ratio(self.numerator, self.denominator)
+    }
 }
 
 impl Default for GasPrice {
@@ -72,9 +77,31 @@ pub struct AmountForOneTask {
     pub agent_fee: u16,
     pub treasury_fee: u16,
     pub gas_price: GasPrice,
+    /// Percentage of the `gas_price` task agrees to pay at most,
+    /// if manager reads the gas price at the execution time
+    #[serde(default)]
+    pub gas_price_tolerance: Option<u16>,
+    /// Denom the task pays the gas in, native denom of the manager if not set
+    #[serde(default)]
+    pub gas_denom: Option<String>,
 }
 
 impl AmountForOneTask {
+    /// Denom of the gas, falls back to the given native denom
+    pub fn gas_denom<'a>(&'a self, native_denom: &'a str) -> &'a str {
+        self.gas_denom.as_deref().unwrap_or(native_denom)
+    }
+
+    /// Given gas amount with the agent and treasury fees on top of it
+    pub fn gas_with_fees(&self, gas_amount: u64) -> Result<u64, SdkError> {
+        let fee = self.agent_fee as u64 + self.treasury_fee as u64;
+        gas_amount
+            .checked_mul(fee)
+            .map(|fee| fee / 100)
+            .and_then(|fee| fee.checked_add(gas_amount))
+            .ok_or(SdkError::InvalidGas {})
+    }
+
     pub fn add_gas(&mut self, gas: u64) {
         self.gas = self.gas.saturating_add(gas);
     }
@@ -0,0 +1,93 @@
+use std::cmp::Ordering;
+
+use cosmwasm_std::{Response, StdError, Storage};
+use cw2::{get_contract_version, set_contract_version};
+
+use crate::error::MigrationError;
+
+/// State transformation, that runs during contract migration
+pub type StateMigration<E> = fn(&mut dyn Storage) -> Result<(), E>;
+
+/// Compares two dot separated versions, like `0.1` or `1.0.0`
+/// Missing parts treated as zeros, so `1.0` and `1.0.0` are equal
+/// Returns `None` if any of the versions is not numeric
+pub fn compare_versions(left: &str, right: &str) -> Option<Ordering> {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect()
+    };
+    let left = parse(left)?;
+    let right = parse(right)?;
+    let len = left.len().max(right.len());
+    let ordering = (0..len)
+        .map(|i| {
+            let l = left.get(i).copied().unwrap_or_default();
+            let r = right.get(i).copied().unwrap_or_default();
+            l.cmp(&r)
+        })
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal);
+    Some(ordering)
+}
+
+/// Migration allowed only to the same or newer version
+pub fn can_migrate(from_version: &str, to_version: &str) -> bool {
+    matches!(
+        compare_versions(from_version, to_version),
+        Some(Ordering::Less | Ordering::Equal)
+    )
+}
+
+/// Runs every state migration of the version newer than `from_version`.
+/// Migrations expected to be ordered from the oldest version to the newest,
+/// so every migration runs if contract migrates from the older version
+pub fn run_state_migrations<E>(
+    storage: &mut dyn Storage,
+    from_version: &str,
+    migrations: &[(&str, StateMigration<E>)],
+) -> Result<(), E> {
+    for (version, migration) in migrations {
+        if compare_versions(from_version, version) == Some(Ordering::Less) {
+            migration(storage)?;
+        }
+    }
+    Ok(())
+}
+
+/// Migrates the contract to `contract_version`.
+/// Allowed only from the same contract with the same or older version.
+/// Runs state migrations of every version newer than the stored one
+pub fn migrate_contract<E>(
+    storage: &mut dyn Storage,
+    contract_name: &str,
+    contract_version: &str,
+    migrations: &[(&str, StateMigration<E>)],
+) -> Result<Response, E>
+where
+    E: From<StdError> + From<MigrationError>,
+{
+    let stored = get_contract_version(storage)?;
+    if stored.contract != contract_name {
+        return Err(MigrationError::InvalidMigrationContract {
+            contract: stored.contract,
+        }
+        .into());
+    }
+    if !can_migrate(&stored.version, contract_version) {
+        return Err(MigrationError::InvalidMigrationVersion {
+            from: stored.version,
+            to: contract_version.to_owned(),
+        }
+        .into());
+    }
+
+    run_state_migrations(storage, &stored.version, migrations)?;
+    set_contract_version(storage, contract_name, contract_version)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", contract_version))
+}
@@ -22,6 +22,9 @@ pub struct FactoryInstantiateMsg {
     pub owner_addr: Option<String>,
 }
 
+#[cw_serde]
+pub struct FactoryMigrateMsg {}
+
 #[cw_serde]
 pub enum FactoryExecuteMsg {
     /// Deploys contract and saves metadata of the contract to the factory
@@ -4,3 +4,9 @@ use cw_storage_plus::Map;
 /// Safe way to export map of the croncat-factory, but avoid any contract imports
 /// Contract name with the version to the Addr
 pub const CONTRACT_ADDRS: Map<(&str, &[u8]), Addr> = Map::new("contract_addrs");
+
+/// Contract Addr linked to contract name
+pub const CONTRACT_ADDRS_LOOKUP: Map<Addr, String> = Map::new("contract_addrs_lookup");
+
+/// Latest contract name to the Addr
+pub const LATEST_ADDRS: Map<&str, Addr> = Map::new("latest_addrs");
@@ -1,11 +1,14 @@
-use crate::types::UpdateConfig;
+use crate::types::{TaskEndReason, UpdateConfig};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Coin};
 use croncat_sdk_core::internal_messages::agents::AgentWithdrawOnRemovalArgs;
-use croncat_sdk_core::internal_messages::manager::{ManagerCreateTaskBalance, ManagerRemoveTask};
+use croncat_sdk_core::internal_messages::manager::{
+    ManagerCreateTaskBalance, ManagerImportTaskBalance, ManagerMigrateTaskBalance,
+    ManagerRemoveTask, ManagerUpdateTaskBalance,
+};
 use croncat_sdk_core::types::GasPrice;
 
-use cw20::Cw20Coin;
+use cw20::{Cw20Coin, Cw20CoinVerified};
 
 #[cw_serde]
 pub struct ManagerInstantiateMsg {
@@ -30,6 +33,9 @@ pub struct ManagerInstantiateMsg {
     pub cw20_whitelist: Option<Vec<String>>,
 }
 
+#[cw_serde]
+pub struct ManagerMigrateMsg {}
+
 #[cw_serde]
 pub enum ManagerExecuteMsg {
     /// Updates the croncat Config.
@@ -61,6 +67,20 @@ pub enum ManagerExecuteMsg {
         cw20: Cw20Coin,
     },
 
+    /// Pay for the gas of the task with attached native coins,
+    /// sponsor gets the unused part back when task ends.
+    /// Sender has to be approved by the task owner first
+    SponsorTask {
+        task_hash: String,
+    },
+
+    /// Approve the address to sponsor the task, can only be done by the task owner.
+    /// Previous sponsor gets its unused gas balance back
+    ApproveSponsor {
+        task_hash: String,
+        sponsor_addr: String,
+    },
+
     /// Receive cw20 coin
     Receive(cw20::Cw20ReceiveMsg),
 
@@ -70,6 +90,15 @@ pub enum ManagerExecuteMsg {
     /// Remove task's balance, called by the tasks contract
     RemoveTask(ManagerRemoveTask),
 
+    /// Move task's balance to the updated task hash, called by the tasks contract
+    UpdateTaskBalance(Box<ManagerUpdateTaskBalance>),
+
+    /// Send task's balance to the manager of the newer version, called by the tasks contract
+    MigrateTaskBalance(ManagerMigrateTaskBalance),
+
+    /// Receive task's balance from the other version of the manager
+    ImportTaskBalance(ManagerImportTaskBalance),
+
     /// Move balances from the manager to the owner address, or treasury_addr if set
     OwnerWithdraw {},
 
@@ -79,6 +108,12 @@ pub enum ManagerExecuteMsg {
         limit: Option<u64>,
     },
 
+    /// Release the stake of the tasks, that finished unbonding.
+    /// Coins get back to the task balance, or to the task owner if the task is no longer around
+    ReleaseUnbondings {
+        limit: Option<u64>,
+    },
+
     /// Withdraw agent rewards on agent removal, this should be called only by agent contract
     AgentWithdraw(Option<AgentWithdrawOnRemovalArgs>),
 
@@ -99,9 +134,9 @@ pub enum ManagerQueryMsg {
     #[returns[bool]]
     Paused {},
 
-    /// Gets manager available balances
+    /// Gets manager available balance of the gas denom, native denom by default
     #[returns(cosmwasm_std::Uint128)]
-    TreasuryBalance {},
+    TreasuryBalance { denom: Option<String> },
     /// Gets Cw20 balances of the given wallet address
     #[returns(Vec<cw20::Cw20CoinVerified>)]
     UsersBalances {
@@ -113,8 +148,28 @@ pub enum ManagerQueryMsg {
     #[returns(crate::types::TaskBalanceResponse)]
     TaskBalance { task_hash: String },
 
+    /// Gets agent rewards of the gas denom, native denom by default
     #[returns(cosmwasm_std::Uint128)]
-    AgentRewards { agent_id: String },
+    AgentRewards {
+        agent_id: String,
+        denom: Option<String>,
+    },
+
+    /// Get the latest executions of the task, newest first
+    #[returns(Vec<crate::types::TaskExecutionRecord>)]
+    TaskExecutionHistory {
+        task_hash: String,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    },
+
+    /// Get tasks funded by the sponsor
+    #[returns(Vec<crate::types::SponsoredTask>)]
+    SponsoredTasks {
+        sponsor_addr: String,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    },
 }
 
 #[cw_serde]
@@ -122,9 +177,79 @@ pub enum ManagerReceiveMsg {
     RefillTempBalance {},
     RefillTaskBalance { task_hash: String },
 }
+
+/// Query of the gas price oracle, used by the manager with [`GasPriceSource::Oracle`](crate::types::GasPriceSource::Oracle)
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum GasPriceOracleQueryMsg {
+    /// Current gas price of the denom
+    #[returns(cosmwasm_std::Decimal)]
+    GasPrice { denom: String },
+}
+
+/// Execute message the treasury contract has to implement, used by the manager to pay agents in cw20
+#[cw_serde]
+pub enum TreasuryExecuteMsg {
+    /// Pays the equivalent of the attached native coins to the recipient in the cw20
+    AgentPayout {
+        recipient: String,
+        cw20_addr: String,
+    },
+}
+
+/// Query of the treasury contract, used to check agent payouts in cw20 before accepting them
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum TreasuryQueryMsg {
+    /// Whether treasury pays agents in the cw20
+    #[returns(bool)]
+    SupportsCw20Payout { cw20_addr: String },
+}
+
+/// Sudo messages of the chain modules
+#[cw_serde]
+pub enum ManagerSudoMsg {
+    /// Result of the ibc transfer made by the task, sent by the ibc-hooks module
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        /// Source channel of the transfer
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        /// Source channel of the transfer
+        channel: String,
+        sequence: u64,
+    },
+}
+
+/// Message sent to the callback contracts of the task.
+/// Receiving contract should have `CroncatTaskEnded(TaskEndedCallback)` variant in its execute message
+#[cw_serde]
+pub enum TaskCallbackMsg {
+    CroncatTaskEnded(TaskEndedCallback),
+}
+
+#[cw_serde]
+pub struct TaskEndedCallback {
+    pub task_hash: String,
+    pub end_reason: TaskEndReason,
+    /// Coins refunded to the task owner
+    pub refunded: Vec<Coin>,
+    /// Cw20 moved back to the temporary balance of the task owner
+    pub cw20_refunded: Option<Cw20CoinVerified>,
+}
+
 #[cw_serde]
 pub struct AgentWithdrawCallback {
     pub agent_id: String,
-    pub amount: Uint128,
+    /// Withdrawn rewards of every gas denom
+    pub amount: Vec<Coin>,
     pub payable_account_id: String,
 }
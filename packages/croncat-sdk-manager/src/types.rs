@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, StdError, StdResult, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, StdError, StdResult, Timestamp, Uint128};
 use croncat_sdk_core::types::GasPrice;
 use cw20::Cw20CoinVerified;
 
@@ -14,9 +14,35 @@ pub struct TaskBalance {
     pub native_balance: Uint128,
     pub cw20_balance: Option<Cw20CoinVerified>,
     pub ibc_balance: Option<Coin>,
+    /// Third party paying for the gas of the task
+    #[serde(default)]
+    pub sponsor: Option<TaskSponsor>,
+    /// Denom of the `native_balance`, native denom of the manager if not set
+    #[serde(default)]
+    pub gas_denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct TaskSponsor {
+    pub sponsor_addr: Addr,
+    /// Native coins charged for the gas before the task native balance,
+    /// unused part goes back to the sponsor when task ends
+    pub gas_balance: Uint128,
+}
+
+/// Task funded by the sponsor
+#[cw_serde]
+pub struct SponsoredTask {
+    pub task_hash: String,
+    pub gas_balance: Uint128,
 }
 
 impl TaskBalance {
+    /// Denom the task pays the gas in, falls back to the given native denom
+    pub fn gas_denom<'a>(&'a self, native_denom: &'a str) -> &'a str {
+        self.gas_denom.as_deref().unwrap_or(native_denom)
+    }
+
     pub fn verify_enough_attached(
         &self,
         native_required: Uint128,
@@ -100,6 +126,30 @@ impl TaskBalance {
         }
     }
 
+    /// Balance to verify the requirements against, sponsor covers the gas up to `native_for_gas`
+    pub fn with_sponsored_gas(&self, native_for_gas: Uint128) -> TaskBalance {
+        let sponsored = self.sponsor.as_ref().map_or(Uint128::zero(), |sponsor| {
+            sponsor.gas_balance.min(native_for_gas)
+        });
+        TaskBalance {
+            native_balance: self.native_balance + sponsored,
+            sponsor: None,
+            ..self.clone()
+        }
+    }
+
+    /// Charges the gas from the sponsor first, the rest from the native balance
+    pub fn sub_gas(&mut self, native_for_gas: Uint128) -> StdResult<()> {
+        let mut unsponsored = native_for_gas;
+        if let Some(sponsor) = &mut self.sponsor {
+            let sponsored = sponsor.gas_balance.min(native_for_gas);
+            sponsor.gas_balance -= sponsored;
+            unsponsored -= sponsored;
+        }
+        self.native_balance = self.native_balance.checked_sub(unsponsored)?;
+        Ok(())
+    }
+
     pub fn sub_coin(&mut self, coin: &Coin, native_denom: &str) -> StdResult<()> {
         if coin.denom == native_denom {
             self.native_balance = self
@@ -121,6 +171,25 @@ impl TaskBalance {
         Ok(())
     }
 
+    pub fn add_coin(&mut self, coin: &Coin, native_denom: &str) -> StdResult<()> {
+        if coin.denom == native_denom {
+            self.native_balance = self.native_balance.checked_add(coin.amount)?;
+        } else {
+            match &mut self.ibc_balance {
+                Some(task_coin) if task_coin.denom == coin.denom => {
+                    task_coin.amount = task_coin.amount.checked_add(coin.amount)?;
+                }
+                None => self.ibc_balance = Some(coin.clone()),
+                _ => {
+                    return Err(StdError::generic_err(
+                        "Task balance has different ibc denom",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn sub_cw20(&mut self, cw20: &Cw20CoinVerified) -> StdResult<()> {
         match &mut self.cw20_balance {
             Some(task_cw20) if task_cw20.address == cw20.address => {
@@ -164,27 +233,183 @@ pub struct Config {
     pub agent_fee: u16,
     pub treasury_fee: u16,
     pub gas_price: GasPrice,
+    /// Where the gas price of the task execution comes from
+    #[serde(default)]
+    pub gas_price_source: GasPriceSource,
 
     // Treasury
     pub treasury_addr: Option<Addr>,
     pub cw20_whitelist: Vec<Addr>,
     pub native_denom: String,
+    /// Whitelisted denoms, other than `native_denom`, tasks can pay the gas in
+    #[serde(default)]
+    pub gas_denoms: Vec<GasDenom>,
+    /// Unbonding period of the chain in seconds, undelegated stake of the tasks
+    /// is released after it
+    #[serde(default = "default_unbonding_period")]
+    pub unbonding_period: u64,
 
     // The default query limit
     pub limit: u64,
 }
 
+/// Unbonding period of the config, if not set
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 21 * 24 * 60 * 60;
+
+fn default_unbonding_period() -> u64 {
+    DEFAULT_UNBONDING_PERIOD
+}
+
+impl Config {
+    /// Gas price of the denom, `None` if denom is not whitelisted for the gas
+    pub fn gas_price_of(&self, denom: &str) -> Option<&GasPrice> {
+        if denom == self.native_denom {
+            return Some(&self.gas_price);
+        }
+        self.gas_denoms
+            .iter()
+            .find(|gas_denom| gas_denom.denom == denom)
+            .map(|gas_denom| &gas_denom.gas_price)
+    }
+}
+
+/// Denom whitelisted for paying the gas, with its own gas price
+#[cw_serde]
+pub struct GasDenom {
+    pub denom: String,
+    pub gas_price: GasPrice,
+}
+
+/// Source of the gas price paid by the task at the execution time
+#[cw_serde]
+pub enum GasPriceSource {
+    /// Task pays the gas price frozen at its creation
+    Frozen {},
+    /// Smart query [`GasPriceOracleQueryMsg::GasPrice`](crate::msg::GasPriceOracleQueryMsg::GasPrice) of the oracle contract
+    Oracle {
+        contract_addr: String,
+        limits: GasPriceLimits,
+    },
+    /// Stargate query of the chain fee market.
+    /// First field of the response is the base fee as `sdk.Dec`,
+    /// for example `/osmosis.txfees.v1beta1.Query/GetEipBaseFee`
+    FeeMarket {
+        path: String,
+        data: Binary,
+        limits: GasPriceLimits,
+    },
+}
+
+impl Default for GasPriceSource {
+    fn default() -> Self {
+        GasPriceSource::Frozen {}
+    }
+}
+
+impl GasPriceSource {
+    pub fn limits(&self) -> Option<&GasPriceLimits> {
+        match self {
+            GasPriceSource::Frozen {} => None,
+            GasPriceSource::Oracle { limits, .. } | GasPriceSource::FeeMarket { limits, .. } => {
+                Some(limits)
+            }
+        }
+    }
+}
+
+#[cw_serde]
+pub struct GasPriceLimits {
+    /// Gas price read from the source is bounded by those
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    /// Percentage of the frozen gas price task agrees to pay at most,
+    /// stored in the task at its creation
+    pub tolerance: u16,
+}
+
+impl GasPriceLimits {
+    pub fn is_valid(&self) -> bool {
+        !self.min_price.is_zero() && self.min_price <= self.max_price && self.tolerance >= 100
+    }
+}
+
+/// Amount of the latest executions kept in the history of every task
+pub const TASK_EXECUTION_HISTORY_LIMIT: u64 = 20;
+
+/// Single execution of the task, kept in the task execution history
+#[cw_serde]
+pub struct TaskExecutionRecord {
+    pub block_height: u64,
+    pub block_time: Timestamp,
+    /// Agent that executed the task
+    pub agent_addr: Addr,
+    /// Native coins taken from the task balance for gas and fees
+    pub gas_charged: Coin,
+    /// Result of every action, in the same order as task actions
+    pub action_results: Vec<ActionResult>,
+    /// Set if task ended after this execution
+    pub end_reason: Option<TaskEndReason>,
+}
+
+#[cw_serde]
+pub enum ActionResult {
+    Success,
+    /// Action failed with the given error
+    Failure(String),
+    /// Action condition was not met, action didn't run
+    Skipped,
+}
+
+/// Reason why task ended and its balance got refunded
+#[cw_serde]
+pub enum TaskEndReason {
+    /// Task was not recurring
+    NotRecurring,
+    /// One of the actions failed and task had `stop_on_fail` set
+    StopOnFail,
+    /// Task reached its maximum amount of executions
+    MaxExecutions,
+    /// Task balance is not enough for the next execution
+    BalanceDrained,
+    /// Task is out of its boundary
+    BoundaryEnded,
+    /// Task became invalid after the query transforms
+    Invalidated,
+    /// Gas price task agreed to pay is below the minimum gas price
+    GasPriceTooLow,
+    /// Query transform result is not a valid value for the action
+    TransformFailed,
+}
+
+impl TaskEndReason {
+    /// Task ended before its schedule was done
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            TaskEndReason::StopOnFail
+                | TaskEndReason::BalanceDrained
+                | TaskEndReason::Invalidated
+                | TaskEndReason::GasPriceTooLow
+                | TaskEndReason::TransformFailed
+        )
+    }
+}
+
 #[cw_serde]
 pub struct UpdateConfig {
     pub agent_fee: Option<u16>,
     pub treasury_fee: Option<u16>,
     pub gas_price: Option<GasPrice>,
+    pub gas_price_source: Option<GasPriceSource>,
     pub croncat_tasks_key: Option<(String, [u8; 2])>,
     pub croncat_agents_key: Option<(String, [u8; 2])>,
     pub treasury_addr: Option<String>,
     /// Add supported cw20s
     /// That's seems unfair to undo support of cw20's after user already created a task with it
     pub cw20_whitelist: Option<Vec<String>>,
+    /// Replaces whitelisted gas denoms
+    pub gas_denoms: Option<Vec<GasDenom>>,
+    pub unbonding_period: Option<u64>,
 }
 
 #[cfg(test)]
@@ -194,7 +419,7 @@ mod test {
 
     use crate::SdkError;
 
-    use super::{GasPrice, TaskBalance};
+    use super::{GasPrice, TaskBalance, TaskSponsor};
 
     #[test]
     fn gas_price_validation() {
@@ -282,6 +507,8 @@ mod test {
             native_balance,
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
         assert!(task_balance
             .verify_enough_attached(Uint128::from(100u64), None, None, false, "denom")
@@ -295,6 +522,8 @@ mod test {
             native_balance,
             cw20_balance: Some(cw20.clone()),
             ibc_balance: Some(ibc_coin.clone()),
+            sponsor: None,
+            gas_denom: None,
         };
         // We're now validating you're not adding tokens that never get used, #noMoreBlackHoles
         assert!(task_balance
@@ -340,6 +569,8 @@ mod test {
             native_balance,
             cw20_balance: None,
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
         assert_eq!(
             task_balance
@@ -380,6 +611,8 @@ mod test {
             native_balance,
             cw20_balance: Some(cw20.clone()),
             ibc_balance: Some(ibc_coin.clone()),
+            sponsor: None,
+            gas_denom: None,
         };
         // cw20_balance is not sufficient
         assert_eq!(
@@ -462,6 +695,8 @@ mod test {
             native_balance,
             cw20_balance: None,
             ibc_balance: Some(ibc_coin.clone()),
+            sponsor: None,
+            gas_denom: None,
         };
 
         task_balance
@@ -473,6 +708,8 @@ mod test {
                 native_balance: Uint128::from(90u64),
                 cw20_balance: None,
                 ibc_balance: Some(ibc_coin),
+                sponsor: None,
+                gas_denom: None,
             }
         );
 
@@ -483,6 +720,8 @@ mod test {
                 native_balance: Uint128::from(90u64),
                 cw20_balance: None,
                 ibc_balance: Some(coin(99, "ibc")),
+                sponsor: None,
+                gas_denom: None,
             }
         );
 
@@ -509,6 +748,8 @@ mod test {
             native_balance,
             cw20_balance: Some(cw20),
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
 
         task_balance
@@ -526,6 +767,8 @@ mod test {
                     amount: Uint128::from(90u64),
                 }),
                 ibc_balance: None,
+                sponsor: None,
+                gas_denom: None,
             }
         );
 
@@ -556,6 +799,8 @@ mod test {
                 denom: ibc_denom.to_string(),
                 amount: Uint128::new(200),
             }),
+            sponsor: None,
+            gas_denom: None,
         };
 
         let coin_native = Coin {
@@ -587,6 +832,8 @@ mod test {
                 denom: ibc_denom.to_string(),
                 amount: Uint128::new(200),
             }),
+            sponsor: None,
+            gas_denom: None,
         };
 
         let coin_native_overflow = Coin {
@@ -625,6 +872,8 @@ mod test {
             }),
             native_balance: Uint128::zero(),
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
 
         let cw20 = Cw20CoinVerified {
@@ -649,6 +898,8 @@ mod test {
             }),
             native_balance: Uint128::zero(),
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
 
         let cw20 = Cw20CoinVerified {
@@ -670,6 +921,8 @@ mod test {
             }),
             native_balance: Uint128::zero(),
             ibc_balance: None,
+            sponsor: None,
+            gas_denom: None,
         };
 
         let cw20 = Cw20CoinVerified {
@@ -679,4 +932,41 @@ mod test {
 
         assert!(task_balance.sub_cw20(&cw20).is_err());
     }
+
+    #[test]
+    fn test_sub_gas_sponsored() {
+        let mut task_balance = TaskBalance {
+            native_balance: Uint128::new(100),
+            cw20_balance: None,
+            ibc_balance: None,
+            sponsor: Some(TaskSponsor {
+                sponsor_addr: Addr::unchecked("sponsor"),
+                gas_balance: Uint128::new(30),
+            }),
+            gas_denom: None,
+        };
+        // Sponsor covers the gas, but not the sends
+        assert!(task_balance
+            .with_sponsored_gas(Uint128::new(50))
+            .verify_enough_attached(Uint128::new(130), None, None, false, "denom")
+            .is_ok());
+        assert!(task_balance
+            .with_sponsored_gas(Uint128::new(20))
+            .verify_enough_attached(Uint128::new(130), None, None, false, "denom")
+            .is_err());
+
+        task_balance.sub_gas(Uint128::new(20)).unwrap();
+        assert_eq!(task_balance.native_balance, Uint128::new(100));
+        assert_eq!(
+            task_balance.sponsor.as_ref().unwrap().gas_balance,
+            Uint128::new(10)
+        );
+
+        // Rest of the gas from the task balance
+        task_balance.sub_gas(Uint128::new(20)).unwrap();
+        assert_eq!(task_balance.native_balance, Uint128::new(90));
+        assert!(task_balance.sponsor.as_ref().unwrap().gas_balance.is_zero());
+
+        assert!(task_balance.sub_gas(Uint128::new(91)).is_err());
+    }
 }
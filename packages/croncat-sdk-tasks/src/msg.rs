@@ -1,8 +1,8 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary};
 use croncat_sdk_core::internal_messages::tasks::{TasksRemoveTaskByManager, TasksRescheduleTask};
 
-use crate::types::TaskRequest;
+use crate::types::{Task, TaskRequest, TemplatePlaceholder};
 
 #[cw_serde]
 pub struct TasksInstantiateMsg {
@@ -36,6 +36,9 @@ pub struct TasksInstantiateMsg {
     pub gas_limit: Option<u64>,
 }
 
+#[cw_serde]
+pub struct TasksMigrateMsg {}
+
 #[cw_serde]
 pub struct UpdateConfigMsg {
     pub croncat_factory_addr: Option<String>,
@@ -48,6 +51,14 @@ pub struct UpdateConfigMsg {
     pub gas_limit: Option<u64>,
 }
 
+/// Task migrated from the other version of the tasks contract
+#[cw_serde]
+pub struct TasksImportTask {
+    /// Hash of the task in the previous tasks contract, task balance imported with this hash
+    pub task_hash: String,
+    pub task: Task,
+}
+
 #[cw_serde]
 pub enum TasksExecuteMsg {
     UpdateConfig(UpdateConfigMsg),
@@ -62,7 +73,55 @@ pub enum TasksExecuteMsg {
     RemoveTask {
         task_hash: String,
     },
+
+    /// Replaces the task with the updated one, can only be done by the task owner.
+    /// Task balance gets moved to the new task hash, additional funds can be attached if needed.
+    UpdateTask {
+        task_hash: String,
+        task: Box<TaskRequest>,
+    },
+
+    /// Takes the task out of the schedule, can only be done by the task owner.
+    /// Task balance stays locked in the manager until task is resumed or removed.
+    PauseTask {
+        task_hash: String,
+    },
+
+    /// Puts paused task back into the schedule, can only be done by the task owner.
+    ResumeTask {
+        task_hash: String,
+    },
+
+    /// Registers a reusable task shape, values at the placeholder paths get replaced
+    /// by the params every time a task gets created from the template.
+    RegisterTemplate {
+        name: String,
+        task: Box<TaskRequest>,
+        placeholders: Vec<TemplatePlaceholder>,
+    },
+
+    /// Deletes the template, can only be done by the template owner.
+    /// Tasks created from the template stay untouched.
+    RemoveTemplate {
+        template_id: u64,
+    },
+
+    /// Creates the task from the template, sender becomes the owner of the task.
+    /// `params` are json values for each of the template placeholders, in the same order.
+    CreateTaskFromTemplate {
+        template_id: u64,
+        params: Vec<Binary>,
+    },
+
+    /// Moves the task to the latest tasks contract of the given version, can only be done by the task owner.
+    /// Task balance moved to the latest manager contract of the same version.
+    MigrateTask {
+        task_hash: String,
+        to_version: [u8; 2],
+    },
     // Methods for other internal contracts
+    /// Receive task from the other version of the tasks contract
+    ImportTask(Box<TasksImportTask>),
     /// Remove task, used by the manager if task reached it's stop condition
     RemoveTaskByManager(TasksRemoveTaskByManager),
     /// Try to reschedule a task, if possible, used by the manager
@@ -127,6 +186,28 @@ pub enum TasksQueryMsg {
     /// Simulate task_hash by the given task
     #[returns(String)]
     TaskHash { task: Box<crate::types::Task> },
+    /// Simulate task creation, returns the costs of the task and
+    /// errors that the task creation would fail with.
+    /// Deposit covers given amount of `executions`, but not less than the task creation requires
+    #[returns(crate::types::SimulateTaskResponse)]
+    SimulateTask {
+        task: Box<TaskRequest>,
+        executions: Option<u64>,
+        /// Address that creates the task, checks depending on the task owner are skipped if not set
+        sender: Option<String>,
+    },
+    /// Get the latest hash of the task, if it was updated by the owner
+    #[returns(Option<String>)]
+    UpdatedTaskHash { task_hash: String },
+    /// Get task template by id
+    #[returns(Option<crate::types::TaskTemplateResponse>)]
+    Template { template_id: u64 },
+    /// Get list of all task templates
+    #[returns(Vec<crate::types::TaskTemplateResponse>)]
+    Templates {
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    },
     /// Get slot hashes by given slot
     #[returns(crate::types::SlotHashesResponse)]
     SlotHashes { slot: Option<u64> },
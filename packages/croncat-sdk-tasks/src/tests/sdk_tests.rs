@@ -1,6 +1,6 @@
 use cosmwasm_std::Uint64;
 
-use crate::types::{get_next_block_by_offset, BoundaryHeight};
+use crate::types::{get_next_block_by_offset, BoundaryHeight, QueryRule};
 
 #[test]
 fn test_get_next_block_by_offset() {
@@ -52,3 +52,25 @@ fn test_get_next_block_by_offset() {
     let result = get_next_block_by_offset(block_height, &boundary_with_end, interval);
     assert_eq!(boundary_with_end.end.unwrap().u64(), result);
 }
+
+#[test]
+fn test_query_rule_evaluate() {
+    // Second query is not a croncat query
+    let results = [Some(true), None, Some(false)];
+
+    assert!(QueryRule::Query(0).evaluate(&results));
+    assert!(!QueryRule::Query(1).evaluate(&results));
+    assert!(!QueryRule::Query(2).evaluate(&results));
+    assert!(!QueryRule::Query(3).evaluate(&results));
+
+    let passed_or_deadline = QueryRule::Or(vec![QueryRule::Query(2), QueryRule::Query(0)]);
+    assert!(passed_or_deadline.evaluate(&results));
+    let passed_and_deadline = QueryRule::And(vec![QueryRule::Query(2), QueryRule::Query(0)]);
+    assert!(!passed_and_deadline.evaluate(&results));
+    assert!(QueryRule::Not(Box::new(passed_and_deadline)).evaluate(&results));
+    assert!(QueryRule::And(vec![
+        QueryRule::Query(0),
+        QueryRule::Not(Box::new(QueryRule::Query(2)))
+    ])
+    .evaluate(&results));
+}
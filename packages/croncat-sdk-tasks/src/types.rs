@@ -1,14 +1,18 @@
 use std::{fmt::Display, str::FromStr};
 
+use chrono::{Duration, FixedOffset, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    Addr, Binary, CosmosMsg, Empty, Env, Timestamp, TransactionInfo, Uint64, WasmQuery,
+    Addr, Binary, Coin, CosmosMsg, Decimal256, Empty, Env, StdError, StdResult, Timestamp,
+    TransactionInfo, Uint128, Uint64, WasmQuery,
 };
 use cron_schedule::Schedule;
-use croncat_mod_generic::types::PathToValue;
+use croncat_mod_generic::{types::PathToValue, value_ordering::ValueOrdering};
 pub use croncat_sdk_core::types::AmountForOneTask;
-use cw20::Cw20Coin;
+use cw20::{Cw20Coin, Cw20CoinVerified};
 use hex::ToHex;
+use serde_cw_value::Value;
 use sha2::{Digest, Sha256};
 
 #[cw_serde]
@@ -70,12 +74,42 @@ pub struct TaskRequest {
     /// cw20's will get moved back to the temporary "Users balance".
     /// This is done primarily to save up gas from executing another contract during `proxy_call`
     pub cw20: Option<Cw20Coin>,
+
+    /// Maximum amount of times task can be executed.
+    /// Task ends and unused balance gets refunded after the last execution
+    pub max_executions: Option<u64>,
+
+    /// Contracts notified by the manager when task ends
+    pub callbacks: Option<TaskCallbacks>,
+
+    /// Denom the task pays the gas in, must be whitelisted by the manager.
+    /// Native denom of the manager if not set
+    #[serde(default)]
+    pub gas_denom: Option<String>,
+
+    /// Combines results of the `CosmosQuery::Croncat` queries, task executes only if the rule is true.
+    /// Replaces `check_result` of the queries, which are all required to be true otherwise
+    #[serde(default)]
+    pub query_rule: Option<QueryRule>,
+}
+
+/// Contracts called by the manager when task ends, with the
+/// [`TaskCallbackMsg`](https://docs.rs/croncat-sdk-manager/latest/croncat_sdk_manager/msg/enum.TaskCallbackMsg.html).
+/// Callback gas is paid from the task balance, failed callback doesn't revert the task execution
+#[cw_serde]
+pub struct TaskCallbacks {
+    /// Called when task ends after its schedule is done
+    pub on_complete: Option<String>,
+    /// Called when task ends because of the failure or drained balance
+    pub on_failure: Option<String>,
+    /// Gas limit of the callback, defaults to `gas_action_fee` of the tasks contract
+    pub gas_limit: Option<u64>,
 }
 
 /// Defines the spacing of execution
 /// NOTES:
 /// - Block Height Based: Once, Immediate, Block
-/// - Timestamp Based: Once, Cron
+/// - Timestamp Based: Once, Cron, ZonedCron, Seconds
 /// - No Epoch support directly, advised to use block heights instead
 #[cw_serde]
 pub enum Interval {
@@ -90,6 +124,13 @@ pub enum Interval {
 
     /// Crontab Spec String
     Cron(String),
+
+    /// Allows timing based on fixed period in seconds, counted from the boundary start.
+    /// Task creation time is used as the start if boundary doesn't set it
+    Seconds(u64),
+
+    /// Crontab Spec String, evaluated in the local time of the given timezone
+    ZonedCron { crontab: String, timezone: Timezone },
 }
 
 impl Interval {
@@ -116,7 +157,24 @@ impl Interval {
             // return the first block within a specific range that can be triggered 1 or more times based on timestamps.
             // Uses crontab spec
             (Interval::Cron(crontab), Boundary::Time(boundary_time)) => (
-                get_next_cron_time(env, boundary_time, crontab, slot_granularity_time),
+                get_next_cron_time(env, boundary_time, crontab, None, slot_granularity_time),
+                SlotType::Cron,
+            ),
+            // Same as cron, but crontab spec matched against the local time of the timezone
+            (Interval::ZonedCron { crontab, timezone }, Boundary::Time(boundary_time)) => (
+                get_next_cron_time(
+                    env,
+                    boundary_time,
+                    crontab,
+                    Some(timezone),
+                    slot_granularity_time,
+                ),
+                SlotType::Cron,
+            ),
+            // return the first time within a specific range that can be triggered 1 or more times based on timestamps.
+            // Uses period in seconds (Example: Seconds(90) will trigger every 90 seconds from the boundary start)
+            (Interval::Seconds(seconds), Boundary::Time(boundary_time)) => (
+                get_next_time_by_offset(env, boundary_time, *seconds, slot_granularity_time),
                 SlotType::Cron,
             ),
             // return the block within a specific range that can be triggered 1 or more times based on block heights.
@@ -128,7 +186,7 @@ impl Interval {
                 get_next_block_by_offset(env.block.height, boundary_height, *block),
                 SlotType::Block,
             ),
-            // If interval is cron it means boundary is [`BoundaryTime`], and rest of the items is height
+            // If interval is cron, zoned cron or seconds it means boundary is [`BoundaryTime`], and rest of the items is height
             _ => unreachable!(),
         }
     }
@@ -140,8 +198,90 @@ impl Interval {
                 let s = Schedule::from_str(crontab);
                 s.is_ok()
             }
+            Interval::Seconds(seconds) => *seconds > 0,
+            Interval::ZonedCron { crontab, timezone } => {
+                Schedule::from_str(crontab).is_ok() && timezone.is_valid()
+            }
+        }
+    }
+}
+
+/// Timezone the crontab spec of [`Interval::ZonedCron`] is evaluated in
+#[cw_serde]
+pub enum Timezone {
+    /// IANA timezone name, for example `Europe/Berlin`, daylight saving time transitions included
+    Iana(String),
+
+    /// Fixed offset from UTC in seconds, positive to the east of UTC
+    FixedOffset(i32),
+}
+
+impl Timezone {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Timezone::Iana(name) => Tz::from_str(name).is_ok(),
+            Timezone::FixedOffset(secs) => FixedOffset::east_opt(*secs).is_some(),
+        }
+    }
+
+    /// Get the next time (in nanos) after `utc_ts` matching the schedule in local time
+    /// Local times skipped by a DST transition are shifted forward by the length of the gap,
+    /// local times repeated by a DST transition only match once
+    pub fn next_after(&self, schedule: &Schedule, utc_ts: u64) -> Option<u64> {
+        let mut local_ts = self.to_local(utc_ts)?;
+        loop {
+            let next_local_ts = schedule.next_after(&local_ts)?;
+            let next_utc_ts = self.to_utc(next_local_ts)?;
+            if next_utc_ts > utc_ts {
+                return Some(next_utc_ts);
+            }
+            local_ts = next_local_ts;
         }
     }
+
+    fn to_local(&self, utc_ts: u64) -> Option<u64> {
+        let utc = naive_from_nanos(utc_ts)?;
+        let offset = match self {
+            Timezone::Iana(name) => Tz::from_str(name)
+                .ok()?
+                .offset_from_utc_datetime(&utc)
+                .fix(),
+            Timezone::FixedOffset(secs) => FixedOffset::east_opt(*secs)?,
+        };
+        shift_nanos(utc_ts, offset.local_minus_utc())
+    }
+
+    fn to_utc(&self, local_ts: u64) -> Option<u64> {
+        let local = naive_from_nanos(local_ts)?;
+        let offset = match self {
+            Timezone::Iana(name) => {
+                let tz = Tz::from_str(name).ok()?;
+                match tz.offset_from_local_datetime(&local).earliest() {
+                    Some(offset) => offset.fix(),
+                    // Local time doesn't exist, offset from before the gap moves it past the gap
+                    None => tz
+                        .offset_from_utc_datetime(&(local - Duration::days(1)))
+                        .fix(),
+                }
+            }
+            Timezone::FixedOffset(secs) => FixedOffset::east_opt(*secs)?,
+        };
+        shift_nanos(local_ts, -offset.local_minus_utc())
+    }
+}
+
+fn naive_from_nanos(nanos: u64) -> Option<NaiveDateTime> {
+    let secs = i64::try_from(nanos / 1_000_000_000).ok()?;
+    NaiveDateTime::from_timestamp_opt(secs, 0)
+}
+
+fn shift_nanos(nanos: u64, offset_secs: i32) -> Option<u64> {
+    let offset_nanos = i64::from(offset_secs) * 1_000_000_000;
+    if offset_nanos >= 0 {
+        nanos.checked_add(offset_nanos as u64)
+    } else {
+        nanos.checked_sub(offset_nanos.unsigned_abs())
+    }
 }
 
 /// Start and end block or timestamp when task should be executed for the last time
@@ -176,6 +316,49 @@ pub struct Action<T = Empty> {
 
     /// The gas needed to safely process the execute msg
     pub gas_limit: Option<u64>,
+
+    /// Execute the action on behalf of the task owner through authz `MsgExec`,
+    /// owner has to grant the authorization to the manager.
+    /// Coins of such action stay in the owner's wallet until execution
+    #[serde(default)]
+    pub authz: bool,
+
+    /// Action runs only if the condition is met, otherwise it's skipped along with its gas.
+    /// Conditional action has to have a `gas_limit`
+    #[serde(default)]
+    pub condition: Option<ActionCondition>,
+
+    /// Timeout of the `IbcMsg::Transfer` action in seconds, counted from the execution time.
+    /// Required for the ibc transfers only, timeout of the `IbcMsg::Transfer` itself is ignored
+    #[serde(default)]
+    pub ibc_timeout: Option<u64>,
+}
+
+/// Guard of the action, compares the value of the query response
+#[cw_serde]
+pub struct ActionCondition {
+    /// Index of the query, which response is checked
+    /// first query would be "0"
+    pub query_idx: u64,
+
+    /// Query response key's path to the compared value
+    pub query_response_path: PathToValue,
+
+    /// Comparison of the response value (left side) against the `value`
+    pub ordering: ValueOrdering,
+
+    /// Json value to compare with
+    pub value: Binary,
+}
+
+impl ActionCondition {
+    /// Checks the condition against the query response
+    pub fn is_met(&self, query_response: &Binary) -> StdResult<bool> {
+        let mut response_value: Value = cosmwasm_std::from_binary(query_response)?;
+        let lhs = self.query_response_path.find_value(&mut response_value)?;
+        let rhs: Value = cosmwasm_std::from_binary(&self.value)?;
+        self.ordering.val_cmp(lhs, &rhs)
+    }
 }
 
 /// Transforms of the tasks actions
@@ -199,6 +382,157 @@ pub struct Transform {
     /// A: {B: {C: value}}
     /// In order to reach a value \[A,B,C\] should be used as input
     pub query_response_path: PathToValue,
+
+    /// Operations applied in order to the query value before it replaces the action value.
+    /// Value has to be `Uint128`, `Decimal` or a number
+    #[serde(default)]
+    pub operations: Vec<TransformOperation>,
+
+    /// Type of the value after the operations, required if there are any operations
+    #[serde(default)]
+    pub result_type: Option<TransformResultType>,
+}
+
+/// Type of the transformed value
+#[cw_serde]
+pub enum TransformResultType {
+    /// Rounded down, stays a number if the query value is a number
+    Uint128,
+    Decimal,
+}
+
+/// Arithmetic operation on the transformed value, fails on overflow or division by zero
+#[cw_serde]
+pub enum TransformOperation {
+    Add(TransformOperand),
+    Sub(TransformOperand),
+    Mul(TransformOperand),
+    Div(TransformOperand),
+    /// Percentage of the value, 10 takes the tenth of it
+    Percent(Decimal256),
+    /// Smaller of the value and the operand, caps the value
+    Min(TransformOperand),
+    /// Bigger of the value and the operand, sets the floor for the value
+    Max(TransformOperand),
+}
+
+/// Right side of the transform operation
+#[cw_serde]
+pub enum TransformOperand {
+    /// Constant, for example "1000" or "0.5"
+    Constant(Decimal256),
+    /// Value of the query response
+    Query {
+        query_idx: u64,
+        query_response_path: PathToValue,
+    },
+}
+
+impl Transform {
+    /// Applies the operations to the query value,
+    /// result is converted to the `result_type` after the last operation
+    pub fn apply_operations(
+        &self,
+        value: &Value,
+        query_responses: &[Option<Binary>],
+    ) -> StdResult<Value> {
+        if self.operations.is_empty() {
+            return Ok(value.clone());
+        }
+        let result_type = self
+            .result_type
+            .as_ref()
+            .ok_or_else(|| StdError::generic_err("No result type for the transform operations"))?;
+        let mut number = value_to_number(value)?;
+        for operation in self.operations.iter() {
+            number = operation.apply(number, query_responses)?;
+        }
+        let result = match (result_type, value) {
+            (TransformResultType::Decimal, _) => Value::String(number.to_string()),
+            (TransformResultType::Uint128, Value::String(_)) => {
+                Value::String(Uint128::from_str(&number.floor().to_string())?.to_string())
+            }
+            (TransformResultType::Uint128, _) => Value::U64(
+                u64::from_str(&number.floor().to_string())
+                    .map_err(|e| StdError::parse_err("u64", e))?,
+            ),
+        };
+        Ok(result)
+    }
+}
+
+impl TransformOperation {
+    fn apply(
+        &self,
+        number: Decimal256,
+        query_responses: &[Option<Binary>],
+    ) -> StdResult<Decimal256> {
+        let result = match self {
+            TransformOperation::Add(operand) => {
+                number.checked_add(operand.value(query_responses)?)?
+            }
+            TransformOperation::Sub(operand) => {
+                number.checked_sub(operand.value(query_responses)?)?
+            }
+            TransformOperation::Mul(operand) => {
+                number.checked_mul(operand.value(query_responses)?)?
+            }
+            TransformOperation::Div(operand) => number
+                .checked_div(operand.value(query_responses)?)
+                .map_err(|e| StdError::generic_err(e.to_string()))?,
+            TransformOperation::Percent(percent) => {
+                number.checked_mul(Decimal256::percent(1).checked_mul(*percent)?)?
+            }
+            TransformOperation::Min(operand) => number.min(operand.value(query_responses)?),
+            TransformOperation::Max(operand) => number.max(operand.value(query_responses)?),
+        };
+        Ok(result)
+    }
+
+    /// Operand of the operation, if any
+    pub fn operand(&self) -> Option<&TransformOperand> {
+        match self {
+            TransformOperation::Add(operand)
+            | TransformOperation::Sub(operand)
+            | TransformOperation::Mul(operand)
+            | TransformOperation::Div(operand)
+            | TransformOperation::Min(operand)
+            | TransformOperation::Max(operand) => Some(operand),
+            TransformOperation::Percent(_) => None,
+        }
+    }
+}
+
+impl TransformOperand {
+    fn value(&self, query_responses: &[Option<Binary>]) -> StdResult<Decimal256> {
+        match self {
+            TransformOperand::Constant(constant) => Ok(*constant),
+            TransformOperand::Query {
+                query_idx,
+                query_response_path,
+            } => {
+                let query_response = query_responses
+                    .get(*query_idx as usize)
+                    .and_then(|opt| opt.as_ref())
+                    .ok_or_else(|| StdError::generic_err("No query response for the operand"))?;
+                let mut response_value: Value = cosmwasm_std::from_binary(query_response)?;
+                value_to_number(query_response_path.find_value(&mut response_value)?)
+            }
+        }
+    }
+}
+
+/// Reads `Uint128`, `Decimal` or the plain number
+fn value_to_number(value: &Value) -> StdResult<Decimal256> {
+    let number = match value {
+        Value::String(number) => number.clone(),
+        Value::U64(n) => n.to_string(),
+        Value::U32(n) => n.to_string(),
+        Value::U16(n) => n.to_string(),
+        Value::U8(n) => n.to_string(),
+        _ => return Err(StdError::parse_err("Decimal256", "Value is not a number")),
+    };
+    Decimal256::from_str(&number)
 }
 
 #[cw_serde]
@@ -226,20 +560,65 @@ pub struct Task {
 
     // computed amounts / fees
     pub amount_for_one_task: AmountForOneTask,
-    // pub
+
+    /// Paused task is not scheduled, but keeps its balance
+    #[serde(default)]
+    pub paused: bool,
+
+    /// Maximum amount of times task can be executed, unlimited if not set
+    #[serde(default)]
+    pub max_executions: Option<u64>,
+
+    /// Amount of times task was executed
+    #[serde(default)]
+    pub executions: u64,
+
+    /// Contracts notified when task ends
+    #[serde(default)]
+    pub callbacks: Option<TaskCallbacks>,
+
+    /// Rule over the query results, replaces `check_result` of the queries
+    #[serde(default)]
+    pub query_rule: Option<QueryRule>,
 }
 
 impl Task {
     /// Get the hash of a task based on parameters
+    /// `stop_on_fail` is left out, so hashes of the existing tasks stay the same
     pub fn to_hash(&self, prefix: &str) -> String {
+        // Rule is only part of the hash if set, so hashes of the tasks without it stay the same
+        let query_rule = self
+            .query_rule
+            .as_ref()
+            .map(|rule| format!("{rule:?}"))
+            .unwrap_or_default();
+        let max_executions = self
+            .max_executions
+            .map(|max_executions| format!("max_executions: {max_executions}"))
+            .unwrap_or_default();
+        let callbacks = self
+            .callbacks
+            .as_ref()
+            .map(|callbacks| format!("{callbacks:?}"))
+            .unwrap_or_default();
+        let gas_denom = self
+            .amount_for_one_task
+            .gas_denom
+            .as_ref()
+            .map(|gas_denom| format!("gas_denom: {gas_denom}"))
+            .unwrap_or_default();
         let message = format!(
-            "{:?}{:?}{:?}{:?}{:?}{:?}",
+            "{:?}{:?}{:?}{}{:?}{}{}{}{}{}",
             self.owner_addr,
             self.interval,
             self.boundary,
-            self.actions,
+            self.actions_hash_repr(),
             self.queries,
-            self.transforms
+            self.transforms_hash_repr(),
+            query_rule,
+            max_executions,
+            callbacks,
+            gas_denom
         );
 
         let hash = Sha256::digest(message.as_bytes());
@@ -255,6 +634,60 @@ impl Task {
         format!("{}:{}", prefix, l)
     }
 
+    /// Actions as they were formatted before `authz` and `condition` were added,
+    /// so hashes of the tasks without them stay the same
+    fn actions_hash_repr(&self) -> String {
+        let actions: Vec<String> = self
+            .actions
+            .iter()
+            .map(|action| {
+                let authz = if action.authz { ", authz: true" } else { "" };
+                let condition = action
+                    .condition
+                    .as_ref()
+                    .map(|condition| format!(", condition: {condition:?}"))
+                    .unwrap_or_default();
+                let ibc_timeout = action
+                    .ibc_timeout
+                    .map(|ibc_timeout| format!(", ibc_timeout: {ibc_timeout}"))
+                    .unwrap_or_default();
+                format!(
+                    "Action {{ msg: {:?}, gas_limit: {:?}{}{}{} }}",
+                    action.msg, action.gas_limit, authz, condition, ibc_timeout
+                )
+            })
+            .collect();
+        format!("[{}]", actions.join(", "))
+    }
+
+    /// Transforms as they were formatted before `operations` were added,
+    /// so hashes of the tasks without operations stay the same
+    fn transforms_hash_repr(&self) -> String {
+        let transforms: Vec<String> = self
+            .transforms
+            .iter()
+            .map(|transform| {
+                let operations = if transform.operations.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        ", operations: {:?}, result_type: {:?}",
+                        transform.operations, transform.result_type
+                    )
+                };
+                format!(
+                    "Transform {{ action_idx: {:?}, query_idx: {:?}, action_path: {:?}, query_response_path: {:?}{} }}",
+                    transform.action_idx,
+                    transform.query_idx,
+                    transform.action_path,
+                    transform.query_response_path,
+                    operations
+                )
+            })
+            .collect();
+        format!("[{}]", transforms.join(", "))
+    }
+
     /// Get the hash of a task based on parameters
     pub fn to_hash_vec(&self, prefix: &str) -> Vec<u8> {
         self.to_hash(prefix).into_bytes()
@@ -269,8 +702,15 @@ impl Task {
             && (self.interval == Interval::Once || self.interval == Interval::Immediate)
     }
 
+    /// Amount of executions left before task ends, `None` if unlimited
+    pub fn remaining_executions(&self) -> Option<u64> {
+        self.max_executions
+            .map(|max| max.saturating_sub(self.executions))
+    }
+
     pub fn into_response(self, prefix: &str) -> TaskResponse {
         let task_hash = self.to_hash(prefix);
+        let remaining_executions = self.remaining_executions();
 
         let queries = if !self.queries.is_empty() {
             Some(self.queries)
@@ -290,11 +730,43 @@ impl Task {
                 queries,
                 transforms: self.transforms,
                 version: self.version,
+                paused: self.paused,
+                executions: self.executions,
+                remaining_executions,
+                callbacks: self.callbacks,
+                query_rule: self.query_rule,
             }),
         }
     }
 }
 
+/// Boolean expression over the results of the `CosmosQuery::Croncat` queries
+#[cw_serde]
+pub enum QueryRule {
+    /// Result of the query at the given index
+    /// first query would be "0"
+    Query(u64),
+    /// True if all of the rules are true
+    And(Vec<QueryRule>),
+    /// True if any of the rules is true
+    Or(Vec<QueryRule>),
+    /// Negates the rule
+    Not(Box<QueryRule>),
+}
+
+impl QueryRule {
+    /// Evaluates the rule against the query results,
+    /// queries without the result (non-croncat or out of range) count as false
+    pub fn evaluate(&self, results: &[Option<bool>]) -> bool {
+        match self {
+            QueryRule::Query(idx) => results.get(*idx as usize).copied().flatten() == Some(true),
+            QueryRule::And(rules) => rules.iter().all(|rule| rule.evaluate(results)),
+            QueryRule::Or(rules) => rules.iter().any(|rule| rule.evaluate(results)),
+            QueryRule::Not(rule) => !rule.evaluate(results),
+        }
+    }
+}
+
 /// Query given module contract with a message
 #[cw_serde]
 pub struct CroncatQuery {
@@ -324,6 +796,27 @@ pub struct SlotTasksTotalResponse {
     pub evented_tasks: u64,
 }
 
+#[cw_serde]
+pub struct SimulateTaskResponse {
+    /// Amounts for one execution of the task, `None` if task is invalid
+    pub amount_for_one_task: Option<AmountForOneTask>,
+    /// Native coins spent by one execution, gas and fees included
+    pub native_per_execution: Coin,
+    /// Native coins one execution spends at most,
+    /// if the gas price source raises the gas price up to the tolerance of the task
+    pub max_native_per_execution: Coin,
+    /// Ibc coins sent by one execution
+    pub ibc_per_execution: Option<Coin>,
+    /// Cw20 tokens sent by one execution
+    pub cw20_per_execution: Option<Cw20CoinVerified>,
+    /// Native and ibc coins that have to be attached to the task creation
+    pub min_deposit: Vec<Coin>,
+    /// Cw20 tokens that have to be deposited to the manager before the task creation
+    pub min_cw20_deposit: Option<Cw20CoinVerified>,
+    /// Errors the task creation would fail with, empty if task is valid
+    pub errors: Vec<String>,
+}
+
 #[cw_serde]
 pub struct CurrentTaskInfoResponse {
     pub total: Uint64,
@@ -346,12 +839,44 @@ pub struct TaskInfo {
     pub queries: Option<Vec<CosmosQuery>>,
     pub transforms: Vec<Transform>,
     pub version: String,
+    pub paused: bool,
+    pub executions: u64,
+    /// Amount of executions left before task ends, `None` if unlimited
+    pub remaining_executions: Option<u64>,
+    pub callbacks: Option<TaskCallbacks>,
+    #[serde(default)]
+    pub query_rule: Option<QueryRule>,
 }
 #[cw_serde]
 pub struct TaskResponse {
     pub task: Option<TaskInfo>,
 }
 
+/// Value of the template task that gets replaced by the given param on task creation
+#[cw_serde]
+pub struct TemplatePlaceholder {
+    /// Name of the param, for frontends to show
+    pub name: String,
+    /// Path to the value in the json of the template [`TaskRequest`],
+    /// json `msg` of the wasm execute actions is decoded, so path can go inside it
+    pub path: PathToValue,
+}
+
+/// Reusable task shape, registered once and instantiated with params
+#[cw_serde]
+pub struct TaskTemplate {
+    pub owner_addr: Addr,
+    pub name: String,
+    pub task: TaskRequest,
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
+#[cw_serde]
+pub struct TaskTemplateResponse {
+    pub template_id: u64,
+    pub template: TaskTemplate,
+}
+
 /// This struct is used in two important places.
 /// On the tasks contract, when [`create_task`](crate::msg::TasksExecuteMsg::CreateTask) is called, this struct
 /// is returned in the binary data field of the response.
@@ -503,11 +1028,13 @@ pub(crate) fn get_next_block_by_offset(
 }
 
 /// Get the slot number (in nanos) of the next task according to boundaries
+/// Crontab matched against UTC, or against the local time if timezone is given
 /// Unless current slot is the end slot, don't put in the current slot
 fn get_next_cron_time(
     env: &Env,
     boundary: &BoundaryTime,
     crontab: &str,
+    timezone: Option<&Timezone>,
     slot_granularity_time: u64,
 ) -> u64 {
     let current_block_ts = env.block.time.nanos();
@@ -522,7 +1049,57 @@ fn get_next_cron_time(
 
     // receive time from schedule, calculate slot for this time
     let schedule = Schedule::from_str(crontab).unwrap();
-    let next_ts = schedule.next_after(&current_ts).unwrap();
+    let next_ts = match timezone {
+        Some(timezone) => timezone.next_after(&schedule, current_ts).unwrap(),
+        None => schedule.next_after(&current_ts).unwrap(),
+    };
+    let next_ts_slot = next_ts.saturating_sub(next_ts % slot_granularity_time);
+
+    // put task in the next slot if next_ts_slot in the current slot
+    let next_slot = if next_ts_slot == current_block_slot {
+        next_ts_slot + slot_granularity_time
+    } else {
+        next_ts_slot
+    };
+
+    match boundary.end {
+        Some(end) if current_block_ts > end.nanos() => 0,
+        Some(end) => {
+            let end_slot = end
+                .nanos()
+                .saturating_sub(end.nanos() % slot_granularity_time);
+            u64::min(end_slot, next_slot)
+        }
+        _ => next_slot,
+    }
+}
+
+/// Get the slot number (in nanos) of the next task, repeating every `seconds`
+/// Period counted from the boundary start, or from the zero timestamp if there is no start
+/// Unless current slot is the end slot, don't put in the current slot
+fn get_next_time_by_offset(
+    env: &Env,
+    boundary: &BoundaryTime,
+    seconds: u64,
+    slot_granularity_time: u64,
+) -> u64 {
+    let period = seconds.saturating_mul(1_000_000_000);
+    if period == 0 {
+        return 0;
+    }
+    let current_block_ts = env.block.time.nanos();
+    let current_block_slot =
+        current_block_ts.saturating_sub(current_block_ts % slot_granularity_time);
+
+    let next_ts = match boundary.start {
+        Some(ts) if current_block_ts < ts.nanos() => ts.nanos(),
+        Some(ts) => {
+            let periods_passed = (current_block_ts - ts.nanos()) / period;
+            ts.nanos()
+                .saturating_add((periods_passed + 1).saturating_mul(period))
+        }
+        None => current_block_ts.saturating_sub(current_block_ts % period) + period,
+    };
     let next_ts_slot = next_ts.saturating_sub(next_ts % slot_granularity_time);
 
     // put task in the next slot if next_ts_slot in the current slot
@@ -553,7 +1130,7 @@ mod test {
 
     use crate::types::{Action, BoundaryHeight, CosmosQuery, CroncatQuery, Transform};
 
-    use super::{Boundary, BoundaryTime, Interval, SlotType, Task};
+    use super::{Boundary, BoundaryTime, Interval, SlotType, Task, TaskCallbacks, Timezone};
 
     const TWO_MINUTES: u64 = 120_000_000_000;
 
@@ -573,6 +1150,50 @@ mod test {
 
         let cron_wrong = Interval::Cron("1 * * * * * *".to_string());
         assert!(cron_wrong.is_valid());
+
+        let seconds = Interval::Seconds(90);
+        assert!(seconds.is_valid());
+
+        let seconds_zero = Interval::Seconds(0);
+        assert!(!seconds_zero.is_valid());
+
+        let zoned_cron = Interval::ZonedCron {
+            crontab: "0 0 9 * * *".to_string(),
+            timezone: Timezone::Iana("Europe/Berlin".to_string()),
+        };
+        assert!(zoned_cron.is_valid());
+
+        for name in [
+            "Europe/Madrid",
+            "Europe/Rome",
+            "Europe/Amsterdam",
+            "Europe/Warsaw",
+            "Europe/Stockholm",
+        ] {
+            let zoned_cron = Interval::ZonedCron {
+                crontab: "0 0 9 * * *".to_string(),
+                timezone: Timezone::Iana(name.to_string()),
+            };
+            assert!(zoned_cron.is_valid());
+        }
+
+        let zoned_cron_fixed = Interval::ZonedCron {
+            crontab: "0 0 9 * * *".to_string(),
+            timezone: Timezone::FixedOffset(-18_000),
+        };
+        assert!(zoned_cron_fixed.is_valid());
+
+        let zoned_cron_unknown = Interval::ZonedCron {
+            crontab: "0 0 9 * * *".to_string(),
+            timezone: Timezone::Iana("Mars/Olympus_Mons".to_string()),
+        };
+        assert!(!zoned_cron_unknown.is_valid());
+
+        let zoned_cron_offset = Interval::ZonedCron {
+            crontab: "0 0 9 * * *".to_string(),
+            timezone: Timezone::FixedOffset(86_400),
+        };
+        assert!(!zoned_cron_offset.is_valid());
     }
 
     #[test]
@@ -592,12 +1213,17 @@ mod test {
                 agent_fee: u16::default(),
                 treasury_fee: u16::default(),
                 gas_price: GasPrice::default(),
+                gas_price_tolerance: None,
+                gas_denom: None,
             },
             actions: vec![Action {
                 msg: CosmosMsg::Wasm(WasmMsg::ClearAdmin {
                     contract_addr: "alice".to_string(),
                 }),
                 gas_limit: Some(5),
+                authz: false,
+                condition: None,
+                ibc_timeout: None,
             }],
             queries: vec![CosmosQuery::Croncat(CroncatQuery {
                 msg: Default::default(),
@@ -609,18 +1235,25 @@ mod test {
                 query_idx: 0,
                 action_path: vec![].into(),
                 query_response_path: vec![].into(),
+                operations: vec![],
+                result_type: None,
             }],
             version: String::from(""),
+            paused: false,
+            max_executions: None,
+            executions: 0,
+            callbacks: None,
+            query_rule: None,
         };
 
         let message = format!(
-            "{:?}{:?}{:?}{:?}{:?}{:?}",
+            "{:?}{:?}{:?}{}{:?}{}",
             task.owner_addr,
             task.interval,
             task.boundary,
-            task.actions,
+            r#"[Action { msg: Wasm(ClearAdmin { contract_addr: "alice" }), gas_limit: Some(5) }]"#,
             task.queries,
-            task.transforms
+            "[Transform { action_idx: 0, query_idx: 0, action_path: PathToValue([]), query_response_path: PathToValue([]) }]"
         );
 
         let hash = Sha256::digest(message.as_bytes());
@@ -634,6 +1267,34 @@ mod test {
         // Tests
         assert_eq!(encoded, task.to_hash(prefix));
         assert_eq!(bytes, task.to_hash_vec(prefix));
+
+        // Same actions through authz are the different task
+        let mut authz_task = task.clone();
+        authz_task.actions[0].authz = true;
+        assert_ne!(task.to_hash(prefix), authz_task.to_hash(prefix));
+
+        // Limited executions make the different task
+        let limited_task = Task {
+            max_executions: Some(5),
+            ..task.clone()
+        };
+        assert_ne!(task.to_hash(prefix), limited_task.to_hash(prefix));
+
+        // Notified contracts make the different task
+        let callback_task = Task {
+            callbacks: Some(TaskCallbacks {
+                on_complete: Some("alice".to_owned()),
+                on_failure: None,
+                gas_limit: None,
+            }),
+            ..task.clone()
+        };
+        assert_ne!(task.to_hash(prefix), callback_task.to_hash(prefix));
+
+        // Gas paid in other denom makes the different task
+        let mut denom_task = task.clone();
+        denom_task.amount_for_one_task.gas_denom = Some("ibc".to_owned());
+        assert_ne!(task.to_hash(prefix), denom_task.to_hash(prefix));
     }
 
     #[test]
@@ -1170,4 +1831,220 @@ mod test {
             assert_eq!(outcome_slot_kind, &slot_kind);
         }
     }
+
+    #[test]
+    fn interval_get_next_time_by_offset() {
+        // (input, input, outcome, outcome)
+        // test the case when slot_granularity_time == 1
+        // current time in nanos is 1_571_797_419_879_305_533
+        let cases: Vec<(Interval, Boundary, u64, SlotType)> = vec![
+            // no start, counted from the zero timestamp
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: None,
+                    end: None,
+                }),
+                1_571_797_440_000_000_000,
+                SlotType::Cron,
+            ),
+            // start is now
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_419_879_305_533)),
+                    end: None,
+                }),
+                1_571_797_509_879_305_533,
+                SlotType::Cron,
+            ),
+            // start in the future
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_500_000_000_000)),
+                    end: None,
+                }),
+                1_571_797_500_000_000_000,
+                SlotType::Cron,
+            ),
+            // start in the past
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: None,
+                }),
+                1_571_797_490_000_000_000,
+                SlotType::Cron,
+            ),
+            (
+                Interval::Seconds(129_600),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_700_000_000_000_000)),
+                    end: None,
+                }),
+                1_571_829_600_000_000_000,
+                SlotType::Cron,
+            ),
+            // the next slot is after the end, return end slot
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: Some(Timestamp::from_nanos(1_571_797_450_000_000_000)),
+                }),
+                1_571_797_450_000_000_000,
+                SlotType::Cron,
+            ),
+            // the task has ended
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: Some(Timestamp::from_nanos(1_571_797_419_879_305_532)),
+                }),
+                0,
+                SlotType::Cron,
+            ),
+            (
+                Interval::Seconds(0),
+                Boundary::Time(BoundaryTime {
+                    start: None,
+                    end: None,
+                }),
+                0,
+                SlotType::Cron,
+            ),
+        ];
+        // Check all these cases
+        for (interval, boundary, outcome_time, outcome_slot_kind) in cases.iter() {
+            let env = mock_env();
+            let (next_id, slot_kind) = interval.next(&env, boundary, 1);
+            assert_eq!(outcome_time, &next_id);
+            assert_eq!(outcome_slot_kind, &slot_kind);
+        }
+
+        // slot_granularity_time == 120_000_000_000 ~ 2 minutes
+        let cases: Vec<(Interval, Boundary, u64, SlotType)> = vec![
+            (
+                Interval::Seconds(90),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: None,
+                }),
+                1_571_797_440_000_000_000,
+                SlotType::Cron,
+            ),
+            // the timestamp is in the current slot, so we take the next slot
+            (
+                Interval::Seconds(10),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: None,
+                }),
+                1_571_797_440_000_000_000,
+                SlotType::Cron,
+            ),
+            // boundary end in the current slot
+            (
+                Interval::Seconds(10),
+                Boundary::Time(BoundaryTime {
+                    start: Some(Timestamp::from_nanos(1_571_797_400_000_000_000)),
+                    end: Some(Timestamp::from_nanos(1_571_797_419_879_305_535)),
+                }),
+                1_571_797_320_000_000_000,
+                SlotType::Cron,
+            ),
+        ];
+        // Check all these cases
+        for (interval, boundary, outcome_time, outcome_slot_kind) in cases.iter() {
+            let env = mock_env();
+            let (next_id, slot_kind) = interval.next(&env, boundary, TWO_MINUTES);
+            assert_eq!(outcome_time, &next_id);
+            assert_eq!(outcome_slot_kind, &slot_kind);
+        }
+    }
+
+    #[test]
+    fn interval_get_next_zoned_cron_time() {
+        // (input, input, outcome)
+        // current time and outcome in seconds, slot_granularity_time == 1
+        let berlin = Timezone::Iana("Europe/Berlin".to_string());
+        let cases: Vec<(Interval, u64, u64)> = vec![
+            // 09:00 CEST is 07:00 UTC
+            (
+                Interval::ZonedCron {
+                    crontab: "0 0 9 * * *".to_string(),
+                    timezone: berlin.clone(),
+                },
+                1_685_577_600,
+                1_685_602_800,
+            ),
+            // 09:00 CET is 08:00 UTC
+            (
+                Interval::ZonedCron {
+                    crontab: "0 0 9 * * *".to_string(),
+                    timezone: berlin.clone(),
+                },
+                1_672_617_600,
+                1_672_646_400,
+            ),
+            // 02:30 skipped by the spring transition, fires at 03:30 CEST
+            (
+                Interval::ZonedCron {
+                    crontab: "0 30 2 * * *".to_string(),
+                    timezone: berlin.clone(),
+                },
+                1_679_788_800,
+                1_679_794_200,
+            ),
+            // 02:30 repeated by the fall transition, fires at the first 02:30 CEST
+            (
+                Interval::ZonedCron {
+                    crontab: "0 30 2 * * *".to_string(),
+                    timezone: berlin.clone(),
+                },
+                1_698_537_600,
+                1_698_539_400,
+            ),
+            // between the first 02:30 CEST and the second 02:30 CET, fires next day
+            (
+                Interval::ZonedCron {
+                    crontab: "0 30 2 * * *".to_string(),
+                    timezone: berlin.clone(),
+                },
+                1_698_540_300,
+                1_698_629_400,
+            ),
+            (
+                Interval::ZonedCron {
+                    crontab: "0 30 2 * * *".to_string(),
+                    timezone: berlin,
+                },
+                1_698_541_800,
+                1_698_629_400,
+            ),
+            // 09:00 at UTC-5 is 14:00 UTC
+            (
+                Interval::ZonedCron {
+                    crontab: "0 0 9 * * *".to_string(),
+                    timezone: Timezone::FixedOffset(-18_000),
+                },
+                1_685_577_600,
+                1_685_628_000,
+            ),
+        ];
+        let boundary = Boundary::Time(BoundaryTime {
+            start: None,
+            end: None,
+        });
+        for (interval, current_time, outcome_time) in cases.iter() {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(*current_time);
+            let (next_id, slot_kind) = interval.next(&env, &boundary, 1);
+            assert_eq!(Timestamp::from_seconds(*outcome_time).nanos(), next_id);
+            assert_eq!(SlotType::Cron, slot_kind);
+        }
+    }
 }